@@ -1,4 +1,7 @@
-use bitbuffer::{BigEndian, BitRead, BitReadBuffer, BitReadStream, Endianness, LittleEndian};
+use bitbuffer::{
+    BigEndian, BitRead, BitReadBuffer, BitReadStream, BoundedReader, BoundsCheck, Endianness,
+    LittleEndian,
+};
 use iai::black_box;
 
 fn read_perf<E: Endianness>(buffer: &BitReadBuffer<E>) -> u16 {
@@ -16,7 +19,7 @@ fn read_perf<E: Endianness>(buffer: &BitReadBuffer<E>) -> u16 {
     }
 }
 
-const ONES: [u8; 1024 * 1024 * 10] = [1u8; 1024 * 1024 * 10];
+static ONES: [u8; 1024 * 1024 * 10] = [1u8; 1024 * 1024 * 10];
 
 fn perf_le() {
     let buffer = BitReadBuffer::new(black_box(&ONES), BigEndian);
@@ -85,6 +88,22 @@ fn perf_f64() {
     black_box(result);
 }
 
+fn perf_f64_unaligned() {
+    let buffer = BitReadBuffer::new(black_box(&ONES), BigEndian);
+    let mut pos = 3;
+    let len = buffer.bit_len();
+    let mut result: f64 = 0.0;
+    loop {
+        if pos + 64 > len {
+            break;
+        }
+        let num = buffer.read_float::<f64>(pos).unwrap();
+        result += num;
+        pos += 64;
+    }
+    black_box(result);
+}
+
 fn perf_bool() {
     let buffer = BitReadBuffer::new(black_box(&ONES), BigEndian);
     let mut pos = 0;
@@ -243,9 +262,43 @@ fn perf_struct() {
     }
 }
 
+fn read_bounded<M: BoundsCheck>(reader: &mut BoundedReader<LittleEndian, M>, count: usize) -> u16 {
+    let mut result: u16 = 0;
+    for _ in 0..count {
+        let data = reader.read_int::<u16>(5).unwrap();
+        result = result.wrapping_add(data);
+    }
+    result
+}
+
+const BOUNDED_READS: usize = ONES.len() * 8 / 5;
+
+fn perf_bounded_checked() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(black_box(&ONES), LittleEndian));
+    let data = read_bounded(&mut stream.checked_reader(), BOUNDED_READS);
+    black_box(data);
+}
+
+fn perf_bounded_checked_once() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(black_box(&ONES), LittleEndian));
+    let mut reader = stream.check_once(BOUNDED_READS * 5).unwrap();
+    let data = read_bounded(&mut reader, BOUNDED_READS);
+    black_box(data);
+}
+
+fn perf_bounded_trusted() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(black_box(&ONES), LittleEndian));
+    assert!(stream.bits_left() >= BOUNDED_READS * 5);
+    let data = read_bounded(unsafe { &mut stream.trusted_reader() }, BOUNDED_READS);
+    black_box(data);
+}
+
 iai::main!(
     perf_be,
     perf_bool,
+    perf_bounded_checked,
+    perf_bounded_checked_once,
+    perf_bounded_trusted,
     perf_bytes_be,
     perf_bytes_be_unaligned,
     perf_bytes_le,
@@ -253,6 +306,7 @@ iai::main!(
     perf_f32_be,
     perf_f32_le,
     perf_f64,
+    perf_f64_unaligned,
     perf_le,
     perf_string_be,
     perf_string_le,
@@ -0,0 +1,44 @@
+use crate::err;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{LitStr, Result};
+
+/// Parse the bit literal and pack the bits both most and least significant bit first
+pub fn bits(input: LitStr) -> Result<TokenStream> {
+    let mut bits = Vec::new();
+    for (index, char) in input.value().char_indices() {
+        match char {
+            '0' => bits.push(false),
+            '1' => bits.push(true),
+            '_' => {}
+            char if char.is_whitespace() => {}
+            char => {
+                return err(
+                    format!(
+                        "invalid character '{}' at index {} in bit literal, only '0', '1', '_' and whitespace are allowed",
+                        char, index
+                    ),
+                    input.span(),
+                )
+            }
+        }
+    }
+
+    let msb_first = pack(&bits, |index| 7 - index);
+    let lsb_first = pack(&bits, |index| index);
+    let bit_len = bits.len();
+
+    Ok(quote! {
+        ::bitbuffer::BitReadBuffer::from_bit_literal(&[#(#msb_first),*], &[#(#lsb_first),*], #bit_len)
+    })
+}
+
+fn pack(bits: &[bool], shift: impl Fn(usize) -> usize) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|byte| {
+            byte.iter().enumerate().fold(0u8, |packed, (index, bit)| {
+                packed | (*bit as u8) << shift(index)
+            })
+        })
+        .collect()
+}
@@ -1,4 +1,5 @@
 use crate::err;
+use crate::params::{InputInnerParams, InputParams};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned};
 use std::convert::{TryFrom, TryInto};
@@ -103,3 +104,36 @@ impl Discriminant {
         }
     }
 }
+
+/// Generate a `discriminant(&self)` method for enums with a `discriminant_type`
+///
+/// The method is only generated by the `BitRead` derive, so it's emitted once no matter which other derives the enum has.
+pub fn discriminant_fn(params: &InputParams) -> Option<TokenStream> {
+    let InputInnerParams::Enum(inner) = &params.inner else {
+        return None;
+    };
+    let ty = inner.discriminant_type.as_ref()?;
+    let name = &params.ident;
+
+    let (impl_generics, ty_generics, where_clause) = params.generics.split_for_impl();
+    let span = params.span;
+    let body = if inner.discriminant_repr {
+        quote_spanned!(span => ::core::convert::Into::into(::core::clone::Clone::clone(self)))
+    } else {
+        let arms = inner.discriminant_match_arms();
+        quote_spanned! {span =>
+            match self {
+                #(#arms),*
+            }
+        }
+    };
+
+    Some(quote_spanned! {span =>
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The discriminant that is used for this variant when reading or writing the enum
+            pub fn discriminant(&self) -> #ty {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,148 @@
+use crate::params::parse_attrs;
+use crate::{err, Derivable, DeriveParams};
+use merge::Merge;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use structmeta::StructMeta;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, LitInt, LitStr, Path, Result};
+
+#[derive(Default, StructMeta, Merge, Debug)]
+struct FlagEnumAttrs {
+    flag_bits: Option<LitInt>,
+    #[struct_meta(name = "crate")]
+    crate_path: Option<LitStr>,
+}
+
+/// The integer types that can be used as `#[repr(..)]` of a flag enum, with their bit width
+const REPR_TYPES: &[(&str, usize)] = &[("u8", 8), ("u16", 16), ("u32", 32), ("u64", 64)];
+
+pub struct FlagEnumParams {
+    ident: Ident,
+    span: Span,
+    flag_bits: usize,
+    variants: Vec<Ident>,
+    crate_path: Option<Path>,
+}
+
+impl DeriveParams for FlagEnumParams {
+    fn parse(input: &DeriveInput) -> Result<Self> {
+        let attrs: FlagEnumAttrs = parse_attrs(&input.attrs)?;
+        let Data::Enum(data) = &input.data else {
+            return err("FlagEnum can only be derived for enums", input.span());
+        };
+        if !input.generics.params.is_empty() {
+            return err(
+                "FlagEnum can't be derived for enums with generics",
+                input.generics.span(),
+            );
+        }
+        let variants = data
+            .variants
+            .iter()
+            .map(|variant| match variant.fields {
+                Fields::Unit => Ok(variant.ident.clone()),
+                _ => err("flags can't have fields", variant.span()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let flag_bits = match attrs.flag_bits {
+            Some(bits) => bits.base10_parse()?,
+            None => match repr_bits(&input.attrs)? {
+                Some(bits) => bits,
+                None => {
+                    return err(
+                        "'flag_bits' attribute or a #[repr(u8)], #[repr(u16)], #[repr(u32)] or #[repr(u64)] is required when deriving `FlagEnum`",
+                        input.span(),
+                    )
+                }
+            },
+        };
+        if !(1..=64).contains(&flag_bits) {
+            return err("'flag_bits' has to be between 1 and 64", input.span());
+        }
+
+        Ok(FlagEnumParams {
+            ident: input.ident.clone(),
+            span: input.span(),
+            flag_bits,
+            variants,
+            crate_path: attrs
+                .crate_path
+                .map(|lit| lit.parse::<Path>())
+                .transpose()?,
+        })
+    }
+
+    fn crate_path(&self) -> Option<&Path> {
+        self.crate_path.as_ref()
+    }
+}
+
+/// The number of bits of the integer `#[repr(..)]` of the enum
+fn repr_bits(attrs: &[syn::Attribute]) -> Result<Option<usize>> {
+    let mut bits = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if let Some((_, type_bits)) = REPR_TYPES.iter().find(|(ty, _)| meta.path.is_ident(ty)) {
+                bits = Some(*type_bits);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(bits)
+}
+
+pub struct FlagEnum;
+
+impl Derivable for FlagEnum {
+    type Params = FlagEnumParams;
+
+    fn derive(params: FlagEnumParams) -> Result<TokenStream> {
+        let name = &params.ident;
+        let flag_bits = params.flag_bits;
+        let max_mask = u64::MAX >> (64 - flag_bits);
+        let variants = &params.variants;
+        let checks = variants.iter().map(|variant| {
+            let single_bit = format!(
+                "the discriminant of flag `{}::{}` needs to have a single set bit",
+                name, variant
+            );
+            let fits = format!(
+                "the discriminant of flag `{}::{}` doesn't fit in {} bits",
+                name, variant, flag_bits
+            );
+            quote! {
+                assert!((#name::#variant as u64).is_power_of_two(), #single_bit);
+                assert!((#name::#variant as u64) <= #max_mask, #fits);
+            }
+        });
+        let span = params.span;
+
+        Ok(quote_spanned! {span =>
+            impl __bitbuffer::FlagEnum for #name {
+                const FLAG_BITS: usize = #flag_bits;
+
+                const FLAGS: &'static [Self] = &[#(#name::#variants),*];
+
+                #[inline]
+                fn mask(self) -> u64 {
+                    self as u64
+                }
+            }
+
+            impl ::std::ops::BitOr for #name {
+                type Output = __bitbuffer::FlagSet<#name>;
+
+                #[inline]
+                fn bitor(self, other: Self) -> Self::Output {
+                    __bitbuffer::FlagSet::from(self) | other
+                }
+            }
+
+            const _: () = {
+                #(#checks)*
+            };
+        })
+    }
+}
@@ -12,8 +12,61 @@
 //!  - use a previously defined field as the size using the `size` attribute
 //!  - read a set number of bits as an integer, using the resulting value as size using the `read_bits` attribute
 //!
+//! The meaning of the size depends on the type of the field (number of bits for integers, bytes for strings, items for `Vec`s, etc).
+//! To make the intent explicit, the size can also be set with a unit using `#[size(bits = 12)]`, `#[size(bytes = 4)]` or `#[size(count = 3)]`,
+//! which will be converted into the unit used by the type of the field.
+//!
+//! ```
+//! use bitbuffer::BitRead;
+//!
+//! #[derive(BitRead)]
+//! struct UnitStruct {
+//!     #[size(bytes = 2)] // read as a 16 bit integer
+//!     int: u32,
+//!     #[size(bits = 32)] // read as a 4 byte string
+//!     string: String,
+//!     length: u8,
+//!     #[size(count = "length")]
+//!     list: Vec<u16>,
+//! }
+//! ```
+//!
 //! When deriving `BitReadSized` the input size can be used in the size attribute as the `input_size` field.
 //!
+//! Const generic parameters of the type can also be used in the size attribute,
+//! sizes that only depend on const generics and literals are taken into account for the size of the type.
+//!
+//! ```
+//! use bitbuffer::{bit_size_of, BitRead};
+//!
+//! #[derive(BitRead)]
+//! struct Packet<const N: usize> {
+//!     data: [u8; N],
+//!     #[size = "N * 4"]
+//!     flags: u32,
+//! }
+//!
+//! assert_eq!(Some(24), bit_size_of::<Packet<2>>());
+//! ```
+//!
+//! For `Vec` fields, elements can also be read until a condition is met using the `until` attribute.
+//! The condition is checked after every element, with the last read element available as `last`
+//! and the stream as `stream`, when writing all elements of the `Vec` are written.
+//! Since the condition is only checked after reading an element, at least one element is always read,
+//! even if a condition on the stream already holds before the field is read.
+//!
+//! ```
+//! use bitbuffer::BitRead;
+//!
+//! #[derive(BitRead)]
+//! struct SentinelList {
+//!     #[until = "*last == 0"] // read until (and including) a 0 element
+//!     terminated: Vec<u16>,
+//!     #[until = "stream.bits_left() < 8"] // read until there are no full bytes left
+//!     rest: Vec<u8>,
+//! }
+//! ```
+//!
 //! ## Examples
 //!
 //! ```
@@ -103,6 +156,105 @@
 //! }
 //! ```
 //!
+//! The type of the discriminant can be set using the `discriminant_type` attribute, which allows using large and sparse
+//! discriminant values. The number of bits read for the discriminant defaults to the size of the type,
+//! but can be lowered using the `discriminant_bits` attribute.
+//! When a `discriminant_type` is set, the `BitRead` derive generates a `discriminant(&self)` method that returns the
+//! discriminant for a variant. Enums that only derive `BitWrite` or `BitWriteSized` don't get the method.
+//!
+//! ```
+//! # use bitbuffer::BitRead;
+//! #
+//! #[derive(BitRead)]
+//! #[discriminant_type = "u16"]
+//! enum Opcode {
+//!     Nop = 0x0001,
+//!     Jump = 0x8000,
+//!     Call = 0x8001,
+//! }
+//!
+//! assert_eq!(0x8000, Opcode::Jump.discriminant());
+//! ```
+//!
+//! For enums without variant fields that already convert from and into an integer, for example using `num_enum`,
+//! the `discriminant_repr` attribute uses the existing `TryFrom` and `Into` implementations instead of the variant discriminants.
+//! Writing the enum requires it to implement `Clone`.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite};
+//! # use std::convert::TryFrom;
+//! #
+//! #[derive(BitRead, BitWrite, Clone, Copy)]
+//! #[discriminant_repr]
+//! #[discriminant_bits = 4]
+//! enum Color {
+//!     Red,
+//!     Green,
+//! }
+//!
+//! impl TryFrom<u8> for Color {
+//!     type Error = ();
+//!
+//!     fn try_from(value: u8) -> Result<Self, ()> {
+//!         match value {
+//!             1 => Ok(Color::Red),
+//!             2 => Ok(Color::Green),
+//!             _ => Err(()),
+//!         }
+//!     }
+//! }
+//!
+//! impl From<Color> for u8 {
+//!     fn from(color: Color) -> u8 {
+//!         match color {
+//!             Color::Red => 1,
+//!             Color::Green => 2,
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! For enums where the length of the variant fields is stored between the discriminant and the fields,
+//! the number of bits used for the length can be set with the `payload_size_bits` attribute.
+//! The variant fields are read from a sub-stream with the length of the payload and any bits of the payload
+//! that aren't read by the variant are skipped. The length is stored in bits.
+//! Writing a variant whose fields don't fit in the length returns a
+//! [`BitError::TooManyBits`](../bitbuffer/enum.BitError.html#variant.TooManyBits) error.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite};
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! #[discriminant_bits = 8]
+//! #[payload_size_bits = 16]
+//! enum Record {
+//!     Ping,
+//!     Position(u32),
+//!     Name(String),
+//! }
+//! ```
+//!
+//! A variant marked with `#[discriminant = "_"]` is read for any discriminant that doesn't match another variant,
+//! since the read discriminant isn't stored it's written using the first discriminant above all other variants.
+//! To keep the discriminant, mark a variant with a single field of the discriminant type with `#[unknown]` instead,
+//! the read discriminant is stored in the field and written again when writing the variant.
+//! Writing an unknown variant with the discriminant of another variant, or one that doesn't fit in the discriminant bits,
+//! returns a [`BitError::ValueOutOfRange`](../bitbuffer/enum.BitError.html#variant.ValueOutOfRange) error.
+//! An enum can only have one of these catch-all variants.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite};
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! #[discriminant_bits = 4]
+//! enum Message {
+//!     Ping,
+//!     Pong,
+//!     #[unknown]
+//!     Other(u8),
+//! }
+//! ```
+//!
 //! # Alignment
 //!
 //! You can request alignment for a struct, enum or a field using #\[align\] attribute.
@@ -135,6 +287,371 @@
 //! }
 //! ```
 //!
+//! # Byte swapping
+//!
+//! Integer and float fields can be byte swapped after reading (and before writing) using the `#[byte_swap]` attribute,
+//! which reverses the order of the bytes in the field.
+//! For formats that only swap the bytes within smaller groups, such as the "middle-endian" 32 bit values of the PDP-11,
+//! the size of the groups can be set with `#[byte_swap = 2]`.
+//!
+//! ```
+//! # use bitbuffer::BitRead;
+//! #
+//! #[derive(BitRead)]
+//! struct LegacyHeader {
+//!     #[byte_swap] // reverse all bytes
+//!     length: u16,
+//!     #[byte_swap = 2] // swap the bytes within every 16 bit word
+//!     offset: u32,
+//! }
+//! ```
+//!
+//! # Transparent newtypes
+//!
+//! Structs marked `#[repr(transparent)]` with a single field without any attributes forward all reads and writes
+//! to the field, this includes `BitReadSized` and `BitWriteSized`, allowing the newtype to be used with a `#[size]` attribute.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitReadSized, BitWrite, BitWriteSized};
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! #[repr(transparent)]
+//! struct EntityId(u32);
+//!
+//! #[derive(BitRead, BitWrite)]
+//! struct Entity {
+//!     id: EntityId,
+//!     #[size = 12]
+//!     name: Name,
+//! }
+//!
+//! #[derive(BitReadSized, BitWriteSized)]
+//! #[repr(transparent)]
+//! struct Name(String);
+//! ```
+//!
+//! # Field offsets
+//!
+//! For structs with the `#[field_offsets]` attribute, `derive(BitRead)` also generates a `field_offsets()` method
+//! that lists the bit offset of every field and a `read_<field>(&stream)` method for every field.
+//! These read a single field of a record starting at the current position of the stream without advancing it,
+//! jumping directly to the offset of the field when the fields before it have a fixed size.
+//!
+//! Multiple fields can be read at once using `read_fields_subset(&stream, &["field", ...])`, which returns a tuple
+//! with an `Option` for every field, only the requested fields are read.
+//! Requesting a name that isn't a field with an accessor returns a
+//! [`BitError::UnknownField`](../bitbuffer/enum.BitError.html#variant.UnknownField) error.
+//!
+//! Only fields that come before the first field whose size depends on previously read data get an accessor.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, LittleEndian};
+//! #
+//! #[derive(BitRead)]
+//! #[field_offsets]
+//! struct Sample {
+//!     #[size = 3]
+//!     channel: u8,
+//!     timestamp: u32,
+//!     value: f32,
+//! }
+//!
+//! assert_eq!(Sample::field_offsets()[2], ("value", Some(35)));
+//!
+//! let bytes = vec![0; 16];
+//! let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+//! assert_eq!(Sample::read_value(&stream).unwrap(), 0.0);
+//!
+//! let (channel, timestamp, value) = Sample::read_fields_subset(&stream, &["channel", "value"]).unwrap();
+//! assert_eq!((Some(0), None, Some(0.0)), (channel, timestamp, value));
+//! ```
+//!
+//! # Layout
+//!
+//! For structs with the `#[bitbuffer(layout)]` attribute, `derive(BitRead)` also generates a `layout()` method
+//! that lists the name, bit offset and bit size of every field as a `bitbuffer::FieldLayout`,
+//! which can be used to generate documentation of the format or in debugging tools.
+//!
+//! The offset of a field is `None` if any field before it doesn't have a fixed size.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, FieldLayout};
+//! #
+//! #[derive(BitRead)]
+//! #[bitbuffer(layout)]
+//! struct Packet {
+//!     #[size = 4]
+//!     kind: u8,
+//!     name: String,
+//!     flags: u16,
+//! }
+//!
+//! let layout = Packet::layout();
+//! assert_eq!(layout[0], FieldLayout { name: "kind", offset: Some(0), size: Some(4) });
+//! assert_eq!(layout[1], FieldLayout { name: "name", offset: Some(4), size: None });
+//! assert_eq!(layout[2], FieldLayout { name: "flags", offset: None, size: Some(16) });
+//! ```
+//!
+//! # Lossy strings
+//!
+//! String fields marked with `#[str(lossy)]` replace invalid utf8 sequences with `U+FFFD` instead of failing the read,
+//! see `BitReadStream::read_string_lossy`.
+//!
+//! ```
+//! # use bitbuffer::BitRead;
+//! #
+//! #[derive(BitRead)]
+//! struct Player {
+//!     #[str(lossy)]
+//!     name: String,
+//!     #[str(lossy)]
+//!     #[size = 16]
+//!     clan: String,
+//! }
+//! ```
+//!
+//! `Option<String>` fields marked with `#[str(nullable)]` are read and written as a string that is absent when it's empty,
+//! instead of the default encoding of an `Option` which is prefixed with a bool,
+//! see `BitReadStream::read_nullable_string`. Writing `Some` with an empty string is an error, since it can't be
+//! distinguished from `None`.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite};
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! struct Player {
+//!     #[str(nullable)]
+//!     nickname: Option<String>,
+//!     name_length: u8,
+//!     #[str(nullable)]
+//!     #[size = "name_length"]
+//!     name: Option<String>,
+//! }
+//! ```
+//!
+//! Fixed length string fields normally only have their trailing null bytes removed, `#[str(first_null)]` ends the string
+//! at the first null byte instead and `#[str(keep_nulls)]` keeps all null bytes, see `BitReadStream::read_fixed_string`.
+//!
+//! ```
+//! # use bitbuffer::BitRead;
+//! #
+//! #[derive(BitRead)]
+//! struct Player {
+//!     #[str(first_null)]
+//!     #[size = 16]
+//!     name: String,
+//!     #[str(keep_nulls)]
+//!     #[size = 8]
+//!     raw: String,
+//! }
+//! ```
+//!
+//! # Custom read and write functions
+//!
+//! Fields that need special handling can be read with a custom function using `#[parse_with = "function"]`
+//! and written using `#[write_with = "function"]`.
+//! The read function is called with the stream and the write function with a reference to the field and the stream.
+//!
+//! Instead of a function name, an expression can be used which has access to the `stream` and, when reading,
+//! the fields read before it or, when writing, the `value` of the field.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitReadStream, BitWrite, BitWriteStream, Endianness, Result};
+//! #
+//! fn read_scaled<E: Endianness>(stream: &mut BitReadStream<E>, scale: u8) -> Result<f32> {
+//!     Ok(stream.read_int::<u16>(12)? as f32 / scale as f32)
+//! }
+//!
+//! fn write_scaled<E: Endianness>(value: &f32, stream: &mut BitWriteStream<E>) -> Result<()> {
+//!     stream.write_int((value * 16.0) as u16, 12)
+//! }
+//!
+//! #[derive(BitRead, BitWrite)]
+//! struct Position {
+//!     scale: u8,
+//!     #[parse_with = "read_scaled(stream, scale)"]
+//!     #[write_with = "write_scaled"]
+//!     x: f32,
+//! }
+//! ```
+//!
+//! ## Custom errors
+//!
+//! By default the custom read and write functions return a `bitbuffer::Result`, with `#[bit_error = "MyError"]` on the type
+//! they return `Result<T, MyError>` instead, allowing them to return domain specific errors. `MyError` needs to implement
+//! `std::error::Error + Send + Sync` and will usually implement `From<BitError>` so the functions can use `?` on reads and writes.
+//!
+//! The derived implementations wrap these errors in [`BitError::Custom`](../bitbuffer/enum.BitError.html#variant.Custom),
+//! `BitReadStream::read_with_error` and `BitWriteStream::write_with_error` return them as the original `MyError`,
+//! converting any other error using `From<BitError>`.
+//!
+//! ```
+//! # use bitbuffer::{BitError, BitRead, BitReadBuffer, BitReadStream, Endianness, LittleEndian};
+//! # use std::fmt;
+//! #
+//! #[derive(Debug)]
+//! enum ProtocolError {
+//!     UnknownOpcode { opcode: u8, pos: usize },
+//!     Bits(BitError),
+//! }
+//!
+//! # impl fmt::Display for ProtocolError {
+//! #     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//! #         write!(f, "{:?}", self)
+//! #     }
+//! # }
+//! #
+//! # impl std::error::Error for ProtocolError {}
+//! #
+//! impl From<BitError> for ProtocolError {
+//!     fn from(err: BitError) -> Self {
+//!         ProtocolError::Bits(err)
+//!     }
+//! }
+//!
+//! fn read_opcode<E: Endianness>(stream: &mut BitReadStream<E>) -> Result<u8, ProtocolError> {
+//!     let pos = stream.pos();
+//!     match stream.read()? {
+//!         opcode @ 0..=3 => Ok(opcode),
+//!         opcode => Err(ProtocolError::UnknownOpcode { opcode, pos }),
+//!     }
+//! }
+//!
+//! #[derive(BitRead)]
+//! #[bit_error = "ProtocolError"]
+//! struct Instruction {
+//!     #[parse_with = "read_opcode"]
+//!     opcode: u8,
+//!     argument: u16,
+//! }
+//!
+//! let bytes = [1, 2, 0, 7, 0, 0];
+//! let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+//! assert!(stream.read_with_error::<Instruction, ProtocolError>().is_ok());
+//! assert!(matches!(
+//!     stream.read_with_error::<Instruction, ProtocolError>(),
+//!     Err(ProtocolError::UnknownOpcode { opcode: 7, pos: 24 })
+//! ));
+//! ```
+//!
+//! # Remaining data
+//!
+//! A field marked with `#[rest]` is read from all remaining bits of the stream, the field type needs to implement `BitReadSized`
+//! with a size in bits or bytes, such as a `BitReadStream`. When writing, the field is written using its `BitWrite` implementation.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitReadStream, BitWrite, Endianness};
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! #[endianness = "E"]
+//! struct Packet<'a, E: Endianness> {
+//!     kind: u8,
+//!     #[rest]
+//!     payload: BitReadStream<'a, E>,
+//! }
+//! ```
+//!
+//! Since a `#[rest]` field leaves nothing for the fields after it, it has to be the last field.
+//!
+//! ```compile_fail
+//! # use bitbuffer::{BitRead, BitReadStream, Endianness};
+//! #
+//! #[derive(BitRead)]
+//! #[endianness = "E"]
+//! struct Packet<'a, E: Endianness> {
+//!     #[rest]
+//!     payload: BitReadStream<'a, E>,
+//!     // error: field can never be read, the '#[rest]' field before it consumes the remainder of the stream
+//!     checksum: u8,
+//! }
+//! ```
+//!
+//! # Maps
+//!
+//! The size of a `HashMap` field sets the number of entries. Keys and values that need a size themselves
+//! can be given one with `#[map(key_size = .., value_size = ..)]`, the sizes can refer to previous fields the same as `#[size]`.
+//! When writing, only the entries are written, the number of entries has to be written by a previous field.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite};
+//! # use std::collections::HashMap;
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! struct Headers {
+//!     count: u8,
+//!     name_length: u8,
+//!     #[size = "count"]
+//!     #[map(key_size = "name_length", value_size = 12)]
+//!     headers: HashMap<String, u16>,
+//! }
+//! ```
+//!
+//! # Flags
+//!
+//! With the `enumflags2` feature of `bitbuffer` enabled, `BitFlags` fields are read as a single integer with one bit per flag.
+//! The number of bits for the set of flags defaults to the size of the flags' numeric type and can be set using `#[flags(bits = 12)]`.
+//!
+//! # Flag enums
+//!
+//! `FlagEnum` can be derived for enums without variant fields where every variant is a single flag bit,
+//! a `FlagSet` of the enum is read as an integer with a bit for every flag instead of a single discriminant.
+//! The number of bits of the set is set with `#[flag_bits = N]` or taken from the `#[repr(..)]` of the enum.
+//! Flags can be combined into a set using `|`.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, FlagEnum, FlagSet};
+//! #
+//! #[derive(FlagEnum, Debug, Clone, Copy)]
+//! #[flag_bits = 4]
+//! enum Modifier {
+//!     Shift = 1,
+//!     Control = 2,
+//!     Alt = 8,
+//! }
+//!
+//! #[derive(BitRead)]
+//! struct KeyPress {
+//!     key: u8,
+//!     modifiers: FlagSet<Modifier>,
+//! }
+//!
+//! let shortcut = Modifier::Control | Modifier::Alt;
+//! assert!(shortcut.contains(Modifier::Alt));
+//! ```
+//!
+//! Every discriminant needs to have a single set bit that fits in the number of bits of the set.
+//!
+//! ```compile_fail
+//! # use bitbuffer::FlagEnum;
+//! #
+//! #[derive(FlagEnum, Clone, Copy)]
+//! #[flag_bits = 4]
+//! enum Modifier {
+//!     Shift = 1,
+//!     // error: the discriminant of flag `Modifier::Control` needs to have a single set bit
+//!     Control = 3,
+//! }
+//! ```
+//!
+//! # Crate path
+//!
+//! The generated code refers to the `bitbuffer` crate as `::bitbuffer`,
+//! when `bitbuffer` is renamed or only available through a re-export, the path to the crate can be set using `#[bitbuffer(crate = "path")]`.
+//!
+//! ```
+//! # use bitbuffer::BitRead;
+//! #
+//! mod facade {
+//!     pub use bitbuffer as bits;
+//! }
+//!
+//! #[derive(BitRead)]
+//! #[bitbuffer(crate = "facade::bits")]
+//! struct Reexported {
+//!     foo: u8,
+//! }
+//! ```
+//!
 //! # Endianness
 //!
 //! If the struct that `BitRead` or `BitReadSized` is derived for requires a Endianness type parameter, you need to tell the derive macro the name of the type parameter used
@@ -164,7 +681,28 @@
 //! }
 //! ```
 //!
+//! The derive doesn't add any bounds for other type parameters, generic fields need bounds on the struct that use the named
+//! endianness parameter. Type parameters can have defaults.
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite, Endianness};
+//! # use std::marker::PhantomData;
+//! #
+//! #[derive(BitRead, BitWrite)]
+//! #[endianness = "E"]
+//! struct Message<'a, E: Endianness, T = u16>
+//! where
+//!     T: BitRead<'a, E> + BitWrite<E>,
+//! {
+//!     id: u8,
+//!     payload: T,
+//!     endianness: PhantomData<&'a E>,
+//! }
+//! ```
+//!
+mod bits;
 mod discriminant;
+mod flag_enum;
 mod params;
 mod read;
 mod size_hint;
@@ -172,11 +710,13 @@ mod write;
 
 extern crate proc_macro;
 
+use crate::flag_enum::FlagEnum;
 use crate::read::{Read, ReadSized};
 use crate::write::{Write, WriteSized};
 use proc_macro2::{Span, TokenStream};
+use quote::quote;
 use std::fmt::Display;
-use syn::{parse_macro_input, DeriveInput, Error, Result};
+use syn::{parse_macro_input, parse_quote, DeriveInput, Error, LitStr, Path, Result};
 
 /// See the [crate documentation](index.html) for details
 #[proc_macro_derive(
@@ -186,9 +726,23 @@ use syn::{parse_macro_input, DeriveInput, Error, Result};
         size,
         size_bits,
         discriminant_bits,
+        discriminant_type,
+        discriminant_repr,
+        payload_size_bits,
         discriminant,
         endianness,
-        align
+        align,
+        byte_swap,
+        flags,
+        until,
+        str,
+        parse_with,
+        write_with,
+        rest,
+        map,
+        field_offsets,
+        unknown,
+        bit_error
     )
 )]
 pub fn derive_bitread(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -203,9 +757,23 @@ pub fn derive_bitread(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         size,
         size_bits,
         discriminant_bits,
+        discriminant_type,
+        discriminant_repr,
+        payload_size_bits,
         discriminant,
         endianness,
-        align
+        align,
+        byte_swap,
+        flags,
+        until,
+        str,
+        parse_with,
+        write_with,
+        rest,
+        map,
+        field_offsets,
+        unknown,
+        bit_error
     )
 )]
 pub fn derive_bitread_sized(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -220,9 +788,23 @@ pub fn derive_bitread_sized(input: proc_macro::TokenStream) -> proc_macro::Token
         size,
         size_bits,
         discriminant_bits,
+        discriminant_type,
+        discriminant_repr,
+        payload_size_bits,
         discriminant,
         endianness,
-        align
+        align,
+        byte_swap,
+        flags,
+        until,
+        str,
+        parse_with,
+        write_with,
+        rest,
+        map,
+        field_offsets,
+        unknown,
+        bit_error
     )
 )]
 pub fn derive_bitwrite(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -237,15 +819,70 @@ pub fn derive_bitwrite(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
         size,
         size_bits,
         discriminant_bits,
+        discriminant_type,
+        discriminant_repr,
+        payload_size_bits,
         discriminant,
         endianness,
-        align
+        align,
+        byte_swap,
+        flags,
+        until,
+        str,
+        parse_with,
+        write_with,
+        rest,
+        map,
+        field_offsets,
+        unknown,
+        bit_error
     )
 )]
 pub fn derive_bitwrite_sized(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     derive_trait::<WriteSized>(input)
 }
 
+/// See the [crate documentation](index.html) for details
+#[proc_macro_derive(FlagEnum, attributes(bitbuffer, flag_bits))]
+pub fn derive_flag_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive_trait::<FlagEnum>(input)
+}
+
+/// Create a [`BitReadBuffer`] containing the bits of a literal string of `0`s and `1`s
+///
+/// The bits are listed in the order they are read from the buffer, whitespace and `_` can be used to group the bits
+/// and are ignored. The length of the buffer is the exact number of bits, which doesn't have to be a multiple of 8.
+/// The endianness of the buffer is inferred from its usage.
+///
+/// ```
+/// # use bitbuffer::{bits, BigEndian, BitReadBuffer, BitReadStream, LittleEndian};
+/// #
+/// let mut stream: BitReadStream<BigEndian> = BitReadStream::new(bits!("1011 0101 011"));
+/// assert_eq!(stream.bit_len(), 11);
+/// assert_eq!(stream.read_int::<u8>(4).unwrap(), 0b1011);
+/// assert_eq!(stream.read_int::<u8>(7).unwrap(), 0b0101_011);
+///
+/// let buffer: BitReadBuffer<LittleEndian> = bits!("1000_0000");
+/// assert_eq!(buffer.read_int::<u8>(0, 8).unwrap(), 1);
+/// ```
+///
+/// Any other character results in a compile error
+///
+/// ```compile_fail
+/// # use bitbuffer::{bits, BigEndian, BitReadBuffer};
+/// #
+/// let buffer: BitReadBuffer<BigEndian> = bits!("0x12");
+/// ```
+///
+/// [`BitReadBuffer`]: https://docs.rs/bitbuffer/latest/bitbuffer/struct.BitReadBuffer.html
+#[proc_macro]
+pub fn bits(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as LitStr);
+    bits::bits(input)
+        .unwrap_or_else(|err| err.into_compile_error())
+        .into()
+}
+
 /// Basic wrapper for error handling
 fn derive_trait<Trait: Derivable>(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: DeriveInput = parse_macro_input!(input as DeriveInput);
@@ -256,7 +893,18 @@ fn derive_trait<Trait: Derivable>(input: proc_macro::TokenStream) -> proc_macro:
 
 fn derive_trait_inner<Trait: Derivable>(input: DeriveInput) -> Result<TokenStream> {
     let params = Trait::Params::parse(&input)?;
-    Trait::derive(params)
+    let crate_path = params
+        .crate_path()
+        .cloned()
+        .unwrap_or_else(|| parse_quote!(::bitbuffer));
+    let tokens = Trait::derive(params)?;
+    // the generated code refers to the crate trough the `__bitbuffer` alias, so the path only has to be set here
+    Ok(quote! {
+        const _: () = {
+            use #crate_path as __bitbuffer;
+            #tokens
+        };
+    })
 }
 
 trait Derivable {
@@ -267,6 +915,9 @@ trait Derivable {
 
 trait DeriveParams: Sized {
     fn parse(input: &DeriveInput) -> Result<Self>;
+
+    /// Custom path to the bitbuffer crate to use in the generated code
+    fn crate_path(&self) -> Option<&Path>;
 }
 
 fn err<R, Msg: Display>(msg: Msg, span: Span) -> Result<R> {
@@ -1,14 +1,21 @@
+use crate::discriminant::Discriminant;
 use crate::params::parse_attrs;
 use crate::params::variant::VariantParam;
+use crate::params::{VariantBody, VariantBodyType};
 use merge::Merge;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
+use quote::quote_spanned;
 use structmeta::StructMeta;
-use syn::{Attribute, DataEnum, Error, LitInt, Result};
+use syn::{Attribute, DataEnum, Error, LitInt, LitStr, Result};
 
 #[derive(Default, StructMeta, Merge, Debug)]
 struct EnumAttrs {
     discriminant_bits: Option<LitInt>,
+    discriminant_type: Option<LitStr>,
+    #[merge(strategy = merge::bool::overwrite_false)]
+    discriminant_repr: bool,
+    payload_size_bits: Option<LitInt>,
 }
 
 pub struct EnumParam {
@@ -16,13 +23,24 @@ pub struct EnumParam {
     pub ident: Ident,
     pub variants: Vec<VariantParam>,
     pub discriminant_bits: usize,
+    /// The integer type of the discriminant, set using `#[discriminant_type = "u16"]`
+    pub discriminant_type: Option<Ident>,
+    /// Convert the discriminant using the `TryFrom` and `Into` implementations of the enum instead of generating match arms
+    pub discriminant_repr: bool,
+    /// The number of bits of the payload length that is written between the discriminant and the variant fields
+    pub payload_size_bits: Option<usize>,
 }
 
+/// The integer types that can be used as `discriminant_type`, with their bit width
+const DISCRIMINANT_TYPES: &[(&str, usize)] = &[("u8", 8), ("u16", 16), ("u32", 32), ("u64", 64)];
+
 impl EnumParam {
     pub fn size_can_be_predicted(&self) -> bool {
-        self.variants
-            .iter()
-            .all(|field| field.size_can_be_predicted())
+        self.payload_size_bits.is_none()
+            && self
+                .variants
+                .iter()
+                .all(|field| field.size_can_be_predicted())
     }
 
     pub fn parse(
@@ -37,21 +55,75 @@ impl EnumParam {
             .iter()
             .map(VariantParam::parse)
             .collect::<Result<Vec<VariantParam>>>()?;
-        let discriminant_bits = attrs
-            .discriminant_bits
-            .ok_or_else(|| {
-                Error::new(
+        let discriminant_type = attrs
+            .discriminant_type
+            .map(|ty| {
+                DISCRIMINANT_TYPES
+                    .iter()
+                    .find(|(name, _)| ty.value() == *name)
+                    .map(|(name, bits)| (Ident::new(name, ty.span()), *bits))
+                    .ok_or_else(|| {
+                        Error::new(
+                            ty.span(),
+                            "'discriminant_type' is required to be one of u8, u16, u32 or u64",
+                        )
+                    })
+            })
+            .transpose()?;
+        let discriminant_bits =
+            match (attrs.discriminant_bits, &discriminant_type) {
+                (Some(bits), _) => bits.base10_parse()?,
+                (None, Some((_, type_bits))) => *type_bits,
+                (None, None) => return Err(Error::new(
                     span,
                     "'discriminant_bits' attribute is required when deriving `BinRead` for enums",
-                )
-            })?
-            .base10_parse()?;
+                )),
+            };
+        if let Some((_, type_bits)) = discriminant_type {
+            if discriminant_bits > type_bits {
+                return Err(Error::new(
+                    span,
+                    "'discriminant_bits' can't be larger than the size of the 'discriminant_type'",
+                ));
+            }
+        }
+
+        if let Some(variant) = variants
+            .iter()
+            .filter(|variant| matches!(variant.discriminant, Discriminant::Wildcard))
+            .nth(1)
+        {
+            return Err(Error::new(
+                variant.span(),
+                "only one catch-all variant, using '#[unknown]' or '#[discriminant = \"_\"]', is allowed",
+            ));
+        }
+
+        if attrs.discriminant_repr {
+            if let Some(variant) = variants
+                .iter()
+                .find(|variant| !matches!(variant.body, VariantBody::Unit))
+            {
+                return Err(Error::new(
+                    variant.span(),
+                    "'discriminant_repr' can only be used for enums without variant fields",
+                ));
+            }
+        }
+
+        let payload_size_bits = attrs
+            .payload_size_bits
+            .map(|bits| bits.base10_parse())
+            .transpose()?;
 
         Ok(EnumParam {
             span,
             ident,
             variants,
             discriminant_bits,
+            discriminant_type: discriminant_type.map(|(ty, _)| ty),
+            discriminant_repr: attrs.discriminant_repr,
+            payload_size_bits,
         })
     }
 
@@ -84,8 +156,44 @@ impl EnumParam {
             .unwrap_or(0)
     }
 
+    /// Match arms mapping every variant to the discriminant written for it
+    pub fn discriminant_match_arms(&self) -> impl Iterator<Item = TokenStream> + '_ {
+        let ident = &self.ident;
+        self.variants
+            .iter()
+            .zip(self.write_discriminant_tokens())
+            .map(move |(variant, discriminant_token)| {
+                let span = variant.span();
+                let variant_name = &variant.variant_name;
+                // unknown variants write the discriminant they were read with
+                if let VariantBody::Unknown(_) = &variant.body {
+                    return quote_spanned! { span =>
+                        #[allow(clippy::useless_conversion)]
+                        #ident::#variant_name(__raw) => ::core::convert::Into::into(*__raw)
+                    };
+                }
+                match variant.body.body_type() {
+                    VariantBodyType::Unit => quote_spanned! {span =>
+                        #ident::#variant_name => #discriminant_token
+                    },
+                    VariantBodyType::Unnamed => {
+                        quote_spanned! { span =>
+                            #ident::#variant_name(_) => #discriminant_token
+                        }
+                    }
+                    VariantBodyType::Named => {
+                        quote_spanned! { span =>
+                            #ident::#variant_name{..} => #discriminant_token
+                        }
+                    }
+                }
+            })
+    }
+
     pub fn discriminant_repr(&self) -> TokenStream {
-        if self.discriminant_bits <= 8 {
+        if let Some(ty) = &self.discriminant_type {
+            quote!(#ty)
+        } else if self.discriminant_bits <= 8 {
             quote!(u8)
         } else if self.discriminant_bits <= 16 {
             quote!(u16)
@@ -1,9 +1,11 @@
-use crate::params::{parse_attrs, Alignment, Size};
+use crate::err;
+use crate::params::{parse_attrs, parse_unit_size, size_expression, Alignment, Size};
 use merge::Merge;
-use proc_macro2::{Ident, Span};
-use structmeta::StructMeta;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote_spanned;
+use structmeta::{NameArgs, NameValue, StructMeta};
 use syn::spanned::Spanned;
-use syn::{Expr, Field, Index, LitInt, Member, Result, Type};
+use syn::{parse_str, Expr, Field, Index, LitInt, LitStr, Member, Path, Result, Type};
 
 #[derive(Default, StructMeta, Merge)]
 struct FieldAttrs {
@@ -11,6 +13,73 @@ struct FieldAttrs {
     size_bits: Option<LitInt>,
     #[merge(strategy = merge::bool::overwrite_false)]
     align: bool,
+    byte_swap: Option<NameValue<Option<LitInt>>>,
+    until: Option<LitStr>,
+    str: Option<NameArgs<StrAttrs>>,
+    parse_with: Option<LitStr>,
+    write_with: Option<LitStr>,
+    #[merge(strategy = merge::bool::overwrite_false)]
+    rest: bool,
+    map: Option<NameArgs<MapAttrs>>,
+}
+
+#[derive(StructMeta)]
+struct StrAttrs {
+    lossy: bool,
+    nullable: bool,
+    first_null: bool,
+    keep_nulls: bool,
+}
+
+#[derive(StructMeta)]
+struct MapAttrs {
+    key_size: Expr,
+    value_size: Expr,
+}
+
+/// Separate sizes for the keys and values of a map field, the size of the field sets the number of entries
+pub struct MapSizes {
+    pub key_size: Size,
+    pub value_size: Size,
+}
+
+/// Swap the bytes of the field after reading and before writing
+pub struct ByteSwap {
+    /// Only swap the bytes within groups of this many bytes, swaps all bytes if not set
+    group_size: Option<usize>,
+    span: Span,
+}
+
+impl ByteSwap {
+    pub fn tokens(&self, ty: &Type, value: TokenStream) -> TokenStream {
+        let span = self.span;
+        let group_size = match self.group_size {
+            Some(group_size) => quote_spanned!(span => #group_size),
+            None => quote_spanned!(span => ::std::mem::size_of::<#ty>()),
+        };
+        quote_spanned! { span =>
+            __bitbuffer::ByteSwap::byte_swap(#value, #group_size)
+        }
+    }
+}
+
+/// A user supplied function or expression for reading or writing a field
+pub enum CustomFn {
+    /// Path to a function that is called with the stream
+    Path(Path),
+    /// Expression that is evaluated with the stream bound
+    Expr(TokenStream),
+}
+
+impl CustomFn {
+    fn parse(lit: LitStr) -> Result<Self> {
+        // parse as tokens since parsing expressions like closures requires syn's "full" feature
+        let tokens: TokenStream = lit.parse()?;
+        Ok(match syn::parse2::<Path>(tokens.clone()) {
+            Ok(path) => CustomFn::Path(path),
+            Err(_) => CustomFn::Expr(tokens),
+        })
+    }
 }
 
 pub struct FieldParam {
@@ -19,26 +88,204 @@ pub struct FieldParam {
     pub size: Option<Size>,
     pub align: Alignment,
     pub ty: Type,
+    pub byte_swap: Option<ByteSwap>,
+    /// Condition for stopping to read elements into a `Vec`, evaluated after every element
+    pub until: Option<Expr>,
+    /// Replace invalid utf8 sequences in string fields instead of returning an error
+    pub lossy: bool,
+    /// Read and write an `Option<String>` as a string that is absent when empty
+    pub nullable: bool,
+    /// The `NullPadding` variant used to read a fixed length string
+    pub null_padding: Option<Ident>,
+    /// Custom function or expression used to read the field
+    pub parse_with: Option<CustomFn>,
+    /// Custom function or expression used to write the field
+    pub write_with: Option<CustomFn>,
+    /// Read the field from all remaining bits of the stream
+    pub rest: bool,
+    /// Sizes for the keys and values of a map field
+    pub map_sizes: Option<MapSizes>,
+    /// The error type returned by the `parse_with` and `write_with` functions, set by `#[bit_error]` on the type
+    pub custom_error: Option<Type>,
+    /// The const generic parameters of the type, sizes using only these can be predicted
+    pub const_params: Vec<Ident>,
 }
 
 impl FieldParam {
     /// Whether the size of the field can be determined without having to read further bits
     pub fn size_can_be_predicted(&self) -> bool {
-        if self.align == Alignment::Auto {
+        // nullable strings don't have the size of the `Option` they're read into
+        if self.align == Alignment::Auto
+            || self.until.is_some()
+            || self.parse_with.is_some()
+            || self.nullable
+            || self.rest
+            || self.map_sizes.is_some()
+        {
             return false;
         }
         match &self.size {
-            Some(size) => size.is_const(),
+            Some(size) => size.is_const(&self.const_params),
             None => true,
         }
     }
 
     pub fn parse(input: &Field) -> Result<FieldParam> {
-        let attrs: FieldAttrs = parse_attrs(&input.attrs)?;
+        let (unit_size, attrs) = parse_unit_size(&input.attrs)?;
+        let attrs: FieldAttrs = parse_attrs(&attrs)?;
         let field_name = input.ident.clone();
         let align = attrs.align.into();
-        let size = Size::from_attrs(attrs.size, attrs.size_bits, input.span())?;
+        let size = Size::from_attrs(attrs.size, attrs.size_bits, unit_size, input.span())?;
         let ty = input.ty.clone();
+        let byte_swap = attrs
+            .byte_swap
+            .map(|byte_swap| {
+                Ok::<_, syn::Error>(ByteSwap {
+                    group_size: byte_swap
+                        .value
+                        .map(|group_size| group_size.base10_parse())
+                        .transpose()?,
+                    span: byte_swap.name_span,
+                })
+            })
+            .transpose()?;
+        if byte_swap.is_some() && size.is_some() {
+            return err(
+                "'byte_swap' attribute can't be combined with a size",
+                input.span(),
+            );
+        }
+
+        let until = attrs
+            .until
+            .map(|until| parse_str::<Expr>(&until.value()))
+            .transpose()?;
+        if until.is_some() && (size.is_some() || byte_swap.is_some()) {
+            return err(
+                "'until' attribute can't be combined with a size or 'byte_swap'",
+                input.span(),
+            );
+        }
+
+        let lossy = attrs
+            .str
+            .as_ref()
+            .map(|attrs| attrs.args.lossy)
+            .unwrap_or_default();
+        let nullable = attrs
+            .str
+            .as_ref()
+            .map(|attrs| attrs.args.nullable)
+            .unwrap_or_default();
+        let null_padding = match attrs.str.as_ref().map(|attrs| &attrs.args) {
+            Some(StrAttrs {
+                first_null: true,
+                keep_nulls: true,
+                ..
+            }) => {
+                return err(
+                    "'str(first_null)' and 'str(keep_nulls)' can't be combined",
+                    input.span(),
+                );
+            }
+            Some(StrAttrs {
+                first_null: true, ..
+            }) => Some(Ident::new("FirstNull", input.span())),
+            Some(StrAttrs {
+                keep_nulls: true, ..
+            }) => Some(Ident::new("Keep", input.span())),
+            _ => None,
+        };
+        if null_padding.is_some() && (size.is_none() || lossy || nullable) {
+            return err(
+                "'str(first_null)' and 'str(keep_nulls)' require a size and can't be combined with 'str(lossy)' or 'str(nullable)'",
+                input.span(),
+            );
+        }
+        if (lossy || nullable || null_padding.is_some()) && (until.is_some() || byte_swap.is_some())
+        {
+            return err(
+                "'str' attribute can't be combined with 'until' or 'byte_swap'",
+                input.span(),
+            );
+        }
+        if lossy && nullable {
+            return err(
+                "'str(lossy)' and 'str(nullable)' can't be combined",
+                input.span(),
+            );
+        }
+
+        let parse_with = attrs.parse_with.map(CustomFn::parse).transpose()?;
+        let write_with = attrs.write_with.map(CustomFn::parse).transpose()?;
+        if (parse_with.is_some() || write_with.is_some())
+            && (size.is_some()
+                || until.is_some()
+                || byte_swap.is_some()
+                || lossy
+                || nullable
+                || null_padding.is_some())
+        {
+            return err(
+                "'parse_with' and 'write_with' attributes can't be combined with a size, 'until', 'byte_swap' or 'str'",
+                input.span(),
+            );
+        }
+
+        let rest = attrs.rest;
+        if rest {
+            let conflict = if size.is_some() {
+                Some("a size")
+            } else if until.is_some() {
+                Some("'until'")
+            } else if byte_swap.is_some() {
+                Some("'byte_swap'")
+            } else if lossy {
+                Some("'str(lossy)'")
+            } else if nullable {
+                Some("'str(nullable)'")
+            } else if parse_with.is_some() {
+                Some("'parse_with'")
+            } else {
+                None
+            };
+            if let Some(conflict) = conflict {
+                return err(
+                    format!("'rest' attribute can't be combined with {}", conflict),
+                    input.span(),
+                );
+            }
+        }
+
+        let map_sizes = attrs
+            .map
+            .map(|map| {
+                let span = map.name_span;
+                Ok::<_, syn::Error>(MapSizes {
+                    key_size: Size::Expression(size_expression(map.args.key_size)?, span),
+                    value_size: Size::Expression(size_expression(map.args.value_size)?, span),
+                })
+            })
+            .transpose()?;
+        if map_sizes.is_some() && size.is_none() {
+            return err(
+                "'map' attribute requires a size for the number of entries",
+                input.span(),
+            );
+        }
+        if map_sizes.is_some()
+            && (byte_swap.is_some()
+                || lossy
+                || nullable
+                || null_padding.is_some()
+                || write_with.is_some()
+                || rest)
+        {
+            return err(
+                "'map' attribute can't be combined with 'byte_swap', 'str', 'write_with' or 'rest'",
+                input.span(),
+            );
+        }
 
         Ok(FieldParam {
             span: input.span(),
@@ -46,13 +293,63 @@ impl FieldParam {
             size,
             align,
             ty,
+            byte_swap,
+            until,
+            lossy,
+            nullable,
+            null_padding,
+            parse_with,
+            write_with,
+            rest,
+            map_sizes,
+            custom_error: None,
+            const_params: Vec::new(),
         })
     }
 
+    /// Ensure no fields follow a field that consumes the remainder of the stream,
+    /// since those fields could never be read
+    pub fn check_rest(fields: &[FieldParam]) -> Result<()> {
+        let mut fields = fields.iter().skip_while(|field| !field.rest);
+        match (fields.next(), fields.next()) {
+            (Some(_), Some(unreachable)) => err(
+                "field can never be read, the '#[rest]' field before it consumes the remainder of the stream",
+                unreachable.span(),
+            ),
+            _ => Ok(()),
+        }
+    }
+
     pub fn span(&self) -> Span {
         self.span
     }
 
+    /// Whether the field is read and written without any of the field attributes
+    pub fn is_plain(&self) -> bool {
+        self.size.is_none()
+            && self.align == Alignment::None
+            && self.byte_swap.is_none()
+            && self.until.is_none()
+            && !self.lossy
+            && !self.nullable
+            && self.null_padding.is_none()
+            && self.parse_with.is_none()
+            && self.write_with.is_none()
+            && self.map_sizes.is_none()
+    }
+
+    /// Tokens for the size as `Option<usize>`, for fields that are either sized or have a dynamic size
+    pub fn optional_size_tokens(&self) -> TokenStream {
+        let span = self.span;
+        match &self.size {
+            Some(size) => {
+                let size = size.tokens(&self.ty);
+                quote_spanned!(span => Some(#size))
+            }
+            None => quote_spanned!(span => None),
+        }
+    }
+
     pub fn member(&self, index: u32) -> Member {
         match self.field_name.as_ref() {
             Some(name) => Member::Named(name.clone()),
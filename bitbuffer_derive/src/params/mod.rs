@@ -3,7 +3,7 @@ mod field;
 mod r#struct;
 mod variant;
 
-pub use crate::params::field::FieldParam;
+pub use crate::params::field::{CustomFn, FieldParam};
 pub use crate::params::r#enum::EnumParam;
 pub use crate::params::r#struct::StructParam;
 pub use crate::params::variant::{VariantBody, VariantBodyType, VariantParam};
@@ -21,24 +21,21 @@ use syn::token::Paren;
 use syn::{
     parse_quote, parse_str, Attribute, Data, DeriveInput, Expr, ExprLit, ExprPath, GenericParam,
     Generics, ImplGenerics, Lifetime, Lit, LitBool, LitInt, LitStr, MacroDelimiter, Meta, MetaList,
-    Result, TypeGenerics, WhereClause,
+    Path, Result, Type, TypeGenerics, WhereClause,
 };
 
 pub enum Size {
     Expression(Expr, Span),
     Bits(usize, Span),
+    /// A size with an explicit unit, converted into the size unit of the field type
+    Unit(Ident, Expr, Span),
 }
 
 impl Size {
-    pub fn is_const(&self) -> bool {
+    /// Whether the size is known without reading, `const_params` are the const generic parameters of the type
+    pub fn is_const(&self, const_params: &[Ident]) -> bool {
         match self {
-            Size::Expression(
-                Expr::Lit(ExprLit {
-                    lit: Lit::Int(_), ..
-                }),
-                _,
-            ) => true,
-            Size::Expression(Expr::Path(ExprPath { path, .. }), _) => path.is_ident("input_size"),
+            Size::Expression(expr, _) | Size::Unit(_, expr, _) => is_const_expr(expr, const_params),
             _ => false,
         }
     }
@@ -46,47 +43,135 @@ impl Size {
     pub fn from_attrs(
         size: Option<Expr>,
         size_bits: Option<LitInt>,
+        unit_size: Option<Size>,
         span: Span,
     ) -> Result<Option<Self>> {
-        Ok(match (size, size_bits) {
-            (
-                Some(Expr::Lit(ExprLit {
-                    lit: Lit::Str(field),
-                    ..
-                })),
-                None,
-            ) => Some(Size::Expression(parse_str(&field.value())?, span)),
-            (Some(size), None) => Some(Size::Expression(size, span)),
-            (None, Some(bits)) => Some(Size::Bits(bits.base10_parse()?, span)),
-            (Some(_), Some(_)) => err("#[size] and #[size_bits] are mutually exclusive", span)?,
-            (None, None) => None,
+        Ok(match (size, size_bits, unit_size) {
+            (Some(size), None, None) => Some(Size::Expression(size_expression(size)?, span)),
+            (None, Some(bits), None) => Some(Size::Bits(bits.base10_parse()?, span)),
+            (None, None, Some(unit_size)) => Some(unit_size),
+            (None, None, None) => None,
+            _ => err(
+                "#[size], #[size(..)] and #[size_bits] are mutually exclusive",
+                span,
+            )?,
         })
     }
-}
 
-impl ToTokens for Size {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+    /// Tokens for the size as `usize`, converting explicit units into the size unit of `ty`
+    pub fn tokens(&self, ty: &Type) -> TokenStream {
+        self.tokens_with_error(ty, quote!(?))
+    }
+
+    /// Tokens for the size as `usize` in a context that returns an `Option`
+    pub fn hint_tokens(&self, ty: &Type) -> TokenStream {
+        self.tokens_with_error(ty, quote!(.ok()?))
+    }
+
+    fn tokens_with_error(&self, ty: &Type, error_handle: TokenStream) -> TokenStream {
         match self {
             Size::Expression(expr, span) => {
                 let span = *span;
-                tokens.append_all(quote_spanned! {span => {
+                quote_spanned! {span => {
                         #[allow(clippy::unnecessary_cast)]
                         let __size = (#expr) as usize;
                         __size
                     }
-                });
+                }
             }
             Size::Bits(bits, span) => {
                 let span = *span;
-                tokens.append_all(quote_spanned! {span => {
+                quote_spanned! {span => {
                         __stream.read_int::<usize>(#bits)?
                     }
-                });
+                }
             }
+            Size::Unit(unit, expr, span) => {
+                let span = *span;
+                quote_spanned! {span => {
+                        #[allow(clippy::unnecessary_cast)]
+                        let __size = (#expr) as usize;
+                        __bitbuffer::SizeUnit::#unit.convert(__size, <#ty as __bitbuffer::HasSizeUnit>::SIZE_UNIT)#error_handle
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether an expression only uses integer literals, the input size and const generic parameters
+fn is_const_expr(expr: &Expr, const_params: &[Ident]) -> bool {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(_), ..
+        }) => true,
+        Expr::Path(ExprPath { path, .. }) => {
+            path.is_ident("input_size") || const_params.iter().any(|param| path.is_ident(param))
+        }
+        Expr::Binary(binary) => {
+            is_const_expr(&binary.left, const_params) && is_const_expr(&binary.right, const_params)
         }
+        Expr::Paren(paren) => is_const_expr(&paren.expr, const_params),
+        Expr::Group(group) => is_const_expr(&group.expr, const_params),
+        _ => false,
     }
 }
 
+/// Allow using a string literal to refer to a field or input size, e.g. `#[size = "length"]`
+fn size_expression(expr: Expr) -> Result<Expr> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(field),
+            ..
+        }) => parse_str(&field.value()),
+        expr => Ok(expr),
+    }
+}
+
+#[derive(StructMeta)]
+struct SizeUnitAttrs {
+    bits: Option<Expr>,
+    bytes: Option<Expr>,
+    count: Option<Expr>,
+}
+
+/// Parse the `#[size(bits = ..)]`, `#[size(bytes = ..)]` and `#[size(count = ..)]` forms of the
+/// size attribute and the `#[flags(bits = ..)]` attribute, returning the remaining attributes.
+fn parse_unit_size(attrs: &[Attribute]) -> Result<(Option<Size>, Vec<Attribute>)> {
+    let mut size = None;
+    let mut rest = Vec::with_capacity(attrs.len());
+    for attr in attrs {
+        let is_flags = attr.path().is_ident("flags");
+        if !((attr.path().is_ident("size") || is_flags) && matches!(attr.meta, Meta::List(_))) {
+            rest.push(attr.clone());
+            continue;
+        }
+        let span = attr.span();
+        if size.is_some() {
+            return err("only a single size can be set", span);
+        }
+        let unit_attrs: SizeUnitAttrs = attr.parse_args()?;
+        let (unit, expr) = match (unit_attrs.bits, unit_attrs.bytes, unit_attrs.count) {
+            (Some(bits), None, None) => ("Bits", bits),
+            (None, Some(bytes), None) if !is_flags => ("Bytes", bytes),
+            (None, None, Some(count)) if !is_flags => ("Count", count),
+            _ if is_flags => return err("'bits' is required for #[flags(..)]", span),
+            _ => {
+                return err(
+                    "exactly one of 'bits', 'bytes' or 'count' is required for #[size(..)]",
+                    span,
+                )
+            }
+        };
+        size = Some(Size::Unit(
+            Ident::new(unit, span),
+            size_expression(expr)?,
+            span,
+        ));
+    }
+    Ok((size, rest))
+}
+
 #[derive(Default, PartialOrd, PartialEq, Copy, Clone, Debug)]
 pub enum Alignment {
     #[default]
@@ -144,6 +229,13 @@ struct InputAttrs {
     endianness: Option<LitStr>,
     #[merge(strategy = merge::bool::overwrite_false)]
     align: bool,
+    #[merge(strategy = merge::bool::overwrite_false)]
+    field_offsets: bool,
+    #[merge(strategy = merge::bool::overwrite_false)]
+    layout: bool,
+    #[struct_meta(name = "crate")]
+    crate_path: Option<LitStr>,
+    bit_error: Option<LitStr>,
 }
 
 pub struct InputParams {
@@ -155,6 +247,12 @@ pub struct InputParams {
     pub generics_with_endianness: Generics,
     pub inner: InputInnerParams,
     pub lifetime: Lifetime,
+    /// Generate accessors for reading single fields at their offset
+    pub field_offsets: bool,
+    /// Generate a `layout()` method listing the offset and size of every field
+    pub layout: bool,
+    /// Path to the bitbuffer crate if it's not available as `::bitbuffer`
+    pub crate_path: Option<Path>,
 }
 
 pub enum InputInnerParams {
@@ -165,7 +263,7 @@ pub enum InputInnerParams {
 impl DeriveParams for InputParams {
     fn parse(input: &DeriveInput) -> Result<Self> {
         let attrs: InputAttrs = parse_attrs(&input.attrs)?;
-        let inner = match &input.data {
+        let mut inner = match &input.data {
             Data::Struct(data) => InputInnerParams::Struct(StructParam::parse(
                 data,
                 input.ident.clone(),
@@ -181,8 +279,38 @@ impl DeriveParams for InputParams {
             _ => return err("Only structs and enums are supported", input.span()),
         };
 
+        if let Some(bit_error) = attrs.bit_error {
+            let error = bit_error.parse::<Type>()?;
+            inner.for_each_field(|field| field.custom_error = Some(error.clone()));
+        }
+
+        let const_params: Vec<Ident> = input
+            .generics
+            .const_params()
+            .map(|param| param.ident.clone())
+            .collect();
+        if !const_params.is_empty() {
+            inner.for_each_field(|field| field.const_params = const_params.clone());
+        }
+
         let endianness = attrs.endianness.map(|lit| lit.value());
+        let crate_path = attrs
+            .crate_path
+            .map(|lit| lit.parse::<Path>())
+            .transpose()?;
         let align = attrs.align.into();
+        if attrs.field_offsets && !matches!(inner, InputInnerParams::Struct(_)) {
+            return err(
+                "'field_offsets' attribute is only supported for structs",
+                input.span(),
+            );
+        }
+        if attrs.layout && !matches!(inner, InputInnerParams::Struct(_)) {
+            return err(
+                "'layout' attribute is only supported for structs",
+                input.span(),
+            );
+        }
 
         let generics = input.generics.clone();
         let mut generics_with_endianness = generics.clone();
@@ -211,7 +339,7 @@ impl DeriveParams for InputParams {
         if endianness.is_none() {
             generics_with_endianness
                 .params
-                .push(parse_quote!(_E: ::bitbuffer::Endianness));
+                .push(parse_quote!(_E: __bitbuffer::Endianness));
         }
 
         Ok(InputParams {
@@ -223,8 +351,31 @@ impl DeriveParams for InputParams {
             generics_with_endianness,
             lifetime,
             inner,
+            field_offsets: attrs.field_offsets,
+            layout: attrs.layout,
+            crate_path,
         })
     }
+
+    fn crate_path(&self) -> Option<&Path> {
+        self.crate_path.as_ref()
+    }
+}
+
+impl InputInnerParams {
+    /// Apply `f` to the fields of the struct or all enum variants
+    fn for_each_field(&mut self, mut f: impl FnMut(&mut FieldParam)) {
+        match self {
+            InputInnerParams::Struct(inner) => inner.fields.iter_mut().for_each(f),
+            InputInnerParams::Enum(inner) => {
+                for variant in &mut inner.variants {
+                    if let VariantBody::Fields(fields) = &mut variant.body {
+                        fields.iter_mut().for_each(&mut f);
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl InputParams {
@@ -236,7 +387,7 @@ impl InputParams {
         }
     }
 
-    pub fn generics_for_impl(&self) -> (ImplGenerics, TypeGenerics, Option<&WhereClause>) {
+    pub fn generics_for_impl(&self) -> (ImplGenerics<'_>, TypeGenerics<'_>, Option<&WhereClause>) {
         // we need these separate generics to only add out Endianness param to the 'impl'
         let (_, ty_generics, where_clause) = self.generics.split_for_impl();
         let (impl_generics, _, _) = self.generics_with_endianness.split_for_impl();
@@ -244,21 +395,65 @@ impl InputParams {
         (impl_generics, ty_generics, where_clause)
     }
 
+    /// The field of a `#[repr(transparent)]` struct with a single field, reads and writes are forwarded to the field
+    pub fn transparent_field(&self) -> Option<&FieldParam> {
+        match &self.inner {
+            InputInnerParams::Struct(inner)
+                if inner.transparent && self.align == Alignment::None =>
+            {
+                inner.fields.first()
+            }
+            _ => None,
+        }
+    }
+
+    /// The generic params that are added to the generics of the type for the implementation
+    pub fn added_generics(&self) -> impl Iterator<Item = &GenericParam> {
+        self.generics_with_endianness
+            .params
+            .iter()
+            .skip(self.generics.params.len())
+    }
+
     pub fn endianness(&self) -> Ident {
         Ident::new(self.endianness.as_deref().unwrap_or("_E"), self.span)
     }
+
+    /// The endianness for code outside of the trait implementations, where the generic endianness isn't available
+    ///
+    /// Sizes don't depend on the endianness, so little endian is used if the type doesn't have a fixed endianness.
+    pub fn fixed_endianness(&self) -> TokenStream {
+        match &self.endianness {
+            Some(endianness) => Ident::new(endianness, self.span).to_token_stream(),
+            None => quote!(__bitbuffer::LittleEndian),
+        }
+    }
 }
 
 const BARE_ATTRS: &[&str] = &[
     "size",
     "size_bits",
     "discriminant_bits",
+    "discriminant_type",
+    "discriminant_repr",
+    "payload_size_bits",
     "discriminant",
     "endianness",
     "align",
+    "byte_swap",
+    "until",
+    "str",
+    "parse_with",
+    "write_with",
+    "rest",
+    "field_offsets",
+    "unknown",
+    "bit_error",
+    "flag_bits",
+    "map",
 ];
 
-fn parse_attrs<T: Parse + Default + Merge>(attrs: &[Attribute]) -> Result<T> {
+pub fn parse_attrs<T: Parse + Default + Merge>(attrs: &[Attribute]) -> Result<T> {
     let mut result = T::default();
     for attr in attrs {
         let parsed = if BARE_ATTRS
@@ -279,8 +474,11 @@ fn parse_attrs<T: Parse + Default + Merge>(attrs: &[Attribute]) -> Result<T> {
                 meta: wrapped_meta,
             };
             wrapped.parse_args()
-        } else {
+        } else if attr.path().is_ident("bitbuffer") {
             attr.parse_args()
+        } else {
+            // attributes for other derives or the compiler, like doc comments
+            continue;
         };
         match parsed {
             Ok(parsed) => {
@@ -7,6 +7,8 @@ pub struct StructParam {
     pub ident: Ident,
     pub fields: Vec<FieldParam>,
     pub is_unit: bool,
+    /// Single field `#[repr(transparent)]` struct that forwards reads and writes to its field
+    pub transparent: bool,
 }
 
 impl StructParam {
@@ -19,7 +21,7 @@ impl StructParam {
     pub fn parse(
         data: &DataStruct,
         ident: Ident,
-        _attrs: &[Attribute],
+        attrs: &[Attribute],
         span: Span,
     ) -> Result<StructParam> {
         let fields = data
@@ -27,14 +29,22 @@ impl StructParam {
             .iter()
             .map(FieldParam::parse)
             .collect::<Result<Vec<FieldParam>>>()?;
+        FieldParam::check_rest(&fields)?;
 
         let is_unit = matches!(data.fields, Fields::Unit);
+        let is_repr_transparent = attrs.iter().any(|attr| {
+            attr.path().is_ident("repr")
+                && matches!(attr.parse_args::<Ident>(), Ok(repr) if repr == "transparent")
+        });
+        let transparent =
+            is_repr_transparent && matches!(fields.as_slice(), [field] if field.is_plain());
 
         Ok(StructParam {
             span,
             ident,
             fields,
             is_unit,
+            transparent,
         })
     }
 
@@ -1,13 +1,13 @@
 use crate::discriminant::Discriminant;
 use crate::err;
 use crate::params::field::FieldParam;
-use crate::params::{parse_attrs, Alignment, Size};
+use crate::params::{parse_attrs, parse_unit_size, Alignment, Size};
 use merge::Merge;
 use proc_macro2::{Ident, Span};
 use std::convert::TryFrom;
 use structmeta::StructMeta;
 use syn::spanned::Spanned;
-use syn::{Expr, ExprLit, Fields, Lit, LitInt, Result, Variant};
+use syn::{Expr, ExprLit, Fields, Lit, LitInt, Result, Type, Variant};
 
 #[derive(Default, StructMeta, Merge)]
 struct VariantAttrs {
@@ -16,6 +16,8 @@ struct VariantAttrs {
     #[merge(strategy = merge::bool::overwrite_false)]
     align: bool,
     discriminant: Option<Lit>,
+    #[merge(strategy = merge::bool::overwrite_false)]
+    unknown: bool,
 }
 
 pub struct VariantParam {
@@ -34,12 +36,15 @@ pub enum VariantBodyType {
 pub enum VariantBody {
     Unit,
     Fields(Vec<FieldParam>),
+    /// Catch-all variant storing the raw discriminant in a field of the given type
+    Unknown(Type),
 }
 
 impl VariantBody {
     pub fn body_type(&self) -> VariantBodyType {
         match self {
             VariantBody::Unit => VariantBodyType::Unit,
+            VariantBody::Unknown(_) => VariantBodyType::Unnamed,
             VariantBody::Fields(fields) => {
                 let named = fields.iter().any(|f| f.field_name.is_some());
                 if named {
@@ -57,15 +62,16 @@ impl VariantParam {
     pub fn size_can_be_predicted(&self) -> bool {
         match &self.body {
             VariantBody::Fields(fields) => fields.iter().all(|field| field.size_can_be_predicted()),
-            VariantBody::Unit => true,
+            VariantBody::Unit | VariantBody::Unknown(_) => true,
         }
     }
 
     pub fn parse(input: &Variant) -> Result<VariantParam> {
-        let attrs: VariantAttrs = parse_attrs(&input.attrs)?;
+        let (unit_size, attrs) = parse_unit_size(&input.attrs)?;
+        let attrs: VariantAttrs = parse_attrs(&attrs)?;
         let variant_name = input.ident.clone();
         let align = attrs.align.into();
-        let size = Size::from_attrs(attrs.size, attrs.size_bits, input.span())?;
+        let size = Size::from_attrs(attrs.size, attrs.size_bits, unit_size, input.span())?;
 
         if attrs.discriminant.is_some() && input.discriminant.is_some() {
             err(
@@ -74,6 +80,43 @@ impl VariantParam {
             )?;
         }
 
+        if attrs.unknown {
+            if attrs.discriminant.is_some() || input.discriminant.is_some() {
+                err(
+                    "'unknown' variants match all unknown discriminants and can't have a discriminant set",
+                    input.span(),
+                )?;
+            }
+            if align == Alignment::Auto || size.is_some() {
+                err(
+                    "'align' and 'size' attributes are not allowed on 'unknown' variants",
+                    input.span(),
+                )?;
+            }
+            let ty = match &input.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let field = &fields.unnamed[0];
+                    if !FieldParam::parse(field)?.is_plain() {
+                        err(
+                            "the field of an 'unknown' variant can't have any attributes",
+                            field.span(),
+                        )?;
+                    }
+                    field.ty.clone()
+                }
+                _ => err(
+                    "'unknown' variants need a single unnamed field to store the discriminant",
+                    input.span(),
+                )?,
+            };
+            return Ok(VariantParam {
+                span: input.span(),
+                variant_name,
+                discriminant: Discriminant::Wildcard,
+                body: VariantBody::Unknown(ty),
+            });
+        }
+
         let discriminant = attrs
             .discriminant
             .map(|lit| {
@@ -112,6 +155,7 @@ impl VariantParam {
                 .iter()
                 .map(FieldParam::parse)
                 .collect::<Result<Vec<FieldParam>>>()?;
+            FieldParam::check_rest(&fields)?;
 
             // align and size attributes on the variant go to the first field
             if let (Some(field), Alignment::Auto) = (fields.first_mut(), align) {
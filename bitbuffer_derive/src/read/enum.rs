@@ -1,6 +1,6 @@
 use crate::params::{EnumParam, VariantBody};
 use crate::read::field::read_struct_or_enum;
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::TokenStream;
 use quote::quote_spanned;
 use syn::Path;
 
@@ -25,46 +25,93 @@ pub fn derive_encode_enum(params: &EnumParam, unchecked: bool) -> TokenStream {
                 VariantBody::Unit => quote_spanned! { span =>
                     Ok(#ident::#variant_name)
                 },
-                VariantBody::Fields(fields) => {
-                    read_struct_or_enum(&variant_path, fields, span, unchecked)
-                }
+                VariantBody::Unknown(ty) => quote_spanned! { span =>
+                    #[allow(clippy::useless_conversion)]
+                    let __raw = <#ty as ::core::convert::From<#repr>>::from(discriminant);
+                    Ok(#ident::#variant_name(__raw))
+                },
+                VariantBody::Fields(fields) => read_struct_or_enum(
+                    &variant_path,
+                    fields,
+                    span,
+                    // the payload is read from a separate stream that isn't covered by the checked read
+                    unchecked && params.payload_size_bits.is_none(),
+                ),
             };
 
             quote_spanned! {span=>
-                #discriminant_token => #read_variant,
+                #discriminant_token => { #read_variant },
             }
         });
 
-    let read_fn = Ident::new(
-        if unchecked {
-            "read_int_unchecked"
+    let name = ident.to_string();
+
+    // read the variant fields from a sub stream of the payload length, skipping any unread bits
+    let payload = params.payload_size_bits.map(|payload_size_bits| {
+        quote_spanned! {span =>
+            let __length: usize = __stream.read_int(#payload_size_bits)?;
+            let mut __payload = __stream.read_bits(__length)?;
+            let __stream = &mut __payload;
+        }
+    });
+
+    if unchecked {
+        // the unchecked read can't go through `read_enum_raw`, build the error here
+        let unmatched = quote_spanned! {span =>
+            {
+                #[allow(clippy::unnecessary_cast)]
+                let __discriminant = discriminant as usize;
+                #[allow(clippy::unnecessary_cast)]
+                let __discriminant_bits = #discriminant_bits as usize;
+                __bitbuffer::BitError::UnmatchedDiscriminant {
+                    discriminant: __discriminant,
+                    enum_name: #name,
+                    pos: __discriminant_pos,
+                    discriminant_bits: __discriminant_bits,
+                }
+            }
+        };
+        let body = if params.discriminant_repr {
+            quote_spanned! {span =>
+                <Self as ::core::convert::TryFrom<#repr>>::try_from(discriminant).map_err(|_| #unmatched)
+            }
         } else {
-            "read_int"
-        },
-        span,
-    );
-    let end_param = if unchecked {
-        Some(quote_spanned!(span => end))
-    } else {
-        None
-    };
-    let error_handle = if unchecked {
-        None
+            quote_spanned! {span =>
+                match discriminant {
+                    #(#match_arms)*
+                    _ => Err(#unmatched),
+                }
+            }
+        };
+        return quote_spanned! {span =>
+            let __discriminant_pos = __stream.pos();
+            #[allow(clippy::unnecessary_cast)]
+            let discriminant:#repr = __stream.read_int_unchecked(#discriminant_bits as usize, __checked);
+            #payload
+            #body
+        };
+    }
+
+    let body = if params.discriminant_repr {
+        quote_spanned! {span =>
+            Ok(<Self as ::core::convert::TryFrom<#repr>>::try_from(discriminant).ok())
+        }
     } else {
-        Some(quote_spanned!(span => ?))
+        quote_spanned! {span =>
+            let __variant: __bitbuffer::Result<Self> = match discriminant {
+                #(#match_arms)*
+                _ => return Ok(None),
+            };
+            __variant.map(Some)
+        }
     };
 
-    let name = ident.to_string();
-
     quote_spanned! {span =>
         #[allow(clippy::unnecessary_cast)]
-        let discriminant:#repr = __stream.#read_fn(#discriminant_bits as usize, #end_param)#error_handle;
-        match discriminant {
-            #(#match_arms)*
-            _ => {
-                #[allow(clippy::unnecessary_cast)]
-                return Err(::bitbuffer::BitError::UnmatchedDiscriminant{discriminant: discriminant as usize, enum_name: #name.to_string()})
-            }
-        }
+        let __discriminant_bits = #discriminant_bits as usize;
+        __stream.read_enum_raw::<#repr, Self, _>(__discriminant_bits, #name, |__stream, discriminant| {
+            #payload
+            #body
+        })
     }
 }
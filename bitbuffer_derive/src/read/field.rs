@@ -1,4 +1,4 @@
-use crate::params::FieldParam;
+use crate::params::{CustomFn, FieldParam};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote_spanned;
 use syn::Path;
@@ -10,44 +10,7 @@ pub fn read_struct_or_enum(
     unchecked: bool,
 ) -> TokenStream {
     let named = fields.iter().any(|f| f.field_name.is_some());
-    let values = fields.iter().map(|f| {
-        let align = &f.align;
-        let field_type = &f.ty;
-        let span = f.span();
-        let read_fn = Ident::new(if unchecked { "read_unchecked" } else { "read" }, span);
-        let read_sized_fn = Ident::new(
-            if unchecked {
-                "read_sized_unchecked"
-            } else {
-                "read_sized"
-            },
-            span,
-        );
-        let end_param = if unchecked {
-            Some(quote_spanned!(span => end))
-        } else {
-            None
-        };
-        match &f.size {
-            Some(size) => {
-                quote_spanned! { span =>
-                    {
-                        #align
-                        let _size: usize = #size;
-                        __stream.#read_sized_fn::<#field_type>(_size, #end_param)?
-                    }
-                }
-            }
-            None => {
-                quote_spanned! { span =>
-                    {
-                        #align
-                        __stream.#read_fn::<#field_type>(#end_param)?
-                    }
-                }
-            }
-        }
-    });
+    let values = fields.iter().map(|f| read_field(f, unchecked));
 
     if named {
         let definitions = fields.iter().zip(values).map(|(f, value)| {
@@ -80,3 +43,152 @@ pub fn read_struct_or_enum(
         }
     }
 }
+
+/// Read a single field from `__stream`
+pub fn read_field(f: &FieldParam, unchecked: bool) -> TokenStream {
+    let align = &f.align;
+    let field_type = &f.ty;
+    let span = f.span();
+    let read_fn = Ident::new(if unchecked { "read_unchecked" } else { "read" }, span);
+    let read_sized_fn = Ident::new(
+        if unchecked {
+            "read_sized_unchecked"
+        } else {
+            "read_sized"
+        },
+        span,
+    );
+    let checked_param = if unchecked {
+        Some(quote_spanned!(span => __checked))
+    } else {
+        None
+    };
+    if let Some(parse_with) = &f.parse_with {
+        let read = match parse_with {
+            // a plain function is called with the stream
+            CustomFn::Path(path) => quote_spanned!(span => #path(__stream)),
+            // other expressions can use the stream and previous fields
+            CustomFn::Expr(expr) => quote_spanned! { span =>
+                {
+                    let stream: &mut __bitbuffer::BitReadStream<_> = __stream;
+                    #expr
+                }
+            },
+        };
+        return match &f.custom_error {
+            Some(error) => quote_spanned! { span =>
+                {
+                    #align
+                    let __value: ::std::result::Result<#field_type, #error> = #read;
+                    __value.map_err(__bitbuffer::BitError::custom)?
+                }
+            },
+            None => quote_spanned! { span =>
+                {
+                    #align
+                    let __value: __bitbuffer::Result<#field_type> = #read;
+                    __value?
+                }
+            },
+        };
+    }
+    if let Some(until) = &f.until {
+        return quote_spanned! { span =>
+            {
+                #align
+                let mut __items: #field_type = ::std::default::Default::default();
+                loop {
+                    __items.push(__stream.read()?);
+                    #[allow(unused_variables)]
+                    let __done = {
+                        let last = __items.last().unwrap();
+                        let stream: &__bitbuffer::BitReadStream<_> = __stream;
+                        #until
+                    };
+                    if __done {
+                        break;
+                    }
+                }
+                __items
+            }
+        };
+    }
+    if f.lossy || f.nullable {
+        let size = f.optional_size_tokens();
+        let read = if f.lossy {
+            quote_spanned!(span => ::std::convert::From::from(__stream.read_string_lossy(_size)?))
+        } else {
+            quote_spanned!(span => __stream.read_nullable_string(_size)?.map(::std::convert::From::from))
+        };
+        return quote_spanned! { span =>
+            {
+                #align
+                let _size: Option<usize> = #size;
+                #read
+            }
+        };
+    }
+    if let (Some(padding), Some(size)) = (&f.null_padding, &f.size) {
+        let size = size.tokens(field_type);
+        return quote_spanned! { span =>
+            {
+                #align
+                let _size: usize = #size;
+                ::std::convert::From::from(
+                    __stream.read_fixed_string(_size, __bitbuffer::NullPadding::#padding)?,
+                )
+            }
+        };
+    }
+    if f.rest {
+        return quote_spanned! { span =>
+            {
+                #align
+                let _size: usize = __bitbuffer::SizeUnit::Bits.convert(
+                    __stream.bits_left(),
+                    <#field_type as __bitbuffer::HasSizeUnit>::SIZE_UNIT,
+                )?;
+                __stream.#read_sized_fn::<#field_type>(_size, #checked_param)?
+            }
+        };
+    }
+    if let (Some(map_sizes), Some(size)) = (&f.map_sizes, &f.size) {
+        let size = size.tokens(field_type);
+        let key_size = map_sizes.key_size.tokens(field_type);
+        let value_size = map_sizes.value_size.tokens(field_type);
+        return quote_spanned! { span =>
+            {
+                #align
+                let _size: usize = #size;
+                let _key_size: usize = #key_size;
+                let _value_size: usize = #value_size;
+                __stream.read_map_sized(_size, _key_size, _value_size)?
+            }
+        };
+    }
+    match &f.size {
+        Some(size) => {
+            let size = size.tokens(field_type);
+            quote_spanned! { span =>
+                {
+                    #align
+                    let _size: usize = #size;
+                    __stream.#read_sized_fn::<#field_type>(_size, #checked_param)?
+                }
+            }
+        }
+        None => {
+            let value = quote_spanned!(span => __stream.#read_fn::<#field_type>(#checked_param)?);
+            let value = match &f.byte_swap {
+                Some(byte_swap) => byte_swap.tokens(field_type, value),
+                None => value,
+            };
+            quote_spanned! { span =>
+                {
+                    #align
+                    #value
+                }
+            }
+        }
+    }
+}
@@ -4,11 +4,12 @@ mod r#struct;
 
 use self::r#enum::derive_encode_enum;
 use self::r#struct::derive_encode_struct;
-use crate::params::{InputInnerParams, InputParams};
+use crate::discriminant::discriminant_fn;
+use crate::params::{Alignment, FieldParam, InputInnerParams, InputParams};
 use crate::size_hint::SizeHint;
 use crate::Derivable;
-use proc_macro2::TokenStream;
-use quote::{quote, quote_spanned};
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::Result;
 
 fn parse_impl(params: &InputParams, unchecked: bool) -> Result<TokenStream> {
@@ -18,6 +19,222 @@ fn parse_impl(params: &InputParams, unchecked: bool) -> Result<TokenStream> {
     })
 }
 
+/// Construct `Self` from `__value` for a transparent struct
+fn transparent_construct(field: &FieldParam) -> TokenStream {
+    match &field.field_name {
+        Some(name) => quote!(Self { #name: __value }),
+        None => quote!(Self(__value)),
+    }
+}
+
+/// Whether the struct is read from the bytes of its in-memory layout, which is only the case if all
+/// fields are read without attributes that change how they are read, like `#[byte_swap]`
+fn reads_memory_layout(params: &InputParams) -> TokenStream {
+    let lifetime = &params.lifetime;
+    let endianness = params.endianness();
+    match &params.inner {
+        InputInnerParams::Struct(inner)
+            if params.align == Alignment::None
+                && inner
+                    .fields
+                    .iter()
+                    .all(|field| field.is_plain() && !field.rest) =>
+        {
+            let types = inner.fields.iter().map(|field| &field.ty);
+            quote!(true #(&& <#types as __bitbuffer::BitRead<#lifetime, #endianness>>::reads_memory_layout())*)
+        }
+        _ => quote!(false),
+    }
+}
+
+/// The label, bit size and bit offset of a list of fields, sizes and offsets are `Option<usize>` expressions
+struct FieldPositions {
+    labels: Vec<String>,
+    sizes: Vec<TokenStream>,
+    offsets: Vec<TokenStream>,
+}
+
+impl FieldPositions {
+    fn new(params: &InputParams, fields: &[&FieldParam]) -> Self {
+        let span = params.span;
+        let size_endianness = params.fixed_endianness();
+        let labels = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.field_name {
+                Some(name) => name.to_string(),
+                None => index.to_string(),
+            })
+            .collect();
+        let sizes: Vec<_> = fields
+            .iter()
+            .map(|field| field.size_hint(&size_endianness))
+            .collect();
+        let offsets = (0..sizes.len())
+            .map(|index| {
+                let sizes = &sizes[0..index];
+                quote_spanned!(span => Some(0usize)#(.and_then(|sum: usize| Some(sum + #sizes?)))*)
+            })
+            .collect();
+        FieldPositions {
+            labels,
+            sizes,
+            offsets,
+        }
+    }
+}
+
+/// Generate a `field_offsets()` method and `read_<field>` accessors for structs with the
+/// `field_offsets` attribute
+fn field_offsets_fn(params: &InputParams) -> Option<TokenStream> {
+    if !params.field_offsets {
+        return None;
+    }
+    let InputInnerParams::Struct(inner) = &params.inner else {
+        return None;
+    };
+    let (impl_generics, ty_generics, where_clause) = params.generics.split_for_impl();
+    let added_generics: Vec<_> = params.added_generics().collect();
+    let name = &params.ident;
+    let lifetime = &params.lifetime;
+    let endianness = params.endianness();
+    let align = params.align;
+    let span = params.span;
+
+    // only fields that are preceded by fields with a predictable size can be accessed directly
+    let fields: Vec<_> = inner
+        .fields
+        .iter()
+        .take_while(|field| field.size_can_be_predicted())
+        .collect();
+    let FieldPositions {
+        labels, offsets, ..
+    } = FieldPositions::new(params, &fields);
+    let count = fields.len();
+
+    let accessors = fields.iter().enumerate().map(|(index, field)| {
+        let fn_name = Ident::new(&format!("read_{}", labels[index]), field.span());
+        let ty = &field.ty;
+        let offset = &offsets[index];
+        let read = field::read_field(field, false);
+        let skip_previous = fields[0..index].iter().map(|field| {
+            let ty = &field.ty;
+            let read = field::read_field(field, false);
+            quote_spanned!(span => let _: #ty = #read;)
+        });
+        let doc = format!(
+            "Read the `{}` field of a record starting at the current position of the stream, without advancing the stream",
+            labels[index]
+        );
+        quote_spanned! {span =>
+            #[doc = #doc]
+            ///
+            /// If all fields before it have a fixed size the field is read directly from its offset,
+            /// otherwise the fields before it are read first.
+            #[allow(unused_braces)]
+            pub fn #fn_name<#(#added_generics),*>(
+                stream: &__bitbuffer::BitReadStream<#lifetime, #endianness>,
+            ) -> __bitbuffer::Result<#ty> {
+                let mut __stream = stream.clone();
+                let __stream = &mut __stream;
+                #align
+                match #offset {
+                    Some(offset) => __stream.skip_bits(offset)?,
+                    None => {
+                        #(#skip_previous)*
+                    }
+                }
+                Ok(#read)
+            }
+        }
+    });
+
+    let types = fields.iter().map(|field| &field.ty);
+    let subset_reads = fields.iter().enumerate().map(|(index, field)| {
+        let fn_name = Ident::new(&format!("read_{}", labels[index]), field.span());
+        let label = &labels[index];
+        quote_spanned! {span =>
+            if fields.contains(&#label) {
+                Some(Self::#fn_name(stream)?)
+            } else {
+                None
+            }
+        }
+    });
+
+    Some(quote_spanned! {span =>
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The bit offset of every field that can be read directly, relative to the start of the record
+            ///
+            /// The offset is `None` if a field before it doesn't have a fixed size.
+            pub fn field_offsets() -> [(&'static str, Option<usize>); #count] {
+                [#((#labels, #offsets)),*]
+            }
+
+            #(#accessors)*
+
+            /// Read the named fields of a record starting at the current position of the stream, without advancing the stream
+            ///
+            /// Returns a tuple with the value of every field listed in [`field_offsets`](Self::field_offsets), in order,
+            /// fields that aren't requested are not read and are `None`.
+            ///
+            /// # Errors
+            ///
+            /// - `BitError::UnknownField`: one of the names isn't listed in [`field_offsets`](Self::field_offsets)
+            /// - any error returned when reading the requested fields
+            #[allow(clippy::type_complexity)]
+            pub fn read_fields_subset<#(#added_generics),*>(
+                stream: &__bitbuffer::BitReadStream<#lifetime, #endianness>,
+                fields: &[&str],
+            ) -> __bitbuffer::Result<(#(::std::option::Option<#types>,)*)> {
+                for field in fields {
+                    if ![#(#labels),*].contains(field) {
+                        return Err(__bitbuffer::BitError::UnknownField {
+                            name: ::std::string::ToString::to_string(field),
+                            type_name: ::std::stringify!(#name),
+                        });
+                    }
+                }
+                Ok((#(#subset_reads,)*))
+            }
+        }
+    })
+}
+
+/// Generate a `layout()` method for structs with the `#[bitbuffer(layout)]` attribute
+fn layout_fn(params: &InputParams) -> Option<TokenStream> {
+    if !params.layout {
+        return None;
+    }
+    let InputInnerParams::Struct(inner) = &params.inner else {
+        return None;
+    };
+    let (impl_generics, ty_generics, where_clause) = params.generics.split_for_impl();
+    let name = &params.ident;
+    let span = params.span;
+
+    let fields: Vec<_> = inner.fields.iter().collect();
+    let FieldPositions {
+        labels,
+        sizes,
+        offsets,
+    } = FieldPositions::new(params, &fields);
+    let count = sizes.len();
+
+    Some(quote_spanned! {span =>
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The name, bit offset and bit size of every field, in the order they are read
+            pub fn layout() -> [__bitbuffer::FieldLayout; #count] {
+                [#(__bitbuffer::FieldLayout {
+                    name: #labels,
+                    offset: #offsets,
+                    size: #sizes,
+                }),*]
+            }
+        }
+    })
+}
+
 pub struct Read;
 
 impl Derivable for Read {
@@ -28,23 +245,64 @@ impl Derivable for Read {
 
         let parse = parse_impl(&params, false)?;
         let parse_unchecked = parse_impl(&params, true)?;
-        let size = params.size_hint();
+        let size = params.size_hint(&params.endianness().to_token_stream());
         let lifetime = params.lifetime.clone();
         let endianness = params.endianness();
         let name = params.ident.clone();
         let align = params.align;
         let span = params.span;
+        let discriminant_fn = discriminant_fn(&params);
+        let field_offsets_fn = field_offsets_fn(&params);
+        let layout_fn = layout_fn(&params);
+        let reads_memory_layout = reads_memory_layout(&params);
+
+        if let Some(field) = params.transparent_field() {
+            let ty = &field.ty;
+            let construct = transparent_construct(field);
+            return Ok(quote_spanned! {span =>
+                #field_offsets_fn
+                #layout_fn
+
+                impl #impl_generics __bitbuffer::BitRead<#lifetime, #endianness> for #name #ty_generics #where_clause {
+                    #[inline]
+                    fn read(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>) -> __bitbuffer::Result<Self> {
+                        let __value = <#ty as __bitbuffer::BitRead<#lifetime, #endianness>>::read(__stream)?;
+                        Ok(#construct)
+                    }
+
+                    #[inline]
+                    unsafe fn read_unchecked(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>, __checked: __bitbuffer::CheckedRead) -> __bitbuffer::Result<Self> {
+                        let __value = <#ty as __bitbuffer::BitRead<#lifetime, #endianness>>::read_unchecked(__stream, __checked)?;
+                        Ok(#construct)
+                    }
+
+                    #[inline]
+                    fn bit_size() -> Option<usize> {
+                        <#ty as __bitbuffer::BitRead<#lifetime, #endianness>>::bit_size()
+                    }
+
+                    #[inline]
+                    fn reads_memory_layout() -> bool {
+                        <#ty as __bitbuffer::BitRead<#lifetime, #endianness>>::reads_memory_layout()
+                    }
+                }
+            });
+        }
 
         Ok(quote_spanned! {span =>
-            impl #impl_generics ::bitbuffer::BitRead<#lifetime, #endianness> for #name #ty_generics #where_clause {
+            #discriminant_fn
+            #field_offsets_fn
+            #layout_fn
+
+            impl #impl_generics __bitbuffer::BitRead<#lifetime, #endianness> for #name #ty_generics #where_clause {
                 #[allow(unused_braces, unused_variables)]
-                fn read(__stream: &mut ::bitbuffer::BitReadStream<#lifetime, #endianness>) -> ::bitbuffer::Result<Self> {
+                fn read(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>) -> __bitbuffer::Result<Self> {
                     // if the read has a predicable size, we can do the bounds check in one go
-                    match <Self as ::bitbuffer::BitRead<#endianness>>::bit_size() {
+                    match <Self as __bitbuffer::BitRead<#endianness>>::bit_size() {
                         Some(size) => {
-                            let end = __stream.check_read(size)?;
+                            let __checked = __stream.check_read(size)?;
                             unsafe {
-                                <Self as ::bitbuffer::BitRead<#endianness>>::read_unchecked(__stream, end)
+                                <Self as __bitbuffer::BitRead<#endianness>>::read_unchecked(__stream, __checked)
                             }
                         },
                         None => {
@@ -55,7 +313,7 @@ impl Derivable for Read {
                 }
 
                 #[allow(unused_braces, unused_variables)]
-                unsafe fn read_unchecked(__stream: &mut ::bitbuffer::BitReadStream<#lifetime, #endianness>, end: bool) -> ::bitbuffer::Result<Self> {
+                unsafe fn read_unchecked(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>, __checked: __bitbuffer::CheckedRead) -> __bitbuffer::Result<Self> {
                     #align
                     #parse_unchecked
                 }
@@ -63,6 +321,10 @@ impl Derivable for Read {
                 fn bit_size() -> Option<usize> {
                     #size
                 }
+
+                fn reads_memory_layout() -> bool {
+                    #reads_memory_layout
+                }
             }
         })
     }
@@ -78,22 +340,47 @@ impl Derivable for ReadSized {
 
         let parse = parse_impl(&params, false)?;
         let parse_unchecked = parse_impl(&params, true)?;
-        let size = params.size_hint();
+        let size = params.size_hint(&params.endianness().to_token_stream());
         let lifetime = params.lifetime.clone();
         let endianness = params.endianness();
         let name = params.ident.clone();
         let align = params.align;
 
+        if let Some(field) = params.transparent_field() {
+            let ty = &field.ty;
+            let construct = transparent_construct(field);
+            return Ok(quote! {
+                impl #impl_generics __bitbuffer::BitReadSized<#lifetime, #endianness> for #name #ty_generics #where_clause {
+                    #[inline]
+                    fn read(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>, input_size: usize) -> __bitbuffer::Result<Self> {
+                        let __value = <#ty as __bitbuffer::BitReadSized<#lifetime, #endianness>>::read(__stream, input_size)?;
+                        Ok(#construct)
+                    }
+
+                    #[inline]
+                    unsafe fn read_unchecked(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>, input_size: usize, __checked: __bitbuffer::CheckedRead) -> __bitbuffer::Result<Self> {
+                        let __value = <#ty as __bitbuffer::BitReadSized<#lifetime, #endianness>>::read_unchecked(__stream, input_size, __checked)?;
+                        Ok(#construct)
+                    }
+
+                    #[inline]
+                    fn bit_size_sized(input_size: usize) -> Option<usize> {
+                        <#ty as __bitbuffer::BitReadSized<#lifetime, #endianness>>::bit_size_sized(input_size)
+                    }
+                }
+            });
+        }
+
         Ok(quote! {
-            impl #impl_generics ::bitbuffer::BitReadSized<#lifetime, #endianness> for #name #ty_generics #where_clause {
+            impl #impl_generics __bitbuffer::BitReadSized<#lifetime, #endianness> for #name #ty_generics #where_clause {
                 #[allow(unused_braces)]
-                fn read(__stream: &mut ::bitbuffer::BitReadStream<#lifetime, #endianness>, input_size: usize) -> ::bitbuffer::Result<Self> {
+                fn read(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>, input_size: usize) -> __bitbuffer::Result<Self> {
                     // if the read has a predicable size, we can do the bounds check in one go
-                    match <Self as ::bitbuffer::BitReadSized<#endianness>>::bit_size_sized(input_size) {
+                    match <Self as __bitbuffer::BitReadSized<#endianness>>::bit_size_sized(input_size) {
                         Some(size) => {
-                            let end = __stream.check_read(size)?;
+                            let __checked = __stream.check_read(size)?;
                             unsafe {
-                                <Self as ::bitbuffer::BitReadSized<#endianness>>::read_unchecked(__stream, input_size, end)
+                                <Self as __bitbuffer::BitReadSized<#endianness>>::read_unchecked(__stream, input_size, __checked)
                             }
                         },
                         None => {
@@ -104,7 +391,7 @@ impl Derivable for ReadSized {
                 }
 
                 #[allow(unused_braces)]
-                unsafe fn read_unchecked(__stream: &mut ::bitbuffer::BitReadStream<#lifetime, #endianness>, input_size: usize, end: bool) -> ::bitbuffer::Result<Self> {
+                unsafe fn read_unchecked(__stream: &mut __bitbuffer::BitReadStream<#lifetime, #endianness>, input_size: usize, __checked: __bitbuffer::CheckedRead) -> __bitbuffer::Result<Self> {
                     #align
                     #parse_unchecked
                 }
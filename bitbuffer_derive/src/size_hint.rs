@@ -2,15 +2,16 @@ use crate::params::{
     Alignment, EnumParam, FieldParam, InputInnerParams, InputParams, StructParam, VariantBody,
     VariantParam,
 };
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, quote_spanned};
 
 pub trait SizeHint {
-    fn size_hint(&self) -> TokenStream;
+    /// The size in bits, if it doesn't depend on the data, `endianness` is the endianness to read the fields with
+    fn size_hint(&self, endianness: &TokenStream) -> TokenStream;
 }
 
 impl SizeHint for FieldParam {
-    fn size_hint(&self) -> TokenStream {
+    fn size_hint(&self, endianness: &TokenStream) -> TokenStream {
         let span = self.span;
         let field_type = &self.ty;
         if !self.size_can_be_predicted() {
@@ -18,35 +19,39 @@ impl SizeHint for FieldParam {
         }
         match &self.size {
             Some(size) => {
+                let size = size.hint_tokens(field_type);
                 quote_spanned! { span =>
-                    <#field_type as ::bitbuffer::BitReadSized<'_, ::bitbuffer::LittleEndian>>::bit_size_sized(#size)
+                    <#field_type as __bitbuffer::BitReadSized<'_, #endianness>>::bit_size_sized(#size)
                 }
             }
             None => quote_spanned! { span =>
-                <#field_type as ::bitbuffer::BitRead<'_, ::bitbuffer::LittleEndian>>::bit_size()
+                <#field_type as __bitbuffer::BitRead<'_, #endianness>>::bit_size()
             },
         }
     }
 }
 
 impl SizeHint for VariantParam {
-    fn size_hint(&self) -> TokenStream {
+    fn size_hint(&self, endianness: &TokenStream) -> TokenStream {
         match &self.body {
-            VariantBody::Unit => quote!(Some(0)),
-            VariantBody::Fields(fields) => product_size_hint(fields, self.span),
+            VariantBody::Unit | VariantBody::Unknown(_) => quote!(Some(0)),
+            VariantBody::Fields(fields) => product_size_hint(fields, self.span, endianness),
         }
     }
 }
 
 impl SizeHint for StructParam {
-    fn size_hint(&self) -> TokenStream {
-        product_size_hint(&self.fields, self.span)
+    fn size_hint(&self, endianness: &TokenStream) -> TokenStream {
+        product_size_hint(&self.fields, self.span, endianness)
     }
 }
 
 impl SizeHint for EnumParam {
-    fn size_hint(&self) -> TokenStream {
-        let fields = sum_size_hint(&self.variants, self.span);
+    fn size_hint(&self, endianness: &TokenStream) -> TokenStream {
+        if self.payload_size_bits.is_some() {
+            return quote!(None);
+        }
+        let fields = sum_size_hint(&self.variants, self.span, endianness);
         let bits = self.discriminant_bits;
         quote_spanned!(self.span => {
             Some(#bits + #fields?)
@@ -55,24 +60,28 @@ impl SizeHint for EnumParam {
 }
 
 impl SizeHint for InputParams {
-    fn size_hint(&self) -> TokenStream {
+    fn size_hint(&self, endianness: &TokenStream) -> TokenStream {
         match (self.align, &self.inner) {
             (Alignment::Auto, _) => quote!(None),
-            (_, InputInnerParams::Struct(inner)) => inner.size_hint(),
-            (_, InputInnerParams::Enum(inner)) => inner.size_hint(),
+            (_, InputInnerParams::Struct(inner)) => inner.size_hint(endianness),
+            (_, InputInnerParams::Enum(inner)) => inner.size_hint(endianness),
         }
     }
 }
 
-fn product_size_hint<T: SizeHint>(children: &[T], span: Span) -> TokenStream {
-    let sizes = children.iter().map(|child| child.size_hint());
+fn product_size_hint<T: SizeHint>(
+    children: &[T],
+    span: Span,
+    endianness: &TokenStream,
+) -> TokenStream {
+    let sizes = children.iter().map(|child| child.size_hint(endianness));
     quote_spanned!(span => Some(0usize)#(.and_then(|sum: usize| Some(sum + #sizes?)))*)
 }
 
 // sum types have a fixed size if all children have the same fixed size
-fn sum_size_hint<T: SizeHint>(children: &[T], span: Span) -> TokenStream {
+fn sum_size_hint<T: SizeHint>(children: &[T], span: Span, endianness: &TokenStream) -> TokenStream {
     // todo, some actual clever logic that can be const folded away
-    let mut sizes = children.iter().map(|child| child.size_hint());
+    let mut sizes = children.iter().map(|child| child.size_hint(endianness));
     let Some(first) = sizes.next() else {
         return quote!(Some(0));
     };
@@ -82,3 +91,37 @@ fn sum_size_hint<T: SizeHint>(children: &[T], span: Span) -> TokenStream {
         None
     }))*)
 }
+
+/// The number of bits written for a struct, if it doesn't depend on the value
+pub fn write_size_hint(params: &InputParams, endianness: &Ident) -> TokenStream {
+    match (params.align, &params.inner) {
+        (Alignment::None, InputInnerParams::Struct(inner)) => {
+            let sizes = inner
+                .fields
+                .iter()
+                .map(|field| field_write_size_hint(field, endianness));
+            quote_spanned!(inner.span() => Some(0usize)#(.and_then(|sum: usize| Some(sum + #sizes?)))*)
+        }
+        _ => quote!(None),
+    }
+}
+
+fn field_write_size_hint(field: &FieldParam, endianness: &Ident) -> TokenStream {
+    let span = field.span;
+    let field_type = &field.ty;
+    // custom write functions can write any number of bits, regardless of the field type
+    if !field.size_can_be_predicted() || field.write_with.is_some() {
+        return quote_spanned! { span => None::<usize>};
+    }
+    match &field.size {
+        Some(size) => {
+            let size = size.hint_tokens(field_type);
+            quote_spanned! { span =>
+                <#field_type as __bitbuffer::BitWriteSized<#endianness>>::write_bit_size_sized(#size)
+            }
+        }
+        None => quote_spanned! { span =>
+            <#field_type as __bitbuffer::BitWrite<#endianness>>::write_bit_size()
+        },
+    }
+}
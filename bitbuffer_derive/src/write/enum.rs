@@ -1,4 +1,4 @@
-use crate::params::{EnumParam, VariantBody, VariantBodyType};
+use crate::params::{EnumParam, VariantBody};
 use crate::write::field::write_enum_variant;
 use proc_macro2::TokenStream;
 use quote::quote_spanned;
@@ -10,25 +10,44 @@ pub fn derive_encode_enum(params: &EnumParam) -> TokenStream {
     let ident = params.ident.clone();
     let span = params.span();
 
-    let discriminant_value = params
+    let empty_payload = params.payload_size_bits.map(|payload_size_bits| {
+        quote_spanned! {span=>
+            __stream.write_int(0usize, #payload_size_bits)?;
+        }
+    });
+
+    if params.discriminant_repr {
+        return quote_spanned! {span=>
+            let discriminant:#repr = ::core::convert::Into::into(::core::clone::Clone::clone(self));
+            #[allow(clippy::unnecessary_cast)]
+            __stream.write_int(discriminant, #discriminant_bits as usize)?;
+            #empty_payload
+            Ok(())
+        };
+    }
+
+    let discriminant_value = params.discriminant_match_arms();
+
+    // the raw discriminant of an unknown variant can't be written if it would be read as another variant
+    let check_unknown = params
         .variants
         .iter()
-        .zip(params.write_discriminant_tokens())
-        .map(|(variant, discriminant_token)| {
-            let span = variant.span();
-            let variant_name = &variant.variant_name;
-            match variant.body.body_type() {
-                VariantBodyType::Unit => quote_spanned! {span =>
-                    #ident::#variant_name => #discriminant_token
-                },
-                VariantBodyType::Unnamed => {
-                    quote_spanned! { span =>
-                        #ident::#variant_name(_) => #discriminant_token
-                    }
-                }
-                VariantBodyType::Named => {
-                    quote_spanned! { span =>
-                        #ident::#variant_name{..} => #discriminant_token
+        .find(|variant| matches!(variant.body, VariantBody::Unknown(_)))
+        .map(|unknown| {
+            let span = unknown.span();
+            let variant_name = &unknown.variant_name;
+            let name = ident.to_string();
+            let known = params
+                .variants
+                .iter()
+                .zip(params.read_discriminant_tokens())
+                .filter(|(variant, _)| !matches!(variant.body, VariantBody::Unknown(_)))
+                .map(|(_, discriminant_token)| discriminant_token);
+            quote_spanned! {span=>
+                #[allow(clippy::unnecessary_cast)]
+                if let #ident::#variant_name(_) = self {
+                    if #(discriminant == #known ||)* discriminant.checked_shr(#discriminant_bits as u32).unwrap_or(0) != 0 {
+                        return Err(__bitbuffer::BitError::ValueOutOfRange { type_name: #name });
                     }
                 }
             }
@@ -45,19 +64,42 @@ pub fn derive_encode_enum(params: &EnumParam) -> TokenStream {
                     #path => {},
                 }
             }
+            VariantBody::Unknown(_) => {
+                quote_spanned! {span =>
+                    #path(_) => {},
+                }
+            }
             VariantBody::Fields(fields) => write_enum_variant(path, fields, span),
         }
     });
 
+    let write_variant = quote_spanned! {span=>
+        match &self {
+            #(#write_inner)*
+        }
+    };
+
+    // the payload length is filled in after the variant fields are written
+    let write_payload = match params.payload_size_bits {
+        Some(payload_size_bits) => {
+            quote_spanned! {span=>
+                __stream.reserve_length(#payload_size_bits, |__stream| {
+                    #write_variant
+                    Ok::<(), __bitbuffer::BitError>(())
+                })?;
+            }
+        }
+        None => write_variant,
+    };
+
     quote_spanned! {span=>
         let discriminant:#repr = match &self {
             #(#discriminant_value),*
         };
+        #check_unknown
         #[allow(clippy::unnecessary_cast)]
         __stream.write_int(discriminant, #discriminant_bits as usize)?;
-        match &self {
-            #(#write_inner)*
-        }
+        #write_payload
         Ok(())
     }
 }
@@ -1,9 +1,9 @@
-use crate::params::FieldParam;
+use crate::params::{CustomFn, FieldParam};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote_spanned;
 use syn::Path;
 
-pub fn write_struct(fields: &[FieldParam], span: Span) -> TokenStream {
+pub fn write_struct(fields: &[FieldParam], span: Span, unchecked: bool) -> TokenStream {
     let expand = fields
         .iter()
         .enumerate()
@@ -23,7 +23,7 @@ pub fn write_struct(fields: &[FieldParam], span: Span) -> TokenStream {
                 let #name = &self.#member;
             }
         });
-    let writes = writes(fields);
+    let writes = writes(fields, unchecked);
 
     quote_spanned! {span=>
         #(#expand)*
@@ -38,26 +38,112 @@ fn names(fields: &[FieldParam]) -> impl Iterator<Item = Ident> + '_ {
         .map(|(index, field)| Ident::new(&format!("__field_{}", index), field.span()))
 }
 
-fn writes(fields: &[FieldParam]) -> impl Iterator<Item = TokenStream> + '_ {
+fn writes(fields: &[FieldParam], unchecked: bool) -> impl Iterator<Item = TokenStream> + '_ {
     let names = names(fields);
-    fields.iter().zip(names).map(|(field, name)| {
+    fields.iter().enumerate().zip(names).map(move |((index, field), name)| {
         let align = &field.align.write();
         let span = field.span();
+        // add the field to errors about the written value
+        let field_path = match &field.field_name {
+            Some(field_name) => field_name.to_string(),
+            None => index.to_string(),
+        };
+        let in_field = quote_spanned!(span => .map_err(|err| err.in_field(#field_path)));
+        let write_fn = Ident::new(
+            if unchecked {
+                "write_unchecked"
+            } else {
+                "write"
+            },
+            span,
+        );
+        let write_sized_fn = Ident::new(
+            if unchecked {
+                "write_sized_unchecked"
+            } else {
+                "write_sized"
+            },
+            span,
+        );
+        let checked_param = if unchecked {
+            Some(quote_spanned!(span => , __checked))
+        } else {
+            None
+        };
+        if let Some(write_with) = &field.write_with {
+            let write = match write_with {
+                // a plain function is called with the value and the stream
+                CustomFn::Path(path) => quote_spanned!(span => #path(#name, __stream)),
+                // other expressions can use the value and stream
+                CustomFn::Expr(expr) => quote_spanned! { span =>
+                    {
+                        let value = #name;
+                        let stream: &mut __bitbuffer::BitWriteStream<_> = __stream;
+                        #expr
+                    }
+                },
+            };
+            let write = match &field.custom_error {
+                Some(error) => quote_spanned! { span =>
+                    {
+                        let __result: ::std::result::Result<(), #error> = #write;
+                        __result.map_err(__bitbuffer::BitError::custom)
+                    }
+                },
+                None => write,
+            };
+            return quote_spanned! { span =>
+                {
+                    #align
+                    #write #in_field?;
+                }
+            };
+        }
+        if field.nullable {
+            let size = field.optional_size_tokens();
+            return quote_spanned! { span =>
+                {
+                    #align
+                    let _size: Option<usize> = #size;
+                    __stream.write_nullable_string(::std::option::Option::as_deref(#name), _size)#in_field?;
+                }
+            };
+        }
+        if let Some(map_sizes) = &field.map_sizes {
+            let key_size = map_sizes.key_size.tokens(&field.ty);
+            let value_size = map_sizes.value_size.tokens(&field.ty);
+            return quote_spanned! { span =>
+                {
+                    #align
+                    let _key_size: usize = #key_size;
+                    let _value_size: usize = #value_size;
+                    __stream.write_map_sized(#name, _key_size, _value_size)#in_field?;
+                }
+            };
+        }
         match &field.size {
             Some(size) => {
+                let size = size.tokens(&field.ty);
                 quote_spanned! { span =>
                     {
                         #align
                         let _size: usize = #size;
-                        __stream.write_sized(#name, _size)?;
+                        __stream.#write_sized_fn(#name, _size #checked_param)#in_field?;
                     }
                 }
             }
             None => {
+                let value = match &field.byte_swap {
+                    Some(byte_swap) => {
+                        let swapped = byte_swap.tokens(&field.ty, quote_spanned!(span => *#name));
+                        quote_spanned!(span => &#swapped)
+                    }
+                    None => quote_spanned!(span => #name),
+                };
                 quote_spanned! { span =>
                     {
                         #align
-                        __stream.write(#name)?;
+                        __stream.#write_fn(#value #checked_param)#in_field?;
                     }
                 }
             }
@@ -68,7 +154,7 @@ fn writes(fields: &[FieldParam]) -> impl Iterator<Item = TokenStream> + '_ {
 pub fn write_enum_variant(variant: Path, fields: &[FieldParam], span: Span) -> TokenStream {
     let names = names(fields);
     let named = fields.iter().any(|f| f.field_name.is_some());
-    let writes = writes(fields);
+    let writes = writes(fields, false);
     if named {
         quote_spanned!(span => #variant{#(#names,)*} => {
             #(#writes;)*
@@ -5,14 +5,15 @@ pub mod r#struct;
 use self::r#enum::derive_encode_enum;
 use self::r#struct::derive_encode_struct;
 use crate::params::{InputInnerParams, InputParams};
+use crate::size_hint::write_size_hint;
 use crate::Derivable;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Result;
 
-fn encode_impl(params: &InputParams) -> Result<TokenStream> {
+fn encode_impl(params: &InputParams, unchecked: bool) -> Result<TokenStream> {
     Ok(match &params.inner {
-        InputInnerParams::Struct(inner) => derive_encode_struct(inner),
+        InputInnerParams::Struct(inner) => derive_encode_struct(inner, unchecked),
         InputInnerParams::Enum(inner) => derive_encode_enum(inner),
     })
 }
@@ -25,17 +26,62 @@ impl Derivable for Write {
     fn derive(params: Self::Params) -> Result<TokenStream> {
         let (impl_generics, ty_generics, where_clause) = params.generics_for_impl();
 
-        let encode = encode_impl(&params)?;
         let endianness = params.endianness();
         let name = params.ident.clone();
+
+        if let Some(field) = params.transparent_field() {
+            let ty = &field.ty;
+            let member = field.member(0);
+            return Ok(quote! {
+                impl #impl_generics __bitbuffer::BitWrite<#endianness> for #name #ty_generics #where_clause {
+                    #[inline]
+                    fn write(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>) -> __bitbuffer::Result<()> {
+                        <#ty as __bitbuffer::BitWrite<#endianness>>::write(&self.#member, __stream)
+                    }
+
+                    #[inline]
+                    fn write_unchecked(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>, __checked: __bitbuffer::CheckedWrite) -> __bitbuffer::Result<()> {
+                        <#ty as __bitbuffer::BitWrite<#endianness>>::write_unchecked(&self.#member, __stream, __checked)
+                    }
+
+                    #[inline]
+                    fn write_bit_size() -> Option<usize> {
+                        <#ty as __bitbuffer::BitWrite<#endianness>>::write_bit_size()
+                    }
+                }
+            });
+        }
+
+        let encode = encode_impl(&params, false)?;
+        let encode_unchecked = encode_impl(&params, true)?;
         let align = params.align.write();
+        let size = write_size_hint(&params, &endianness);
 
         Ok(quote! {
-            impl #impl_generics ::bitbuffer::BitWrite<#endianness> for #name #ty_generics #where_clause {
+            impl #impl_generics __bitbuffer::BitWrite<#endianness> for #name #ty_generics #where_clause {
                 #[allow(unused_braces)]
-                fn write(&self, __stream: &mut ::bitbuffer::BitWriteStream<#endianness>) -> ::bitbuffer::Result<()> {
+                fn write(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>) -> __bitbuffer::Result<()> {
+                    // if the write has a predicable size, we can do the capacity check in one go
+                    match <Self as __bitbuffer::BitWrite<#endianness>>::write_bit_size() {
+                        Some(size) => {
+                            let __checked = __stream.check_write(size)?;
+                            <Self as __bitbuffer::BitWrite<#endianness>>::write_unchecked(self, __stream, __checked)
+                        }
+                        None => {
+                            #align
+                            #encode
+                        }
+                    }
+                }
+
+                #[allow(unused_braces)]
+                fn write_unchecked(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>, __checked: __bitbuffer::CheckedWrite) -> __bitbuffer::Result<()> {
                     #align
-                    #encode
+                    #encode_unchecked
+                }
+
+                fn write_bit_size() -> Option<usize> {
+                    #size
                 }
             }
         })
@@ -50,17 +96,63 @@ impl Derivable for WriteSized {
     fn derive(params: Self::Params) -> Result<TokenStream> {
         let (impl_generics, ty_generics, where_clause) = params.generics_for_impl();
 
-        let encode = encode_impl(&params)?;
         let endianness = params.endianness();
         let name = params.ident.clone();
+
+        if let Some(field) = params.transparent_field() {
+            let ty = &field.ty;
+            let member = field.member(0);
+            return Ok(quote! {
+                impl #impl_generics __bitbuffer::BitWriteSized<#endianness> for #name #ty_generics #where_clause {
+                    #[inline]
+                    fn write_sized(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>, input_size: usize) -> __bitbuffer::Result<()> {
+                        <#ty as __bitbuffer::BitWriteSized<#endianness>>::write_sized(&self.#member, __stream, input_size)
+                    }
+
+                    #[inline]
+                    fn write_sized_unchecked(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>, input_size: usize, __checked: __bitbuffer::CheckedWrite) -> __bitbuffer::Result<()> {
+                        <#ty as __bitbuffer::BitWriteSized<#endianness>>::write_sized_unchecked(&self.#member, __stream, input_size, __checked)
+                    }
+
+                    #[inline]
+                    fn write_bit_size_sized(input_size: usize) -> Option<usize> {
+                        <#ty as __bitbuffer::BitWriteSized<#endianness>>::write_bit_size_sized(input_size)
+                    }
+                }
+            });
+        }
+
+        let encode = encode_impl(&params, false)?;
+        let encode_unchecked = encode_impl(&params, true)?;
         let align = params.align.write();
+        let size = write_size_hint(&params, &endianness);
 
         Ok(quote! {
-            impl #impl_generics ::bitbuffer::BitWriteSized<#endianness> for #name #ty_generics #where_clause {
+            impl #impl_generics __bitbuffer::BitWriteSized<#endianness> for #name #ty_generics #where_clause {
                 #[allow(unused_braces)]
-                fn write_sized(&self, __stream: &mut ::bitbuffer::BitWriteStream<#endianness>, input_size: usize) -> ::bitbuffer::Result<()> {
+                fn write_sized(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>, input_size: usize) -> __bitbuffer::Result<()> {
+                    // if the write has a predicable size, we can do the capacity check in one go
+                    match <Self as __bitbuffer::BitWriteSized<#endianness>>::write_bit_size_sized(input_size) {
+                        Some(size) => {
+                            let __checked = __stream.check_write(size)?;
+                            <Self as __bitbuffer::BitWriteSized<#endianness>>::write_sized_unchecked(self, __stream, input_size, __checked)
+                        }
+                        None => {
+                            #align
+                            #encode
+                        }
+                    }
+                }
+
+                #[allow(unused_braces)]
+                fn write_sized_unchecked(&self, __stream: &mut __bitbuffer::BitWriteStream<#endianness>, input_size: usize, __checked: __bitbuffer::CheckedWrite) -> __bitbuffer::Result<()> {
                     #align
-                    #encode
+                    #encode_unchecked
+                }
+
+                #[allow(unused_variables)]
+                fn write_bit_size_sized(input_size: usize) -> Option<usize> {
+                    #size
                 }
             }
         })
@@ -2,14 +2,14 @@
 #![allow(unreachable_patterns)]
 
 use bitbuffer::{
-    bit_size_of, bit_size_of_sized, BigEndian, BitReadBuffer, BitReadStream, Endianness,
-    LittleEndian,
+    bit_size_of, bit_size_of_sized, BigEndian, BitError, BitReadBuffer, BitReadStream, Endianness,
+    FieldLayout, LittleEndian,
 };
 use bitbuffer_derive::{BitRead, BitReadSized};
 
 #[derive(BitRead, PartialEq, Debug)]
 struct TestStruct {
-    foo: u8,
+    byte: u8,
     str: String,
     #[size = 2]
     truncated: String,
@@ -24,19 +24,21 @@ struct TestStruct {
 }
 
 #[test]
+// the literals are grouped by the bits of each field
+#[allow(clippy::unusual_byte_groupings)]
 fn test_read_struct() {
     let float: [u8; 4] = 12.5f32.to_bits().to_le_bytes();
     let bytes = vec![
         12,
-        'h' as u8,
-        'e' as u8,
-        'l' as u8,
-        'l' as u8,
-        'o' as u8,
+        b'h',
+        b'e',
+        b'l',
+        b'l',
+        b'o',
         0,
-        'f' as u8,
-        'o' as u8,
-        'o' as u8,
+        b'f',
+        b'o',
+        b'o',
         0,
         float[0],
         float[1],
@@ -49,7 +51,7 @@ fn test_read_struct() {
     let mut stream = BitReadStream::from(buffer);
     assert_eq!(
         TestStruct {
-            foo: 12,
+            byte: 12,
             str: "hello".to_owned(),
             truncated: "fo".to_owned(),
             bar: 'o' as u16,
@@ -63,9 +65,11 @@ fn test_read_struct() {
     assert_eq!(None, bit_size_of::<TestStruct>());
 }
 
+/// Doc comments on the enum and variants are ignored
 #[derive(BitRead, PartialEq, Debug)]
 #[discriminant_bits = 2]
 enum TestBareEnum {
+    /// The first variant
     Foo,
     Bar,
     Asd = 3,
@@ -88,10 +92,35 @@ fn test_read_bare_enum() {
     assert_eq!(TestBareEnum::Asd, stream.read().unwrap());
     assert_eq!(TestBareEnum::Foo, stream.read().unwrap());
     assert_eq!(TestBareEnum::Bar, stream.read().unwrap());
-    assert_eq!(true, stream.read::<TestBareEnum>().is_err());
+    assert!(stream.read::<TestBareEnum>().is_err());
     assert_eq!(Some(2), bit_size_of::<TestBareEnum>());
 }
 
+#[test]
+fn test_read_enum_unmatched_context() {
+    let bytes = vec![0b1100_0110, 0b0100_0000];
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    let mut stream = BitReadStream::from(buffer);
+    stream.skip_bits(6).unwrap();
+    let Err(bitbuffer::BitError::UnmatchedDiscriminant {
+        discriminant,
+        enum_name,
+        pos,
+        discriminant_bits,
+    }) = stream.read::<TestBareEnum>()
+    else {
+        panic!("expected unmatched discriminant");
+    };
+    assert_eq!(2, discriminant);
+    assert_eq!("TestBareEnum", enum_name);
+    assert_eq!(6, pos);
+    assert_eq!(2, discriminant_bits);
+
+    // recover by skipping the unknown variant
+    assert_eq!(8, stream.pos());
+    assert_eq!(TestBareEnum::Bar, stream.read().unwrap());
+}
+
 #[derive(BitRead, PartialEq, Debug)]
 #[discriminant_bits = 2]
 enum TestUnnamedFieldEnum {
@@ -103,6 +132,8 @@ enum TestUnnamedFieldEnum {
 }
 
 #[test]
+// the literals are grouped by the bits of each field
+#[allow(clippy::unusual_byte_groupings)]
 fn test_read_unnamed_field_enum() {
     let bytes = vec![
         0b1100_0110,
@@ -132,7 +163,7 @@ fn test_read_unnamed_field_enum() {
 
 #[derive(BitReadSized, PartialEq, Debug)]
 struct TestStructSized {
-    foo: u8,
+    byte: u8,
     #[size = "input_size"]
     string: String,
     #[size = "input_size"]
@@ -141,14 +172,12 @@ struct TestStructSized {
 
 #[test]
 fn test_read_struct_sized() {
-    let bytes = vec![
-        12, 'h' as u8, 'e' as u8, 'l' as u8, 'l' as u8, 'o' as u8, 0, 0, 0, 0, 0, 0,
-    ];
+    let bytes = vec![12, b'h', b'e', b'l', b'l', b'o', 0, 0, 0, 0, 0, 0];
     let buffer = BitReadBuffer::new(&bytes, LittleEndian);
     let mut stream = BitReadStream::from(buffer);
     assert_eq!(
         TestStructSized {
-            foo: 12,
+            byte: 12,
             string: "hel".to_owned(),
             int: 4,
         },
@@ -169,6 +198,8 @@ enum TestUnnamedFieldEnumSized {
 }
 
 #[test]
+// the literals are grouped by the bits of each field
+#[allow(clippy::unusual_byte_groupings)]
 fn test_read_unnamed_field_enum_sized() {
     let bytes = vec![
         0b1100_0110,
@@ -201,17 +232,17 @@ struct TestStruct2 {
 fn test_read_struct2() {
     let bytes = vec![
         0b0000_0101,
-        'h' as u8,
-        'e' as u8,
-        'l' as u8,
-        'l' as u8,
-        'o' as u8,
-        ' ' as u8,
-        'w' as u8,
-        'o' as u8,
-        'r' as u8,
-        'l' as u8,
-        'e' as u8,
+        b'h',
+        b'e',
+        b'l',
+        b'l',
+        b'o',
+        b' ',
+        b'w',
+        b'o',
+        b'r',
+        b'l',
+        b'e',
     ];
     let buffer = BitReadBuffer::new(&bytes, BigEndian);
     let mut stream = BitReadStream::from(buffer);
@@ -278,9 +309,7 @@ fn test_read_rest_enum() {
 struct UnnamedSize(u8, #[size = 5] String, bool);
 
 fn test_unnamed_struct() {
-    let bytes = vec![
-        12, 'h' as u8, 'e' as u8, 'l' as u8, 'l' as u8, 'o' as u8, 0, 0, 0, 0, 0, 0,
-    ];
+    let bytes = vec![12, b'h', b'e', b'l', b'l', b'o', 0, 0, 0, 0, 0, 0];
     let buffer = BitReadBuffer::new(&bytes, LittleEndian);
     let mut stream = BitReadStream::from(buffer);
 
@@ -304,7 +333,7 @@ fn test_empty_struct() {
 
 #[derive(BitRead)]
 struct SizeStruct {
-    foo: u8,
+    byte: u8,
     #[size = 6]
     str: String,
     bar: bool,
@@ -321,7 +350,7 @@ fn test_bit_size() {
 
 #[derive(BitReadSized)]
 struct SizeStructSized {
-    foo: u8,
+    byte: u8,
     #[size = "input_size"]
     str: String,
     bar: bool,
@@ -355,7 +384,7 @@ fn test_align() {
 #[align]
 struct AlignFieldStruct {
     #[size = 1]
-    foo: u8,
+    byte: u8,
     #[align]
     bar: u8,
 }
@@ -366,7 +395,7 @@ fn test_align_field() {
     let buffer = BitReadBuffer::new(&bytes, BigEndian);
     let mut stream = BitReadStream::from(buffer);
     assert_eq!(
-        AlignFieldStruct { foo: 0, bar: 0x80 },
+        AlignFieldStruct { byte: 0, bar: 0x80 },
         stream.read().unwrap()
     );
     assert_eq!(16, stream.pos());
@@ -411,3 +440,546 @@ fn test_align_enum_field() {
     assert_eq!(24, stream.pos());
     assert_eq!(None, bit_size_of::<AlignEnum>());
 }
+
+#[derive(BitRead, PartialEq, Debug)]
+#[discriminant_type = "u16"]
+enum TypedDiscriminantEnum {
+    Nop = 0x0001,
+    Jump = 0x8000,
+    Call = 0x8001,
+    #[discriminant = "_"]
+    Other,
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+#[discriminant_type = "u32"]
+#[discriminant_bits = 20]
+enum TypedDiscriminantBitsEnum {
+    #[discriminant = 0x80000]
+    Foo(u8),
+    Bar(u8),
+}
+
+#[test]
+fn test_typed_discriminant() {
+    let bytes = vec![0x80, 0x01, 0x00, 0x01, 0x12, 0x34];
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(TypedDiscriminantEnum::Call, stream.read().unwrap());
+    assert_eq!(TypedDiscriminantEnum::Nop, stream.read().unwrap());
+    assert_eq!(TypedDiscriminantEnum::Other, stream.read().unwrap());
+    assert_eq!(Some(16), bit_size_of::<TypedDiscriminantEnum>());
+
+    assert_eq!(0x8000, TypedDiscriminantEnum::Jump.discriminant());
+    assert_eq!(0x8001, TypedDiscriminantEnum::Call.discriminant());
+    assert_eq!(0x8002, TypedDiscriminantEnum::Other.discriminant());
+
+    let bytes = vec![0x80, 0x00, 0x11, 0x80];
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(TypedDiscriminantBitsEnum::Bar(0x18), stream.read().unwrap());
+    assert_eq!(Some(28), bit_size_of::<TypedDiscriminantBitsEnum>());
+    assert_eq!(0x80000u32, TypedDiscriminantBitsEnum::Foo(1).discriminant());
+    assert_eq!(0x80001u32, TypedDiscriminantBitsEnum::Bar(1).discriminant());
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+struct ByteSwapStruct {
+    #[byte_swap]
+    int: u32,
+    #[byte_swap = 2]
+    middle_endian: u32,
+    #[byte_swap]
+    float: f32,
+}
+
+#[test]
+fn test_byte_swap() {
+    let float = 1.5f32.to_bits().to_le_bytes();
+    let bytes = vec![
+        0x12, 0x34, 0x56, 0x78, 0x12, 0x34, 0x56, 0x78, float[0], float[1], float[2], float[3],
+    ];
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(
+        ByteSwapStruct {
+            int: 0x7856_3412,
+            middle_endian: 0x3412_7856,
+            float: 1.5,
+        },
+        stream.read().unwrap()
+    );
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+struct UntilStruct {
+    #[until = "*last == 0"]
+    terminated: Vec<u8>,
+    count: u8,
+    #[until = "stream.bits_left() < 16"]
+    rest: Vec<String>,
+}
+
+#[test]
+fn test_read_until() {
+    let bytes = vec![1, 2, 0, 1, b'a', 0, b'b', 0, b'c', 0];
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(
+        UntilStruct {
+            terminated: vec![1, 2, 0],
+            count: 1,
+            rest: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        },
+        stream.read().unwrap()
+    );
+    assert_eq!(None, bit_size_of::<UntilStruct>());
+
+    // the condition is checked after reading, so one element is read even though less than 16 bits are left
+    let bytes = vec![0, 1, 0];
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(
+        UntilStruct {
+            terminated: vec![0],
+            count: 1,
+            rest: vec![String::new()],
+        },
+        stream.read().unwrap()
+    );
+
+    let bytes = vec![1, 2, 3];
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert!(stream.read::<UntilStruct>().is_err());
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+struct LossyStringStruct {
+    #[str(lossy)]
+    name: String,
+    #[str(lossy)]
+    #[size = 4]
+    fixed: String,
+    #[str(lossy)]
+    #[size(bits = 16)]
+    short: String,
+    trailer: u8,
+}
+
+#[test]
+fn test_read_lossy_string() {
+    let bytes = vec![b'a', 0xc3, 0, 0xff, b'b', 0, 0, b'c', 0xfe, 0x12];
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(
+        LossyStringStruct {
+            name: "a\u{FFFD}".to_string(),
+            fixed: "\u{FFFD}b".to_string(),
+            short: "c\u{FFFD}".to_string(),
+            trailer: 0x12,
+        },
+        stream.read().unwrap()
+    );
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+struct NullPaddingStruct {
+    #[size = 4]
+    trimmed: String,
+    #[str(first_null)]
+    #[size = 4]
+    first_null: String,
+    #[str(keep_nulls)]
+    #[size = 4]
+    kept: String,
+    trailer: u8,
+}
+
+#[test]
+fn test_read_string_null_padding() {
+    let bytes = b"a\0b\0c\0\xffdef\0\0\x12";
+    let buffer = BitReadBuffer::new(bytes, LittleEndian);
+    let mut stream = BitReadStream::from(buffer);
+    assert_eq!(
+        NullPaddingStruct {
+            trimmed: "a\0b".to_string(),
+            first_null: "c".to_string(),
+            kept: "ef\0\0".to_string(),
+            trailer: 0x12,
+        },
+        stream.read().unwrap()
+    );
+    assert_eq!(Some(104), bit_size_of::<NullPaddingStruct>());
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+#[field_offsets]
+struct FixedRecord {
+    flag: bool,
+    #[size = 12]
+    id: u16,
+    #[size = 4]
+    kind: u8,
+    value: i32,
+    name: String,
+    after_name: u8,
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+#[field_offsets]
+#[endianness = "E"]
+struct GenericRecord<'a, E: Endianness> {
+    first: Option<u8>,
+    second: u8,
+    #[size = "second"]
+    third: BitReadStream<'a, E>,
+}
+
+#[test]
+fn test_field_offsets() {
+    assert_eq!(
+        [
+            ("flag", Some(0)),
+            ("id", Some(1)),
+            ("kind", Some(13)),
+            ("value", Some(17)),
+            ("name", Some(49)),
+            ("after_name", None),
+        ],
+        FixedRecord::field_offsets()
+    );
+
+    let mut data = vec![0b1010_1010; 7];
+    data.extend_from_slice(b"foo\0");
+    data.push(0xaa);
+    let mut stream = BitReadStream::new(BitReadBuffer::new_owned(data, BigEndian));
+    stream.skip_bits(7).unwrap();
+    let record: FixedRecord = stream.clone().read().unwrap();
+
+    assert_eq!(record.id, FixedRecord::read_id(&stream).unwrap());
+    assert_eq!(record.kind, FixedRecord::read_kind(&stream).unwrap());
+    assert_eq!(record.value, FixedRecord::read_value(&stream).unwrap());
+    assert_eq!(
+        record.after_name,
+        FixedRecord::read_after_name(&stream).unwrap()
+    );
+    assert_eq!("foo", record.name);
+    assert_eq!(7, stream.pos());
+
+    let (flag, id, kind, value, name, after_name) =
+        FixedRecord::read_fields_subset(&stream, &["value", "id", "after_name"]).unwrap();
+    assert_eq!(
+        (None, Some(record.id), None, Some(record.value), None),
+        (flag, id, kind, value, name)
+    );
+    assert_eq!(Some(record.after_name), after_name);
+    assert_eq!(7, stream.pos());
+
+    assert_eq!(
+        [("first", Some(0)), ("second", None)],
+        GenericRecord::<LittleEndian>::field_offsets()
+    );
+    let bytes = vec![0b1010_1010, 0b1111_1010];
+    let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    assert_eq!(0b0101_0101, GenericRecord::read_second(&stream).unwrap());
+}
+
+#[test]
+fn test_read_fields_subset_unknown_field() {
+    let bytes = vec![0; 16];
+    let stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    let err = FixedRecord::read_fields_subset(&stream, &["id", "missing"]).unwrap_err();
+    assert!(matches!(
+        &err,
+        BitError::UnknownField { name, type_name: "FixedRecord" } if name == "missing"
+    ));
+    assert_eq!(
+        "missing is not a field that can be read directly from FixedRecord",
+        err.to_string()
+    );
+}
+
+mod facade {
+    pub use bitbuffer as bits;
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+#[bitbuffer(crate = "crate::facade::bits")]
+struct CustomCratePath {
+    #[size = 4]
+    size: u8,
+    #[size = "size"]
+    value: u16,
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+#[bitbuffer(crate = "facade::bits")]
+#[discriminant_bits = 2]
+enum CustomCratePathEnum {
+    Foo,
+    Bar(u8),
+}
+
+#[test]
+fn test_custom_crate_path() {
+    let bytes = vec![0b1010_0011, 0b0000_1100, 0b0110_1000, 0b1100_0000];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert_eq!(
+        CustomCratePath {
+            size: 0b1010,
+            value: 0b00_1100_0011,
+        },
+        stream.read().unwrap()
+    );
+    assert_eq!(CustomCratePathEnum::Foo, stream.read().unwrap());
+    assert_eq!(
+        CustomCratePathEnum::Bar(0b1010_0011),
+        stream.read().unwrap()
+    );
+}
+
+#[derive(BitRead)]
+#[bitbuffer(layout)]
+#[endianness = "LittleEndian"]
+struct LayoutTuple(#[size = 3] u8, #[align] u8, bool, #[size_bits = 4] String);
+
+#[test]
+fn test_layout() {
+    assert_eq!(
+        [
+            FieldLayout {
+                name: "0",
+                offset: Some(0),
+                size: Some(3)
+            },
+            FieldLayout {
+                name: "1",
+                offset: Some(3),
+                size: None
+            },
+            FieldLayout {
+                name: "2",
+                offset: None,
+                size: Some(1)
+            },
+            FieldLayout {
+                name: "3",
+                offset: None,
+                size: None
+            },
+        ],
+        LayoutTuple::layout()
+    );
+    assert_eq!(Some(3), LayoutTuple::layout()[0].end());
+}
+
+#[derive(Debug)]
+enum CustomError {
+    Invalid(u8),
+    Bits(bitbuffer::BitError),
+}
+
+impl std::fmt::Display for CustomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for CustomError {}
+
+impl From<bitbuffer::BitError> for CustomError {
+    fn from(err: bitbuffer::BitError) -> Self {
+        CustomError::Bits(err)
+    }
+}
+
+fn read_even<E: Endianness>(stream: &mut BitReadStream<E>) -> Result<u8, CustomError> {
+    match stream.read::<u8>()? {
+        value if value % 2 == 0 => Ok(value),
+        value => Err(CustomError::Invalid(value)),
+    }
+}
+
+#[derive(BitRead, Debug, PartialEq)]
+#[bit_error = "CustomError"]
+struct CustomErrorStruct {
+    #[parse_with = "read_even"]
+    even: u8,
+    #[parse_with = "read_even(stream).map(|value| value / 2)"]
+    half: u8,
+}
+
+#[derive(BitRead, Debug, PartialEq)]
+#[bit_error = "CustomError"]
+#[discriminant_bits = 2]
+enum CustomErrorEnum {
+    Plain(u8),
+    Checked(#[parse_with = "read_even"] u8),
+}
+
+#[test]
+fn test_read_custom_error() {
+    let bytes = vec![2, 4, 3, 4];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert_eq!(
+        CustomErrorStruct { even: 2, half: 2 },
+        stream.read_with_error::<_, CustomError>().unwrap()
+    );
+    assert!(matches!(
+        stream.read_with_error::<CustomErrorStruct, CustomError>(),
+        Err(CustomError::Invalid(3))
+    ));
+    // other errors are converted
+    assert!(matches!(
+        stream.read_with_error::<CustomErrorStruct, CustomError>(),
+        Err(CustomError::Bits(bitbuffer::BitError::NotEnoughData { .. }))
+    ));
+    // reading with plain `read` returns the wrapped error
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    stream.skip_bits(8).unwrap();
+    assert!(matches!(
+        stream.read::<CustomErrorStruct>(),
+        Err(bitbuffer::BitError::Custom(_))
+    ));
+
+    let bytes = vec![0b0100_0011, 0b0100_0000];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert!(matches!(
+        stream.read_with_error::<CustomErrorEnum, CustomError>(),
+        Err(CustomError::Invalid(0b0000_1101))
+    ));
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+#[field_offsets]
+struct ConstGenericStruct<const N: usize> {
+    data: [u8; N],
+    #[size = "N * 2"]
+    bits: u8,
+    tail: u8,
+}
+
+#[derive(BitReadSized, PartialEq, Debug)]
+#[discriminant_bits = 1]
+enum ConstGenericEnum<const N: usize> {
+    #[size = "input_size"]
+    Short(u8),
+    #[size = "N"]
+    Long(u32),
+}
+
+#[test]
+fn test_read_const_generic() {
+    let bytes = vec![1, 2, 0b1011_0000, 0b0011_0000];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert_eq!(
+        ConstGenericStruct {
+            data: [1, 2],
+            bits: 0b1011,
+            tail: 3
+        },
+        stream.read().unwrap()
+    );
+    assert_eq!(Some(28), bit_size_of::<ConstGenericStruct<2>>());
+    assert_eq!(Some(18), bit_size_of::<ConstGenericStruct<1>>());
+    assert_eq!(Some(20), ConstGenericStruct::<2>::field_offsets()[2].1);
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert_eq!(
+        ConstGenericStruct {
+            data: [1],
+            bits: 0,
+            tail: 0b0000_1010
+        },
+        stream.read().unwrap()
+    );
+
+    assert_eq!(Some(9), bit_size_of_sized::<ConstGenericEnum<8>>(8));
+    assert_eq!(None, bit_size_of_sized::<ConstGenericEnum<16>>(8));
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert_eq!(
+        ConstGenericEnum::<8>::Short(1),
+        stream.read_sized(7).unwrap()
+    );
+}
+
+/// Flags of a test
+#[derive(bitbuffer_derive::FlagEnum, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+enum TestFlag {
+    /// The first flag
+    First = 1,
+    Second = 1 << 1,
+    Last = 0b1000_0000,
+}
+
+#[derive(bitbuffer_derive::FlagEnum, PartialEq, Debug, Clone, Copy)]
+#[flag_bits = 3]
+enum SmallFlag {
+    A = 1,
+    B = 2,
+    C = 4,
+}
+
+#[derive(BitRead, PartialEq, Debug)]
+struct FlagStruct {
+    flags: bitbuffer::FlagSet<TestFlag>,
+    small: bitbuffer::FlagSet<SmallFlag>,
+    #[size = 5]
+    wide: bitbuffer::FlagSet<SmallFlag>,
+}
+
+#[test]
+fn test_read_flag_set() {
+    use bitbuffer::{FlagEnum, FlagSet};
+
+    assert_eq!(8, TestFlag::FLAG_BITS);
+    assert_eq!(3, SmallFlag::FLAG_BITS);
+    assert_eq!(Some(16), bit_size_of::<FlagStruct>());
+
+    let bytes = vec![0b1000_0010, 0b101_10110];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    let read: FlagStruct = stream.read().unwrap();
+    assert_eq!(TestFlag::Second | TestFlag::Last, read.flags);
+    assert_eq!(
+        vec![TestFlag::Second, TestFlag::Last],
+        read.flags.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(SmallFlag::A | SmallFlag::C, read.small);
+    assert!(!read.small.contains(SmallFlag::B));
+
+    // unknown bits are kept
+    assert_eq!(0b10110, read.wide.bits());
+    assert_eq!(0b10000, read.wide.unknown_bits());
+    assert!(read.wide.contains(SmallFlag::B));
+    assert_eq!("{B, C, 0b10000}", format!("{:?}", read.wide));
+
+    let mut set = FlagSet::<SmallFlag>::empty();
+    set |= SmallFlag::B;
+    set.insert(SmallFlag::C);
+    set.remove(SmallFlag::B);
+    assert_eq!(set, SmallFlag::C);
+    assert_eq!(FlagSet::from_bits(0b111), FlagSet::<SmallFlag>::all());
+}
+
+#[derive(BitRead)]
+struct MapStruct {
+    count: u8,
+    #[size = "count"]
+    #[map(key_size = 1, value_size = 4)]
+    entries: std::collections::HashMap<String, u8>,
+}
+
+#[test]
+fn test_read_map_sized() {
+    assert_eq!(None, bit_size_of::<MapStruct>());
+
+    let bytes = vec![2, b'a', 0x1_6, 0x2_f];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    let read: MapStruct = stream.read().unwrap();
+    assert_eq!(2, read.entries.len());
+    assert_eq!(1, read.entries["a"]);
+    assert_eq!(15, read.entries["b"]);
+    assert_eq!(32, stream.pos());
+}
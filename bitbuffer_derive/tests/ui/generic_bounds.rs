@@ -0,0 +1,60 @@
+use bitbuffer::{
+    BigEndian, BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, Endianness,
+    LittleEndian,
+};
+use std::marker::PhantomData;
+
+#[derive(BitRead, BitWrite, Debug, PartialEq)]
+#[endianness = "E"]
+struct Wrapper<'a, E: Endianness, T>
+where
+    T: BitRead<'a, E> + BitWrite<E>,
+{
+    id: u8,
+    inner: T,
+    _endianness: PhantomData<&'a E>,
+}
+
+#[derive(BitRead, BitWrite, Debug, PartialEq)]
+#[endianness = "E"]
+struct Defaulted<'a, E: Endianness + 'a, T: BitRead<'a, E> + BitWrite<E> = u16> {
+    inner: T,
+    #[size = 4]
+    flags: u8,
+    _endianness: PhantomData<&'a E>,
+}
+
+#[derive(BitRead, BitWrite, Debug, PartialEq)]
+#[endianness = "LittleEndian"]
+struct Fixed<T = u8>
+where
+    T: for<'b> BitRead<'b, LittleEndian> + BitWrite<LittleEndian>,
+{
+    inner: T,
+}
+
+fn main() {
+    let bytes = [1, 2, 3, 4];
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    let wrapper: Wrapper<BigEndian, u16> = stream.read().unwrap();
+    assert_eq!(1, wrapper.id);
+    assert_eq!(0x0203, wrapper.inner);
+    assert_eq!(Some(24), Wrapper::<BigEndian, u16>::bit_size());
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let defaulted: Defaulted<LittleEndian> = stream.read().unwrap();
+    assert_eq!(0x0201, defaulted.inner);
+    assert_eq!(3, defaulted.flags);
+    assert_eq!(Some(20), Defaulted::<LittleEndian>::bit_size());
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let fixed: Fixed = stream.read().unwrap();
+    assert_eq!(1, fixed.inner);
+
+    let mut data = Vec::new();
+    BitWriteStream::new(&mut data, BigEndian)
+        .write(&wrapper)
+        .unwrap();
+    assert_eq!([1, 2, 3], data.as_slice());
+}
@@ -0,0 +1,28 @@
+use bitbuffer::{BigEndian, BitWrite, BitWriteSized, BitWriteStream};
+
+// the discriminant method is only generated by the BitRead derive
+#[derive(BitWrite, BitWriteSized)]
+#[discriminant_type = "u8"]
+enum Command {
+    Start = 0x10,
+    Stop = 0x20,
+}
+
+impl Command {
+    fn discriminant(&self) -> &'static str {
+        match self {
+            Command::Start => "start",
+            Command::Stop => "stop",
+        }
+    }
+}
+
+fn main() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&Command::Start).unwrap();
+    stream.write_sized(&Command::Stop, 8).unwrap();
+    drop(stream);
+    assert_eq!(vec![0x10, 0x20], data);
+    assert_eq!("stop", Command::Stop.discriminant());
+}
@@ -2,13 +2,14 @@
 #![allow(unreachable_patterns)]
 
 use bitbuffer::{
-    BigEndian, BitReadBuffer, BitReadSized, BitReadStream, BitWriteStream, Endianness, LittleEndian,
+    BigEndian, BitError, BitReadBuffer, BitReadSized, BitReadStream, BitWriteStream, Endianness,
+    LittleEndian, Skip,
 };
 use bitbuffer_derive::{BitRead, BitWrite, BitWriteSized};
 
 #[derive(BitWrite, PartialEq, Debug)]
 struct TestStruct {
-    foo: u8,
+    byte: u8,
     str: String,
     #[size = 2]
     truncated: String,
@@ -21,19 +22,21 @@ struct TestStruct {
 }
 
 #[test]
+// the literals are grouped by the bits of each field
+#[allow(clippy::unusual_byte_groupings)]
 fn test_read_struct() {
     let float: [u8; 4] = 12.5f32.to_bits().to_le_bytes();
     let bytes = vec![
         12,
-        'h' as u8,
-        'e' as u8,
-        'l' as u8,
-        'l' as u8,
-        'o' as u8,
+        b'h',
+        b'e',
+        b'l',
+        b'l',
+        b'o',
         0,
-        'f' as u8,
-        'o' as u8,
-        'o' as u8,
+        b'f',
+        b'o',
+        b'o',
         0,
         float[0],
         float[1],
@@ -42,7 +45,7 @@ fn test_read_struct() {
         0b1010_0101,
     ];
     let val = TestStruct {
-        foo: 12,
+        byte: 12,
         str: "hello".to_owned(),
         truncated: "fo".to_owned(),
         bar: 'o' as u16,
@@ -87,6 +90,8 @@ enum TestUnnamedFieldEnum {
 }
 
 #[test]
+// the literals are grouped by the bits of each field
+#[allow(clippy::unusual_byte_groupings)]
 fn test_read_unnamed_field_enum() {
     let bytes = vec![0b1100_0110, 0b1000_0110, 0b1011_0000];
     let mut data = Vec::new();
@@ -115,7 +120,7 @@ fn test_read_unnamed_field_enum() {
 
 #[derive(BitWriteSized, BitReadSized, PartialEq, Debug)]
 struct TestStructSized {
-    foo: u8,
+    byte: u8,
     #[size = "input_size"]
     string: String,
     #[size = "input_size"]
@@ -124,11 +129,11 @@ struct TestStructSized {
 
 #[test]
 fn test_read_struct_sized() {
-    let bytes = vec![12, 'h' as u8, 'e' as u8, 'l' as u8, 0b1000_0000];
+    let bytes = vec![12, b'h', b'e', b'l', 0b1000_0000];
     let mut data = Vec::new();
     let mut stream = BitWriteStream::new(&mut data, BigEndian);
     let val = TestStructSized {
-        foo: 12,
+        byte: 12,
         string: "hel".to_owned(),
         int: 4,
     };
@@ -152,6 +157,8 @@ enum TestUnnamedFieldEnumSized {
 }
 
 #[test]
+// the literals are grouped by the bits of each field
+#[allow(clippy::unusual_byte_groupings)]
 fn test_read_unnamed_field_enum_sized() {
     let bytes = vec![0b1100_0110];
     let mut data = Vec::new();
@@ -173,16 +180,16 @@ struct TestStruct2 {
 fn test_read_struct2() {
     let bytes = vec![
         0b0000_0101,
-        'h' as u8,
-        'e' as u8,
-        'l' as u8,
-        'l' as u8,
-        'o' as u8,
-        ' ' as u8,
-        'w' as u8,
-        'o' as u8,
-        'r' as u8,
-        'l' as u8,
+        b'h',
+        b'e',
+        b'l',
+        b'l',
+        b'o',
+        b' ',
+        b'w',
+        b'o',
+        b'r',
+        b'l',
     ];
     let mut data = Vec::new();
     let mut stream = BitWriteStream::new(&mut data, BigEndian);
@@ -247,9 +254,7 @@ fn test_read_rest_enum() {
 struct UnnamedSize(u8, #[size = 5] String, bool);
 
 fn test_unnamed_struct() {
-    let bytes = vec![
-        12, 'h' as u8, 'e' as u8, 'l' as u8, 'l' as u8, 'o' as u8, 0, 0, 0, 0, 0, 0,
-    ];
+    let bytes = vec![12, b'h', b'e', b'l', b'l', b'o', 0, 0, 0, 0, 0, 0];
     let mut data = Vec::new();
     let mut stream = BitWriteStream::new(&mut data, LittleEndian);
     stream
@@ -309,7 +314,7 @@ fn test_align() {
 #[align]
 struct AlignFieldStruct {
     #[size = 1]
-    foo: u8,
+    byte: u8,
     #[align]
     bar: u8,
 }
@@ -319,7 +324,7 @@ fn test_align_field() {
     let bytes = vec![0, 0x80];
     let mut data = Vec::new();
     let mut stream = BitWriteStream::new(&mut data, BigEndian);
-    let val = AlignFieldStruct { foo: 0, bar: 0x80 };
+    let val = AlignFieldStruct { byte: 0, bar: 0x80 };
     stream.write(&val).unwrap();
     assert_eq!(bytes, data);
 }
@@ -362,3 +367,788 @@ fn test_align_enum_field() {
     stream.write(&val).unwrap();
     assert_eq!(bytes, data);
 }
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+#[discriminant_type = "u16"]
+enum TypedDiscriminantEnum {
+    Nop = 0x0001,
+    Jump = 0x8000,
+    Call = 0x8001,
+}
+
+#[test]
+fn test_write_typed_discriminant() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&TypedDiscriminantEnum::Jump).unwrap();
+    stream.write(&TypedDiscriminantEnum::Nop).unwrap();
+    assert_eq!(vec![0x80, 0x00, 0x00, 0x01], data);
+    assert_eq!(0x8001u16, TypedDiscriminantEnum::Call.discriminant());
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+#[discriminant_bits = 1]
+enum ByteSwapEnum {
+    Int(#[byte_swap] u16),
+    Float(#[byte_swap] f64),
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+struct ByteSwapStruct {
+    #[byte_swap = 2]
+    middle_endian: i32,
+    inner: ByteSwapEnum,
+}
+
+#[test]
+fn test_write_byte_swap() {
+    let val = ByteSwapStruct {
+        middle_endian: 0x1234_5678,
+        inner: ByteSwapEnum::Int(0x1234),
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(vec![0x56, 0x78, 0x12, 0x34, 0x24, 0x68, 0x00], data);
+
+    let mut read = BitReadStream::<LittleEndian>::from(data.as_slice());
+    assert_eq!(val, read.read().unwrap());
+
+    let val = ByteSwapEnum::Float(-12.25);
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&val).unwrap();
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(val, read.read().unwrap());
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+struct UntilStruct {
+    #[until = "*last == 0"]
+    terminated: Vec<u16>,
+    trailer: u8,
+}
+
+#[test]
+fn test_write_until() {
+    let val = UntilStruct {
+        terminated: vec![0x1234, 1, 0],
+        trailer: 0xff,
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(vec![0x34, 0x12, 1, 0, 0, 0, 0xff], data);
+
+    let mut read = BitReadStream::<LittleEndian>::from(data.as_slice());
+    assert_eq!(val, read.read().unwrap());
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+struct InteriorMutableStruct {
+    counter: std::cell::Cell<u16>,
+    #[size = 4]
+    small: std::cell::Cell<u8>,
+    name: std::cell::RefCell<String>,
+}
+
+#[test]
+fn test_write_interior_mutable() {
+    let val = InteriorMutableStruct {
+        counter: 1234.into(),
+        small: 5.into(),
+        name: String::from("foo").into(),
+    };
+    val.counter.set(val.counter.get() + 1);
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(16 + 4 + 32, stream.bit_len());
+
+    let mut read = BitReadStream::<LittleEndian>::from(data.as_slice());
+    let result: InteriorMutableStruct = read.read().unwrap();
+    assert_eq!(1235, result.counter.get());
+    assert_eq!(val, result);
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug, Clone, Copy)]
+#[discriminant_repr]
+#[discriminant_type = "u16"]
+#[discriminant_bits = 12]
+enum ReprEnum {
+    Low,
+    High,
+}
+
+impl std::convert::TryFrom<u16> for ReprEnum {
+    type Error = ();
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0x001 => Ok(ReprEnum::Low),
+            0x800 => Ok(ReprEnum::High),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<ReprEnum> for u16 {
+    fn from(value: ReprEnum) -> Self {
+        match value {
+            ReprEnum::Low => 0x001,
+            ReprEnum::High => 0x800,
+        }
+    }
+}
+
+#[test]
+fn test_discriminant_repr() {
+    assert_eq!(0x800, ReprEnum::High.discriminant());
+    assert_eq!(Some(12), bitbuffer::bit_size_of::<ReprEnum>());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&ReprEnum::High).unwrap();
+    stream.write(&ReprEnum::Low).unwrap();
+    stream.write_int(0x123u16, 12).unwrap();
+    assert_eq!(vec![0x80, 0x00, 0x01, 0x12, 0x30], data);
+
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(ReprEnum::High, read.read().unwrap());
+    assert_eq!(ReprEnum::Low, read.read().unwrap());
+    assert!(matches!(
+        read.read::<ReprEnum>(),
+        Err(bitbuffer::BitError::UnmatchedDiscriminant {
+            discriminant: 0x123,
+            ..
+        })
+    ));
+}
+
+fn read_vec3<E: Endianness>(stream: &mut BitReadStream<E>) -> bitbuffer::Result<[i8; 3]> {
+    Ok([stream.read()?, stream.read()?, stream.read()?])
+}
+
+fn write_vec3<E: Endianness>(
+    value: &[i8; 3],
+    stream: &mut BitWriteStream<E>,
+) -> bitbuffer::Result<()> {
+    value.iter().try_for_each(|v| stream.write(v))
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+struct CustomFunctions {
+    count: u8,
+    #[parse_with = "read_vec3"]
+    #[write_with = "write_vec3"]
+    position: [i8; 3],
+    #[parse_with = "(0..count).map(|_| stream.read_int::<u8>(4)).collect()"]
+    #[write_with = "value.iter().try_for_each(|v| stream.write_int(*v, 4))"]
+    nibbles: Vec<u8>,
+    trailer: bool,
+}
+
+#[test]
+fn test_parse_with() {
+    let val = CustomFunctions {
+        count: 3,
+        position: [1, -1, 2],
+        nibbles: vec![1, 2, 3],
+        trailer: true,
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(8 + 24 + 12 + 1, stream.bit_len());
+    assert_eq!(vec![3, 1, 0xff, 2, 0x12, 0x38], data);
+    assert_eq!(None, bitbuffer::bit_size_of::<CustomFunctions>());
+
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(val, read.read().unwrap());
+}
+
+#[derive(BitWrite)]
+struct WideCustomWrite {
+    #[write_with = "stream.write_int(*value as u32, 20)"]
+    value: u8,
+    flag: bool,
+}
+
+#[test]
+fn test_write_with_more_bits_than_type() {
+    let val = WideCustomWrite {
+        value: 0xab,
+        flag: true,
+    };
+    assert_eq!(
+        None,
+        <WideCustomWrite as bitbuffer::BitWrite<BigEndian>>::write_bit_size()
+    );
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(21, stream.bit_len());
+    drop(stream);
+    assert_eq!(vec![0x00, 0x0a, 0b1011_1000], data);
+
+    let mut slice = [0u8; 2];
+    let mut stream = BitWriteStream::from_slice(&mut slice, BigEndian);
+    assert!(matches!(
+        stream.write(&val),
+        Err(BitError::WriteOverflow { .. })
+    ));
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+struct NullableStrings {
+    #[str(nullable)]
+    terminated: Option<String>,
+    #[str(nullable)]
+    #[size = 3]
+    fixed: Option<String>,
+    length: u8,
+    #[str(nullable)]
+    #[size = "length"]
+    prefixed: Option<String>,
+}
+
+#[test]
+fn test_nullable_string() {
+    let val = NullableStrings {
+        terminated: None,
+        fixed: Some("ab".into()),
+        length: 0,
+        prefixed: None,
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(vec![0, b'a', b'b', 0, 0], data);
+    assert_eq!(None, bitbuffer::bit_size_of::<NullableStrings>());
+
+    let mut read = BitReadStream::<LittleEndian>::from(data.as_slice());
+    assert_eq!(val, read.read().unwrap());
+
+    let bytes = b"foo\0\0\0\0\x03bar";
+    let mut read = BitReadStream::<LittleEndian>::from(bytes.as_slice());
+    assert_eq!(
+        NullableStrings {
+            terminated: Some("foo".into()),
+            fixed: None,
+            length: 3,
+            prefixed: Some("bar".into()),
+        },
+        read.read().unwrap()
+    );
+}
+
+#[test]
+fn test_nullable_string_empty() {
+    let val = NullableStrings {
+        terminated: Some(String::new()),
+        fixed: None,
+        length: 0,
+        prefixed: None,
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    assert!(matches!(
+        stream.write(&val),
+        Err(bitbuffer::BitError::ValueOutOfRange { .. })
+    ));
+    assert!(matches!(
+        stream.write_nullable_string(Some("\0\0"), Some(3)),
+        Err(bitbuffer::BitError::ValueOutOfRange { .. })
+    ));
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+#[repr(transparent)]
+struct EntityId(u32);
+
+#[derive(BitReadSized, BitWriteSized, PartialEq, Debug)]
+#[repr(transparent)]
+struct Tag {
+    value: u16,
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+struct Entity {
+    id: EntityId,
+    #[size = 5]
+    tag: Tag,
+}
+
+#[test]
+fn test_transparent() {
+    assert_eq!(Some(32), bitbuffer::bit_size_of::<EntityId>());
+    assert_eq!(Some(37), bitbuffer::bit_size_of::<Entity>());
+
+    let val = Entity {
+        id: EntityId(0x12345678),
+        tag: Tag { value: 0b10101 },
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&val).unwrap();
+    assert_eq!(vec![0x78, 0x56, 0x34, 0x12, 0b10101], data);
+
+    let mut read = BitReadStream::<LittleEndian>::from(data.as_slice());
+    assert_eq!(val, read.read().unwrap());
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+#[discriminant_bits = 4]
+#[payload_size_bits = 6]
+enum Tlv {
+    Empty,
+    Short(#[size = 3] u8),
+    Word(u16),
+}
+
+#[test]
+fn test_payload_size_bits() {
+    assert_eq!(None, bitbuffer::bit_size_of::<Tlv>());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&Tlv::Short(5)).unwrap();
+    stream.write(&Tlv::Word(0x1234)).unwrap();
+    stream.write(&Tlv::Empty).unwrap();
+    assert_eq!(3 * 10 + 3 + 16, stream.bit_len());
+    drop(stream);
+
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(Tlv::Short(5), read.read().unwrap());
+    assert_eq!(Tlv::Word(0x1234), read.read().unwrap());
+    assert_eq!(Tlv::Empty, read.read().unwrap());
+
+    // unread bits of the payload are skipped
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write_int(1u8, 4).unwrap();
+    stream.write_int(8u8, 6).unwrap();
+    stream.write_int(0b101_11111u8, 8).unwrap();
+    stream.write_int(0u8, 4).unwrap();
+    stream.write_int(0u8, 6).unwrap();
+    drop(stream);
+
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(Tlv::Short(5), read.read().unwrap());
+    assert_eq!(Tlv::Empty, read.read().unwrap());
+
+    // a payload longer than the data is an error
+    let mut read = BitReadStream::<BigEndian>::from([0b0010_1111, 0b1100_0000].as_slice());
+    assert!(read.read::<Tlv>().is_err());
+
+    // a payload longer than the length can hold is an error
+    #[derive(BitWrite)]
+    #[discriminant_bits = 4]
+    #[payload_size_bits = 4]
+    enum SmallTlv {
+        Word(u16),
+    }
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    assert!(matches!(
+        stream.write(&SmallTlv::Word(1)),
+        Err(BitError::TooManyBits {
+            requested: 5,
+            max: 4
+        })
+    ));
+}
+
+#[derive(BitRead, BitWrite)]
+#[endianness = "E"]
+struct RestPacket<'a, E: Endianness> {
+    kind: u8,
+    #[rest]
+    payload: BitReadStream<'a, E>,
+}
+
+#[test]
+fn test_rest() {
+    assert_eq!(None, bitbuffer::bit_size_of::<RestPacket<LittleEndian>>());
+
+    let bytes = [12, 1, 2, 3];
+    let mut read = BitReadStream::<LittleEndian>::from(bytes.as_slice());
+    let packet: RestPacket<LittleEndian> = read.read().unwrap();
+    assert_eq!(12, packet.kind);
+    assert_eq!(24, packet.payload.bit_len());
+    assert_eq!(0, read.bits_left());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&packet).unwrap();
+    drop(stream);
+    assert_eq!(bytes.as_slice(), data.as_slice());
+}
+
+#[derive(BitRead, BitWrite, Debug, PartialEq)]
+struct Reserved {
+    first: u8,
+    reserved: Skip<u16>,
+    #[size = 3]
+    padding: Skip<u8>,
+    last: bool,
+}
+
+#[test]
+fn test_skip() {
+    assert_eq!(Some(28), bitbuffer::bit_size_of::<Reserved>());
+
+    let value = Reserved {
+        first: 12,
+        reserved: Skip::new(),
+        padding: Skip::new(),
+        last: true,
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&value).unwrap();
+    assert_eq!(28, stream.bit_len());
+    drop(stream);
+    assert_eq!([12, 0, 0, 0b0001_0000], data.as_slice());
+
+    // skipped bits are ignored when reading
+    let mut read = BitReadStream::<BigEndian>::from([12, 0xff, 0xff, 0b1111_0000].as_slice());
+    assert_eq!(value, read.read().unwrap());
+    assert_eq!(4, read.bits_left());
+
+    // a skipped type without fixed size can't be written
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    assert!(matches!(
+        stream.write(&Skip::<String>::new()),
+        Err(bitbuffer::BitError::UnknownSkipSize { .. })
+    ));
+}
+
+#[derive(BitWriteSized, Debug, PartialEq)]
+struct FixedWrite {
+    first: u8,
+    #[size = 3]
+    small: u8,
+    flags: [bool; 3],
+    float: f32,
+    #[size = "input_size"]
+    sized: u16,
+}
+
+#[derive(BitWrite)]
+struct NestedFixedWrite {
+    #[size = 10]
+    inner: FixedWrite,
+    last: (u8, bool),
+}
+
+#[test]
+fn test_write_bit_size() {
+    use bitbuffer::{BitWrite, BitWriteSized};
+
+    assert_eq!(
+        Some(8 + 3 + 3 + 32 + 10),
+        <FixedWrite as BitWriteSized<LittleEndian>>::write_bit_size_sized(10)
+    );
+    assert_eq!(
+        Some(56 + 9),
+        <NestedFixedWrite as BitWrite<LittleEndian>>::write_bit_size()
+    );
+    assert_eq!(
+        None,
+        <TestStruct as BitWrite<LittleEndian>>::write_bit_size()
+    );
+
+    let value = NestedFixedWrite {
+        inner: FixedWrite {
+            first: 12,
+            small: 5,
+            flags: [true, false, true],
+            float: 1.5,
+            sized: 1000,
+        },
+        last: (255, true),
+    };
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&value).unwrap();
+    assert_eq!(65, stream.bit_len());
+    drop(stream);
+
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(12u8, read.read::<u8>().unwrap());
+    assert_eq!(5u8, read.read_int::<u8>(3).unwrap());
+    assert_eq!([true, false, true], read.read::<[bool; 3]>().unwrap());
+    assert_eq!(1.5f32, read.read::<f32>().unwrap());
+    assert_eq!(1000u16, read.read_int::<u16>(10).unwrap());
+    assert_eq!((255u8, true), read.read::<(u8, bool)>().unwrap());
+
+    // the capacity is checked before writing anything
+    let mut data = [0; 8];
+    let mut stream = BitWriteStream::from_slice(&mut data, BigEndian);
+    stream.write_bool(true).unwrap();
+    assert!(matches!(
+        stream.write(&value),
+        Err(bitbuffer::BitError::WriteOverflow {
+            capacity_bits: 64,
+            attempted_bits: 66
+        })
+    ));
+    assert_eq!(1, stream.bit_len());
+}
+
+#[derive(BitWrite, BitRead, PartialEq, Debug)]
+struct CountedList {
+    count: u8,
+    #[size = "count"]
+    items: Vec<u16>,
+}
+
+#[test]
+fn test_write_counted_vec() {
+    let list = CountedList {
+        count: 2,
+        items: vec![0x0102, 0x0304],
+    };
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&list).unwrap();
+    drop(stream);
+    assert_eq!(data, [2, 1, 2, 3, 4]);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, BigEndian));
+    assert_eq!(list, read.read().unwrap());
+}
+
+#[derive(BitWrite)]
+struct ValueInner {
+    bits: u8,
+    #[size = "bits"]
+    value: u8,
+}
+
+#[derive(BitWrite)]
+struct ValueOuter(u8, ValueInner);
+
+#[test]
+fn test_write_value_error_field_path() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    let err = stream
+        .write(&ValueOuter(1, ValueInner { bits: 12, value: 5 }))
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        bitbuffer::BitError::TooManyBitsForValue { value, requested: 12, max: 8, field: Some(field) }
+            if value == "5" && field == "1.value"
+    ));
+    assert_eq!(
+        "Too many bits requested to write the value 5 for field '1.value', requested to write 12 bits while only 8 bits fit in the data type",
+        err.to_string()
+    );
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+#[discriminant_type = "u8"]
+#[discriminant_bits = 4]
+enum UnknownEnum {
+    Foo,
+    Bar(u8),
+    #[unknown]
+    Other(u8),
+}
+
+#[test]
+fn test_unknown_variant_roundtrip() {
+    let bytes = vec![0b1001_0001, 0b0000_0001, 0b0000_0000];
+    let mut read = BitReadStream::<BigEndian>::from(bytes.as_slice());
+    assert_eq!(UnknownEnum::Other(9), read.read().unwrap());
+    assert_eq!(UnknownEnum::Bar(1), read.read().unwrap());
+    assert_eq!(UnknownEnum::Foo, read.read().unwrap());
+    assert_eq!(9, UnknownEnum::Other(9).discriminant());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&UnknownEnum::Other(9)).unwrap();
+    stream.write(&UnknownEnum::Bar(1)).unwrap();
+    stream.write(&UnknownEnum::Foo).unwrap();
+    assert_eq!(bytes, data);
+}
+
+#[test]
+fn test_unknown_variant_invalid_discriminant() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    assert!(matches!(
+        stream.write(&UnknownEnum::Other(0)),
+        Err(bitbuffer::BitError::ValueOutOfRange { .. })
+    ));
+    assert!(matches!(
+        stream.write(&UnknownEnum::Other(1)),
+        Err(bitbuffer::BitError::ValueOutOfRange { .. })
+    ));
+    assert!(matches!(
+        stream.write(&UnknownEnum::Other(200)),
+        Err(bitbuffer::BitError::ValueOutOfRange { .. })
+    ));
+    stream.write(&UnknownEnum::Other(15)).unwrap();
+    assert_eq!(4, stream.bit_len());
+}
+
+#[derive(Debug)]
+enum LimitError {
+    TooLarge(u8),
+    Bits(bitbuffer::BitError),
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl From<bitbuffer::BitError> for LimitError {
+    fn from(err: bitbuffer::BitError) -> Self {
+        LimitError::Bits(err)
+    }
+}
+
+fn write_limited<E: Endianness>(
+    value: &u8,
+    stream: &mut BitWriteStream<E>,
+) -> Result<(), LimitError> {
+    if *value > 100 {
+        return Err(LimitError::TooLarge(*value));
+    }
+    Ok(stream.write(value)?)
+}
+
+#[derive(BitWrite)]
+#[bit_error = "LimitError"]
+struct LimitedStruct {
+    #[write_with = "write_limited"]
+    value: u8,
+}
+
+#[test]
+fn test_write_custom_error() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream
+        .write_with_error::<_, LimitError>(&LimitedStruct { value: 12 })
+        .unwrap();
+    assert!(matches!(
+        stream.write_with_error(&LimitedStruct { value: 200 }),
+        Err(LimitError::TooLarge(200))
+    ));
+
+    let mut slice = [0u8; 0];
+    let mut stream = BitWriteStream::from_slice(&mut slice, LittleEndian);
+    assert!(matches!(
+        stream.write_with_error(&LimitedStruct { value: 1 }),
+        Err(LimitError::Bits(bitbuffer::BitError::WriteOverflow { .. }))
+    ));
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+struct ConstGenericStruct<const N: usize> {
+    data: [u8; N],
+    #[size = "N * 2"]
+    bits: u8,
+}
+
+#[test]
+fn test_write_const_generic() {
+    let value = ConstGenericStruct {
+        data: [1, 2],
+        bits: 0b1011,
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&value).unwrap();
+    assert_eq!(20, stream.bit_len());
+    assert_eq!(
+        Some(20),
+        <ConstGenericStruct<2> as bitbuffer::BitWrite<BigEndian>>::write_bit_size()
+    );
+    drop(stream);
+    assert_eq!(vec![1, 2, 0b1011_0000], data);
+
+    let mut read = BitReadStream::<BigEndian>::from(data.as_slice());
+    assert_eq!(value, read.read().unwrap());
+}
+
+#[derive(bitbuffer_derive::FlagEnum, PartialEq, Debug, Clone, Copy)]
+#[repr(u16)]
+#[flag_bits = 10]
+enum WriteFlag {
+    Low = 1,
+    High = 1 << 9,
+}
+
+#[derive(BitWrite, PartialEq, Debug)]
+struct FlagWriteStruct {
+    flags: bitbuffer::FlagSet<WriteFlag>,
+    #[size = 6]
+    sized: bitbuffer::FlagSet<WriteFlag>,
+}
+
+#[test]
+fn test_write_flag_set() {
+    let val = FlagWriteStruct {
+        flags: WriteFlag::Low | WriteFlag::High,
+        sized: WriteFlag::Low.into(),
+    };
+    let mut data = Vec::new();
+    {
+        let mut stream = BitWriteStream::new(&mut data, BigEndian);
+        stream.write(&val).unwrap();
+        assert_eq!(16, stream.bit_len());
+    }
+    assert_eq!([0b1000_0000, 0b01_000001], data.as_slice());
+
+    // a flag that doesn't fit in the size
+    let val = FlagWriteStruct {
+        flags: WriteFlag::Low.into(),
+        sized: WriteFlag::High.into(),
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    assert!(matches!(
+        stream.write(&val),
+        Err(BitError::ValueOutOfRange {
+            type_name: "FlagSet"
+        })
+    ));
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+struct MapStruct {
+    count: u8,
+    #[size = "count"]
+    #[map(key_size = 2, value_size = 12)]
+    entries: std::collections::HashMap<String, u16>,
+}
+
+#[test]
+fn test_write_map_sized() {
+    let value = MapStruct {
+        count: 1,
+        entries: vec![(String::from("ab"), 0x123)].into_iter().collect(),
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&value).unwrap();
+    assert_eq!(36, stream.bit_len());
+    drop(stream);
+    assert_eq!(vec![1, b'a', b'b', 0x12, 0x30], data);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, BigEndian));
+    assert_eq!(value, read.read().unwrap());
+}
@@ -0,0 +1,139 @@
+//! [`BitReadSized`] and [`BitWriteSized`] implementations for [`BigUint`] and [`BigInt`]
+//!
+//! The size sets the number of bits of the integer, the bits are read in the same order as
+//! [`read_int`](crate::BitReadStream::read_int) does for integers of the same size. Signed integers
+//! are stored in two's complement.
+//!
+//! Note that fixed size wide integers can also be read as arrays of integers (e.g. `[u64; 4]`),
+//! which reads every element in the endianness of the stream.
+use crate::{BitError, BitReadSized, BitReadStream, BitWriteSized, BitWriteStream};
+use crate::{Endianness, HasSizeUnit, Result, SizeUnit};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
+use std::cmp::min;
+
+const DIGIT_BITS: usize = 32;
+
+fn read_biguint<E: Endianness>(stream: &mut BitReadStream<E>, size: usize) -> Result<BigUint> {
+    stream.check_read(size)?;
+
+    let digit_count = (size + DIGIT_BITS - 1) / DIGIT_BITS;
+    let mut digits = Vec::with_capacity(digit_count);
+    if E::is_le() {
+        let mut remaining = size;
+        while remaining > 0 {
+            let bits = min(remaining, DIGIT_BITS);
+            digits.push(stream.read_int::<u32>(bits)?);
+            remaining -= bits;
+        }
+    } else {
+        // the most significant digit comes first and holds the bits that don't fill a full digit
+        let top_bits = size - (digit_count.saturating_sub(1) * DIGIT_BITS);
+        for i in 0..digit_count {
+            let bits = if i == 0 { top_bits } else { DIGIT_BITS };
+            digits.push(stream.read_int::<u32>(bits)?);
+        }
+        digits.reverse();
+    }
+    Ok(BigUint::new(digits))
+}
+
+fn write_biguint<E: Endianness>(
+    value: &BigUint,
+    stream: &mut BitWriteStream<E>,
+    size: usize,
+    type_name: &'static str,
+) -> Result<()> {
+    if value.bits() > size as u64 {
+        return Err(BitError::ValueOutOfRange { type_name });
+    }
+
+    let digit_count = (size + DIGIT_BITS - 1) / DIGIT_BITS;
+    let mut digits = value.to_u32_digits();
+    digits.resize(digit_count, 0);
+    if E::is_le() {
+        let mut remaining = size;
+        for digit in digits {
+            let bits = min(remaining, DIGIT_BITS);
+            stream.write_int(digit, bits)?;
+            remaining -= bits;
+        }
+    } else {
+        let top_bits = size - (digit_count.saturating_sub(1) * DIGIT_BITS);
+        for (i, digit) in digits.into_iter().rev().enumerate() {
+            let bits = if i == 0 { top_bits } else { DIGIT_BITS };
+            stream.write_int(digit, bits)?;
+        }
+    }
+    Ok(())
+}
+
+impl<E: Endianness> BitReadSized<'_, E> for BigUint {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        read_biguint(stream, size)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(size)
+    }
+}
+
+impl<E: Endianness> BitReadSized<'_, E> for BigInt {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        let unsigned = read_biguint(stream, size)?;
+        if size > 0 && unsigned.bit(size as u64 - 1) {
+            Ok(BigInt::from(unsigned) - (BigInt::one() << size))
+        } else {
+            Ok(BigInt::from(unsigned))
+        }
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(size)
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for BigUint {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        write_biguint(self, stream, len, "BigUint")
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for BigInt {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        let out_of_range = BitError::ValueOutOfRange {
+            type_name: "BigInt",
+        };
+        if len == 0 {
+            return if self.is_zero() {
+                Ok(())
+            } else {
+                Err(out_of_range)
+            };
+        }
+
+        let limit = BigInt::one() << (len - 1);
+        if *self >= limit || *self < -&limit {
+            return Err(out_of_range);
+        }
+        let unsigned = match self.sign() {
+            Sign::Minus => self + (limit << 1),
+            _ => self.clone(),
+        };
+        write_biguint(unsigned.magnitude(), stream, len, "BigInt")
+    }
+}
+
+impl HasSizeUnit for BigUint {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Bits;
+}
+
+impl HasSizeUnit for BigInt {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Bits;
+}
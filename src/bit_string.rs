@@ -0,0 +1,87 @@
+//! Textual representation of encoded values for golden tests
+use crate::{BitError, BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream};
+use crate::{Endianness, Result};
+
+/// Encode a value and format the written bits as a string of `0`s and `1`s
+///
+/// The bits are listed in the order they are written to the stream and grouped per byte, which makes
+/// the result suited for locking down a wire format in a test.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{to_bit_string, BigEndian, LittleEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// assert_eq!(to_bit_string(&0x1234u16, BigEndian)?, "00010010 00110100");
+/// assert_eq!(to_bit_string(&(true, 5u8), LittleEndian)?, "11010000 0");
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub fn to_bit_string<T, E>(value: &T, endianness: E) -> Result<String>
+where
+    T: BitWrite<E> + ?Sized,
+    E: Endianness,
+{
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, endianness);
+    value.write(&mut stream)?;
+    let bit_len = stream.bit_len();
+
+    let mut buffer = BitReadBuffer::new(&data, E::endianness());
+    buffer.truncate(bit_len)?;
+    let mut stream = BitReadStream::new(buffer);
+    let mut result = String::with_capacity(bit_len + bit_len / 8);
+    for index in 0..bit_len {
+        if index > 0 && index % 8 == 0 {
+            result.push(' ');
+        }
+        result.push(if stream.read_bool()? { '1' } else { '0' });
+    }
+    Ok(result)
+}
+
+/// Decode a value from a string of `0`s and `1`s as produced by [`to_bit_string`]
+///
+/// Whitespace and `_` can be used to group the bits and are ignored.
+///
+/// # Errors
+///
+/// - [`BitError::InvalidBitString`]: the string contains a character other than `0`, `1`, `_` or whitespace
+/// - any error returned while reading the value
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{from_bit_string, BigEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let value: u16 = from_bit_string("0001_0010 0011_0100", BigEndian)?;
+/// assert_eq!(value, 0x1234);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub fn from_bit_string<T, E>(bits: &str, endianness: E) -> Result<T>
+where
+    T: BitRead<'static, E>,
+    E: Endianness,
+{
+    let mut data = Vec::with_capacity(bits.len() / 8 + 1);
+    let mut stream = BitWriteStream::new(&mut data, endianness);
+    for (index, char) in bits.char_indices() {
+        match char {
+            '0' => stream.write_bool(false)?,
+            '1' => stream.write_bool(true)?,
+            '_' => {}
+            char if char.is_whitespace() => {}
+            char => return Err(BitError::InvalidBitString { index, char }),
+        }
+    }
+    let bit_len = stream.bit_len();
+
+    let mut buffer = BitReadBuffer::new_owned(data, E::endianness());
+    buffer.truncate(bit_len)?;
+    BitReadStream::new(buffer).read()
+}
@@ -0,0 +1,92 @@
+//! Conversions between [`BitSlice`]/[`BitVec`] and the bitbuffer read and write types
+//!
+//! The conversions keep the order of the bits, the first bit of the slice is the first bit read
+//! from the stream, regardless of the [`BitOrder`] of the slice and the [`Endianness`] of the stream.
+use crate::writestream::pack_bits;
+use crate::{BitReadBuffer, BitReadStream, BitWriteStream, Endianness, Result};
+use bitvec::order::BitOrder;
+use bitvec::slice::BitSlice;
+use bitvec::store::BitStore;
+use bitvec::vec::BitVec;
+
+impl<T: BitStore, O: BitOrder, E: Endianness> From<&BitSlice<T, O>> for BitReadBuffer<'static, E> {
+    fn from(bits: &BitSlice<T, O>) -> Self {
+        let mut data = Vec::with_capacity((bits.len() + 7) / 8);
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream
+            .write_bits_from_bitslice(bits)
+            .expect("writing to a vec can't fail");
+        let mut buffer = BitReadBuffer::new_owned(data, E::endianness());
+        buffer
+            .truncate(bits.len())
+            .expect("buffer contains all written bits");
+        buffer
+    }
+}
+
+impl<T: BitStore, O: BitOrder, E: Endianness> From<&BitSlice<T, O>> for BitReadStream<'static, E> {
+    fn from(bits: &BitSlice<T, O>) -> Self {
+        BitReadStream::new(bits.into())
+    }
+}
+
+impl<E: Endianness, O: BitOrder> From<&BitReadStream<'_, E>> for BitVec<u8, O> {
+    /// Copy the remaining bits of the stream into a [`BitVec`]
+    fn from(stream: &BitReadStream<'_, E>) -> Self {
+        let mut stream = stream.clone();
+        let mut bits = BitVec::with_capacity(stream.bits_left());
+        while stream.bits_left() > 0 {
+            let chunk = stream.bits_left().min(u64::BITS as usize);
+            let packed: u64 = stream.read_int(chunk).expect("chunk fits in the stream");
+            if E::is_le() {
+                bits.extend((0..chunk).map(|index| packed & (1 << index) != 0));
+            } else {
+                bits.extend((0..chunk).rev().map(|index| packed & (1 << index) != 0));
+            }
+        }
+        bits
+    }
+}
+
+impl<E: Endianness, O: BitOrder> From<&BitReadBuffer<'_, E>> for BitVec<u8, O> {
+    fn from(buffer: &BitReadBuffer<'_, E>) -> Self {
+        (&BitReadStream::new(buffer.clone())).into()
+    }
+}
+
+impl<E: Endianness> BitWriteStream<'_, E> {
+    /// Write all bits from a [`BitSlice`] to the stream, in the order of the slice
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, BigEndian, Result};
+    /// use bitvec::prelude::*;
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    /// stream.write_bits_from_bitslice(bits![u8, Lsb0; 1, 0, 1, 1])?;
+    /// assert_eq!(data, [0b1011_0000]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_bits_from_bitslice<T: BitStore, O: BitOrder>(
+        &mut self,
+        bits: &BitSlice<T, O>,
+    ) -> Result<()> {
+        if let Some(capacity) = self.remaining_capacity_bits() {
+            if bits.len() > capacity {
+                return Err(crate::BitError::WriteOverflow {
+                    capacity_bits: self.bit_len() + capacity,
+                    attempted_bits: self.bit_len() + bits.len(),
+                });
+            }
+        }
+        for chunk in bits.chunks(u64::BITS as usize) {
+            self.write_int(pack_bits::<E, _>(chunk.iter().by_vals()), chunk.len())?;
+        }
+        Ok(())
+    }
+}
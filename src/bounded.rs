@@ -0,0 +1,269 @@
+//! Selecting how reads are bounds checked at compile time
+use crate::num_traits::{Float, PrimInt, WrappingSub};
+use crate::num_traits::{IsSigned, UncheckedPrimitiveFloat, UncheckedPrimitiveInt};
+use crate::{BitError, BitRead, BitReadStream, CheckedRead, Endianness, Result};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ops::BitOrAssign;
+
+/// The bounds checking strategy of a [`BoundedReader`], one of [`Checked`], [`CheckedOnce`] or [`Trusted`]
+///
+/// Parsing code that is generic over the strategy can be used both for untrusted input and
+/// for input that has been fully validated before, without per-read checks in the latter case.
+pub trait BoundsCheck: private::Sealed {
+    /// Check that `count` bits can be read from the stream
+    #[doc(hidden)]
+    fn check<E: Endianness>(
+        stream: &BitReadStream<E>,
+        region: CheckedRead,
+        count: usize,
+    ) -> Result<CheckedRead>;
+}
+
+/// Every read is checked against the end of the stream, the read budget and cancellation, like reads from the stream itself
+#[derive(Debug, Clone, Copy)]
+pub enum Checked {}
+
+/// The region is checked once when the reader is created, reads only check that they stay inside the region
+///
+/// Reading a type without a fixed [`bit_size`](BitRead::bit_size) using [`BoundedReader::read`] falls back
+/// to the regular checks of the stream, so those reads can extend past the region.
+#[derive(Debug, Clone, Copy)]
+pub enum CheckedOnce {}
+
+/// Reads aren't checked, the caller guarantees that the input is long enough when creating the reader
+///
+/// Reading past the end of the stream panics instead of returning an error.
+#[derive(Debug, Clone, Copy)]
+pub enum Trusted {}
+
+impl BoundsCheck for Checked {
+    #[inline]
+    fn check<E: Endianness>(
+        stream: &BitReadStream<E>,
+        _region: CheckedRead,
+        count: usize,
+    ) -> Result<CheckedRead> {
+        stream.check_read(count)
+    }
+}
+
+impl BoundsCheck for CheckedOnce {
+    #[inline]
+    fn check<E: Endianness>(
+        stream: &BitReadStream<E>,
+        region: CheckedRead,
+        count: usize,
+    ) -> Result<CheckedRead> {
+        stream.check_within(region, count)?;
+        Ok(region)
+    }
+}
+
+impl BoundsCheck for Trusted {
+    #[inline(always)]
+    fn check<E: Endianness>(
+        _stream: &BitReadStream<E>,
+        region: CheckedRead,
+        _count: usize,
+    ) -> Result<CheckedRead> {
+        Ok(region)
+    }
+}
+
+/// Reader that bounds checks according to the [`BoundsCheck`] strategy `M`
+///
+/// Created by [`BitReadStream::checked_reader`], [`BitReadStream::check_once`] or [`BitReadStream::trusted_reader`].
+/// The underlying stream is advanced by the reads as they are done.
+///
+/// All strategies return a `Result` from the reads, so the same parsing code can be used with each of them.
+/// With [`Trusted`] the bounds checks always succeed, only the assertion against reading past the end of the stream remains.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitReadBuffer, BitReadStream, BoundedReader, BoundsCheck, LittleEndian, Result};
+/// #
+/// fn parse<M: BoundsCheck>(reader: &mut BoundedReader<LittleEndian, M>) -> Result<(u8, u16)> {
+///     Ok((reader.read_int(4)?, reader.read_int(12)?))
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let bytes = vec![0x21, 0x43];
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+/// assert_eq!((1, 0x432), parse(&mut stream.checked_reader())?);
+///
+/// stream.set_pos(0)?;
+/// assert_eq!((1, 0x432), parse(&mut stream.check_once(16)?)?);
+///
+/// stream.set_pos(0)?;
+/// // the input has been validated to contain 16 bits
+/// assert_eq!((1, 0x432), parse(unsafe { &mut stream.trusted_reader() })?);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub struct BoundedReader<'s, 'a, E: Endianness, M: BoundsCheck = Checked> {
+    stream: &'s mut BitReadStream<'a, E>,
+    region: CheckedRead,
+    mode: PhantomData<M>,
+}
+
+impl<'a, E: Endianness> BitReadStream<'a, E> {
+    /// Create a reader that checks every read
+    pub fn checked_reader(&mut self) -> BoundedReader<'_, 'a, E, Checked> {
+        BoundedReader::new(self)
+    }
+
+    /// Check that `count` bits are available and create a reader that only checks that reads stay inside those bits
+    ///
+    /// The read budget and cancellation of the stream are only checked when creating the reader.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available in the stream
+    pub fn check_once(&mut self, count: usize) -> Result<BoundedReader<'_, 'a, E, CheckedOnce>> {
+        let region = self.check_read(count)?;
+        Ok(BoundedReader {
+            stream: self,
+            region,
+            mode: PhantomData,
+        })
+    }
+
+    /// Create a reader that doesn't check reads
+    ///
+    /// The read budget and cancellation of the stream aren't checked.
+    ///
+    /// # Safety
+    ///
+    /// The input has to be validated to contain enough data for every read made trough the reader,
+    /// reading past the end of the stream panics.
+    pub unsafe fn trusted_reader(&mut self) -> BoundedReader<'_, 'a, E, Trusted> {
+        BoundedReader::new(self)
+    }
+}
+
+impl<'s, 'a, E: Endianness, M: BoundsCheck> BoundedReader<'s, 'a, E, M> {
+    fn new(stream: &'s mut BitReadStream<'a, E>) -> Self {
+        BoundedReader {
+            region: stream.check_remaining(),
+            stream,
+            mode: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn check(&self, count: usize) -> Result<CheckedRead> {
+        M::check(self.stream, self.region, count)
+    }
+
+    /// The underlying stream
+    pub fn stream(&self) -> &BitReadStream<'a, E> {
+        self.stream
+    }
+
+    /// Read a single bit as boolean
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available
+    #[inline]
+    pub fn read_bool(&mut self) -> Result<bool> {
+        let checked = self.check(1)?;
+        Ok(unsafe { self.stream.read_bool_unchecked(checked) })
+    }
+
+    /// Read a sequence of bits as integer
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available
+    /// - [`BitError::TooManyBits`]: to many bits requested for the chosen integer type
+    #[inline]
+    pub fn read_int<T>(&mut self, count: usize) -> Result<T>
+    where
+        T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + WrappingSub,
+    {
+        let max = size_of::<T>() * 8;
+        if count > max {
+            return Err(BitError::TooManyBits {
+                requested: count,
+                max,
+            });
+        }
+        let checked = self.check(count)?;
+        Ok(unsafe { self.stream.read_int_unchecked(count, checked) })
+    }
+
+    /// Read a float
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available
+    #[inline]
+    pub fn read_float<T>(&mut self) -> Result<T>
+    where
+        T: Float + UncheckedPrimitiveFloat,
+    {
+        let checked = self.check(size_of::<T>() * 8)?;
+        Ok(unsafe { self.stream.read_float_unchecked(checked) })
+    }
+
+    /// Read a series of bytes
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available
+    #[inline]
+    pub fn read_bytes(&mut self, byte_count: usize) -> Result<Cow<'a, [u8]>> {
+        let count = byte_count
+            .checked_mul(8)
+            .ok_or_else(|| BitError::NotEnoughData {
+                requested: usize::MAX,
+                bits_left: self.stream.bits_left(),
+            })?;
+        let checked = self.check(count)?;
+        Ok(unsafe { self.stream.read_bytes_unchecked(byte_count, checked) })
+    }
+
+    /// Read a value based on the provided type
+    ///
+    /// Only the reads of types with a fixed [`bit_size`](BitRead::bit_size) use the bounds checking strategy,
+    /// other types are read with the regular checks of the stream.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available
+    /// - any error returned by the [`BitRead`] implementation of `T`
+    #[inline]
+    pub fn read<T: BitRead<'a, E>>(&mut self) -> Result<T> {
+        match T::bit_size() {
+            Some(size) => {
+                let checked = self.check(size)?;
+                unsafe { self.stream.read_unchecked(checked) }
+            }
+            None => self.stream.read(),
+        }
+    }
+
+    /// Skip a number of bits
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available
+    #[inline]
+    pub fn skip_bits(&mut self, count: usize) -> Result<()> {
+        self.check(count)?;
+        self.stream.skip_bits(count)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::Checked {}
+    impl Sealed for super::CheckedOnce {}
+    impl Sealed for super::Trusted {}
+}
@@ -0,0 +1,58 @@
+use std::mem::size_of;
+
+/// Swap the bytes of a value, used by the `#[byte_swap]` attribute of the derive macros
+///
+/// This allows reading and writing values in formats that use a mixed byte order,
+/// such as the "middle-endian" 32 bit values used by the PDP-11.
+pub trait ByteSwap: Copy {
+    /// Reverse the order of the bytes in every group of `group_size` bytes
+    ///
+    /// A `group_size` equal to the size of the type reverses all bytes of the value,
+    /// the size of the type should be a multiple of the `group_size`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitbuffer::ByteSwap;
+    ///
+    /// assert_eq!(0x7856_3412, 0x1234_5678u32.byte_swap(4));
+    /// assert_eq!(0x3412_7856, 0x1234_5678u32.byte_swap(2));
+    /// ```
+    fn byte_swap(self, group_size: usize) -> Self;
+}
+
+macro_rules! impl_byte_swap_int {
+    ($($type:ty),*) => {
+        $(
+            impl ByteSwap for $type {
+                #[inline]
+                fn byte_swap(self, group_size: usize) -> Self {
+                    if group_size >= size_of::<Self>() {
+                        return self.swap_bytes();
+                    }
+                    let mut bytes = self.to_ne_bytes();
+                    for group in bytes.chunks_mut(group_size.max(1)) {
+                        group.reverse();
+                    }
+                    Self::from_ne_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_byte_swap_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl ByteSwap for f32 {
+    #[inline]
+    fn byte_swap(self, group_size: usize) -> Self {
+        f32::from_bits(self.to_bits().byte_swap(group_size))
+    }
+}
+
+impl ByteSwap for f64 {
+    #[inline]
+    fn byte_swap(self, group_size: usize) -> Self {
+        f64::from_bits(self.to_bits().byte_swap(group_size))
+    }
+}
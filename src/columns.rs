@@ -0,0 +1,108 @@
+use crate::{BitRead, BitReadStream, CheckedRead, Endianness, Result};
+
+/// Trait for reading records into a struct-of-arrays layout
+///
+/// Every record is read field by field and each field is appended to its own column, without
+/// constructing the intermediate per-record value.
+///
+/// This is implemented for `Vec<T>` and for tuples of up to 8 `Vec`s, where each tuple element
+/// holds one field of the record in the order the fields are stored in the stream.
+///
+/// See [`BitReadStream::read_struct_of_arrays`] for reading records into columns.
+pub trait BitReadColumns<'a, E: Endianness> {
+    /// Read a single record and append its fields to the columns
+    fn read_record(&mut self, stream: &mut BitReadStream<'a, E>) -> Result<()>;
+
+    /// Read a single record and append its fields to the columns without checking the bounds
+    /// of the stream
+    ///
+    /// # Safety
+    ///
+    /// Callers have to ensure that the stream has enough data left for [`record_bit_size`] bits
+    ///
+    /// [`record_bit_size`]: BitReadColumns::record_bit_size
+    #[doc(hidden)]
+    #[inline]
+    unsafe fn read_record_unchecked(
+        &mut self,
+        stream: &mut BitReadStream<'a, E>,
+        _checked: CheckedRead,
+    ) -> Result<()> {
+        self.read_record(stream)
+    }
+
+    /// Reserve space for `additional` records in every column
+    fn reserve(&mut self, additional: usize);
+
+    /// The number of bits a single record takes in the stream, if it is fixed
+    fn record_bit_size() -> Option<usize>;
+}
+
+impl<'a, E: Endianness, T: BitRead<'a, E>> BitReadColumns<'a, E> for Vec<T> {
+    #[inline]
+    fn read_record(&mut self, stream: &mut BitReadStream<'a, E>) -> Result<()> {
+        self.push(stream.read()?);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn read_record_unchecked(
+        &mut self,
+        stream: &mut BitReadStream<'a, E>,
+        checked: CheckedRead,
+    ) -> Result<()> {
+        self.push(stream.read_unchecked(checked)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional)
+    }
+
+    #[inline]
+    fn record_bit_size() -> Option<usize> {
+        T::bit_size()
+    }
+}
+
+macro_rules! impl_read_columns {
+    ($($type:ident => $index:tt),*) => {
+        impl<'a, E: Endianness, $($type: BitRead<'a, E>),*> BitReadColumns<'a, E> for ($(Vec<$type>,)*) {
+            #[inline]
+            fn read_record(&mut self, stream: &mut BitReadStream<'a, E>) -> Result<()> {
+                $(self.$index.push(stream.read()?);)*
+                Ok(())
+            }
+
+            #[inline]
+            unsafe fn read_record_unchecked(
+                &mut self,
+                stream: &mut BitReadStream<'a, E>,
+                checked: CheckedRead,
+            ) -> Result<()> {
+                $(self.$index.push(stream.read_unchecked(checked)?);)*
+                Ok(())
+            }
+
+            #[inline]
+            fn reserve(&mut self, additional: usize) {
+                $(self.$index.reserve(additional);)*
+            }
+
+            #[inline]
+            fn record_bit_size() -> Option<usize> {
+                Some(0)$(.and_then(|sum| <$type>::bit_size().map(|size| sum + size)))*
+            }
+        }
+    };
+}
+
+impl_read_columns!(T1 => 0);
+impl_read_columns!(T1 => 0, T2 => 1);
+impl_read_columns!(T1 => 0, T2 => 1, T3 => 2);
+impl_read_columns!(T1 => 0, T2 => 1, T3 => 2, T4 => 3);
+impl_read_columns!(T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4);
+impl_read_columns!(T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5);
+impl_read_columns!(T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6);
+impl_read_columns!(T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6, T8 => 7);
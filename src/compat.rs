@@ -0,0 +1,56 @@
+//! Names from `bitstream_reader`, the previous name of this crate
+//!
+//! Code written against `bitstream_reader` can switch its imports to this module and then migrate to the
+//! new names incrementally, the aliases refer to the same types so old and new code can be mixed freely.
+//!
+//! The buffers of `bitstream_reader` always owned their data, [`BitBuffer`] and [`BitStream`] are aliases for
+//! the owned `'static` versions of [`BitReadBuffer`] and [`BitReadStream`]. A few things need changes while migrating:
+//!
+//! - `BitBuffer::new(bytes, endianness)` took an owned `Vec<u8>`, use [`BitReadBuffer::new_owned`] or
+//!   `BitBuffer::from((bytes, endianness))` instead, streams can be created the same way with `BitStream::from((bytes, endianness))`
+//! - the [`BitRead`] and [`BitReadSized`] traits take a lifetime parameter for the data borrowed from the stream,
+//!   manual implementations need to add it: `impl<'a, E: Endianness> BitRead<'a, E> for Foo`
+//! - [`ReadError`] is the combined error for reading and writing, matching on it needs a wildcard arm
+//!
+//! This module requires the `compat` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitbuffer::compat::{BitBuffer, BitStream, LittleEndian, ReadError, Result};
+//!
+//! fn parse(bytes: Vec<u8>) -> Result<u16> {
+//!     let buffer = BitBuffer::from((bytes, LittleEndian));
+//!     let mut stream = BitStream::new(buffer);
+//!     stream.read_int(12)
+//! }
+//!
+//! assert_eq!(parse(vec![0xff, 0xff]).unwrap(), 0xfff);
+//! assert!(matches!(parse(vec![0xff]), Err(ReadError::NotEnoughData { .. })));
+//! ```
+use crate::{BitError, BitReadBuffer, BitReadStream};
+
+pub use crate::{BigEndian, BitRead, BitReadSized, Endianness, LittleEndian, Result};
+
+/// The owned buffer from `bitstream_reader`, see [`BitReadBuffer`]
+pub type BitBuffer<E> = BitReadBuffer<'static, E>;
+
+/// The stream over an owned buffer from `bitstream_reader`, see [`BitReadStream`]
+pub type BitStream<E> = BitReadStream<'static, E>;
+
+/// The read error from `bitstream_reader`, see [`BitError`]
+pub type ReadError = BitError;
+
+/// Create an owned buffer from the bytes and endianness that `BitBuffer::new` from `bitstream_reader` took
+impl<E: Endianness> From<(Vec<u8>, E)> for BitBuffer<E> {
+    fn from((bytes, endianness): (Vec<u8>, E)) -> Self {
+        BitReadBuffer::new_owned(bytes, endianness)
+    }
+}
+
+/// Create a stream over an owned buffer from the bytes and endianness that `BitBuffer::new` from `bitstream_reader` took
+impl<E: Endianness> From<(Vec<u8>, E)> for BitStream<E> {
+    fn from(bytes: (Vec<u8>, E)) -> Self {
+        BitReadStream::new(BitBuffer::from(bytes))
+    }
+}
@@ -0,0 +1,97 @@
+use std::cmp::min;
+use std::ops::Range;
+
+/// Record of which bits of a stream have been read
+///
+/// Positions are stored relative to the position of the stream that started the tracking.
+#[derive(Debug, Clone)]
+pub(crate) struct ReadCoverage {
+    /// Position in the underlying buffer of the first tracked bit
+    start: usize,
+    /// Number of tracked bits
+    len: usize,
+    high_water_mark: usize,
+    bitmap: Vec<u64>,
+}
+
+impl ReadCoverage {
+    pub fn new(start: usize, len: usize) -> Self {
+        ReadCoverage {
+            start,
+            len,
+            high_water_mark: 0,
+            bitmap: vec![0; (len + 63) / 64],
+        }
+    }
+
+    /// Mark `count` bits starting at the buffer position `pos` as read
+    pub fn mark(&mut self, pos: usize, count: usize) {
+        let start = pos.saturating_sub(self.start);
+        let end = min((pos + count).saturating_sub(self.start), self.len);
+        if start >= end {
+            return;
+        }
+        self.high_water_mark = self.high_water_mark.max(end);
+
+        let mut i = start;
+        while i < end {
+            let bit = i % 64;
+            let n = min(64 - bit, end - i);
+            let mask = if n == 64 {
+                u64::MAX
+            } else {
+                ((1u64 << n) - 1) << bit
+            };
+            self.bitmap[i / 64] |= mask;
+            i += n;
+        }
+    }
+
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    pub fn unread_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut unread_start = None;
+        let mut i = 0;
+        while i < self.len {
+            let word = self.bitmap[i / 64];
+            // skip trough fully read or fully unread words in one go
+            let (read, n) = if i % 64 == 0 && i + 64 <= self.len && (word == 0 || word == u64::MAX)
+            {
+                (word == u64::MAX, 64)
+            } else {
+                ((word >> (i % 64)) & 1 == 1, 1)
+            };
+            match (read, unread_start) {
+                (true, Some(start)) => {
+                    ranges.push(start..i);
+                    unread_start = None;
+                }
+                (false, None) => unread_start = Some(i),
+                _ => {}
+            }
+            i += n;
+        }
+        if let Some(start) = unread_start {
+            ranges.push(start..self.len);
+        }
+        ranges
+    }
+}
+
+#[test]
+fn test_coverage_ranges() {
+    let mut coverage = ReadCoverage::new(10, 200);
+    assert_eq!(coverage.unread_ranges(), vec![Range { start: 0, end: 200 }]);
+
+    coverage.mark(10, 3);
+    coverage.mark(15, 100);
+    coverage.mark(190, 100);
+    assert_eq!(vec![3..5, 105..180], coverage.unread_ranges());
+    assert_eq!(200, coverage.high_water_mark());
+
+    coverage.mark(0, 300);
+    assert!(coverage.unread_ranges().is_empty());
+}
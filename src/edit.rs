@@ -0,0 +1,216 @@
+//! Reading and overwriting bits in existing data
+use crate::num_traits::{IsSigned, SplitFitUsize, UncheckedPrimitiveInt};
+use crate::num_traits::{PrimInt, WrappingSub};
+use crate::{
+    BitError, BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteSized, BitWriteStream,
+    Endianness, Result,
+};
+use std::cmp::min;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{BitOrAssign, BitXor};
+
+/// Buffer for reading and overwriting bits at arbitrary positions in existing data
+///
+/// Unlike [`BitWriteStream`], writes overwrite the existing bits at the given position instead of appending,
+/// which allows patching fields in existing data without reading and writing all of it.
+/// Writes can't extend past the end of the data.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitEditBuffer, BigEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let mut data = vec![0b1010_0000, 0xff];
+/// let mut buffer = BitEditBuffer::new(&mut data, BigEndian);
+/// assert_eq!(buffer.read_int::<u8>(0, 3)?, 0b101);
+/// buffer.write_bool(1, true)?;
+/// buffer.write_int(6, 0u8, 4)?;
+/// assert_eq!(data, [0b1110_0000, 0b0011_1111]);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub struct BitEditBuffer<'a, E: Endianness> {
+    bytes: &'a mut [u8],
+    endianness: PhantomData<E>,
+}
+
+impl<'a, E: Endianness> BitEditBuffer<'a, E> {
+    /// Create a new edit buffer for existing data
+    pub fn new(bytes: &'a mut [u8], _endianness: E) -> Self {
+        BitEditBuffer {
+            bytes,
+            endianness: PhantomData,
+        }
+    }
+
+    /// The number of bits in the buffer
+    pub fn bit_len(&self) -> usize {
+        self.bytes.len() * 8
+    }
+
+    /// Borrow the buffer for reading
+    pub fn read_buffer(&self) -> BitReadBuffer<'_, E> {
+        BitReadBuffer::new(self.bytes, E::endianness())
+    }
+
+    /// Read a single bit at `position`
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: `position` is outside the buffer
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read_bool(&self, position: usize) -> Result<bool> {
+        self.read_buffer().read_bool(position)
+    }
+
+    /// Read an integer of `count` bits at `position`
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the buffer
+    /// - [`ReadError::TooManyBits`]: to many bits requested for the chosen integer type
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::TooManyBits`]: enum.ReadError.html#variant.TooManyBits
+    pub fn read_int<T>(&self, position: usize, count: usize) -> Result<T>
+    where
+        T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + BitXor + WrappingSub,
+    {
+        self.read_buffer().read_int(position, count)
+    }
+
+    /// Read a value at `position`
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::IndexOutOfBounds`]: `position` is outside the buffer
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the buffer
+    /// - any error returned by the [`BitRead`] implementation of `T`
+    ///
+    /// [`ReadError::IndexOutOfBounds`]: enum.ReadError.html#variant.IndexOutOfBounds
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read<'b, T: BitRead<'b, E>>(&'b self, position: usize) -> Result<T> {
+        let mut stream = BitReadStream::new(self.read_buffer());
+        stream.set_pos(position)?;
+        stream.read()
+    }
+
+    /// Overwrite a single bit at `position`
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: `position` is outside the buffer
+    pub fn write_bool(&mut self, position: usize, value: bool) -> Result<()> {
+        if position >= self.bit_len() {
+            return Err(BitError::WriteOverflow {
+                capacity_bits: self.bit_len(),
+                attempted_bits: position.saturating_add(1),
+            });
+        }
+
+        let bit_offset = position % 8;
+        let mask = bit_mask::<E>(bit_offset, bit_offset + 1);
+        let target = &mut self.bytes[position / 8];
+        if value {
+            *target |= mask;
+        } else {
+            *target &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Overwrite `count` bits at `position` with an integer
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: the integer would extend past the end of the buffer
+    /// - [`BitError::TooManyBitsForValue`]: `count` is larger than the number of bits in `T`
+    pub fn write_int<T>(&mut self, position: usize, value: T, count: usize) -> Result<()>
+    where
+        T: PrimInt
+            + BitOrAssign
+            + IsSigned
+            + UncheckedPrimitiveInt
+            + BitXor
+            + Debug
+            + SplitFitUsize,
+    {
+        self.write_with(position, |stream| stream.write_int(value, count))
+    }
+
+    /// Overwrite the bits at `position` with a value
+    ///
+    /// Nothing is written if the value doesn't fit in the buffer or writing it fails.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: the value would extend past the end of the buffer
+    /// - any error returned by the [`BitWrite`] implementation of `T`
+    pub fn write<T: BitWrite<E> + ?Sized>(&mut self, position: usize, value: &T) -> Result<()> {
+        self.write_with(position, |stream| stream.write(value))
+    }
+
+    /// Overwrite the bits at `position` with a value using a specific length
+    ///
+    /// Nothing is written if the value doesn't fit in the buffer or writing it fails.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: the value would extend past the end of the buffer
+    /// - any error returned by the [`BitWriteSized`] implementation of `T`
+    pub fn write_sized<T: BitWriteSized<E> + ?Sized>(
+        &mut self,
+        position: usize,
+        value: &T,
+        len: usize,
+    ) -> Result<()> {
+        self.write_with(position, |stream| stream.write_sized(value, len))
+    }
+
+    /// Write into a temporary stream at the same bit offset and merge the written bits into the buffer
+    fn write_with(
+        &mut self,
+        position: usize,
+        write: impl FnOnce(&mut BitWriteStream<E>) -> Result<()>,
+    ) -> Result<()> {
+        let bit_offset = position % 8;
+        let mut written = Vec::new();
+        let mut stream = BitWriteStream::new(&mut written, E::endianness());
+        stream.write_int(0u8, bit_offset)?;
+        write(&mut stream)?;
+        let end = stream.bit_len();
+
+        match (position - bit_offset).checked_add(end) {
+            Some(attempted_bits) if attempted_bits <= self.bit_len() => {}
+            attempted_bits => {
+                return Err(BitError::WriteOverflow {
+                    capacity_bits: self.bit_len(),
+                    attempted_bits: attempted_bits.unwrap_or(usize::MAX),
+                });
+            }
+        }
+
+        let target = &mut self.bytes[position / 8..];
+        for (index, (target, written)) in target.iter_mut().zip(written).enumerate() {
+            let first = bit_offset.saturating_sub(index * 8);
+            let last = min(end - index * 8, 8);
+            let mask = bit_mask::<E>(first, last);
+            *target = (*target & !mask) | (written & mask);
+        }
+        Ok(())
+    }
+}
+
+/// Mask for the bits `first..last` of a byte, in the bit order of the endianness
+fn bit_mask<E: Endianness>(first: usize, last: usize) -> u8 {
+    let mask = ((1u16 << last) - (1u16 << first)) as u8;
+    if E::is_le() {
+        mask
+    } else {
+        mask.reverse_bits()
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::marker::PhantomData;
+
 /// Trait for specifying endianness of bit buffer
 pub trait Endianness: private::Sealed {
     /// Get the endianness as string, either LittleEndian or BigEndian
@@ -9,14 +12,52 @@ pub trait Endianness: private::Sealed {
         }
     }
 
-    /// Input is little endian
+    /// Input is little endian, bits are filled least significant bit first
     fn is_le() -> bool;
-    /// Input is big endian
+    /// Input is big endian, bits are filled most significant bit first
     fn is_be() -> bool;
+    /// Multi byte values are stored with the least significant byte first
+    ///
+    /// This is the same as [`is_le`](Endianness::is_le) except for the mixed orders [`LsbFirst`] and [`MsbFirst`].
+    fn is_byte_order_le() -> bool {
+        Self::is_le()
+    }
     /// Get an instance of the endianness
     fn endianness() -> Self;
 }
 
+/// Whether the byte order of multi byte values differs from the bit fill order
+#[inline(always)]
+pub(crate) fn swaps_bytes<E: Endianness>() -> bool {
+    E::is_le() != E::is_byte_order_le()
+}
+
+/// Reverse the order of the bytes of a `count` bit value, as read in the bit fill order of a stream
+///
+/// The bits are split into bytes starting from the bits that are read first, when `count` isn't
+/// a multiple of 8, the byte that is read last only has the remaining bits.
+/// With `lsb_first` the first read bits are the least significant bits of `value`, the first read
+/// byte ends up as the most significant byte of the result, without it the reverse happens.
+///
+/// Reversing the bytes of a value with `lsb_first` can be undone by reversing them again without.
+#[inline]
+pub(crate) fn swap_byte_groups(value: u128, count: usize, lsb_first: bool) -> u128 {
+    let mut result = 0;
+    let mut before = 0;
+    while before < count {
+        let size = (count - before).min(8);
+        let mask = (1u128 << size) - 1;
+        let after = count - before - size;
+        if lsb_first {
+            result |= (value >> before & mask) << after;
+        } else {
+            result |= (value >> after & mask) << before;
+        }
+        before += size;
+    }
+    result
+}
+
 /// Marks the buffer or stream as big endian
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -50,6 +91,107 @@ macro_rules! impl_endianness {
 impl_endianness!(BigEndian, false, BigEndian);
 impl_endianness!(LittleEndian, true, LittleEndian);
 
+/// Marks the buffer or stream as filling bits least significant bit first, with the byte order of `E`
+///
+/// `LsbFirst<BigEndian>` describes formats where the bits are filled the same as for [`LittleEndian`],
+/// but multi byte integers and floats are stored with the most significant byte first.
+/// `LsbFirst<LittleEndian>` is the same as `LittleEndian`.
+///
+/// When the number of bits of an integer isn't a multiple of 8, the byte that is read last only
+/// contains the remaining bits.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BigEndian, BitReadBuffer, BitReadStream, Endianness, LsbFirst, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let bytes = vec![0b0001_0011, 0x12, 0x34];
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LsbFirst::<BigEndian>::endianness()));
+/// assert_eq!(0b011, stream.read_int::<u8>(3)?);
+/// stream.skip_bits(5)?;
+/// assert_eq!(0x1234, stream.read_int::<u16>(16)?);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub struct LsbFirst<E>(PhantomData<E>);
+
+/// Marks the buffer or stream as filling bits most significant bit first, with the byte order of `E`
+///
+/// `MsbFirst<LittleEndian>` describes formats where the bits are filled the same as for [`BigEndian`],
+/// but multi byte integers and floats are stored with the least significant byte first.
+/// `MsbFirst<BigEndian>` is the same as `BigEndian`.
+pub struct MsbFirst<E>(PhantomData<E>);
+
+macro_rules! impl_mixed_endianness {
+    ($type:ident, $le:expr, $name:expr) => {
+        impl<E: Endianness> Endianness for $type<E> {
+            fn as_string() -> &'static str {
+                match (Self::is_le(), Self::is_byte_order_le()) {
+                    (true, true) => "LittleEndian",
+                    (false, false) => "BigEndian",
+                    (_, true) => concat!($name, "<LittleEndian>"),
+                    (_, false) => concat!($name, "<BigEndian>"),
+                }
+            }
+
+            #[inline(always)]
+            fn is_le() -> bool {
+                $le
+            }
+
+            #[inline(always)]
+            fn is_be() -> bool {
+                !$le
+            }
+
+            #[inline(always)]
+            fn is_byte_order_le() -> bool {
+                E::is_byte_order_le()
+            }
+
+            fn endianness() -> Self {
+                $type(PhantomData)
+            }
+        }
+
+        impl<E> fmt::Debug for $type<E>
+        where
+            Self: Endianness,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(Self::as_string())
+            }
+        }
+
+        impl<E> Clone for $type<E> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<E> Copy for $type<E> {}
+
+        impl<E> PartialEq for $type<E> {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        impl<E> Eq for $type<E> {}
+
+        impl<E: Endianness> Default for $type<E> {
+            fn default() -> Self {
+                Self::endianness()
+            }
+        }
+    };
+}
+
+impl_mixed_endianness!(LsbFirst, true, "LsbFirst");
+impl_mixed_endianness!(MsbFirst, false, "MsbFirst");
+
 mod private {
     pub trait Sealed {}
 
@@ -57,4 +199,8 @@ mod private {
     impl Sealed for super::BigEndian {}
 
     impl Sealed for super::LittleEndian {}
+
+    impl<E: super::Endianness> Sealed for super::LsbFirst<E> {}
+
+    impl<E: super::Endianness> Sealed for super::MsbFirst<E> {}
 }
@@ -0,0 +1,155 @@
+//! Infallible reads from a region of a stream that has been bounds checked upfront
+use crate::num_traits::{Float, PrimInt, WrappingSub};
+use crate::num_traits::{IsSigned, UncheckedPrimitiveFloat, UncheckedPrimitiveInt};
+use crate::{BitReadStream, CheckedRead, Endianness, Result};
+use std::borrow::Cow;
+use std::mem::size_of;
+use std::ops::BitOrAssign;
+
+/// Reader for a fixed number of bits that have been checked to be available in the stream
+///
+/// Created by [`BitReadStream::take_exact`], reads from the reader don't return a `Result` since
+/// the bounds check has already been done. Reading more bits than were taken panics.
+///
+/// The underlying stream is advanced by the reads as they are done.
+pub struct ExactReader<'s, 'a, E: Endianness> {
+    stream: &'s mut BitReadStream<'a, E>,
+    checked: CheckedRead,
+    remaining: usize,
+}
+
+impl<'a, E: Endianness> BitReadStream<'a, E> {
+    /// Check that `count` bits are available and return a reader for them with infallible reads
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`](crate::BitError::NotEnoughData): not enough bits available in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0b1010_0101, 0x34, 0x12, 0xff];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    ///
+    /// let mut reader = stream.take_exact(24)?;
+    /// assert_eq!(true, reader.read_bool());
+    /// assert_eq!(0b1010_010, reader.read_int::<u8>(7));
+    /// assert_eq!(0x1234, reader.read_int::<u16>(16));
+    /// assert_eq!(0, reader.remaining());
+    ///
+    /// assert_eq!(24, stream.pos());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn take_exact(&mut self, count: usize) -> Result<ExactReader<'_, 'a, E>> {
+        let checked = self.check_read(count)?;
+        Ok(ExactReader {
+            stream: self,
+            checked,
+            remaining: count,
+        })
+    }
+}
+
+impl<'s, 'a, E: Endianness> ExactReader<'s, 'a, E> {
+    /// The number of bits that can still be read
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    #[inline]
+    #[track_caller]
+    fn take(&mut self, count: usize) {
+        assert!(
+            count <= self.remaining,
+            "read of {} bits with only {} bits remaining in the exact reader",
+            count,
+            self.remaining
+        );
+        self.remaining -= count;
+    }
+
+    /// Read a single bit as boolean
+    ///
+    /// # Panics
+    ///
+    /// Panics if no bits are remaining
+    #[inline]
+    #[track_caller]
+    pub fn read_bool(&mut self) -> bool {
+        self.take(1);
+        unsafe { self.stream.read_bool_unchecked(self.checked) }
+    }
+
+    /// Read a sequence of bits as integer
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `count` bits are remaining or `count` is larger than the size of `T`
+    #[inline]
+    #[track_caller]
+    pub fn read_int<T>(&mut self, count: usize) -> T
+    where
+        T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + WrappingSub,
+    {
+        assert!(
+            count <= size_of::<T>() * 8,
+            "read of {} bits into a {} bit integer",
+            count,
+            size_of::<T>() * 8
+        );
+        self.take(count);
+        unsafe { self.stream.read_int_unchecked(count, self.checked) }
+    }
+
+    /// Read a float
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer bits than the size of `T` are remaining
+    #[inline]
+    #[track_caller]
+    pub fn read_float<T>(&mut self) -> T
+    where
+        T: Float + UncheckedPrimitiveFloat,
+    {
+        self.take(size_of::<T>() * 8);
+        unsafe { self.stream.read_float_unchecked(self.checked) }
+    }
+
+    /// Read a series of bytes
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `byte_count * 8` bits are remaining
+    #[inline]
+    #[track_caller]
+    pub fn read_bytes(&mut self, byte_count: usize) -> Cow<'a, [u8]> {
+        let count = byte_count.checked_mul(8).unwrap_or_else(|| {
+            panic!(
+                "read of {} bytes with only {} bits remaining in the exact reader",
+                byte_count, self.remaining
+            )
+        });
+        self.take(count);
+        unsafe { self.stream.read_bytes_unchecked(byte_count, self.checked) }
+    }
+
+    /// Skip a number of bits
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `count` bits are remaining
+    #[inline]
+    #[track_caller]
+    pub fn skip_bits(&mut self, count: usize) {
+        self.take(count);
+        self.stream
+            .skip_bits(count)
+            .expect("skipping bits within the checked region");
+    }
+}
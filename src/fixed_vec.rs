@@ -0,0 +1,148 @@
+//! [`BitReadSized`] and [`BitWrite`] implementations for small and fixed capacity vectors
+//!
+//! Like `Vec`, the collections are read sized with the size as the number of elements and written as
+//! all elements back-to-back without a length prefix.
+//!
+//! - `SmallVec<[T; N]>` (with the `smallvec` feature) stores up to `N` elements inline and allocates for larger sizes
+//! - `ArrayVec<T, N>` (with the `arrayvec` feature) and `heapless::Vec<T, N>` (with the `heapless` feature)
+//!   never allocate, reading more than `N` elements fails with [`BitError::CapacityExceeded`] without reading any data
+use crate::{
+    BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized, BitWriteStream, CheckedWrite,
+    Endianness, HasSizeUnit, Result, SizeUnit,
+};
+
+/// Ensure `count` elements fit in a collection with a capacity of `capacity` elements
+#[cfg(any(feature = "arrayvec", feature = "heapless"))]
+fn check_capacity(count: usize, capacity: usize) -> Result<()> {
+    if count > capacity {
+        Err(crate::BitError::CapacityExceeded {
+            requested: count,
+            capacity,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+macro_rules! impl_write_fixed_vec {
+    ($type:ty, [$($generics:tt)*] $(, $($bounds:tt)+)?) => {
+        /// Write all elements back-to-back, without a length prefix
+        impl<T: BitWrite<E>, E: Endianness, $($generics)*> BitWrite<E> for $type
+        where
+            $($($bounds)+)?
+        {
+            #[inline]
+            fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+                self[..].write(stream)
+            }
+
+            #[inline]
+            fn write_unchecked(
+                &self,
+                stream: &mut BitWriteStream<E>,
+                checked: CheckedWrite,
+            ) -> Result<()> {
+                self[..].write_unchecked(stream, checked)
+            }
+        }
+
+        /// Write all elements, the size has to match the number of elements
+        impl<T: BitWrite<E>, E: Endianness, $($generics)*> BitWriteSized<E> for $type
+        where
+            $($($bounds)+)?
+        {
+            #[inline]
+            fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+                self[..].write_sized(stream, len)
+            }
+
+            #[inline]
+            fn write_sized_unchecked(
+                &self,
+                stream: &mut BitWriteStream<E>,
+                len: usize,
+                checked: CheckedWrite,
+            ) -> Result<()> {
+                self[..].write_sized_unchecked(stream, len, checked)
+            }
+        }
+
+        impl<T, $($generics)*> HasSizeUnit for $type
+        where
+            $($($bounds)+)?
+        {
+            const SIZE_UNIT: SizeUnit = SizeUnit::Count;
+        }
+    };
+}
+
+#[cfg(feature = "smallvec")]
+/// Read `size` elements, elements past the inline capacity are stored on the heap
+impl<'a, E: Endianness, T: BitRead<'a, E>, const N: usize> BitReadSized<'a, E>
+    for smallvec::SmallVec<[T; N]>
+where
+    [T; N]: smallvec::Array<Item = T>,
+{
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        let mut vec = smallvec::SmallVec::with_capacity(stream.capacity_hint(size, T::bit_size()));
+        for _ in 0..size {
+            vec.push(stream.read()?);
+        }
+        Ok(vec)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        T::bit_size().and_then(|bit_size| bit_size.checked_mul(size))
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl_write_fixed_vec!(smallvec::SmallVec<[T; N]>, [const N: usize], [T; N]: smallvec::Array<Item = T>);
+
+#[cfg(feature = "arrayvec")]
+/// Read `size` elements, failing if `size` is larger than the capacity
+impl<'a, E: Endianness, T: BitRead<'a, E>, const N: usize> BitReadSized<'a, E>
+    for arrayvec::ArrayVec<T, N>
+{
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        check_capacity(size, N)?;
+        let mut vec = arrayvec::ArrayVec::new();
+        for _ in 0..size {
+            vec.push(stream.read()?);
+        }
+        Ok(vec)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        T::bit_size().and_then(|bit_size| bit_size.checked_mul(size))
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl_write_fixed_vec!(arrayvec::ArrayVec<T, N>, [const N: usize]);
+
+#[cfg(feature = "heapless")]
+/// Read `size` elements, failing if `size` is larger than the capacity
+impl<'a, E: Endianness, T: BitRead<'a, E>, const N: usize> BitReadSized<'a, E>
+    for heapless::Vec<T, N>
+{
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        check_capacity(size, N)?;
+        let mut vec = heapless::Vec::new();
+        for _ in 0..size {
+            // the capacity is checked before reading
+            let _ = vec.push(stream.read()?);
+        }
+        Ok(vec)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        T::bit_size().and_then(|bit_size| bit_size.checked_mul(size))
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl_write_fixed_vec!(heapless::Vec<T, N>, [const N: usize]);
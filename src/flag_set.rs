@@ -0,0 +1,266 @@
+//! Sets of enum variants stored as one bit per variant
+use crate::{
+    BitError, BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized, BitWriteStream,
+    Endianness, HasSizeUnit, Result, SizeUnit,
+};
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{BitOr, BitOrAssign};
+
+/// A fieldless enum where every variant is a single flag bit, used as the flags of a [`FlagSet`]
+///
+/// This trait can be derived for enums where every variant has a discriminant with a single set bit,
+/// the number of bits for the set of flags is set with `#[flag_bits = N]` or taken from the `#[repr(..)]` of the enum.
+/// The derive also implements `BitOr` for the enum so flags can be combined into a set with `|`.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, FlagEnum, FlagSet, Result};
+/// #
+/// #[derive(FlagEnum, Debug, Clone, Copy, PartialEq)]
+/// #[repr(u8)]
+/// #[flag_bits = 3]
+/// enum Permission {
+///     Read = 0b001,
+///     Write = 0b010,
+///     Execute = 0b100,
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let bytes = [0b1010_0000];
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+/// let permissions: FlagSet<Permission> = stream.read()?;
+/// assert_eq!(3, stream.pos());
+/// assert!(permissions.contains(Permission::Execute));
+/// assert!(!permissions.contains(Permission::Write));
+/// assert_eq!(Permission::Read | Permission::Execute, permissions);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub trait FlagEnum: Copy + 'static {
+    /// The number of bits used to store a set of the flags
+    const FLAG_BITS: usize;
+
+    /// All flags of the enum
+    const FLAGS: &'static [Self];
+
+    /// The bit of the flag
+    fn mask(self) -> u64;
+}
+
+/// A set of flags, read and written as an integer of [`FlagEnum::FLAG_BITS`] bits with a bit set for every flag in the set
+///
+/// When read or written sized, the size sets the number of bits of the integer.
+/// Set bits that don't belong to any flag are kept, so reading and writing a set doesn't change it.
+///
+/// Writing a set with bits that don't fit in the number of bits results in a [`BitError::ValueOutOfRange`].
+pub struct FlagSet<T: FlagEnum> {
+    bits: u64,
+    flags: PhantomData<T>,
+}
+
+impl<T: FlagEnum> FlagSet<T> {
+    /// A set without any flags
+    pub fn empty() -> Self {
+        FlagSet::from_bits(0)
+    }
+
+    /// A set with all flags
+    pub fn all() -> Self {
+        T::FLAGS.iter().copied().collect()
+    }
+
+    /// Create a set from the raw bits
+    pub fn from_bits(bits: u64) -> Self {
+        FlagSet {
+            bits,
+            flags: PhantomData,
+        }
+    }
+
+    /// The raw bits of the set
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// The set bits that don't belong to any flag
+    pub fn unknown_bits(&self) -> u64 {
+        self.bits & !Self::all().bits
+    }
+
+    /// Whether the flag is in the set
+    pub fn contains(&self, flag: T) -> bool {
+        self.bits & flag.mask() != 0
+    }
+
+    /// Whether the set contains no bits
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Add a flag to the set
+    pub fn insert(&mut self, flag: T) {
+        self.bits |= flag.mask();
+    }
+
+    /// Remove a flag from the set
+    pub fn remove(&mut self, flag: T) {
+        self.bits &= !flag.mask();
+    }
+
+    /// Iterate over the flags in the set, in the order they are defined in the enum
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        T::FLAGS
+            .iter()
+            .copied()
+            .filter(move |flag| self.contains(*flag))
+    }
+}
+
+impl<T: FlagEnum> Clone for FlagSet<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: FlagEnum> Copy for FlagSet<T> {}
+
+impl<T: FlagEnum> PartialEq for FlagSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<T: FlagEnum> Eq for FlagSet<T> {}
+
+impl<T: FlagEnum> Hash for FlagSet<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state)
+    }
+}
+
+impl<T: FlagEnum> Default for FlagSet<T> {
+    fn default() -> Self {
+        FlagSet::empty()
+    }
+}
+
+impl<T: FlagEnum + Debug> Debug for FlagSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        set.entries(self.iter());
+        if self.unknown_bits() != 0 {
+            set.entry(&format_args!("{:#b}", self.unknown_bits()));
+        }
+        set.finish()
+    }
+}
+
+impl<T: FlagEnum> From<T> for FlagSet<T> {
+    fn from(flag: T) -> Self {
+        FlagSet::from_bits(flag.mask())
+    }
+}
+
+impl<T: FlagEnum> FromIterator<T> for FlagSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = FlagSet::empty();
+        for flag in iter {
+            set.insert(flag);
+        }
+        set
+    }
+}
+
+impl<T: FlagEnum> BitOr<T> for FlagSet<T> {
+    type Output = Self;
+
+    fn bitor(mut self, flag: T) -> Self {
+        self.insert(flag);
+        self
+    }
+}
+
+impl<T: FlagEnum> BitOr for FlagSet<T> {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        FlagSet::from_bits(self.bits | other.bits)
+    }
+}
+
+impl<T: FlagEnum> BitOrAssign<T> for FlagSet<T> {
+    fn bitor_assign(&mut self, flag: T) {
+        self.insert(flag);
+    }
+}
+
+impl<T: FlagEnum> PartialEq<T> for FlagSet<T> {
+    fn eq(&self, flag: &T) -> bool {
+        self.bits == flag.mask()
+    }
+}
+
+impl<E: Endianness, T: FlagEnum> BitRead<'_, E> for FlagSet<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        stream.read_int(T::FLAG_BITS).map(FlagSet::from_bits)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(T::FLAG_BITS)
+    }
+}
+
+impl<E: Endianness, T: FlagEnum> BitReadSized<'_, E> for FlagSet<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        stream.read_int(size).map(FlagSet::from_bits)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(size)
+    }
+}
+
+fn write_flags<T: FlagEnum, E: Endianness>(
+    flags: &FlagSet<T>,
+    stream: &mut BitWriteStream<E>,
+    size: usize,
+) -> Result<()> {
+    if size < 64 && flags.bits >> size != 0 {
+        return Err(BitError::ValueOutOfRange {
+            type_name: "FlagSet",
+        });
+    }
+    stream.write_int(flags.bits, size)
+}
+
+impl<E: Endianness, T: FlagEnum> BitWrite<E> for FlagSet<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        write_flags(self, stream, T::FLAG_BITS)
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        Some(T::FLAG_BITS)
+    }
+}
+
+impl<E: Endianness, T: FlagEnum> BitWriteSized<E> for FlagSet<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        write_flags(self, stream, len)
+    }
+}
+
+impl<T: FlagEnum> HasSizeUnit for FlagSet<T> {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Bits;
+}
@@ -0,0 +1,121 @@
+//! [`BitRead`], [`BitReadSized`], [`BitWrite`] and [`BitWriteSized`] implementations for [`BitFlags`]
+//!
+//! A set of flags is stored as a single integer where every flag sets the bit at its position,
+//! read and written unsized the integer has the size of the numeric type of the flags,
+//! when read or written sized, the size sets the number of bits of the integer.
+//!
+//! Reading a set bit that doesn't belong to any flag results in a [`BitError::ValueOutOfRange`],
+//! as does writing a flag that doesn't fit in the number of bits.
+use crate::{BitError, BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized};
+use crate::{BitWriteStream, Endianness, HasSizeUnit, Result, SizeUnit};
+use enumflags2::{BitFlag, BitFlags};
+use std::mem::size_of;
+
+/// The bit position of a single flag
+fn flag_position<T: BitFlag>(flag: T) -> u32
+where
+    T::Numeric: Into<u128>,
+{
+    // flags are a single set bit
+    flag.bits().into().trailing_zeros()
+}
+
+fn read_flags<T: BitFlag, E: Endianness>(
+    stream: &mut BitReadStream<E>,
+    size: usize,
+) -> Result<BitFlags<T>>
+where
+    T::Numeric: Into<u128>,
+{
+    let raw = stream.read_int::<u128>(size)?;
+    let mut flags = BitFlags::empty();
+    let mut known_bits = 0u128;
+    for flag in BitFlags::<T>::all().iter() {
+        let bit = 1u128 << flag_position(flag);
+        known_bits |= bit;
+        if raw & bit != 0 {
+            flags |= flag;
+        }
+    }
+    if raw & !known_bits != 0 {
+        return Err(BitError::ValueOutOfRange {
+            type_name: "BitFlags",
+        });
+    }
+    Ok(flags)
+}
+
+fn write_flags<T: BitFlag, E: Endianness>(
+    flags: &BitFlags<T>,
+    stream: &mut BitWriteStream<E>,
+    size: usize,
+) -> Result<()>
+where
+    T::Numeric: Into<u128>,
+{
+    let mut raw = 0u128;
+    for flag in flags.iter() {
+        let position = flag_position(flag) as usize;
+        if position >= size {
+            return Err(BitError::ValueOutOfRange {
+                type_name: "BitFlags",
+            });
+        }
+        raw |= 1 << position;
+    }
+    stream.write_int(raw, size)
+}
+
+impl<E: Endianness, T: BitFlag> BitRead<'_, E> for BitFlags<T>
+where
+    T::Numeric: Into<u128>,
+{
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        read_flags(stream, size_of::<T::Numeric>() * 8)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(size_of::<T::Numeric>() * 8)
+    }
+}
+
+impl<E: Endianness, T: BitFlag> BitReadSized<'_, E> for BitFlags<T>
+where
+    T::Numeric: Into<u128>,
+{
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        read_flags(stream, size)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(size)
+    }
+}
+
+impl<E: Endianness, T: BitFlag> BitWrite<E> for BitFlags<T>
+where
+    T::Numeric: Into<u128>,
+{
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        write_flags(self, stream, size_of::<T::Numeric>() * 8)
+    }
+}
+
+impl<E: Endianness, T: BitFlag> BitWriteSized<E> for BitFlags<T>
+where
+    T::Numeric: Into<u128>,
+{
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        write_flags(self, stream, len)
+    }
+}
+
+impl<T: BitFlag> HasSizeUnit for BitFlags<T> {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Bits;
+}
@@ -0,0 +1,17 @@
+/// Position and size of a field in a derived struct, see the `#[bitbuffer(layout)]` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// The name of the field, or its index for tuple structs
+    pub name: &'static str,
+    /// The bit offset of the field relative to the start of the struct, `None` if a field before it doesn't have a fixed size
+    pub offset: Option<usize>,
+    /// The size of the field in bits, `None` if it isn't fixed
+    pub size: Option<usize>,
+}
+
+impl FieldLayout {
+    /// The bit offset of the end of the field, if both the offset and size are fixed
+    pub fn end(&self) -> Option<usize> {
+        Some(self.offset? + self.size?)
+    }
+}
@@ -21,6 +21,11 @@
 //!
 //! Just like the read counterparts, [`BitWrite`] and [`BitWriteSized`] traits can be used with `#[derive]` if all fields implement [`BitWrite`] or [`BitWriteSized`].
 //!
+//! Streams with a fixed endianness can be created without naming the endianness using [`BitReadStream::le`], [`BitReadStream::be`],
+//! [`BitWriteStream::le`] and [`BitWriteStream::be`].
+//!
+//! The most commonly used traits and types can be imported at once using `use bitbuffer::prelude::*;`, see the [`prelude`] module.
+//!
 //! # Examples
 //!
 //! ```
@@ -58,6 +63,39 @@
 //! # }
 //! ```
 //!
+//! # Panics
+//!
+//! Reading and writing never panics on malformed input or sizes, instead an error is returned.
+//! The only methods that can panic are the ones explicitly documented to do so:
+//!
+//! - indexing a [`BitReadBuffer`] out of bounds, use [`BitReadBuffer::read_bool`] for a fallible alternative
+//! - reading more bits than checked from an [`ExactReader`]
+//! - writing a `RefCell` that is already mutably borrowed
+//! - extending a [`BitWriteStream`] backed by a slice past its capacity
+//! - unchecked reads past the checked size, or with the proof of another stream
+//! - unchecked writes past the checked size, in debug builds or with the `bounds-debug` feature
+//! - reading past the new end of a buffer from a stream created before the buffer was truncated, in debug builds or with the `bounds-debug` feature
+//!
+//! # Minimal builds
+//!
+//! Both runtime dependencies of the crate are enabled by default features:
+//!
+//! - `memchr` uses the `memchr` crate to speed up searching for the end of null terminated strings,
+//!   without it a plain loop is used.
+//! - `num-traits` uses the [`PrimInt`](num_traits::PrimInt), [`Float`](num_traits::Float) and
+//!   [`WrappingSub`](num_traits::WrappingSub) traits from the `num-traits` crate in the bounds of the read and write methods,
+//!   without it small built-in traits with the same names are used, which are implemented for all primitive numbers.
+//!
+//! For targets where binary size matters more than string reading performance, like wasm, the crate can be used with
+//! `default-features = false` to build it without any runtime dependencies. The API is the same either way, as long as
+//! generic code uses the traits from [`num_traits`] or the [`prelude`] instead of the `num-traits` crate directly.
+//! The `num-bigint` feature enables `num-traits`.
+//!
+//! ```toml
+//! [dependencies]
+//! bitbuffer = { version = "0.12", default-features = false }
+//! ```
+//!
 //! [`read_bool`]: BitReadStream::read_bool
 //! [`read_int`]: BitReadStream::read_int
 //! [`read_float`]: BitReadStream::read_float
@@ -75,50 +113,130 @@
 
 #![warn(missing_docs)]
 
-use thiserror::Error;
+use std::fmt;
 
-pub use bitbuffer_derive::{BitRead, BitReadSized, BitWrite, BitWriteSized};
+pub use bit_string::{from_bit_string, to_bit_string};
+pub use bitbuffer_derive::{bits, BitRead, BitReadSized, BitWrite, BitWriteSized, FlagEnum};
+pub use bounded::{BoundedReader, BoundsCheck, Checked, CheckedOnce, Trusted};
+pub use byte_swap::ByteSwap;
+pub use columns::BitReadColumns;
+pub use edit::BitEditBuffer;
 pub use endianness::*;
+pub use exact_read::ExactReader;
+pub use flag_set::{FlagEnum, FlagSet};
+pub use layout::FieldLayout;
+#[cfg(feature = "budget")]
+pub use limited::LimitedReadStream;
+pub use multi_read::IntTuple;
+pub use presence::Presence;
+pub use raw_str::{RawStr, RawString};
 pub use read::{BitRead, BitReadSized, LazyBitRead, LazyBitReadSized};
-pub use readbuffer::BitReadBuffer;
-pub use readstream::BitReadStream;
+pub use readbuffer::{BitReadBuffer, NullPadding};
+pub use readstream::{BitReadStream, BytesIter, CheckedRead, Marker};
+pub use section_stats::{SectionSize, SectionStats};
+pub use size_unit::{ByteLen, HasElementCount, HasSizeUnit, SizeUnit};
+pub use skip::Skip;
+#[cfg(feature = "stats")]
+pub use stats::{ReadCount, ReadStats};
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
+pub use trailer::BitTrailer;
+pub use transcode::{transcode, transcode_all};
 pub use write::{BitWrite, BitWriteSized};
-pub use writestream::BitWriteStream;
+pub use writebuffer::GrowthStrategy;
+pub use writestream::{BitWriteStream, CheckedWrite};
 
+/// Assert the bounds of an unchecked read or write
+///
+/// The assertion is only made in debug builds or when the `bounds-debug` feature is enabled.
+macro_rules! bounds_assert {
+    ($($arg:tt)*) => {
+        if cfg!(any(debug_assertions, feature = "bounds-debug")) {
+            assert!($($arg)*);
+        }
+    };
+}
+
+#[cfg(feature = "num-bigint")]
+mod bigint;
+mod bit_string;
+#[cfg(feature = "bitvec")]
+mod bitvec;
+mod bounded;
+mod byte_swap;
+mod columns;
+#[cfg(feature = "compat")]
+pub mod compat;
+#[cfg(feature = "coverage")]
+mod coverage;
+mod edit;
 mod endianness;
+mod exact_read;
+#[cfg(any(feature = "smallvec", feature = "arrayvec", feature = "heapless"))]
+mod fixed_vec;
+mod flag_set;
+#[cfg(feature = "enumflags2")]
+mod flags;
+mod layout;
+#[cfg(feature = "budget")]
+mod limited;
 
+mod multi_read;
+#[cfg(not(feature = "num-traits"))]
+mod num_shim;
 /// A number of traits to help being generic over numbers
 pub mod num_traits;
+#[cfg(feature = "bytemuck")]
+mod pod;
+pub mod prelude;
+mod presence;
+mod raw_str;
 mod read;
 mod readbuffer;
 mod readstream;
+mod section_stats;
+mod size_unit;
+mod skip;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "std-types")]
+mod std_types;
+#[cfg(feature = "proptest")]
+pub mod testing;
+mod trailer;
+mod transcode;
+mod typed_read;
+mod varint;
 mod write;
 mod writebuffer;
 mod writestream;
 
 /// Errors that can be returned when trying to read from or write to a buffer
-#[derive(Debug, Error)]
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum BitError {
     /// Too many bits requested to fit in the requested data type
-    #[error(
-        "Too many bits requested to fit in the requested data type, requested to read {} bits while only {} fit in the datatype",
-        requested,
-        max
-    )]
+    ///
+    /// Writing a value with more bits than its type returns [`BitError::TooManyBitsForValue`] instead,
+    /// since 0.12 this includes [`BitWriteStream::write_int`] and [`BitWriteStream::write_float_sized`].
     TooManyBits {
         /// The number of bits requested to read
         requested: usize,
         /// The number of bits that fit in the requested data type
         max: usize,
     },
+    /// Too many bits requested to write an integer or float value
+    TooManyBitsForValue {
+        /// The value that was being written, formatted using its `Debug` implementation
+        value: String,
+        /// The number of bits requested to write
+        requested: usize,
+        /// The number of bits that fit in the data type of the value
+        max: usize,
+        /// The path of the field that was being written, when written from a derived `BitWrite` implementation
+        field: Option<String>,
+    },
     /// Not enough data in the buffer to read all requested bits
-    #[error(
-        "Not enough data in the buffer to read all requested bits, requested to read {} bits while only {} bits are left",
-        requested,
-        bits_left
-    )]
     NotEnoughData {
         /// The number of bits requested to read
         requested: usize,
@@ -126,11 +244,6 @@ pub enum BitError {
         bits_left: usize,
     },
     /// The requested position is outside the bounds of the stream or buffer
-    #[error(
-        "The requested position is outside the bounds of the stream, requested position {} while the stream or buffer is only {} bits long",
-        pos,
-        size
-    )]
     IndexOutOfBounds {
         /// The requested position
         pos: usize,
@@ -138,32 +251,403 @@ pub enum BitError {
         size: usize,
     },
     /// Unmatched discriminant found while trying to read an enum
-    #[error(
-        "Unmatched discriminant '{}' found while trying to read enum '{}'",
-        discriminant,
-        enum_name
-    )]
     UnmatchedDiscriminant {
         /// The read discriminant
         discriminant: usize,
         /// The name of the enum that is trying to be read
-        enum_name: String,
+        enum_name: &'static str,
+        /// The position of the discriminant in the stream
+        pos: usize,
+        /// The number of bits of the discriminant
+        discriminant_bits: usize,
     },
     /// The read slice of bytes are not valid utf8
-    #[error("The read slice of bytes are not valid utf8: {}", _0)]
     Utf8Error(Utf8Error, usize),
     /// The string that was requested to be written does not fit in the specified fixed length
-    #[error(
-        "The string that was requested to be written does not fit in the specified fixed length, string is {} bytes long, while a size of {} has been specified",
-        string_length,
-        requested_length
-    )]
     StringToLong {
         /// Length of the string that was requested to be written
         string_length: usize,
         /// The requested fixed size to encode the string into
         requested_length: usize,
     },
+    /// The read or written value is outside the range that can be represented by the type or encoding
+    ValueOutOfRange {
+        /// The name of the type that was being read or written
+        type_name: &'static str,
+    },
+    /// Not enough capacity left in the fixed size output to write all bits
+    WriteOverflow {
+        /// The total number of bits the output can hold
+        capacity_bits: usize,
+        /// The total number of bits that would be in the output after the write
+        attempted_bits: usize,
+    },
+    /// The size specified for a field can not be converted into the unit used by the field type
+    SizeUnitMismatch {
+        /// The specified size
+        size: usize,
+        /// The unit of the specified size
+        unit: SizeUnit,
+        /// The unit used by the field type
+        target: SizeUnit,
+    },
+    /// The total size for a sized read or write can not be divided evenly over the elements
+    UnevenSize {
+        /// The specified total size
+        size: usize,
+        /// The number of elements the size is divided over
+        element_count: usize,
+    },
+    /// The stream is required to be at a byte boundary but isn't
+    NotAligned {
+        /// The number of bits the stream is past the last byte boundary
+        bit_offset: usize,
+    },
+    /// A read crossed a position marked with [`BitReadStream::mark`]
+    MarkerCrossed {
+        /// The tag of the crossed marker
+        tag: &'static str,
+        /// The position of the crossed marker
+        pos: usize,
+        /// The position the read started at
+        read_start: usize,
+        /// The position the read ended at
+        read_end: usize,
+    },
+    /// A bit string contains a character that isn't a bit
+    InvalidBitString {
+        /// The byte index of the character in the string
+        index: usize,
+        /// The invalid character
+        char: char,
+    },
+    /// No null terminator was found within the maximum length of a string
+    UnterminatedString {
+        /// The maximum number of bytes that were scanned for the terminator
+        max_bytes: usize,
+    },
+    /// A skipped field was written for a type without a fixed size
+    UnknownSkipSize {
+        /// The name of the skipped type
+        type_name: &'static str,
+    },
+    /// A lazy read was attempted for a type without a fixed size
+    UnknownLazySize {
+        /// The name of the lazily read type
+        type_name: &'static str,
+    },
+    /// A read would exceed the total number of bits allowed to be read from a `LimitedReadStream`, with the `budget` feature
+    ReadBudgetExceeded {
+        /// The number of bits requested to read
+        requested: usize,
+        /// The number of bits left in the budget
+        remaining: usize,
+    },
+    /// The read was cancelled by the progress callback or the cancellation flag of the stream
+    ReadCancelled {
+        /// The position of the stream when the read was attempted
+        pos: usize,
+    },
+    /// The number of elements to write doesn't match the specified size
+    ElementCountMismatch {
+        /// The number of elements required by the specified size
+        expected: usize,
+        /// The number of elements provided
+        actual: usize,
+    },
+    /// The number of elements to read doesn't fit in a fixed capacity collection
+    CapacityExceeded {
+        /// The number of elements requested to read
+        requested: usize,
+        /// The capacity of the collection
+        capacity: usize,
+    },
+    /// The data doesn't end with a valid bit length trailer, see [`BitReadBuffer::strip_trailer`]
+    InvalidTrailer {
+        /// The kind of trailer that was expected
+        trailer: BitTrailer,
+    },
+    /// A string with a null byte was written null terminated, it would end at the null byte when read
+    InteriorNul {
+        /// The index of the first null byte in the string
+        position: usize,
+    },
+    /// The sentinel byte wasn't found before the end of the stream
+    SentinelNotFound {
+        /// The byte that was searched for
+        sentinel: u8,
+    },
+    /// A field name passed to a derived `read_fields_subset` method isn't a field that can be read directly
+    UnknownField {
+        /// The requested field name
+        name: String,
+        /// The name of the type the field was requested from
+        type_name: &'static str,
+    },
+    /// An error returned by a user supplied read or write function, see [`BitError::custom`]
+    Custom(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl BitError {
+    /// Wrap a user defined error
+    ///
+    /// This is used by the derived implementations of types with a `#[bit_error = "MyError"]` attribute to return
+    /// the errors from `parse_with` and `write_with` functions, the original error can be retrieved using
+    /// [`into_custom`](BitError::into_custom).
+    pub fn custom<Err: std::error::Error + Send + Sync + 'static>(err: Err) -> Self {
+        BitError::Custom(Box::new(err))
+    }
+
+    /// Convert the error into a user defined error type
+    ///
+    /// If this error wraps an error of type `Err` created with [`custom`](BitError::custom), that error is returned,
+    /// otherwise the error is converted using `From<BitError>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::BitError;
+    /// #
+    /// #[derive(Debug, thiserror::Error)]
+    /// enum MyError {
+    ///     #[error("unknown opcode {0}")]
+    ///     UnknownOpcode(u8),
+    ///     #[error(transparent)]
+    ///     Bits(#[from] BitError),
+    /// }
+    ///
+    /// let err = BitError::custom(MyError::UnknownOpcode(3));
+    /// assert!(matches!(err.into_custom(), MyError::UnknownOpcode(3)));
+    ///
+    /// let err = BitError::NotEnoughData { requested: 8, bits_left: 2 };
+    /// assert!(matches!(err.into_custom(), MyError::Bits(BitError::NotEnoughData { .. })));
+    /// ```
+    pub fn into_custom<Err: From<BitError> + std::error::Error + 'static>(self) -> Err {
+        match self {
+            BitError::Custom(err) => match err.downcast::<Err>() {
+                Ok(err) => *err,
+                Err(err) => Err::from(BitError::Custom(err)),
+            },
+            err => Err::from(err),
+        }
+    }
+
+    /// Add the name of the field that was being written to errors about the written value
+    ///
+    /// This is used by derived `BitWrite` implementations, nested fields are joined with a `.`.
+    /// Errors that aren't about the written value are returned unchanged.
+    #[doc(hidden)]
+    pub fn in_field(self, name: &str) -> Self {
+        match self {
+            BitError::TooManyBitsForValue {
+                value,
+                requested,
+                max,
+                field,
+            } => BitError::TooManyBitsForValue {
+                value,
+                requested,
+                max,
+                field: Some(match field {
+                    Some(path) => format!("{}.{}", name, path),
+                    None => name.to_string(),
+                }),
+            },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for BitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitError::TooManyBits { requested, max } => write!(
+                f,
+                "Too many bits requested to fit in the requested data type, requested to read {} bits while only {} fit in the datatype",
+                requested,
+                max
+            ),
+            BitError::TooManyBitsForValue {
+                value,
+                requested,
+                max,
+                field,
+            } => write!(
+                f,
+                "Too many bits requested to write the value {}{}, requested to write {} bits while only {} bits fit in the data type",
+                value,
+                field_suffix(field),
+                requested,
+                max
+            ),
+            BitError::NotEnoughData {
+                requested,
+                bits_left,
+            } => write!(
+                f,
+                "Not enough data in the buffer to read all requested bits, requested to read {} bits while only {} bits are left",
+                requested,
+                bits_left
+            ),
+            BitError::IndexOutOfBounds { pos, size } => write!(
+                f,
+                "The requested position is outside the bounds of the stream, requested position {} while the stream or buffer is only {} bits long",
+                pos,
+                size
+            ),
+            BitError::UnmatchedDiscriminant {
+                discriminant,
+                enum_name,
+                pos,
+                discriminant_bits,
+            } => write!(
+                f,
+                "Unmatched discriminant '{}' found while trying to read enum '{}', {} bit discriminant at position {}",
+                discriminant,
+                enum_name,
+                discriminant_bits,
+                pos
+            ),
+            BitError::Utf8Error(err, _) => write!(
+                f,
+                "The read slice of bytes are not valid utf8: {}",
+                err
+            ),
+            BitError::StringToLong {
+                string_length,
+                requested_length,
+            } => write!(
+                f,
+                "The string that was requested to be written does not fit in the specified fixed length, string is {} bytes long, while a size of {} has been specified",
+                string_length,
+                requested_length
+            ),
+            BitError::ValueOutOfRange { type_name } => write!(
+                f,
+                "The read or written value is outside the range that can be represented for '{}'",
+                type_name
+            ),
+            BitError::WriteOverflow {
+                capacity_bits,
+                attempted_bits,
+            } => write!(
+                f,
+                "Not enough capacity left in the output to write all bits, the write requires {} bits while the output can only hold {} bits",
+                attempted_bits,
+                capacity_bits
+            ),
+            BitError::SizeUnitMismatch { size, unit, target } => write!(
+                f,
+                "The size specified for a field can not be converted into the unit used by the field type, a size of {} {} can not be expressed in {}",
+                size,
+                unit,
+                target
+            ),
+            BitError::UnevenSize {
+                size,
+                element_count,
+            } => write!(
+                f,
+                "The total size of {} can not be divided evenly over {} elements",
+                size,
+                element_count
+            ),
+            BitError::NotAligned { bit_offset } => write!(
+                f,
+                "The stream is required to be byte aligned but is {} bits past a byte boundary",
+                bit_offset
+            ),
+            BitError::MarkerCrossed {
+                tag,
+                pos,
+                read_start,
+                read_end,
+            } => write!(
+                f,
+                "The read from bit {} to {} crossed the marker '{}' at bit {}",
+                read_start,
+                read_end,
+                tag,
+                pos
+            ),
+            BitError::InvalidBitString { index, char } => write!(
+                f,
+                "Invalid character '{}' at index {} in bit string, only '0', '1', '_' and whitespace are allowed",
+                char,
+                index
+            ),
+            BitError::UnterminatedString { max_bytes } => write!(
+                f,
+                "No null terminator found in the first {} bytes of the string",
+                max_bytes
+            ),
+            BitError::UnknownSkipSize { type_name } => write!(
+                f,
+                "Can't write a skipped {}, since its size isn't fixed",
+                type_name
+            ),
+            BitError::UnknownLazySize { type_name } => write!(
+                f,
+                "Can't lazily read {}, since its size isn't fixed",
+                type_name
+            ),
+            BitError::ReadBudgetExceeded {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "Reading {} bits would exceed the read budget, only {} bits are left in the budget",
+                requested,
+                remaining
+            ),
+            BitError::ReadCancelled { pos } => write!(f, "Reading was cancelled at bit {}", pos),
+            BitError::ElementCountMismatch { expected, actual } => write!(
+                f,
+                "The specified size requires {} elements to be written, but {} elements were provided",
+                expected,
+                actual
+            ),
+            BitError::CapacityExceeded {
+                requested,
+                capacity,
+            } => write!(
+                f,
+                "Requested to read {} elements into a collection that can only hold {} elements",
+                requested,
+                capacity
+            ),
+            BitError::InvalidTrailer { trailer } => write!(
+                f,
+                "The data doesn't end with a valid {:?} trailer",
+                trailer
+            ),
+            BitError::InteriorNul { position } => write!(
+                f,
+                "Can't write a string with a null byte at {} null terminated",
+                position
+            ),
+            BitError::SentinelNotFound { sentinel } => write!(
+                f,
+                "Sentinel byte {:#04x} not found before the end of the stream",
+                sentinel
+            ),
+            BitError::UnknownField { name, type_name } => write!(
+                f,
+                "{} is not a field that can be read directly from {}",
+                name, type_name
+            ),
+            BitError::Custom(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for BitError {}
+
+fn field_suffix(field: &Option<String>) -> String {
+    match field {
+        Some(field) => format!(" for field '{}'", field),
+        None => String::new(),
+    }
 }
 
 impl From<FromUtf8Error> for BitError {
@@ -0,0 +1,83 @@
+//! Limit the total number of bits read from a stream and all streams derived from it
+use crate::{BitReadStream, Endianness};
+use std::ops::{Deref, DerefMut};
+
+/// Stream wrapper that limits the total number of bits that can be read
+///
+/// Unlike the length of a stream, the budget is shared by every stream derived from the wrapped stream,
+/// such as sub-streams created by [`read_bits`](BitReadStream::read_bits), clones used for peeking and
+/// copies created by [`to_owned`](BitReadStream::to_owned).
+/// Every bit read from any of these streams is subtracted from the same budget, which bounds the total amount of
+/// work done while parsing, even when nested length fields cause the same data to be read many times.
+///
+/// Skipping bits or creating a sub-stream doesn't use any of the budget, only reading data does.
+/// Once a read would exceed the remaining budget, it fails with [`BitError::ReadBudgetExceeded`](crate::BitError::ReadBudgetExceeded)
+/// without advancing the stream.
+///
+/// The wrapper dereferences to the wrapped [`BitReadStream`] so it can be used for reading directly.
+///
+/// Only available with the `budget` feature, so streams that don't need a budget don't pay for checking it.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LimitedReadStream, LittleEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let bytes = vec![0xff; 16];
+/// let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+/// let mut stream = LimitedReadStream::new(stream, 48);
+///
+/// let mut sub_stream = stream.read_bits(64)?;
+/// assert_eq!(0xffff_ffff, sub_stream.read_int::<u32>(32)?);
+/// assert_eq!(0xff, stream.read_int::<u8>(8)?);
+/// assert_eq!(8, stream.remaining_budget());
+/// assert!(matches!(
+///     sub_stream.read_int::<u16>(16),
+///     Err(BitError::ReadBudgetExceeded {
+///         requested: 16,
+///         remaining: 8
+///     })
+/// ));
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LimitedReadStream<'a, E: Endianness> {
+    stream: BitReadStream<'a, E>,
+}
+
+impl<'a, E: Endianness> LimitedReadStream<'a, E> {
+    /// Wrap a stream, allowing at most `max_bits` bits to be read from it and all streams derived from it
+    ///
+    /// Any budget the stream already had is replaced.
+    pub fn new(mut stream: BitReadStream<'a, E>, max_bits: usize) -> Self {
+        stream.set_budget(max_bits);
+        LimitedReadStream { stream }
+    }
+
+    /// The number of bits that can still be read
+    pub fn remaining_budget(&self) -> usize {
+        self.stream.remaining_budget().unwrap_or_default()
+    }
+
+    /// Get the wrapped stream, the budget still applies to the stream and all streams derived from it
+    pub fn into_inner(self) -> BitReadStream<'a, E> {
+        self.stream
+    }
+}
+
+impl<'a, E: Endianness> Deref for LimitedReadStream<'a, E> {
+    type Target = BitReadStream<'a, E>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<'a, E: Endianness> DerefMut for LimitedReadStream<'a, E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
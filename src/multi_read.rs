@@ -0,0 +1,85 @@
+//! Reading multiple integers with a single bounds check
+use crate::num_traits::{IsSigned, UncheckedPrimitiveInt};
+use crate::num_traits::{PrimInt, WrappingSub};
+use crate::{BitError, BitReadBuffer, Endianness, Result};
+use std::mem::size_of;
+use std::ops::{BitOrAssign, BitXor};
+
+/// A tuple of integers that can be read at once using [`BitReadStream::read_multi`](crate::BitReadStream::read_multi)
+///
+/// Implemented for tuples of 2 up to 8 integers.
+pub trait IntTuple: private::Sealed + Sized {
+    /// The bit widths of the integers, one for each element of the tuple
+    type Counts;
+
+    /// Check that every width fits in its integer type and return the total number of bits
+    #[doc(hidden)]
+    fn total_bits(counts: &Self::Counts) -> Result<usize>;
+
+    /// Read the integers without checking the bounds of the buffer
+    ///
+    /// # Safety
+    ///
+    /// `position + total_bits(counts)` can't be larger than the bit length of the buffer
+    #[doc(hidden)]
+    unsafe fn read_unchecked<E: Endianness>(
+        buffer: &BitReadBuffer<E>,
+        position: usize,
+        counts: &Self::Counts,
+    ) -> Self;
+}
+
+macro_rules! impl_int_tuple {
+    ($len:literal; $($type:ident => $index:tt),*) => {
+        impl<$($type),*> private::Sealed for ($($type,)*) {}
+
+        impl<$($type),*> IntTuple for ($($type,)*)
+        where
+            $($type: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + BitXor + WrappingSub),*
+        {
+            type Counts = [usize; $len];
+
+            #[inline]
+            fn total_bits(counts: &Self::Counts) -> Result<usize> {
+                let mut total = 0;
+                $(
+                    let max = size_of::<$type>() * 8;
+                    if counts[$index] > max {
+                        return Err(BitError::TooManyBits {
+                            requested: counts[$index],
+                            max,
+                        });
+                    }
+                    total += counts[$index];
+                )*
+                Ok(total)
+            }
+
+            #[inline]
+            #[allow(unused_assignments)]
+            unsafe fn read_unchecked<E: Endianness>(
+                buffer: &BitReadBuffer<E>,
+                mut position: usize,
+                counts: &Self::Counts,
+            ) -> Self {
+                ($({
+                    let value = buffer.read_int_unchecked::<$type>(position, counts[$index]);
+                    position += counts[$index];
+                    value
+                },)*)
+            }
+        }
+    };
+}
+
+impl_int_tuple!(2; T1 => 0, T2 => 1);
+impl_int_tuple!(3; T1 => 0, T2 => 1, T3 => 2);
+impl_int_tuple!(4; T1 => 0, T2 => 1, T3 => 2, T4 => 3);
+impl_int_tuple!(5; T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4);
+impl_int_tuple!(6; T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5);
+impl_int_tuple!(7; T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6);
+impl_int_tuple!(8; T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6, T8 => 7);
+
+mod private {
+    pub trait Sealed {}
+}
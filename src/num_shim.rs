@@ -0,0 +1,113 @@
+//! Minimal replacements for the traits from `num-traits` used by the crate, used when the `num-traits` feature is disabled
+//!
+//! Only the methods used by the crate are provided, all traits are implemented for the primitive types.
+use std::ops::{Add, BitAnd, BitOr, BitXor, Not, Shl, Shr, Sub};
+
+/// Primitive integer types
+pub trait PrimInt:
+    Copy
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Not<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+{
+    /// The value `0`
+    fn zero() -> Self;
+    /// The value `1`
+    fn one() -> Self;
+    /// Reverse the byte order of the integer
+    fn swap_bytes(self) -> Self;
+}
+
+/// Wrapping subtraction
+pub trait WrappingSub: Sized {
+    /// Subtract, wrapping around at the boundary of the type
+    fn wrapping_sub(&self, v: &Self) -> Self;
+}
+
+/// Casting a primitive with `as`
+pub trait AsPrimitive<T>: Copy {
+    /// Cast the value using `as`
+    fn as_(self) -> T;
+}
+
+macro_rules! impl_prim_int {
+    ($($ty:ty),*) => {
+        $(
+            impl PrimInt for $ty {
+                #[inline(always)]
+                fn zero() -> Self {
+                    0
+                }
+                #[inline(always)]
+                fn one() -> Self {
+                    1
+                }
+                #[inline(always)]
+                fn swap_bytes(self) -> Self {
+                    <$ty>::swap_bytes(self)
+                }
+            }
+
+            impl WrappingSub for $ty {
+                #[inline(always)]
+                fn wrapping_sub(&self, v: &Self) -> Self {
+                    <$ty>::wrapping_sub(*self, *v)
+                }
+            }
+
+            impl AsPrimitive<usize> for $ty {
+                #[inline(always)]
+                fn as_(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_prim_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Primitive float types
+pub trait Float: Copy + PartialOrd {
+    /// The value `0.0`
+    fn zero() -> Self;
+    /// Whether the value is `0.0` or `-0.0`
+    fn is_zero(&self) -> bool;
+    /// A quiet NaN
+    fn nan() -> Self;
+    /// Whether the value is NaN
+    fn is_nan(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {
+        $(
+            impl Float for $ty {
+                #[inline(always)]
+                fn zero() -> Self {
+                    0.0
+                }
+                #[inline(always)]
+                fn is_zero(&self) -> bool {
+                    *self == 0.0
+                }
+                #[inline(always)]
+                fn nan() -> Self {
+                    <$ty>::NAN
+                }
+                #[inline(always)]
+                fn is_nan(self) -> bool {
+                    <$ty>::is_nan(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
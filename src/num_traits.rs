@@ -1,11 +1,57 @@
+#[cfg(not(feature = "num-traits"))]
+pub use crate::num_shim::{AsPrimitive, Float, PrimInt, WrappingSub};
 use crate::Endianness;
-use num_traits::{PrimInt, WrappingSub};
+#[cfg(feature = "num-traits")]
+pub use ::num_traits::{AsPrimitive, Float, PrimInt, WrappingSub};
 use std::array::TryFromSliceError;
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::ops::{BitOrAssign, BitXor};
 
-/// some extra number traits
+// some extra number traits
+
+/// Get the canonical representation of a float
+///
+/// All NaN values are replaced by the same quiet NaN and `-0.0` is replaced by `0.0`,
+/// other values are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use bitbuffer::num_traits::canonicalize_float;
+///
+/// assert_eq!(canonicalize_float(-0.0f32).to_bits(), 0);
+/// assert_eq!(canonicalize_float(-f64::NAN).to_bits(), f64::NAN.to_bits());
+/// assert_eq!(canonicalize_float(1.5f32), 1.5);
+/// ```
+#[inline]
+pub fn canonicalize_float<T: Float>(value: T) -> T {
+    if value.is_nan() {
+        T::nan()
+    } else if value.is_zero() {
+        T::zero()
+    } else {
+        value
+    }
+}
+
+/// Compare two floats by their canonical representation
+///
+/// Unlike `==`, NaN values are equal to each other, while `0.0` and `-0.0` are equal like they are for `==`.
+///
+/// # Examples
+///
+/// ```
+/// use bitbuffer::num_traits::canonical_float_eq;
+///
+/// assert!(canonical_float_eq(f32::NAN, -f32::NAN));
+/// assert!(canonical_float_eq(0.0f64, -0.0));
+/// assert!(!canonical_float_eq(1.0f32, f32::NAN));
+/// ```
+#[inline]
+pub fn canonical_float_eq<T: Float + UncheckedPrimitiveFloat>(a: T, b: T) -> bool {
+    canonicalize_float(a).to_int() == canonicalize_float(b).to_int()
+}
 
 /// Allow casting floats unchecked
 pub trait UncheckedPrimitiveFloat: Sized {
@@ -48,14 +94,14 @@ impl UncheckedPrimitiveFloat for f32 {
         n as f32
     }
     fn to_bytes<E: Endianness>(self) -> Self::BYTES {
-        if E::is_le() {
+        if E::is_byte_order_le() {
             self.to_le_bytes()
         } else {
             self.to_be_bytes()
         }
     }
     fn from_bytes<E: Endianness>(bytes: Self::BYTES) -> Self {
-        if E::is_le() {
+        if E::is_byte_order_le() {
             Self::from_le_bytes(bytes)
         } else {
             Self::from_be_bytes(bytes)
@@ -82,14 +128,14 @@ impl UncheckedPrimitiveFloat for f64 {
         n
     }
     fn to_bytes<E: Endianness>(self) -> Self::BYTES {
-        if E::is_le() {
+        if E::is_byte_order_le() {
             self.to_le_bytes()
         } else {
             self.to_be_bytes()
         }
     }
     fn from_bytes<E: Endianness>(bytes: Self::BYTES) -> Self {
-        if E::is_le() {
+        if E::is_byte_order_le() {
             Self::from_le_bytes(bytes)
         } else {
             Self::from_be_bytes(bytes)
@@ -360,7 +406,7 @@ impl SplitFitUsize for u32 {
     type Iter = array::IntoIter<(usize, u8), 2>;
 
     fn split_fit_usize<E: Endianness>(self, count: u8) -> Self::Iter {
-        Self::Iter::new(if E::is_le() {
+        (if E::is_le() {
             [
                 ((self & (Self::MAX >> 8)) as usize, 24),
                 ((self >> 24) as usize, 8),
@@ -375,6 +421,7 @@ impl SplitFitUsize for u32 {
                 ),
             ]
         })
+        .into_iter()
     }
 }
 
@@ -461,10 +508,7 @@ impl SplitFitUsize for usize {
     fn split_fit_usize<E: Endianness>(self, count: u8) -> Self::Iter {
         (if E::is_le() {
             [
-                (
-                    self & (Self::MAX >> (usize::BITS - 8)),
-                    usize::BITS as u8 - 8,
-                ),
+                (self & (Self::MAX >> 8), usize::BITS as u8 - 8),
                 (self >> (usize::BITS - 8), 8),
             ]
         } else {
@@ -0,0 +1,94 @@
+//! Direct reads of plain old data types using `bytemuck`
+use crate::{BitRead, BitReadStream, Endianness, Result};
+use bytemuck::Pod;
+use std::mem::size_of;
+
+/// Whether the bytes of `T` can be copied directly from a stream with endianness `E`
+///
+/// This requires the multi byte values in the stream to be stored in the native byte order and the
+/// [`BitRead`] implementation of `T` to read exactly the bytes of its in-memory layout, derived implementations
+/// with fields that are read differently, like `#[byte_swap]` fields, are always read field by field.
+fn can_copy<'a, T: Pod + BitRead<'a, E>, E: Endianness>() -> bool {
+    size_of::<T>() > 0
+        && E::is_byte_order_le() == cfg!(target_endian = "little")
+        && T::bit_size() == Some(size_of::<T>() * 8)
+        && T::reads_memory_layout()
+}
+
+impl<'a, E: Endianness> BitReadStream<'a, E> {
+    /// Read a plain old data type by copying its bytes directly from the stream
+    ///
+    /// When the byte order of the stream matches the native byte order and the [`BitRead`] implementation
+    /// of `T` reads exactly `size_of::<T>()` bytes, the value is copied from the stream as-is,
+    /// without copying when the stream is at a byte boundary. Otherwise the value is read field by field
+    /// using [`BitRead`], giving the same result as [`read`](BitReadStream::read) either way.
+    ///
+    /// `T` should be a `#[repr(C)]` type whose [`BitRead`] implementation reads the fields in declaration order.
+    /// Derived types with fields that aren't read as-is, like `#[byte_swap]` fields, are always read field by field.
+    ///
+    /// Only available with the `bytemuck` feature.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`](crate::BitError::NotEnoughData): not enough bits available in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use bytemuck::{Pod, Zeroable};
+    /// #
+    /// #[derive(BitRead, Clone, Copy, Debug, PartialEq)]
+    /// #[repr(C)]
+    /// struct Point {
+    ///     x: u16,
+    ///     y: u16,
+    /// }
+    ///
+    /// unsafe impl Zeroable for Point {}
+    /// unsafe impl Pod for Point {}
+    ///
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0x01, 0x00, 0x02, 0x00];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// assert_eq!(Point { x: 1, y: 2 }, stream.read_pod()?);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn read_pod<T: Pod + BitRead<'a, E>>(&mut self) -> Result<T> {
+        if can_copy::<T, E>() {
+            let bytes = self.read_bytes(size_of::<T>())?;
+            Ok(bytemuck::pod_read_unaligned(&bytes))
+        } else {
+            self.read()
+        }
+    }
+
+    /// Read `count` plain old data values by copying their bytes directly from the stream
+    ///
+    /// See [`read_pod`](BitReadStream::read_pod) for when the values can be copied directly,
+    /// otherwise the values are read one by one.
+    ///
+    /// Only available with the `bytemuck` feature.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`](crate::BitError::NotEnoughData): not enough bits available in the stream
+    pub fn read_pod_vec<T: Pod + BitRead<'a, E>>(&mut self, count: usize) -> Result<Vec<T>> {
+        if can_copy::<T, E>() {
+            let bytes = self.read_bytes(count.saturating_mul(size_of::<T>()))?;
+            Ok(bytes
+                .chunks_exact(size_of::<T>())
+                .map(bytemuck::pod_read_unaligned)
+                .collect())
+        } else {
+            let mut values = Vec::with_capacity(self.capacity_hint(count, T::bit_size()));
+            for _ in 0..count {
+                values.push(self.read()?);
+            }
+            Ok(values)
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Commonly used traits and types
+//!
+//! Importing the prelude brings the read and write traits and their derive macros, the streams, the endianness
+//! types and the number traits used as bounds by the integer and float reads and writes into scope.
+//!
+//! ```
+//! use bitbuffer::prelude::*;
+//!
+//! #[derive(BitRead, BitWrite, PartialEq, Debug)]
+//! struct Point {
+//!     x: u16,
+//!     y: u16,
+//! }
+//!
+//! fn read_scaled<E, T>(stream: &mut BitReadStream<E>, bits: usize) -> Result<f64, BitError>
+//! where
+//!     E: Endianness,
+//!     T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + WrappingSub + Into<f64>,
+//! {
+//!     Ok(stream.read_int::<T>(bits)?.into() / 10.0)
+//! }
+//!
+//! # fn main() -> Result<(), BitError> {
+//! let mut data = Vec::new();
+//! let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+//! stream.write(&Point { x: 1, y: 2 })?;
+//! stream.write_int(15u8, 8)?;
+//!
+//! let mut stream = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+//! assert_eq!(Point { x: 1, y: 2 }, stream.read()?);
+//! assert_eq!(1.5, read_scaled::<_, u8>(&mut stream, 8)?);
+//! #
+//! #     Ok(())
+//! # }
+//! ```
+
+pub use crate::num_traits::{
+    Float, IsSigned, PrimInt, SplitFitUsize, UncheckedPrimitiveFloat, UncheckedPrimitiveInt,
+    WrappingSub,
+};
+pub use crate::{
+    BigEndian, BitError, BitRead, BitReadBuffer, BitReadSized, BitReadStream, BitWrite,
+    BitWriteSized, BitWriteStream, Endianness, LittleEndian,
+};
+pub use std::ops::{BitOrAssign, BitXor};
@@ -0,0 +1,123 @@
+use crate::{
+    BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized, BitWriteStream, Endianness,
+    Result,
+};
+
+/// Optional value prefixed by a presence flag of `BITS` bits
+///
+/// The `Option` impls encode the presence of the value as a single bit, `Presence` allows using a wider flag
+/// and inverting its meaning.
+///
+/// The flag is read as a `BITS` bit unsigned integer, the value is present when the flag is non-zero,
+/// or when the flag is zero if `INVERT` is set. When writing, the flag is written as `1` if the value is present
+/// and `0` if it's absent, with the meaning swapped if `INVERT` is set.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, LittleEndian, Presence, Result};
+/// #
+/// #[derive(BitRead, BitWrite, Debug, PartialEq)]
+/// struct Entry {
+///     // present when the 2 bit flag is zero
+///     name: Presence<u8, 2, true>,
+///     id: u8,
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let entry = Entry {
+///     name: Presence::new(Some(12)),
+///     id: 3,
+/// };
+///
+/// let mut data = Vec::new();
+/// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+/// stream.write(&entry)?;
+/// # drop(stream);
+/// assert_eq!(data, [0b0011_0000, 0b0000_1100, 0]);
+///
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+/// assert_eq!(entry, stream.read()?);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Presence<T, const BITS: usize = 1, const INVERT: bool = false>(pub Option<T>);
+
+impl<T, const BITS: usize, const INVERT: bool> Presence<T, BITS, INVERT> {
+    /// Wrap an optional value
+    pub fn new(value: Option<T>) -> Self {
+        Presence(value)
+    }
+
+    /// Get the wrapped optional value
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+
+    fn read_flag<E: Endianness>(stream: &mut BitReadStream<E>) -> Result<bool> {
+        let flag: u64 = stream.read_int(BITS)?;
+        Ok((flag != 0) != INVERT)
+    }
+
+    fn write_flag<E: Endianness>(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_int((self.0.is_some() != INVERT) as u64, BITS)
+    }
+}
+
+impl<T, const BITS: usize, const INVERT: bool> From<Option<T>> for Presence<T, BITS, INVERT> {
+    fn from(value: Option<T>) -> Self {
+        Presence(value)
+    }
+}
+
+impl<'a, E: Endianness, T: BitRead<'a, E>, const BITS: usize, const INVERT: bool> BitRead<'a, E>
+    for Presence<T, BITS, INVERT>
+{
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        if Self::read_flag(stream)? {
+            Ok(Presence(Some(stream.read()?)))
+        } else {
+            Ok(Presence(None))
+        }
+    }
+}
+
+impl<'a, E: Endianness, T: BitReadSized<'a, E>, const BITS: usize, const INVERT: bool>
+    BitReadSized<'a, E> for Presence<T, BITS, INVERT>
+{
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        if Self::read_flag(stream)? {
+            Ok(Presence(Some(stream.read_sized(size)?)))
+        } else {
+            Ok(Presence(None))
+        }
+    }
+}
+
+impl<E: Endianness, T: BitWrite<E>, const BITS: usize, const INVERT: bool> BitWrite<E>
+    for Presence<T, BITS, INVERT>
+{
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        self.write_flag(stream)?;
+        if let Some(val) = &self.0 {
+            val.write(stream)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: Endianness, T: BitWriteSized<E>, const BITS: usize, const INVERT: bool> BitWriteSized<E>
+    for Presence<T, BITS, INVERT>
+{
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        self.write_flag(stream)?;
+        if let Some(val) = &self.0 {
+            val.write_sized(stream, len)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    BitError, BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized, BitWriteStream,
+    Endianness, Result,
+};
+use std::borrow::Cow;
+use std::fmt;
+
+/// String bytes that are only validated as utf8 when accessed
+///
+/// `RawStr` is read and written the same way as `String`, null terminated or, when read sized,
+/// as a fixed number of bytes with the trailing null bytes removed. Unlike `String`, reading doesn't
+/// validate the bytes, making it cheap to read strings that are skipped or only inspected later.
+///
+/// Like `Cow<str>`, the bytes are borrowed from the stream when the string starts at a byte boundary.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, LittleEndian, RawStr, Result};
+/// #
+/// #[derive(BitRead)]
+/// struct Entry<'a> {
+///     name: RawStr<'a>,
+///     #[size = 4]
+///     tag: RawStr<'a>,
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let bytes = b"foo\0\xffx\0\0";
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+/// let entry: Entry = stream.read()?;
+/// assert_eq!("foo", entry.name.as_str()?);
+/// assert_eq!(b"\xffx", entry.tag.as_bytes());
+/// assert!(entry.tag.as_str().is_err());
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawStr<'a>(Cow<'a, [u8]>);
+
+/// A [`RawStr`] that owns its bytes
+pub type RawString = RawStr<'static>;
+
+impl<'a> RawStr<'a> {
+    /// Wrap the raw bytes of a string
+    pub fn new(bytes: impl Into<Cow<'a, [u8]>>) -> Self {
+        RawStr(bytes.into())
+    }
+
+    /// The raw bytes of the string, without any null terminator or padding
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The number of bytes in the string
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the string contains no bytes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Validate the bytes as utf8 and get the string
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::Utf8Error`]: the bytes are not valid utf8
+    pub fn as_str(&self) -> Result<&str> {
+        std::str::from_utf8(&self.0).map_err(|err| BitError::Utf8Error(err, self.0.len()))
+    }
+
+    /// Get the string, replacing invalid utf8 sequences with `U+FFFD REPLACEMENT CHARACTER`
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+
+    /// Validate the bytes as utf8 and convert them into a string
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::Utf8Error`]: the bytes are not valid utf8
+    pub fn into_string(self) -> Result<String> {
+        Ok(String::from_utf8(self.0.into_owned())?)
+    }
+
+    /// Copy the bytes if they are borrowed, so the string no longer borrows from the stream
+    pub fn into_owned(self) -> RawString {
+        RawStr(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Debug for RawStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl<'a> From<&'a str> for RawStr<'a> {
+    fn from(string: &'a str) -> Self {
+        RawStr(Cow::Borrowed(string.as_bytes()))
+    }
+}
+
+impl From<String> for RawStr<'_> {
+    fn from(string: String) -> Self {
+        RawStr(Cow::Owned(string.into_bytes()))
+    }
+}
+
+impl<'a> From<&'a [u8]> for RawStr<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        RawStr(Cow::Borrowed(bytes))
+    }
+}
+
+impl From<Vec<u8>> for RawStr<'_> {
+    fn from(bytes: Vec<u8>) -> Self {
+        RawStr(Cow::Owned(bytes))
+    }
+}
+
+/// Read a null terminated string without validating it
+impl<'a, E: Endianness> BitRead<'a, E> for RawStr<'a> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        stream.read_raw_string(None).map(RawStr)
+    }
+}
+
+/// Read a string of `size` bytes without validating it, trailing null bytes are removed
+impl<'a, E: Endianness> BitReadSized<'a, E> for RawStr<'a> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        stream.read_raw_string(Some(size)).map(RawStr)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(8 * size)
+    }
+}
+
+/// Write the bytes followed by a null terminator
+impl<E: Endianness> BitWrite<E> for RawStr<'_> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_string_bytes(&self.0, None)
+    }
+}
+
+/// Write the bytes padded with null bytes to `len` bytes
+impl<E: Endianness> BitWriteSized<E> for RawStr<'_> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_string_bytes(&self.0, Some(len))
+    }
+}
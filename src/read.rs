@@ -1,9 +1,10 @@
 use crate::endianness::{BigEndian, LittleEndian};
-use crate::{BitReadStream, Endianness, Result};
+use crate::{BitError, BitReadStream, CheckedRead, Endianness, Result};
+use std::any::type_name;
 use std::borrow::Cow;
-use std::cell::RefCell;
-use std::cmp::min;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::ffi::{CStr, CString};
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::mem::{size_of, MaybeUninit};
@@ -26,6 +27,9 @@ use std::sync::Arc;
 ///  - use a previously defined field as the size using the `size` attribute
 ///  - read a set number of bits as an integer, using the resulting value as size using the `size_bits` attribute
 ///
+/// The size can also be set with an explicit unit using `#[size(bits = 12)]`, `#[size(bytes = 4)]` or `#[size(count = 3)]`,
+/// which is converted into the unit used by the field type (see [`HasSizeUnit`]).
+///
 /// ## Examples
 ///
 /// ```
@@ -89,6 +93,7 @@ use std::sync::Arc;
 ///
 /// [read_sized]: BitReadStream::read_sized
 /// [read]: BitReadStream::read
+/// [`HasSizeUnit`]: crate::HasSizeUnit
 pub trait BitRead<'a, E: Endianness>: Sized {
     /// Read the type from stream
     fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self>;
@@ -98,10 +103,34 @@ pub trait BitRead<'a, E: Endianness>: Sized {
     /// any other validations (e.g. checking for valid utf8) still needs to be done
     #[doc(hidden)]
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, _end: bool) -> Result<Self> {
+    unsafe fn read_unchecked(
+        stream: &mut BitReadStream<'a, E>,
+        _checked: CheckedRead,
+    ) -> Result<Self> {
         Self::read(stream)
     }
 
+    /// Read an array of the type from a stream that has been checked to contain the whole array
+    ///
+    /// Types can override this to read the array at once instead of element by element.
+    #[doc(hidden)]
+    #[inline]
+    unsafe fn read_array_unchecked<const N: usize>(
+        stream: &mut BitReadStream<'a, E>,
+        checked: CheckedRead,
+    ) -> Result<[Self; N]> {
+        // SAFETY: An uninitialized `[MaybeUninit<_>; LEN]` is valid.
+        let mut array = MaybeUninit::<[MaybeUninit<Self>; N]>::uninit().assume_init();
+
+        for item in array.iter_mut() {
+            // length is already checked
+            let val = stream.read_unchecked(checked)?;
+            item.as_mut_ptr().write(val);
+        }
+
+        Ok((&array as *const _ as *const [Self; N]).read())
+    }
+
     /// Skip the type
     ///
     /// This might be faster than reading it if the size is known beforehand
@@ -119,10 +148,20 @@ pub trait BitRead<'a, E: Endianness>: Sized {
     fn bit_size() -> Option<usize> {
         None
     }
+
+    /// Whether the value is read from the bytes of its in-memory layout, in the byte order of the stream
+    ///
+    /// Used by `read_pod` to decide if the value can be copied directly from the stream,
+    /// types that change the bytes while reading, like derived types with `#[byte_swap]` fields, return `false`.
+    #[doc(hidden)]
+    #[inline]
+    fn reads_memory_layout() -> bool {
+        true
+    }
 }
 
 macro_rules! impl_read_int {
-    ($type:ty) => {
+    ($type:ty $(, { $($extra:tt)* })?) => {
         impl<E: Endianness> BitRead<'_, E> for $type {
             #[inline]
             fn read(stream: &mut BitReadStream<E>) -> Result<$type> {
@@ -130,14 +169,19 @@ macro_rules! impl_read_int {
             }
 
             #[inline]
-            unsafe fn read_unchecked(stream: &mut BitReadStream<E>, end: bool) -> Result<$type> {
-                Ok(stream.read_int_unchecked::<$type>(<$type>::BITS as usize, end))
+            unsafe fn read_unchecked(
+                stream: &mut BitReadStream<E>,
+                checked: CheckedRead,
+            ) -> Result<$type> {
+                Ok(stream.read_int_unchecked::<$type>(<$type>::BITS as usize, checked))
             }
 
             #[inline]
             fn bit_size() -> Option<usize> {
                 Some(<$type>::BITS as usize)
             }
+
+            $($($extra)*)?
         }
     };
 }
@@ -153,10 +197,10 @@ macro_rules! impl_read_int_nonzero {
             #[inline]
             unsafe fn read_unchecked(
                 stream: &mut BitReadStream<LittleEndian>,
-                end: bool,
+                checked: CheckedRead,
             ) -> Result<Self> {
                 Ok(<$type>::new(
-                    stream.read_int_unchecked(size_of::<$type>() * 8, end),
+                    stream.read_int_unchecked(size_of::<$type>() * 8, checked),
                 ))
             }
 
@@ -175,10 +219,10 @@ macro_rules! impl_read_int_nonzero {
             #[inline]
             unsafe fn read_unchecked(
                 stream: &mut BitReadStream<BigEndian>,
-                end: bool,
+                checked: CheckedRead,
             ) -> Result<Self> {
                 Ok(<$type>::new(
-                    stream.read_int_unchecked(size_of::<$type>() * 8, end),
+                    stream.read_int_unchecked(size_of::<$type>() * 8, checked),
                 ))
             }
 
@@ -190,7 +234,19 @@ macro_rules! impl_read_int_nonzero {
     };
 }
 
-impl_read_int!(u8);
+impl_read_int!(u8, {
+    /// Copy the bytes at once instead of reading them one by one
+    #[inline]
+    unsafe fn read_array_unchecked<const N: usize>(
+        stream: &mut BitReadStream<E>,
+        checked: CheckedRead,
+    ) -> Result<[u8; N]> {
+        let bytes = stream.read_bytes_unchecked(N, checked);
+        let mut array = [0; N];
+        array.copy_from_slice(&bytes);
+        Ok(array)
+    }
+});
 impl_read_int!(u16);
 impl_read_int!(u32);
 impl_read_int!(u64);
@@ -214,8 +270,8 @@ impl<E: Endianness> BitRead<'_, E> for f32 {
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<E>, end: bool) -> Result<f32> {
-        Ok(stream.read_float_unchecked::<f32>(end))
+    unsafe fn read_unchecked(stream: &mut BitReadStream<E>, checked: CheckedRead) -> Result<f32> {
+        Ok(stream.read_float_unchecked::<f32>(checked))
     }
 
     #[inline]
@@ -231,8 +287,8 @@ impl<E: Endianness> BitRead<'_, E> for f64 {
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<E>, end: bool) -> Result<f64> {
-        Ok(stream.read_float_unchecked::<f64>(end))
+    unsafe fn read_unchecked(stream: &mut BitReadStream<E>, checked: CheckedRead) -> Result<f64> {
+        Ok(stream.read_float_unchecked::<f64>(checked))
     }
 
     #[inline]
@@ -248,8 +304,8 @@ impl<E: Endianness> BitRead<'_, E> for bool {
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<E>, _end: bool) -> Result<bool> {
-        Ok(stream.read_bool_unchecked())
+    unsafe fn read_unchecked(stream: &mut BitReadStream<E>, checked: CheckedRead) -> Result<bool> {
+        Ok(stream.read_bool_unchecked(checked))
     }
 
     #[inline]
@@ -272,6 +328,22 @@ impl<'a, E: Endianness> BitRead<'a, E> for Cow<'a, str> {
     }
 }
 
+/// Read a null terminated C string, see [`BitReadStream::read_c_str`]
+impl<E: Endianness> BitRead<'_, E> for CString {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<CString> {
+        Ok(stream.read_c_str()?.into_owned())
+    }
+}
+
+/// Read a null terminated C string, see [`BitReadStream::read_c_str`]
+impl<'a, E: Endianness> BitRead<'a, E> for Cow<'a, CStr> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Cow<'a, CStr>> {
+        stream.read_c_str()
+    }
+}
+
 impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Rc<T> {
     #[inline]
     fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
@@ -279,8 +351,11 @@ impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Rc<T> {
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, end: bool) -> Result<Self> {
-        Ok(Rc::new(T::read_unchecked(stream, end)?))
+    unsafe fn read_unchecked(
+        stream: &mut BitReadStream<'a, E>,
+        checked: CheckedRead,
+    ) -> Result<Self> {
+        Ok(Rc::new(T::read_unchecked(stream, checked)?))
     }
 
     #[inline]
@@ -296,8 +371,11 @@ impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Arc<T> {
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, end: bool) -> Result<Self> {
-        Ok(Arc::new(T::read_unchecked(stream, end)?))
+    unsafe fn read_unchecked(
+        stream: &mut BitReadStream<'a, E>,
+        checked: CheckedRead,
+    ) -> Result<Self> {
+        Ok(Arc::new(T::read_unchecked(stream, checked)?))
     }
 
     #[inline]
@@ -313,8 +391,11 @@ impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Box<T> {
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, end: bool) -> Result<Self> {
-        Ok(Box::new(T::read_unchecked(stream, end)?))
+    unsafe fn read_unchecked(
+        stream: &mut BitReadStream<'a, E>,
+        checked: CheckedRead,
+    ) -> Result<Self> {
+        Ok(Box::new(T::read_unchecked(stream, checked)?))
     }
 
     #[inline]
@@ -323,6 +404,57 @@ impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Box<T> {
     }
 }
 
+macro_rules! impl_read_wrapper {
+    ($wrapper:ident) => {
+        impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for $wrapper<T> {
+            #[inline]
+            fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+                Ok($wrapper::new(T::read(stream)?))
+            }
+
+            #[inline]
+            unsafe fn read_unchecked(
+                stream: &mut BitReadStream<'a, E>,
+                checked: CheckedRead,
+            ) -> Result<Self> {
+                Ok($wrapper::new(T::read_unchecked(stream, checked)?))
+            }
+
+            #[inline]
+            fn bit_size() -> Option<usize> {
+                T::bit_size()
+            }
+        }
+
+        impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for $wrapper<T> {
+            #[inline]
+            fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+                Ok($wrapper::new(T::read(stream, size)?))
+            }
+
+            #[inline]
+            unsafe fn read_unchecked(
+                stream: &mut BitReadStream<'a, E>,
+                size: usize,
+                checked: CheckedRead,
+            ) -> Result<Self> {
+                Ok($wrapper::new(T::read_unchecked(stream, size, checked)?))
+            }
+
+            #[inline]
+            fn bit_size_sized(size: usize) -> Option<usize> {
+                T::bit_size_sized(size)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std-types")]
+pub(crate) use impl_read_wrapper;
+
+impl_read_wrapper!(Cell);
+impl_read_wrapper!(RefCell);
+
 macro_rules! impl_read_tuple {
     ($($type:ident),*) => {
         impl<'a, E: Endianness, $($type: BitRead<'a, E>),*> BitRead<'a, E> for ($($type),*) {
@@ -332,8 +464,8 @@ macro_rules! impl_read_tuple {
             }
 
             #[inline]
-            unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, end: bool) -> Result<Self> {
-                Ok(($(<$type>::read_unchecked(stream, end)?),*))
+            unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, checked: CheckedRead) -> Result<Self> {
+                Ok(($(<$type>::read_unchecked(stream, checked)?),*))
             }
 
             #[inline]
@@ -348,13 +480,33 @@ impl_read_tuple!(T1, T2);
 impl_read_tuple!(T1, T2, T3);
 impl_read_tuple!(T1, T2, T3, T4);
 
+/// `PhantomData` doesn't contain any data, nothing is read
+impl<'a, E: Endianness, T: ?Sized> BitRead<'a, E> for PhantomData<T> {
+    #[inline]
+    fn read(_stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        Ok(PhantomData)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(0)
+    }
+}
+
+/// Read the elements in order
+///
+/// `[u8; N]` is read as raw bytes, with the same result as reading the `u8`s one by one:
+/// at a byte boundary the bytes are copied from the buffer, otherwise every byte is made of the next 8 bits
+/// in the bit order of the endianness. The endianness never changes the order of the bytes in the array.
+/// Since 0.12 the read is counted as a byte read instead of an integer read when tracking read statistics
+/// with the `stats` feature.
 impl<'a, E: Endianness, T: BitRead<'a, E>, const N: usize> BitRead<'a, E> for [T; N] {
     #[inline]
     fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
         match T::bit_size() {
             Some(bit_size) => {
-                let end = stream.check_read(bit_size * N)?;
-                unsafe { Self::read_unchecked(stream, end) }
+                let checked = stream.check_read(bit_size * N)?;
+                unsafe { Self::read_unchecked(stream, checked) }
             }
             None => {
                 // SAFETY: An uninitialized `[MaybeUninit<_>; LEN]` is valid.
@@ -373,23 +525,22 @@ impl<'a, E: Endianness, T: BitRead<'a, E>, const N: usize> BitRead<'a, E> for [T
     }
 
     #[inline]
-    unsafe fn read_unchecked(stream: &mut BitReadStream<'a, E>, end: bool) -> Result<Self> {
-        // SAFETY: An uninitialized `[MaybeUninit<_>; LEN]` is valid.
-        let mut array = MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init();
-
-        for item in array.iter_mut() {
-            // length is already checked
-            let val = stream.read_unchecked(end)?;
-            item.as_mut_ptr().write(val);
-        }
-
-        Ok((&array as *const _ as *const [T; N]).read())
+    unsafe fn read_unchecked(
+        stream: &mut BitReadStream<'a, E>,
+        checked: CheckedRead,
+    ) -> Result<Self> {
+        T::read_array_unchecked(stream, checked)
     }
 
     #[inline]
     fn bit_size() -> Option<usize> {
         T::bit_size().map(|size| size * N)
     }
+
+    #[inline]
+    fn reads_memory_layout() -> bool {
+        T::reads_memory_layout()
+    }
 }
 
 /// Trait for types that can be read from a stream, requiring the size to be configured
@@ -464,7 +615,7 @@ pub trait BitReadSized<'a, E: Endianness>: Sized {
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        _end: bool,
+        _checked: CheckedRead,
     ) -> Result<Self> {
         Self::read(stream, size)
     }
@@ -500,9 +651,9 @@ macro_rules! impl_read_int_sized {
             unsafe fn read_unchecked(
                 stream: &mut BitReadStream<E>,
                 size: usize,
-                end: bool,
+                checked: CheckedRead,
             ) -> Result<$type> {
-                Ok(stream.read_int_unchecked::<$type>(size, end))
+                Ok(stream.read_int_unchecked::<$type>(size, checked))
             }
 
             #[inline]
@@ -548,6 +699,60 @@ impl<'a, E: Endianness> BitReadSized<'a, E> for Cow<'a, str> {
     }
 }
 
+/// Read a C string from `size` bytes, ending at the first null byte
+///
+/// # Errors
+///
+/// - [`BitError::UnterminatedString`]: the bytes don't contain a null byte
+fn read_c_str_sized<'a, E: Endianness>(
+    stream: &mut BitReadStream<'a, E>,
+    size: usize,
+) -> Result<Cow<'a, CStr>> {
+    let bytes = stream.read_bytes(size)?;
+    let len = match bytes.iter().position(|byte| *byte == 0) {
+        Some(len) => len,
+        None => return Err(BitError::UnterminatedString { max_bytes: size }),
+    };
+    // safety: the bytes end with the first null byte
+    Ok(unsafe {
+        match bytes {
+            Cow::Borrowed(bytes) => {
+                Cow::Borrowed(CStr::from_bytes_with_nul_unchecked(&bytes[..=len]))
+            }
+            Cow::Owned(mut bytes) => {
+                bytes.truncate(len + 1);
+                Cow::Owned(CString::from_vec_with_nul_unchecked(bytes))
+            }
+        }
+    })
+}
+
+/// Read a C string from `size` bytes, ending at the first null byte, the bytes after it are skipped
+impl<E: Endianness> BitReadSized<'_, E> for CString {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<CString> {
+        Ok(read_c_str_sized(stream, size)?.into_owned())
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(8 * size)
+    }
+}
+
+/// Read a C string from `size` bytes, ending at the first null byte, the bytes after it are skipped
+impl<'a, E: Endianness> BitReadSized<'a, E> for Cow<'a, CStr> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Cow<'a, CStr>> {
+        read_c_str_sized(stream, size)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(8 * size)
+    }
+}
+
 impl<'a, E: Endianness> BitReadSized<'a, E> for Cow<'a, [u8]> {
     #[inline]
     fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Cow<'a, [u8]>> {
@@ -596,17 +801,13 @@ impl<'a, E: Endianness> BitReadSized<'a, E> for BitReadStream<'a, E> {
 /// Read `T` `size` times and return as `Vec<T>`
 impl<'a, E: Endianness, T: BitRead<'a, E>> BitReadSized<'a, E> for Vec<T> {
     fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
-        let mut vec = Vec::with_capacity(min(size, 128));
+        let mut vec = Vec::with_capacity(stream.capacity_hint(size, T::bit_size()));
         match T::bit_size() {
             Some(bit_size) => {
-                if stream.check_read(bit_size * size)? {
-                    for _ in 0..size {
-                        vec.push(unsafe { stream.read_unchecked(true) }?)
-                    }
-                } else {
-                    for _ in 0..size {
-                        vec.push(unsafe { stream.read_unchecked(false) }?)
-                    }
+                let checked = stream.check_read(bit_size.saturating_mul(size))?;
+                for index in 0..size {
+                    stream.check_cancelled_periodic(index)?;
+                    vec.push(unsafe { stream.read_unchecked(checked) }?)
                 }
             }
             _ => {
@@ -622,11 +823,12 @@ impl<'a, E: Endianness, T: BitRead<'a, E>> BitReadSized<'a, E> for Vec<T> {
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        end: bool,
+        checked: CheckedRead,
     ) -> Result<Self> {
-        let mut vec = Vec::with_capacity(min(size, 128));
-        for _ in 0..size {
-            vec.push(stream.read_unchecked(end)?)
+        let mut vec = Vec::with_capacity(stream.capacity_hint(size, T::bit_size()));
+        for index in 0..size {
+            stream.check_cancelled_periodic(index)?;
+            vec.push(stream.read_unchecked(checked)?)
         }
         Ok(vec)
     }
@@ -637,6 +839,36 @@ impl<'a, E: Endianness, T: BitRead<'a, E>> BitReadSized<'a, E> for Vec<T> {
     }
 }
 
+macro_rules! impl_read_sized_slice_pointer {
+    ($pointer:ident) => {
+        /// Read `T` `size` times and return as a slice
+        impl<'a, E: Endianness, T: BitRead<'a, E>> BitReadSized<'a, E> for $pointer<[T]> {
+            #[inline]
+            fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+                Ok(stream.read_sized::<Vec<T>>(size)?.into())
+            }
+
+            #[inline]
+            unsafe fn read_unchecked(
+                stream: &mut BitReadStream<'a, E>,
+                size: usize,
+                checked: CheckedRead,
+            ) -> Result<Self> {
+                Ok(stream.read_sized_unchecked::<Vec<T>>(size, checked)?.into())
+            }
+
+            #[inline]
+            fn bit_size_sized(size: usize) -> Option<usize> {
+                <Vec<T> as BitReadSized<'a, E>>::bit_size_sized(size)
+            }
+        }
+    };
+}
+
+impl_read_sized_slice_pointer!(Box);
+impl_read_sized_slice_pointer!(Rc);
+impl_read_sized_slice_pointer!(Arc);
+
 // Once we have something like https://github.com/rust-lang/rfcs/issues/1053 we can do this optimization
 //impl<E: Endianness> ReadSized<E> for Vec<u8> {
 //    #[inline]
@@ -651,7 +883,8 @@ impl<'a, E: Endianness, K: BitRead<'a, E> + Eq + Hash, T: BitRead<'a, E>> BitRea
     for HashMap<K, T>
 {
     fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
-        let mut map = HashMap::with_capacity(min(size, 128));
+        let mut map =
+            HashMap::with_capacity(stream.capacity_hint(size, entry_bit_size::<E, K, T>()));
         for _ in 0..size {
             let key = stream.read()?;
             let value = stream.read()?;
@@ -664,12 +897,14 @@ impl<'a, E: Endianness, K: BitRead<'a, E> + Eq + Hash, T: BitRead<'a, E>> BitRea
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        end: bool,
+        checked: CheckedRead,
     ) -> Result<Self> {
-        let mut map = HashMap::with_capacity(min(size, 128));
-        for _ in 0..size {
-            let key = stream.read_unchecked(end)?;
-            let value = stream.read_unchecked(end)?;
+        let mut map =
+            HashMap::with_capacity(stream.capacity_hint(size, entry_bit_size::<E, K, T>()));
+        for index in 0..size {
+            stream.check_cancelled_periodic(index)?;
+            let key = stream.read_unchecked(checked)?;
+            let value = stream.read_unchecked(checked)?;
             map.insert(key, value);
         }
         Ok(map)
@@ -677,14 +912,15 @@ impl<'a, E: Endianness, K: BitRead<'a, E> + Eq + Hash, T: BitRead<'a, E>> BitRea
 
     #[inline]
     fn bit_size_sized(size: usize) -> Option<usize> {
-        if let (Some(key_size), Some(value_size)) = (K::bit_size(), T::bit_size()) {
-            Some(size * (key_size + value_size))
-        } else {
-            None
-        }
+        entry_bit_size::<E, K, T>().map(|entry_size| size * entry_size)
     }
 }
 
+#[inline]
+fn entry_bit_size<'a, E: Endianness, K: BitRead<'a, E>, T: BitRead<'a, E>>() -> Option<usize> {
+    Some(K::bit_size()? + T::bit_size()?)
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// Struct that lazily reads it's contents from the stream
 pub struct LazyBitRead<'a, T: BitRead<'a, E>, E: Endianness> {
@@ -708,7 +944,9 @@ impl<'a, T: BitRead<'a, E>, E: Endianness> BitRead<'a, E> for LazyBitRead<'a, T,
                 source: stream.read_bits(bit_size)?,
                 inner_type: PhantomData,
             }),
-            None => panic!(),
+            None => Err(BitError::UnknownLazySize {
+                type_name: type_name::<T>(),
+            }),
         }
     }
 
@@ -743,7 +981,9 @@ impl<'a, T: BitReadSized<'a, E>, E: Endianness> BitReadSized<'a, E> for LazyBitR
                 inner_type: PhantomData,
                 size,
             }),
-            None => panic!(),
+            None => Err(BitError::UnknownLazySize {
+                type_name: type_name::<T>(),
+            }),
         }
     }
 
@@ -763,9 +1003,9 @@ impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for Arc<T> {
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        end: bool,
+        checked: CheckedRead,
     ) -> Result<Self> {
-        Ok(Arc::new(T::read_unchecked(stream, size, end)?))
+        Ok(Arc::new(T::read_unchecked(stream, size, checked)?))
     }
 
     #[inline]
@@ -784,9 +1024,9 @@ impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for Rc<T> {
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        end: bool,
+        checked: CheckedRead,
     ) -> Result<Self> {
-        Ok(Rc::new(T::read_unchecked(stream, size, end)?))
+        Ok(Rc::new(T::read_unchecked(stream, size, checked)?))
     }
 
     #[inline]
@@ -805,9 +1045,9 @@ impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for Box<T> {
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        end: bool,
+        checked: CheckedRead,
     ) -> Result<Self> {
-        Ok(Box::new(T::read_unchecked(stream, size, end)?))
+        Ok(Box::new(T::read_unchecked(stream, size, checked)?))
     }
 
     #[inline]
@@ -816,13 +1056,44 @@ impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for Box<T> {
     }
 }
 
+macro_rules! impl_read_sized_tuple {
+    ($($type:ident),*) => {
+        /// The size is applied to every element of the tuple
+        impl<'a, E: Endianness, $($type: BitReadSized<'a, E>),*> BitReadSized<'a, E> for ($($type),*) {
+            #[inline]
+            fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+                Ok(($(<$type>::read(stream, size)?),*))
+            }
+
+            #[inline]
+            unsafe fn read_unchecked(
+                stream: &mut BitReadStream<'a, E>,
+                size: usize,
+                checked: CheckedRead,
+            ) -> Result<Self> {
+                Ok(($(<$type>::read_unchecked(stream, size, checked)?),*))
+            }
+
+            #[inline]
+            fn bit_size_sized(size: usize) -> Option<usize> {
+                Some(0)$(.and_then(|sum| <$type>::bit_size_sized(size).map(|size| sum + size)))*
+            }
+        }
+    };
+}
+
+impl_read_sized_tuple!(T1, T2);
+impl_read_sized_tuple!(T1, T2, T3);
+impl_read_sized_tuple!(T1, T2, T3, T4);
+
+/// The size is applied to every element of the array
 impl<'a, E: Endianness, T: BitReadSized<'a, E>, const N: usize> BitReadSized<'a, E> for [T; N] {
     #[inline]
     fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
         match T::bit_size_sized(size) {
             Some(bit_size) => {
-                let end = stream.check_read(bit_size * N)?;
-                unsafe { Self::read_unchecked(stream, size, end) }
+                let checked = stream.check_read(bit_size * N)?;
+                unsafe { Self::read_unchecked(stream, size, checked) }
             }
             None => {
                 // SAFETY: An uninitialized `[MaybeUninit<_>; LEN]` is valid.
@@ -844,14 +1115,14 @@ impl<'a, E: Endianness, T: BitReadSized<'a, E>, const N: usize> BitReadSized<'a,
     unsafe fn read_unchecked(
         stream: &mut BitReadStream<'a, E>,
         size: usize,
-        end: bool,
+        checked: CheckedRead,
     ) -> Result<Self> {
         // SAFETY: An uninitialized `[MaybeUninit<_>; LEN]` is valid.
         let mut array = MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init();
 
         for item in array.iter_mut() {
             // length is already checked
-            let val = stream.read_sized_unchecked(size, end)?;
+            let val = stream.read_sized_unchecked(size, checked)?;
             item.as_mut_ptr().write(val);
         }
 
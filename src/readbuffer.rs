@@ -5,17 +5,22 @@ use std::marker::PhantomData;
 use std::mem::size_of;
 use std::ops::{BitOrAssign, BitXor, Index, Range, RangeFrom};
 
-use num_traits::{Float, PrimInt, WrappingSub};
+use crate::num_traits::{Float, PrimInt, WrappingSub};
 
-use crate::endianness::Endianness;
+use crate::endianness::{swap_byte_groups, swaps_bytes, Endianness};
 use crate::num_traits::{IsSigned, UncheckedPrimitiveFloat, UncheckedPrimitiveInt};
-use crate::{BitError, Result};
+use crate::{BitError, BitWriteStream, Result};
 use std::borrow::{Borrow, Cow};
+#[cfg(any(debug_assertions, feature = "bounds-debug"))]
+use std::cell::Cell;
 use std::convert::TryInto;
 use std::rc::Rc;
 
 const USIZE_SIZE: usize = size_of::<usize>();
-const USIZE_BIT_SIZE: usize = USIZE_SIZE * 8;
+/// The last `USIZE_SIZE - 1` bytes of the buffer followed by a usize worth of zero bytes
+const TAIL_SIZE: usize = USIZE_SIZE * 2 - 1;
+/// The number of bytes shifted at a time when searching unaligned bytes for a sentinel
+const SENTINEL_CHUNK_SIZE: usize = 64;
 
 // Cow<[u8]> but with cheap clones using Rc
 pub(crate) enum Data<'a> {
@@ -77,6 +82,43 @@ impl<'a> Clone for Data<'a> {
     }
 }
 
+/// How null bytes are handled when reading a fixed length string
+///
+/// See [`BitReadStream::read_fixed_string`](crate::BitReadStream::read_fixed_string).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NullPadding {
+    /// Remove any trailing null bytes, keeping data after interior null bytes
+    ///
+    /// This is the behavior of [`read_string`](crate::BitReadStream::read_string) with a fixed length.
+    #[default]
+    TrimEnd,
+    /// End the string at the first null byte, ignoring anything after it
+    FirstNull,
+    /// Keep all bytes, including any null bytes
+    Keep,
+}
+
+impl NullPadding {
+    /// Convert the bytes of a fixed length string
+    fn to_str(self, bytes: Cow<[u8]>) -> Result<Cow<str>> {
+        let len = bytes.len();
+        let end = match self {
+            NullPadding::TrimEnd => bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1),
+            NullPadding::FirstNull => bytes.iter().position(|b| *b == 0).unwrap_or(len),
+            NullPadding::Keep => len,
+        };
+        Ok(match bytes {
+            Cow::Borrowed(bytes) => Cow::Borrowed(
+                std::str::from_utf8(&bytes[0..end]).map_err(|err| BitError::Utf8Error(err, len))?,
+            ),
+            Cow::Owned(mut bytes) => {
+                bytes.truncate(end);
+                Cow::Owned(String::from_utf8(bytes)?)
+            }
+        })
+    }
+}
+
 /// Buffer that allows reading integers of arbitrary bit length and non byte-aligned integers
 ///
 /// # Examples
@@ -96,6 +138,9 @@ impl<'a> Clone for Data<'a> {
 /// #     Ok(())
 /// # }
 /// ```
+///
+/// The buffer doesn't require any padding after the data, every bit up to the last bit of the
+/// provided bytes can be read.
 pub struct BitReadBuffer<'a, E>
 where
     E: Endianness,
@@ -104,6 +149,80 @@ where
     bit_len: usize,
     endianness: PhantomData<E>,
     slice: &'a [u8],
+    /// Zero padded copy of the end of the slice, used for reads that would go past the end of the slice
+    tail: Tail,
+    pub(crate) watermark: Watermark,
+}
+
+/// Tracks truncation of buffers, to detect streams that read past the end of the buffer they were cloned from
+/// after it was truncated
+///
+/// The tracking is only done in debug builds or when the `bounds-debug` feature is enabled.
+#[derive(Clone, Default)]
+pub(crate) struct Watermark {
+    /// The bit length the buffer was last truncated to
+    #[cfg(any(debug_assertions, feature = "bounds-debug"))]
+    truncated: Rc<Cell<Option<usize>>>,
+    /// The truncated length of the buffer this buffer was cloned from
+    #[cfg(any(debug_assertions, feature = "bounds-debug"))]
+    parent: Option<Rc<Cell<Option<usize>>>>,
+}
+
+impl Watermark {
+    /// Watermark for a copy of the buffer, linked to the truncations of this buffer
+    fn child(&self) -> Self {
+        Watermark {
+            #[cfg(any(debug_assertions, feature = "bounds-debug"))]
+            truncated: Rc::default(),
+            #[cfg(any(debug_assertions, feature = "bounds-debug"))]
+            parent: Some(self.truncated.clone()),
+        }
+    }
+
+    #[cfg_attr(
+        not(any(debug_assertions, feature = "bounds-debug")),
+        allow(unused_variables)
+    )]
+    fn truncate(&self, bit_len: usize) {
+        #[cfg(any(debug_assertions, feature = "bounds-debug"))]
+        self.truncated.set(Some(bit_len));
+    }
+
+    /// Panic if a read ending at `end` goes past the end of the buffer this buffer was cloned from,
+    /// because that buffer has been truncated since
+    #[inline(always)]
+    #[track_caller]
+    #[cfg_attr(
+        not(any(debug_assertions, feature = "bounds-debug")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn check(&self, end: usize) {
+        #[cfg(any(debug_assertions, feature = "bounds-debug"))]
+        if let Some(parent_len) = self.parent.as_ref().and_then(|parent| parent.get()) {
+            assert!(
+                end <= parent_len,
+                "read up to bit {} from a stream whose source buffer was truncated to {} bits",
+                end,
+                parent_len
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Tail {
+    /// Index in the slice of the first byte in the tail
+    start: usize,
+    bytes: [u8; TAIL_SIZE],
+}
+
+impl Tail {
+    fn new(slice: &[u8]) -> Self {
+        let start = slice.len().saturating_sub(USIZE_SIZE - 1);
+        let mut bytes = [0; TAIL_SIZE];
+        bytes[0..slice.len() - start].copy_from_slice(&slice[start..]);
+        Tail { start, bytes }
+    }
 }
 
 impl<'a, E> BitReadBuffer<'a, E>
@@ -131,6 +250,8 @@ where
             bit_len: byte_len * 8,
             endianness: PhantomData,
             slice: bytes,
+            tail: Tail::new(bytes),
+            watermark: Watermark::default(),
         }
     }
 
@@ -151,7 +272,9 @@ where
             bytes,
             bit_len: byte_len * 8,
             endianness: PhantomData,
+            tail: Tail::new(slice),
             slice,
+            watermark: Watermark::default(),
         }
     }
 }
@@ -187,7 +310,9 @@ where
             bytes,
             bit_len: byte_len * 8,
             endianness: PhantomData,
+            tail: Tail::new(slice),
             slice,
+            watermark: Watermark::default(),
         }
     }
 }
@@ -200,7 +325,9 @@ pub(crate) fn get_bits_from_usize<E: Endianness>(
     let shifted = if E::is_le() {
         val >> bit_offset
     } else {
-        val >> (usize::BITS as usize - bit_offset - count)
+        // reading 0 bits at offset 0 would shift out the entire usize
+        val.checked_shr((usize::BITS as usize - bit_offset - count) as u32)
+            .unwrap_or(0)
     };
     let mask = !(usize::MAX << count);
     shifted & mask
@@ -220,37 +347,90 @@ where
         self.slice.len()
     }
 
-    unsafe fn read_usize_bytes(&self, byte_index: usize, end: bool) -> [u8; USIZE_SIZE] {
-        if end {
-            let mut bytes = [0; USIZE_SIZE];
-            let count = min(USIZE_SIZE, self.slice.len() - byte_index);
-            bytes[0..count]
-                .copy_from_slice(self.slice.get_unchecked(byte_index..byte_index + count));
-            bytes
+    /// The number of buffers sharing the owned data backing this buffer, including this one
+    ///
+    /// Cloning an owned buffer, or a stream reading from it, shares the backing data instead of copying it,
+    /// the data is only freed once every buffer sharing it is dropped.
+    ///
+    /// Returns `None` if the buffer borrows its data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let buffer = BitReadBuffer::new_owned(vec![0; 1024], LittleEndian);
+    /// let mut stream = BitReadStream::new(buffer.clone());
+    /// let sub_stream = stream.read_bits(16)?;
+    /// assert_eq!(buffer.strong_count(), Some(3));
+    /// assert_eq!(sub_stream.memory_usage(), 1024);
+    ///
+    /// drop(stream);
+    /// drop(sub_stream);
+    /// assert_eq!(buffer.strong_count(), Some(1));
+    ///
+    /// let bytes = [0; 16];
+    /// assert_eq!(BitReadBuffer::new(&bytes, LittleEndian).strong_count(), None);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn strong_count(&self) -> Option<usize> {
+        match &self.bytes {
+            Data::Borrowed(_) => None,
+            Data::Owned(bytes) => Some(Rc::strong_count(bytes)),
+        }
+    }
+
+    /// The number of bytes of owned data kept alive by this buffer
+    ///
+    /// This is the size of the full backing data, which can be larger than [`byte_len`](BitReadBuffer::byte_len)
+    /// for buffers that only cover part of the data. The memory is shared with every other buffer reported by
+    /// [`strong_count`](BitReadBuffer::strong_count).
+    ///
+    /// Returns `0` if the buffer borrows its data.
+    pub fn memory_usage(&self) -> usize {
+        match &self.bytes {
+            Data::Borrowed(_) => 0,
+            Data::Owned(bytes) => bytes.len(),
+        }
+    }
+
+    /// Read a usize worth of bytes starting from `byte_index`, any bytes past the end of the buffer
+    /// will be 0
+    ///
+    /// `byte_index` can't be larger than the length of the slice
+    #[inline(always)]
+    fn read_usize_bytes(&self, byte_index: usize) -> [u8; USIZE_SIZE] {
+        if byte_index + USIZE_SIZE <= self.slice.len() {
+            // safe because of the bounds check above
+            unsafe {
+                self.slice
+                    .get_unchecked(byte_index..byte_index + USIZE_SIZE)
+                    .try_into()
+                    .unwrap()
+            }
         } else {
-            debug_assert!(byte_index + USIZE_SIZE <= self.slice.len());
-            // this is safe because all calling paths check that byte_index is less than the unpadded
-            // length (because they check based on bit_len), so with padding byte_index + USIZE_SIZE is
-            // always within bounds
-            self.slice
-                .get_unchecked(byte_index..byte_index + USIZE_SIZE)
+            let offset = byte_index - self.tail.start;
+            self.tail.bytes[offset..offset + USIZE_SIZE]
                 .try_into()
                 .unwrap()
         }
     }
 
     /// note that only the bottom USIZE - 1 bytes are usable
-    unsafe fn read_shifted_usize(&self, byte_index: usize, shift: usize, end: bool) -> usize {
-        let raw_bytes: [u8; USIZE_SIZE] = self.read_usize_bytes(byte_index, end);
+    fn read_shifted_usize(&self, byte_index: usize, shift: usize) -> usize {
+        let raw_bytes: [u8; USIZE_SIZE] = self.read_usize_bytes(byte_index);
         let raw_usize: usize = usize::from_le_bytes(raw_bytes);
         raw_usize >> shift
     }
 
-    unsafe fn read_usize(&self, position: usize, count: usize, end: bool) -> usize {
+    fn read_usize(&self, position: usize, count: usize) -> usize {
         let byte_index = position / 8;
         let bit_offset = position & 7;
 
-        let bytes: [u8; USIZE_SIZE] = self.read_usize_bytes(byte_index, end);
+        let bytes: [u8; USIZE_SIZE] = self.read_usize_bytes(byte_index);
 
         let container = if E::is_le() {
             usize::from_le_bytes(bytes)
@@ -314,9 +494,25 @@ where
         }
     }
 
-    #[doc(hidden)]
+    /// Iterate over all bits in the buffer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BigEndian};
+    /// #
+    /// let bytes = [0b1011_0000];
+    /// let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    /// let bits: Vec<bool> = buffer.iter_bits().take(4).collect();
+    /// assert_eq!(bits, [true, false, true, true]);
+    /// ```
+    pub fn iter_bits(&self) -> impl DoubleEndedIterator<Item = bool> + ExactSizeIterator + '_ {
+        (0..self.bit_len()).map(move |position| self[position])
+    }
+
     #[inline]
-    pub unsafe fn read_bool_unchecked(&self, position: usize) -> bool {
+    pub(crate) unsafe fn read_bool_unchecked(&self, position: usize) -> bool {
+        self.debug_assert_in_bounds(position, 1);
         let byte_index = position / 8;
         let bit_offset = position & 7;
 
@@ -371,41 +567,39 @@ where
             });
         }
 
-        if position + count + USIZE_BIT_SIZE > self.bit_len() {
-            if position + count > self.bit_len() {
-                return if position > self.bit_len() {
-                    Err(BitError::IndexOutOfBounds {
-                        pos: position,
-                        size: self.bit_len(),
-                    })
-                } else {
-                    Err(BitError::NotEnoughData {
-                        requested: count,
-                        bits_left: self.bit_len() - position,
-                    })
-                };
-            }
-            Ok(unsafe { self.read_int_unchecked(position, count, true) })
-        } else {
-            Ok(unsafe { self.read_int_unchecked(position, count, false) })
-        }
+        self.check_bits(position, count)?;
+        Ok(unsafe { self.read_int_unchecked(position, count) })
     }
 
-    #[doc(hidden)]
+    /// Read an integer without checking the bounds of the buffer
+    ///
+    /// # Safety
+    ///
+    /// `position + count` can't be larger than the bit length of the buffer
     #[inline]
-    pub unsafe fn read_int_unchecked<T>(&self, position: usize, count: usize, end: bool) -> T
+    pub(crate) unsafe fn read_int_unchecked<T>(&self, position: usize, count: usize) -> T
     where
         T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + BitXor + WrappingSub,
     {
         let type_bit_size = size_of::<T>() * 8;
 
         let bit_offset = position & 7;
+        self.debug_assert_in_bounds(position, count);
 
         let fit_usize = count + bit_offset < usize::BITS as usize;
-        let value = if fit_usize {
-            self.read_fit_usize(position, count, end)
+        let value: T = if fit_usize {
+            self.read_fit_usize(position, count)
+        } else {
+            self.read_no_fit_usize(position, count)
+        };
+        let value = if swaps_bytes::<E>() {
+            T::from_unchecked(swap_byte_groups(
+                value.into_u128_unchecked(),
+                count,
+                E::is_le(),
+            ))
         } else {
-            self.read_no_fit_usize(position, count, end)
+            value
         };
 
         if count == type_bit_size {
@@ -416,15 +610,15 @@ where
     }
 
     #[inline]
-    unsafe fn read_fit_usize<T>(&self, position: usize, count: usize, end: bool) -> T
+    fn read_fit_usize<T>(&self, position: usize, count: usize) -> T
     where
         T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt,
     {
-        let raw = self.read_usize(position, count, end);
+        let raw = self.read_usize(position, count);
         T::from_unchecked(raw)
     }
 
-    unsafe fn read_no_fit_usize<T>(&self, position: usize, count: usize, end: bool) -> T
+    fn read_no_fit_usize<T>(&self, position: usize, count: usize) -> T
     where
         T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt,
     {
@@ -436,7 +630,7 @@ where
         while left_to_read > 0 {
             let bits_left = self.bit_len() - read_pos;
             let read = min(min(left_to_read, max_read), bits_left);
-            let data = T::from_unchecked(self.read_usize(read_pos, read, end));
+            let data = T::from_unchecked(self.read_usize(read_pos, read));
             if E::is_le() {
                 acc |= data << bit_offset;
             } else {
@@ -499,26 +693,18 @@ where
     /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
     #[inline]
     pub fn read_bytes(&self, position: usize, byte_count: usize) -> Result<Cow<'a, [u8]>> {
-        if position + byte_count * 8 > self.bit_len() {
-            if position > self.bit_len() {
-                return Err(BitError::IndexOutOfBounds {
-                    pos: position,
-                    size: self.bit_len(),
-                });
-            } else {
-                return Err(BitError::NotEnoughData {
-                    requested: byte_count * 8,
-                    bits_left: self.bit_len() - position,
-                });
-            }
-        }
+        self.check_bytes(position, byte_count)?;
 
         Ok(unsafe { self.read_bytes_unchecked(position, byte_count) })
     }
 
-    #[doc(hidden)]
     #[inline]
-    pub unsafe fn read_bytes_unchecked(&self, position: usize, byte_count: usize) -> Cow<'a, [u8]> {
+    pub(crate) unsafe fn read_bytes_unchecked(
+        &self,
+        position: usize,
+        byte_count: usize,
+    ) -> Cow<'a, [u8]> {
+        self.debug_assert_in_bounds(position, byte_count * 8);
         let shift = position & 7;
 
         if shift == 0 {
@@ -527,38 +713,151 @@ where
         }
 
         let mut data = Vec::with_capacity(byte_count);
+        self.for_each_byte_chunk_unchecked(position, byte_count, |chunk| {
+            data.extend_from_slice(chunk)
+        });
+        Cow::Owned(data)
+    }
+
+    /// Find the first `sentinel` byte in the `byte_count` bytes starting at `position`, returning the number of bytes before it
+    ///
+    /// Unaligned bytes are shifted in chunks, so the search stops early when the sentinel is found.
+    pub(crate) fn find_sentinel(
+        &self,
+        position: usize,
+        byte_count: usize,
+        sentinel: u8,
+    ) -> Result<Option<usize>> {
+        self.check_bytes(position, byte_count)?;
+        if position & 7 == 0 {
+            let byte_pos = position / 8;
+            return Ok(find_byte(
+                sentinel,
+                &self.slice[byte_pos..byte_pos + byte_count],
+            ));
+        }
+
+        let mut chunk = [0; SENTINEL_CHUNK_SIZE];
+        let mut offset = 0;
+        while offset < byte_count {
+            let len = min(SENTINEL_CHUNK_SIZE, byte_count - offset);
+            self.read_bytes_into(position + offset * 8, &mut chunk[..len])?;
+            if let Some(index) = find_byte(sentinel, &chunk[..len]) {
+                return Ok(Some(offset + index));
+            }
+            offset += len;
+        }
+        Ok(None)
+    }
+
+    /// Read bytes from the buffer, filling all of `out`
+    #[inline]
+    pub(crate) fn read_bytes_into(&self, position: usize, out: &mut [u8]) -> Result<()> {
+        self.check_bytes(position, out.len())?;
+        let mut written = 0;
+        unsafe {
+            self.for_each_byte_chunk_unchecked(position, out.len(), |chunk| {
+                out[written..written + chunk.len()].copy_from_slice(chunk);
+                written += chunk.len();
+            })
+        };
+        Ok(())
+    }
+
+    /// Read bytes from the buffer, appending them to `out`
+    #[inline]
+    pub(crate) fn extend_bytes(
+        &self,
+        position: usize,
+        byte_count: usize,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        self.check_bytes(position, byte_count)?;
+        out.reserve(byte_count);
+        unsafe {
+            self.for_each_byte_chunk_unchecked(position, byte_count, |chunk| {
+                out.extend_from_slice(chunk)
+            })
+        };
+        Ok(())
+    }
+
+    #[inline]
+    #[track_caller]
+    fn debug_assert_in_bounds(&self, position: usize, count: usize) {
+        bounds_assert!(
+            position + count <= self.bit_len(),
+            "unchecked read of {} bits at {} past the end of the buffer of {} bits",
+            count,
+            position,
+            self.bit_len()
+        );
+    }
+
+    /// Check that `count` bits starting at `position` are within the buffer, without overflowing for large inputs
+    #[inline]
+    fn check_bits(&self, position: usize, count: usize) -> Result<()> {
+        if position > self.bit_len() {
+            Err(BitError::IndexOutOfBounds {
+                pos: position,
+                size: self.bit_len(),
+            })
+        } else if count > self.bit_len() - position {
+            Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bit_len() - position,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn check_bytes(&self, position: usize, byte_count: usize) -> Result<()> {
+        self.check_bits(position, byte_count.saturating_mul(8))
+    }
+
+    /// Pass the bytes starting at `position` to `f` in one or more chunks
+    #[inline]
+    unsafe fn for_each_byte_chunk_unchecked<F: FnMut(&[u8])>(
+        &self,
+        position: usize,
+        byte_count: usize,
+        mut f: F,
+    ) {
+        self.debug_assert_in_bounds(position, byte_count * 8);
+        let shift = position & 7;
+
+        if shift == 0 {
+            let byte_pos = position / 8;
+            f(&self.slice[byte_pos..byte_pos + byte_count]);
+            return;
+        }
+
         let mut byte_left = byte_count;
         let mut read_pos = position / 8;
 
         if E::is_le() {
             while byte_left > USIZE_SIZE - 1 {
-                let raw = self.read_shifted_usize(read_pos, shift, false);
-                let bytes = if E::is_le() {
-                    raw.to_le_bytes()
-                } else {
-                    raw.to_be_bytes()
-                };
+                let raw = self.read_shifted_usize(read_pos, shift);
+                let bytes = raw.to_le_bytes();
                 let read_bytes = USIZE_SIZE - 1;
-                let usable_bytes = &bytes[0..read_bytes];
-                data.extend_from_slice(usable_bytes);
+                f(&bytes[0..read_bytes]);
 
                 read_pos += read_bytes;
                 byte_left -= read_bytes;
             }
 
-            let bytes = self.read_shifted_usize(read_pos, shift, true).to_le_bytes();
-            let usable_bytes = &bytes[0..byte_left];
-            data.extend_from_slice(usable_bytes);
+            let bytes = self.read_shifted_usize(read_pos, shift).to_le_bytes();
+            f(&bytes[0..byte_left]);
         } else {
             let mut pos = position;
             while byte_left > 0 {
-                data.push(self.read_int_unchecked::<u8>(pos, 8, true));
+                f(&[self.read_int_unchecked::<u8>(pos, 8)]);
                 byte_left -= 1;
                 pos += 8;
             }
         }
-
-        Cow::Owned(data)
     }
 
     /// Read a series of bytes from the buffer as string
@@ -630,15 +929,50 @@ where
         }
     }
 
+    /// Read a fixed length utf8 string, with configurable handling of null bytes
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the buffer
+    /// - [`ReadError::Utf8Error`]: the read bytes are not valid utf8
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, LittleEndian, NullPadding, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let buffer = BitReadBuffer::new(b"foo\0bar\0", LittleEndian);
+    /// assert_eq!(buffer.read_fixed_string(0, 8, NullPadding::TrimEnd)?, "foo\0bar");
+    /// assert_eq!(buffer.read_fixed_string(0, 8, NullPadding::FirstNull)?, "foo");
+    /// assert_eq!(buffer.read_fixed_string(0, 8, NullPadding::Keep)?, "foo\0bar\0");
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::Utf8Error`]: enum.ReadError.html#variant.Utf8Error
+    #[inline]
+    pub fn read_fixed_string(
+        &self,
+        position: usize,
+        byte_len: usize,
+        padding: NullPadding,
+    ) -> Result<Cow<'a, str>> {
+        padding.to_str(self.read_bytes(position, byte_len)?)
+    }
+
     #[inline]
     fn find_null_byte(&self, byte_index: usize) -> usize {
-        memchr::memchr(0, &self.slice[byte_index..])
+        find_null_byte(&self.slice[byte_index..])
             .map(|index| index + byte_index)
             .unwrap_or(self.slice.len()) // due to padding we always have 0 bytes at the end
     }
 
+    /// Read the bytes of a null terminated string, without the terminating null byte
     #[inline]
-    fn read_string_bytes(&self, position: usize) -> Result<Cow<'a, [u8]>> {
+    pub(crate) fn read_string_bytes(&self, position: usize) -> Result<Cow<'a, [u8]>> {
         let shift = position & 7;
         if shift == 0 {
             let byte_index = position / 8;
@@ -647,44 +981,56 @@ where
             ))
         } else {
             let mut acc = Vec::with_capacity(32);
-            if E::is_le() {
-                let mut byte_index = position / 8;
-                loop {
-                    // note: if less then a usize worth of data is left in the buffer, read_usize_bytes
-                    // will automatically pad with null bytes, triggering the loop termination
-                    // thus no separate logic for dealing with the end of the bytes is required
-                    //
-                    // This is safe because the final usize is filled with 0's, thus triggering the exit clause
-                    // before reading any out of bounds
-                    let shifted = unsafe { self.read_shifted_usize(byte_index, shift, true) };
-
-                    let has_null = contains_zero_byte_non_top(shifted);
-                    let bytes: [u8; USIZE_SIZE] = shifted.to_le_bytes();
-                    let usable_bytes = &bytes[0..USIZE_SIZE - 1];
-
-                    if has_null {
-                        for i in 0..USIZE_SIZE - 1 {
-                            if usable_bytes[i] == 0 {
-                                acc.extend_from_slice(&usable_bytes[0..i]);
-                                return Ok(Cow::Owned(acc));
-                            }
+            self.extend_string_bytes(position, &mut acc)?;
+            Ok(Cow::Owned(acc))
+        }
+    }
+
+    /// Read the bytes of a null terminated string, without the terminating null byte, appending them to `out`
+    #[inline]
+    pub(crate) fn extend_string_bytes(&self, position: usize, out: &mut Vec<u8>) -> Result<()> {
+        let shift = position & 7;
+        if shift == 0 {
+            let byte_index = position / 8;
+            out.extend_from_slice(&self.slice[byte_index..self.find_null_byte(byte_index)]);
+            Ok(())
+        } else if E::is_le() {
+            let mut byte_index = position / 8;
+            loop {
+                // note: if less then a usize worth of data is left in the buffer, read_usize_bytes
+                // will automatically pad with null bytes, triggering the loop termination
+                // thus no separate logic for dealing with the end of the bytes is required
+                //
+                // This is safe because the final usize is filled with 0's, thus triggering the exit clause
+                // before reading any out of bounds
+                let shifted = self.read_shifted_usize(byte_index, shift);
+
+                let has_null = contains_zero_byte_non_top(shifted);
+                let bytes: [u8; USIZE_SIZE] = shifted.to_le_bytes();
+                let usable_bytes = &bytes[0..USIZE_SIZE - 1];
+
+                if has_null {
+                    for i in 0..USIZE_SIZE - 1 {
+                        if usable_bytes[i] == 0 {
+                            out.extend_from_slice(&usable_bytes[0..i]);
+                            return Ok(());
                         }
                     }
+                }
 
-                    acc.extend_from_slice(&usable_bytes[0..USIZE_SIZE - 1]);
+                out.extend_from_slice(&usable_bytes[0..USIZE_SIZE - 1]);
 
-                    byte_index += USIZE_SIZE - 1;
-                }
-            } else {
-                let mut pos = position;
-                loop {
-                    let byte = self.read_int::<u8>(pos, 8)?;
-                    pos += 8;
-                    if byte == 0 {
-                        return Ok(Cow::Owned(acc));
-                    } else {
-                        acc.push(byte);
-                    }
+                byte_index += USIZE_SIZE - 1;
+            }
+        } else {
+            let mut pos = position;
+            loop {
+                let byte = self.read_int::<u8>(pos, 8)?;
+                pos += 8;
+                if byte == 0 {
+                    return Ok(());
+                } else {
+                    out.push(byte);
                 }
             }
         }
@@ -719,33 +1065,21 @@ where
     where
         T: Float + UncheckedPrimitiveFloat,
     {
-        let type_bit_size = size_of::<T>() * 8;
-        if position + type_bit_size + USIZE_BIT_SIZE > self.bit_len() {
-            if position + type_bit_size > self.bit_len() {
-                if position > self.bit_len() {
-                    return Err(BitError::IndexOutOfBounds {
-                        pos: position,
-                        size: self.bit_len(),
-                    });
-                } else {
-                    return Err(BitError::NotEnoughData {
-                        requested: size_of::<T>() * 8,
-                        bits_left: self.bit_len() - position,
-                    });
-                }
-            }
-            Ok(unsafe { self.read_float_unchecked(position, true) })
-        } else {
-            Ok(unsafe { self.read_float_unchecked(position, false) })
-        }
+        self.check_bits(position, size_of::<T>() * 8)?;
+        Ok(unsafe { self.read_float_unchecked(position) })
     }
 
-    #[doc(hidden)]
+    /// Read a float without checking the bounds of the buffer
+    ///
+    /// # Safety
+    ///
+    /// `position` plus the size of the float can't be larger than the bit length of the buffer
     #[inline]
-    pub unsafe fn read_float_unchecked<T>(&self, position: usize, end: bool) -> T
+    pub(crate) unsafe fn read_float_unchecked<T>(&self, position: usize) -> T
     where
         T: Float + UncheckedPrimitiveFloat,
     {
+        self.debug_assert_in_bounds(position, size_of::<T>() * 8);
         if position & 7 == 0 {
             let byte_pos = position / 8;
             let bytes = self.slice[byte_pos..byte_pos + size_of::<T>()]
@@ -753,8 +1087,70 @@ where
                 .unwrap();
             T::from_bytes::<E>(bytes)
         } else {
-            let int = self.read_int_unchecked(position, size_of::<T>() * 8, end);
-            T::from_int(int)
+            let bits = self.read_unaligned_float_bits::<T>(position);
+            T::from_int(if swaps_bytes::<E>() {
+                bits.swap_bytes()
+            } else {
+                bits
+            })
+        }
+    }
+
+    /// Read the bits of a float that doesn't start at a byte boundary
+    ///
+    /// Instead of going through the generic integer path, which has to split reads that don't fit
+    /// in a usize, the `size_of::<T>() + 1` bytes containing the float are loaded into a single
+    /// `u128` and shifted into place.
+    #[inline]
+    unsafe fn read_unaligned_float_bits<T>(&self, position: usize) -> T::INT
+    where
+        T: UncheckedPrimitiveFloat,
+    {
+        const WINDOW_BITS: usize = u128::BITS as usize;
+        let type_bit_size = size_of::<T>() * 8;
+        let byte_index = position / 8;
+        let bit_offset = position & 7;
+        let byte_count = size_of::<T>() + 1;
+
+        // an unaligned float always spans one more byte than the size of the float, since the caller
+        // made sure that `position + type_bit_size <= bit_len`, these bytes are all within the slice
+        let mut bytes = [0; WINDOW_BITS / 8];
+        bytes[0..byte_count].copy_from_slice(
+            self.slice
+                .get_unchecked(byte_index..byte_index + byte_count),
+        );
+
+        let window = if E::is_le() {
+            u128::from_le_bytes(bytes) >> bit_offset
+        } else {
+            u128::from_be_bytes(bytes) >> (WINDOW_BITS - type_bit_size - bit_offset)
+        };
+        let mask = u128::MAX >> (WINDOW_BITS - type_bit_size);
+        T::INT::from_u128_unchecked(window & mask)
+    }
+
+    /// Create a buffer from the bits packed by the `bits!` macro
+    #[doc(hidden)]
+    pub fn from_bit_literal(
+        msb_first: &'static [u8],
+        lsb_first: &'static [u8],
+        bit_len: usize,
+    ) -> BitReadBuffer<'static, E> {
+        let bytes = if E::is_le() { lsb_first } else { msb_first };
+        let mut buffer = BitReadBuffer::new(bytes, E::endianness());
+        buffer.bit_len = bit_len;
+        buffer
+    }
+
+    /// The same data, read with a different endianness
+    pub(crate) fn cast_endianness<E2: Endianness>(&self) -> BitReadBuffer<'a, E2> {
+        BitReadBuffer {
+            bytes: self.bytes.clone(),
+            bit_len: self.bit_len,
+            endianness: PhantomData,
+            slice: self.slice,
+            tail: self.tail,
+            watermark: self.watermark.clone(),
         }
     }
 
@@ -771,6 +1167,8 @@ where
             bit_len,
             endianness: PhantomData,
             slice: self.slice,
+            tail: self.tail,
+            watermark: self.watermark.clone(),
         })
     }
 
@@ -784,8 +1182,17 @@ where
         }
 
         self.bit_len = bit_len;
+        self.watermark.truncate(bit_len);
         Ok(())
     }
+
+    /// Copy of the buffer that is tracked as the same buffer, instead of a clone of it
+    pub(crate) fn share(&self) -> Self {
+        BitReadBuffer {
+            watermark: self.watermark.clone(),
+            ..self.clone()
+        }
+    }
 }
 
 impl<'a, E: Endianness> From<&'a [u8]> for BitReadBuffer<'a, E> {
@@ -800,6 +1207,55 @@ impl<'a, E: Endianness> From<Vec<u8>> for BitReadBuffer<'a, E> {
     }
 }
 
+/// Create an owned buffer containing the bits of the iterator
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitReadBuffer, LittleEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let buffer: BitReadBuffer<LittleEndian> = [true, false, true].into_iter().collect();
+/// assert_eq!(3, buffer.bit_len());
+/// assert_eq!(5, buffer.read_int::<u8>(0, 3)?);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+impl<E: Endianness> FromIterator<bool> for BitReadBuffer<'static, E> {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.extend(iter);
+        let bit_len = stream.bit_len();
+        let mut buffer = BitReadBuffer::new_owned(data, E::endianness());
+        // the buffer holds at least `bit_len` bits
+        buffer.bit_len = bit_len;
+        buffer
+    }
+}
+
+/// Access a single bit of the buffer
+///
+/// # Panics
+///
+/// Panics if the position is outside the bounds of the buffer, use [`BitReadBuffer::read_bool`] for a fallible alternative.
+impl<E: Endianness> Index<usize> for BitReadBuffer<'_, E> {
+    type Output = bool;
+
+    fn index(&self, position: usize) -> &bool {
+        match self.read_bool(position) {
+            Ok(true) => &true,
+            Ok(false) => &false,
+            Err(_) => panic!(
+                "bit index {} out of bounds for a buffer of {} bits",
+                position,
+                self.bit_len()
+            ),
+        }
+    }
+}
+
 impl<'a, E: Endianness> Clone for BitReadBuffer<'a, E> {
     fn clone(&self) -> Self {
         BitReadBuffer {
@@ -807,18 +1263,60 @@ impl<'a, E: Endianness> Clone for BitReadBuffer<'a, E> {
             bit_len: self.bit_len(),
             endianness: PhantomData,
             slice: self.slice,
+            tail: self.tail,
+            watermark: self.watermark.child(),
+        }
+    }
+}
+
+/// The number of bits shown by the non-alternate `Debug` output of buffers and streams
+pub(crate) const DEBUG_WINDOW: usize = 32;
+
+/// `Debug` helper that formats a range of bits from a buffer as `0`s and `1`s, grouped per byte
+///
+/// Only the first [`DEBUG_WINDOW`] bits are shown unless the alternate flag is set.
+pub(crate) struct DebugBits<'b, 'a, E: Endianness> {
+    pub(crate) buffer: &'b BitReadBuffer<'a, E>,
+    pub(crate) start: usize,
+    pub(crate) len: usize,
+}
+
+impl<E: Endianness> Debug for DebugBits<'_, '_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shown = if f.alternate() {
+            self.len
+        } else {
+            min(self.len, DEBUG_WINDOW)
+        };
+        for index in 0..shown {
+            if index > 0 && index % 8 == 0 {
+                f.write_str(" ")?;
+            }
+            let bit = unsafe { self.buffer.read_bool_unchecked(self.start + index) };
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        if shown < self.len {
+            f.write_str(" ...")?;
         }
+        Ok(())
     }
 }
 
+/// Shows the length, endianness and the first 32 bits of the buffer, the alternate format (`{:#?}`) shows all bits
 impl<E: Endianness> Debug for BitReadBuffer<'_, E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "BitBuffer {{ bit_len: {}, endianness: {} }}",
-            self.bit_len(),
-            E::as_string()
-        )
+        f.debug_struct("BitReadBuffer")
+            .field("bit_len", &self.bit_len())
+            .field("endianness", &format_args!("{}", E::as_string()))
+            .field(
+                "bits",
+                &DebugBits {
+                    buffer: self,
+                    start: 0,
+                    len: self.bit_len(),
+                },
+            )
+            .finish()
     }
 }
 
@@ -842,6 +1340,26 @@ impl<'a, E: Endianness> PartialEq for BitReadBuffer<'a, E> {
     }
 }
 
+/// Find the index of the first null byte
+#[inline]
+pub(crate) fn find_null_byte(bytes: &[u8]) -> Option<usize> {
+    find_byte(0, bytes)
+}
+
+/// Find the index of the first `needle` byte
+#[cfg(feature = "memchr")]
+#[inline]
+fn find_byte(needle: u8, bytes: &[u8]) -> Option<usize> {
+    memchr::memchr(needle, bytes)
+}
+
+/// Find the index of the first `needle` byte
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find_byte(needle: u8, bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|byte| *byte == needle)
+}
+
 /// Return `true` if `x` contains any zero byte except for the topmost byte.
 ///
 /// From *Matters Computational*, J. Arndt
@@ -1,15 +1,121 @@
 use std::mem::size_of;
 use std::ops::BitOrAssign;
 
-use num_traits::{Float, PrimInt, WrappingSub};
+use crate::num_traits::{AsPrimitive, Float, PrimInt, WrappingSub};
 
-use crate::endianness::Endianness;
+use crate::endianness::{BigEndian, Endianness, LittleEndian};
 use crate::num_traits::{IsSigned, UncheckedPrimitiveFloat, UncheckedPrimitiveInt};
-use crate::readbuffer::Data;
-use crate::BitReadBuffer;
-use crate::{BitError, BitRead, BitReadSized, Result};
+use crate::readbuffer::{find_null_byte, Data, DebugBits};
+use crate::size_unit::element_size;
+use crate::varint::zigzag_decode;
+use crate::{BitError, BitRead, BitReadColumns, BitReadSized, HasElementCount, IntTuple, Result};
+use crate::{BitReadBuffer, NullPadding};
 use std::borrow::Cow;
+#[cfg(any(feature = "budget", feature = "progress"))]
+use std::cell::Cell;
+#[cfg(any(feature = "coverage", feature = "stats", feature = "progress"))]
+use std::cell::RefCell;
 use std::cmp::min;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+#[cfg(feature = "progress")]
+use std::ops::ControlFlow;
+use std::rc::Rc;
+#[cfg(feature = "cancel")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "cancel")]
+use std::sync::Arc;
+
+#[cfg(feature = "coverage")]
+use crate::coverage::ReadCoverage;
+#[cfg(feature = "stats")]
+use crate::stats::ReadStats;
+#[cfg(feature = "coverage")]
+use std::ops::Range;
+
+/// The kind of data consumed by a read, used for the read statistics
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ReadKind {
+    Bool,
+    Int,
+    Float,
+    Bytes,
+    String,
+    Other,
+}
+
+/// Iterator over the remaining bytes of a stream, see [`BitReadStream::bytes_iter`]
+pub struct BytesIter<'s, 'a, E: Endianness> {
+    stream: &'s mut BitReadStream<'a, E>,
+}
+
+impl<E: Endianness> Iterator for BytesIter<'_, '_, E> {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        if self.stream.bits_left() < 8 {
+            return None;
+        }
+        self.stream.read_int(8).ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.stream.bits_left() / 8))
+    }
+}
+
+/// The number of elements read by bulk reads between checks of the cancellation flag
+pub(crate) const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// Progress callback of a stream, see [`BitReadStream::on_progress`]
+#[cfg(feature = "progress")]
+struct ReadProgress {
+    interval: usize,
+    /// The position in the buffer at which the callback is next invoked
+    next: Cell<usize>,
+    cancelled: Cell<bool>,
+    callback: RefCell<Box<dyn FnMut(usize) -> ControlFlow<()>>>,
+}
+
+#[cfg(feature = "progress")]
+impl ReadProgress {
+    #[cold]
+    fn report(&self, pos: usize) {
+        self.next.set(
+            (pos / self.interval)
+                .saturating_add(1)
+                .saturating_mul(self.interval),
+        );
+        if (self.callback.borrow_mut())(pos).is_break() {
+            self.cancelled.set(true);
+        }
+    }
+}
+
+/// Proof that a stream has been checked to contain enough data for a read
+///
+/// This is returned by [`BitReadStream::check_read`] and required by the unchecked read methods
+/// used by the derived implementations, it can't be constructed in any other way.
+///
+/// The unchecked reads are only valid for the number of bits that were checked, reading past that
+/// or using the proof with a stream that doesn't contain the checked bits panics.
+#[derive(Debug, Clone, Copy)]
+pub struct CheckedRead {
+    /// Position in the buffer up to which reads have been checked
+    limit: usize,
+}
+
+/// A tagged position in a stream, see [`BitReadStream::mark`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    /// The marked position in bits, relative to the start of the stream
+    pub pos: usize,
+    /// The tag of the marker
+    pub tag: &'static str,
+}
 
 /// Stream that provides an easy way to iterate trough a [`BitBuffer`]
 ///
@@ -27,7 +133,6 @@ use std::cmp::min;
 /// ```
 ///
 /// [`BitBuffer`]: struct.BitBuffer.html
-#[derive(Debug)]
 pub struct BitReadStream<'a, E>
 where
     E: Endianness,
@@ -35,6 +140,21 @@ where
     buffer: BitReadBuffer<'a, E>,
     start_pos: usize,
     pos: usize,
+    #[cfg(feature = "coverage")]
+    coverage: Option<Rc<RefCell<ReadCoverage>>>,
+    #[cfg(feature = "stats")]
+    stats: Option<Rc<RefCell<ReadStats>>>,
+    /// Markers sorted by their absolute position in the buffer
+    markers: Option<Rc<Vec<Marker>>>,
+    /// Number of bits that can still be read, shared with all streams derived from this one
+    #[cfg(feature = "budget")]
+    budget: Option<Rc<Cell<usize>>>,
+    /// Progress callback, shared with all streams derived from this one
+    #[cfg(feature = "progress")]
+    progress: Option<Rc<ReadProgress>>,
+    /// Flag to cancel reading, shared with all streams derived from this one
+    #[cfg(feature = "cancel")]
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl<'a, E> BitReadStream<'a, E>
@@ -62,7 +182,79 @@ where
             start_pos: 0,
             pos: 0,
             buffer,
+            #[cfg(feature = "coverage")]
+            coverage: None,
+            #[cfg(feature = "stats")]
+            stats: None,
+            markers: None,
+            #[cfg(feature = "budget")]
+            budget: None,
+            #[cfg(feature = "progress")]
+            progress: None,
+            #[cfg(feature = "cancel")]
+            cancel: None,
+        }
+    }
+
+    /// Advance the stream after reading `count` bits
+    #[inline(always)]
+    #[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+    fn consume(&mut self, count: usize, kind: ReadKind) {
+        #[cfg(feature = "coverage")]
+        if let Some(coverage) = &self.coverage {
+            coverage.borrow_mut().mark(self.pos, count);
+        }
+        #[cfg(feature = "stats")]
+        if let Some(stats) = &self.stats {
+            stats.borrow_mut().record(kind, count);
+        }
+        #[cfg(feature = "budget")]
+        if let Some(budget) = &self.budget {
+            budget.set(budget.get().saturating_sub(count));
+        }
+        self.pos += count;
+        self.report_progress();
+    }
+
+    /// Invoke the progress callback if the stream advanced past the next reporting interval
+    #[inline(always)]
+    fn report_progress(&self) {
+        #[cfg(feature = "progress")]
+        if let Some(progress) = &self.progress {
+            if self.pos >= progress.next.get() {
+                progress.report(self.pos);
+            }
+        }
+    }
+
+    /// Ensure that the read budget allows reading another `count` bits
+    #[inline(always)]
+    #[cfg_attr(not(feature = "budget"), allow(unused_variables))]
+    fn check_budget(&self, count: usize) -> Result<()> {
+        self.buffer.watermark.check(self.pos.saturating_add(count));
+        self.check_cancelled()?;
+        #[cfg(feature = "budget")]
+        if let Some(budget) = &self.budget {
+            if budget.get() < count {
+                return Err(BitError::ReadBudgetExceeded {
+                    requested: count,
+                    remaining: budget.get(),
+                });
+            }
         }
+        Ok(())
+    }
+
+    /// Limit the total number of bits that can be read from this stream and all streams derived from it
+    #[cfg(feature = "budget")]
+    pub(crate) fn set_budget(&mut self, max_bits: usize) {
+        self.budget = Some(Rc::new(Cell::new(max_bits)));
+    }
+
+    /// The number of bits that can still be read, if the stream has a read budget
+    #[cfg(feature = "budget")]
+    pub(crate) fn remaining_budget(&self) -> Option<usize> {
+        self.budget.as_ref().map(|budget| budget.get())
     }
 
     /// Read a single bit from the stream as boolean
@@ -94,18 +286,20 @@ where
     /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
     #[inline]
     pub fn read_bool(&mut self) -> Result<bool> {
+        self.check_budget(1)?;
         let result = self.buffer.read_bool(self.pos);
         if result.is_ok() {
-            self.pos += 1;
+            self.consume(1, ReadKind::Bool);
         }
         result
     }
 
     #[doc(hidden)]
     #[inline]
-    pub unsafe fn read_bool_unchecked(&mut self) -> bool {
+    pub unsafe fn read_bool_unchecked(&mut self, checked: CheckedRead) -> bool {
+        self.assert_checked(checked, 1);
         let result = self.buffer.read_bool_unchecked(self.pos);
-        self.pos += 1;
+        self.consume(1, ReadKind::Bool);
         result
     }
 
@@ -143,24 +337,145 @@ where
     where
         T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + WrappingSub,
     {
+        self.check_budget(count)?;
         let result = self.buffer.read_int(self.pos, count);
         if result.is_ok() {
-            self.pos += count;
+            self.consume(count, ReadKind::Int);
         }
         result
     }
 
+    /// Read an enum discriminant of `discriminant_bits` bits followed by the matching variant
+    ///
+    /// `read_variant` is called with the discriminant to read the rest of the variant and returns `None`
+    /// if the discriminant doesn't match any variant, in which case the read fails with
+    /// [`ReadError::UnmatchedDiscriminant`] containing the position and width of the discriminant.
+    ///
+    /// This is used by the derived `BitRead` implementations for enums and can be used to recover from
+    /// unknown variants: the stream is left positioned after the discriminant and anything `read_variant` read,
+    /// so the caller can skip over a payload of known size and continue reading.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::TooManyBits`]: to many bits requested for the discriminant type
+    /// - [`ReadError::UnmatchedDiscriminant`]: `read_variant` didn't match the discriminant
+    /// - any error returned by `read_variant`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// // discriminant 3 is unknown, followed by an 8 bit payload and a known variant
+    /// let bytes = vec![0b1111_1111, 0b1000_0100];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    ///
+    /// let read = |stream: &mut BitReadStream<LittleEndian>| {
+    ///     stream.read_enum_raw(2, "Message", |stream, discriminant: u8| match discriminant {
+    ///         1 => Ok(Some(stream.read_int::<u8>(4)?)),
+    ///         _ => Ok(None),
+    ///     })
+    /// };
+    ///
+    /// match read(&mut stream) {
+    ///     Err(BitError::UnmatchedDiscriminant { pos, discriminant_bits, .. }) => {
+    ///         // every variant has an 8 bit payload, skip it and read the next message
+    ///         stream.set_pos(pos + discriminant_bits + 8)?;
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(8, read(&mut stream)?);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::TooManyBits`]: enum.ReadError.html#variant.TooManyBits
+    /// [`ReadError::UnmatchedDiscriminant`]: enum.ReadError.html#variant.UnmatchedDiscriminant
+    pub fn read_enum_raw<T, V, F>(
+        &mut self,
+        discriminant_bits: usize,
+        enum_name: &'static str,
+        read_variant: F,
+    ) -> Result<V>
+    where
+        T: PrimInt
+            + BitOrAssign
+            + IsSigned
+            + UncheckedPrimitiveInt
+            + WrappingSub
+            + AsPrimitive<usize>,
+        F: FnOnce(&mut Self, T) -> Result<Option<V>>,
+    {
+        let pos = self.pos();
+        let discriminant: T = self.read_int(discriminant_bits)?;
+        read_variant(self, discriminant)?.ok_or_else(|| BitError::UnmatchedDiscriminant {
+            discriminant: discriminant.as_(),
+            enum_name,
+            pos,
+            discriminant_bits,
+        })
+    }
+
     #[doc(hidden)]
     #[inline]
-    pub unsafe fn read_int_unchecked<T>(&mut self, count: usize, end: bool) -> T
+    pub unsafe fn read_int_unchecked<T>(&mut self, count: usize, checked: CheckedRead) -> T
     where
         T: PrimInt + BitOrAssign + IsSigned + UncheckedPrimitiveInt + WrappingSub,
     {
-        let result = self.buffer.read_int_unchecked(self.pos, count, end);
-        self.pos += count;
+        self.assert_checked(checked, count);
+        let result = self.buffer.read_int_unchecked(self.pos, count);
+        self.consume(count, ReadKind::Int);
         result
     }
 
+    /// Read multiple integers of different widths with a single bounds check
+    ///
+    /// Gives the same result as calling [`read_int`](BitReadStream::read_int) for each integer,
+    /// but checks the bounds and updates the position of the stream only once, which makes a measurable
+    /// difference when parsing many small headers. Nothing is read if any of the integers can't be read.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::TooManyBits`]: one of the widths is larger than the number of bits in its integer type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0b1010_0000, 0b0000_0111];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    /// let (version, length, flags) = stream.read_multi::<(u8, u16, u8)>(&[3, 11, 2])?;
+    /// assert_eq!((version, length, flags), (0b101, 1, 0b11));
+    /// assert_eq!(stream.pos(), 16);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::TooManyBits`]: enum.ReadError.html#variant.TooManyBits
+    #[inline]
+    pub fn read_multi<T: IntTuple>(&mut self, counts: &T::Counts) -> Result<T> {
+        let count = T::total_bits(counts)?;
+        self.check_budget(count)?;
+        if count > self.bits_left() {
+            return Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bits_left(),
+            });
+        }
+        let result = unsafe { T::read_unchecked(&self.buffer, self.pos, counts) };
+        self.consume(count, ReadKind::Int);
+        Ok(result)
+    }
+
     /// Read a sequence of bits from the stream as float
     ///
     /// # Errors
@@ -193,22 +508,68 @@ where
         T: Float + UncheckedPrimitiveFloat,
     {
         let count = size_of::<T>() * 8;
+        self.check_budget(count)?;
         let result = self.buffer.read_float(self.pos);
         if result.is_ok() {
-            self.pos += count;
+            self.consume(count, ReadKind::Float);
         }
         result
     }
 
+    /// Read a float truncated to the first `count` bits of its IEEE 754 representation
+    ///
+    /// The bits are used as the sign, exponent and most significant mantissa bits of the float, with the remaining
+    /// mantissa bits set to zero. See [`BitWriteStream::write_float_sized`](crate::BitWriteStream::write_float_sized).
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::TooManyBits`]: `count` is larger than the number of bits in the float
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0x3f, 0xc0, 0x00];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    /// assert_eq!(stream.read_float_sized::<f32>(24)?, 1.5);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::TooManyBits`]: enum.ReadError.html#variant.TooManyBits
+    pub fn read_float_sized<T>(&mut self, count: usize) -> Result<T>
+    where
+        T: Float + UncheckedPrimitiveFloat,
+    {
+        let type_bit_size = size_of::<T>() * 8;
+        if count > type_bit_size {
+            return Err(BitError::TooManyBits {
+                requested: count,
+                max: type_bit_size,
+            });
+        }
+        if count == 0 {
+            return Ok(T::zero());
+        }
+        let bits: T::INT = self.read_int(count)?;
+        Ok(T::from_int(bits << (type_bit_size - count)))
+    }
+
     #[doc(hidden)]
     #[inline]
-    pub unsafe fn read_float_unchecked<T>(&mut self, end: bool) -> T
+    pub unsafe fn read_float_unchecked<T>(&mut self, checked: CheckedRead) -> T
     where
         T: Float + UncheckedPrimitiveFloat,
     {
         let count = size_of::<T>() * 8;
-        let result = self.buffer.read_float_unchecked(self.pos, end);
-        self.pos += count;
+        self.assert_checked(checked, count);
+        let result = self.buffer.read_float_unchecked(self.pos);
+        self.consume(count, ReadKind::Float);
         result
     }
 
@@ -241,23 +602,72 @@ where
     /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
     #[inline]
     pub fn read_bytes(&mut self, byte_count: usize) -> Result<Cow<'a, [u8]>> {
-        let count = byte_count * 8;
+        let count = byte_count.saturating_mul(8);
+        self.check_budget(count)?;
         let result = self.buffer.read_bytes(self.pos, byte_count);
         if result.is_ok() {
-            self.pos += count;
+            self.consume(count, ReadKind::Bytes);
         }
         result
     }
 
     #[doc(hidden)]
     #[inline]
-    pub unsafe fn read_bytes_unchecked(&mut self, byte_count: usize) -> Cow<'a, [u8]> {
+    pub unsafe fn read_bytes_unchecked(
+        &mut self,
+        byte_count: usize,
+        checked: CheckedRead,
+    ) -> Cow<'a, [u8]> {
         let count = byte_count * 8;
+        self.assert_checked(checked, count);
         let result = self.buffer.read_bytes_unchecked(self.pos, byte_count);
-        self.pos += count;
+        self.consume(count, ReadKind::Bytes);
         result
     }
 
+    /// Read the bytes up to the first `sentinel` byte
+    ///
+    /// The bytes are read from the current position, which doesn't need to be aligned.
+    /// The sentinel itself is not read, the stream is left at the start of the sentinel byte.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::SentinelNotFound`]: the sentinel doesn't occur in the remaining bytes of the stream
+    ///
+    /// The stream is not advanced if the sentinel is not found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = b"foo\nbar\n";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// assert_eq!(stream.read_until_byte(b'\n')?.as_ref(), b"foo");
+    /// assert_eq!(3 * 8, stream.pos());
+    /// stream.skip_bits(8)?;
+    /// assert_eq!(stream.read_until_byte(b'\n')?.as_ref(), b"bar");
+    /// assert!(matches!(
+    ///     stream.read_until_byte(b';'),
+    ///     Err(BitError::SentinelNotFound { sentinel: b';' })
+    /// ));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::SentinelNotFound`]: enum.ReadError.html#variant.SentinelNotFound
+    pub fn read_until_byte(&mut self, sentinel: u8) -> Result<Cow<'a, [u8]>> {
+        match self
+            .buffer
+            .find_sentinel(self.pos, self.bits_left() / 8, sentinel)?
+        {
+            Some(len) => self.read_bytes(len),
+            None => Err(BitError::SentinelNotFound { sentinel }),
+        }
+    }
+
     /// Read a series of bytes from the stream as utf8 string
     ///
     /// You can either read a fixed number of bytes, or a dynamic length null-terminated string
@@ -310,10 +720,13 @@ where
             .map_err(|mut err| {
                 // still advance the stream on malformed utf8
                 if let BitError::Utf8Error(_, len) = &mut err {
-                    self.pos += match byte_len {
-                        Some(len) => len * 8,
-                        None => min((*len + 1) * 8, max_length * 8),
-                    };
+                    self.consume(
+                        match byte_len {
+                            Some(len) => len * 8,
+                            None => min((*len + 1) * 8, max_length * 8),
+                        },
+                        ReadKind::String,
+                    );
 
                     *len = (*len).min(max_length);
                 }
@@ -336,18 +749,66 @@ where
                 }
                 acc.push(c);
             }
-            self.pos += acc.len() * 8;
+            self.check_budget(acc.len() * 8)?;
+            self.consume(acc.len() * 8, ReadKind::String);
             return Ok(Cow::Owned(acc));
         }
-        self.pos += read;
+        self.check_budget(read)?;
+        self.consume(read, ReadKind::String);
         Ok(result)
     }
 
-    /// Read a sequence of bits from the stream as a BitStream
+    /// Read the bytes of a string without validating them as utf8
+    ///
+    /// The bytes are read the same way as [`read_string`](BitReadStream::read_string), without the null terminator
+    /// or the trailing null bytes of a fixed length string.
+    pub(crate) fn read_raw_string(&mut self, byte_len: Option<usize>) -> Result<Cow<'a, [u8]>> {
+        match byte_len {
+            Some(len) => {
+                let count = len.saturating_mul(8);
+                self.check_budget(count)?;
+                let bytes = self.buffer.read_bytes(self.pos, len)?;
+                self.consume(count, ReadKind::String);
+                let trimmed_len = bytes.len() - bytes.iter().rev().take_while(|b| **b == 0).count();
+                Ok(match bytes {
+                    Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[..trimmed_len]),
+                    Cow::Owned(mut bytes) => {
+                        bytes.truncate(trimmed_len);
+                        Cow::Owned(bytes)
+                    }
+                })
+            }
+            None => {
+                let max_length = self.bits_left() / 8;
+                let mut bytes = self.buffer.read_string_bytes(self.pos)?;
+                // same as with `read_string`, the terminator might be past the end of a sub stream
+                let read = if bytes.len() < max_length {
+                    bytes.len() + 1
+                } else {
+                    match &mut bytes {
+                        Cow::Borrowed(borrowed) => *borrowed = &borrowed[..max_length],
+                        Cow::Owned(owned) => owned.truncate(max_length),
+                    }
+                    max_length
+                };
+                self.check_budget(read * 8)?;
+                self.consume(read * 8, ReadKind::String);
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Read a null terminated C string
+    ///
+    /// Unlike [`read_string(None)`](BitReadStream::read_string), the bytes aren't validated as utf8 and the
+    /// null terminator is required, reaching the end of the stream before it is an error.
+    /// The string is borrowed from the stream when it starts at a byte boundary.
     ///
     /// # Errors
     ///
-    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::UnterminatedString`]: no null terminator in the remaining bytes of the stream
+    ///
+    /// The stream is not advanced if no null terminator is found.
     ///
     /// # Examples
     ///
@@ -355,79 +816,107 @@ where
     /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// let mut bits = stream.read_bits(3)?;
-    /// assert_eq!(stream.pos(), 3);
-    /// assert_eq!(bits.pos(), 0);
-    /// assert_eq!(bits.bit_len(), 3);
-    /// assert_eq!(stream.read_int::<u8>(3)?, 0b110);
-    /// assert_eq!(bits.read_int::<u8>(3)?, 0b101);
-    /// assert_eq!(true, bits.read_int::<u8>(1).is_err());
+    /// let bytes = b"foo\0bar";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// assert_eq!(stream.read_c_str()?.to_bytes(), b"foo");
+    /// assert_eq!(4 * 8, stream.pos());
+    /// assert!(stream.read_c_str().is_err());
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
-    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
-    pub fn read_bits(&mut self, count: usize) -> Result<Self> {
-        let result = BitReadStream {
-            buffer: self.buffer.get_sub_buffer(self.pos + count)?,
-            start_pos: self.pos,
-            pos: self.pos,
+    /// [`ReadError::UnterminatedString`]: enum.ReadError.html#variant.UnterminatedString
+    pub fn read_c_str(&mut self) -> Result<Cow<'a, CStr>> {
+        let max_bytes = self.bits_left() / 8;
+        let len = match self.buffer.find_sentinel(self.pos, max_bytes, 0)? {
+            Some(len) => len,
+            None => return Err(BitError::UnterminatedString { max_bytes }),
         };
-        self.pos += count;
-        Ok(result)
+        self.check_budget((len + 1) * 8)?;
+        let bytes = self.buffer.read_bytes(self.pos, len + 1)?;
+        self.consume((len + 1) * 8, ReadKind::String);
+        // safety: the bytes end with the first null byte
+        Ok(unsafe {
+            match bytes {
+                Cow::Borrowed(bytes) => Cow::Borrowed(CStr::from_bytes_with_nul_unchecked(bytes)),
+                Cow::Owned(bytes) => Cow::Owned(CString::from_vec_with_nul_unchecked(bytes)),
+            }
+        })
     }
 
-    /// Skip a number of bits in the stream
+    /// Read a null terminated utf8 string, scanning at most `max_bytes` bytes for the terminator
+    ///
+    /// Unlike [`read_string(None)`](BitReadStream::read_string), which searches for the null terminator
+    /// until the end of the data, this stops after `max_bytes` bytes, including the terminator itself.
+    /// Reaching the end of the stream before `max_bytes` bytes ends the string the same as `read_string` does.
     ///
     /// # Errors
     ///
-    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream to skip
+    /// - [`ReadError::UnterminatedString`]: no null terminator in the first `max_bytes` bytes
+    /// - [`ReadError::Utf8Error`]: the read bytes are not valid utf8
+    ///
+    /// The stream is not advanced if no null terminator is found.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LittleEndian, Result};
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// stream.skip_bits(3)?;
-    /// assert_eq!(stream.pos(), 3);
-    /// assert_eq!(stream.read_int::<u8>(3)?, 0b110);
+    /// let bytes = b"foo\0barbaz\0";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// assert_eq!(stream.read_string_capped(4)?, "foo");
+    /// assert_eq!(4 * 8, stream.pos());
+    /// assert!(matches!(
+    ///     stream.read_string_capped(6),
+    ///     Err(BitError::UnterminatedString { max_bytes: 6 })
+    /// ));
+    /// assert_eq!(stream.read_string_capped(7)?, "barbaz");
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
-    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
-    pub fn skip_bits(&mut self, count: usize) -> Result<()> {
-        if count <= self.bits_left() {
-            self.pos += count;
-            Ok(())
-        } else {
-            Err(BitError::NotEnoughData {
-                requested: count,
-                bits_left: self.bits_left(),
-            })
+    /// [`ReadError::UnterminatedString`]: enum.ReadError.html#variant.UnterminatedString
+    /// [`ReadError::Utf8Error`]: enum.ReadError.html#variant.Utf8Error
+    pub fn read_string_capped(&mut self, max_bytes: usize) -> Result<Cow<'a, str>> {
+        let scan_bytes = min(max_bytes, self.bits_left() / 8);
+        let bytes = self.buffer.read_bytes(self.pos, scan_bytes)?;
+        let len = match find_null_byte(&bytes) {
+            Some(len) => len,
+            None if scan_bytes == max_bytes => {
+                return Err(BitError::UnterminatedString { max_bytes })
+            }
+            None => scan_bytes,
+        };
+
+        let read = min(len + 1, scan_bytes) * 8;
+        self.check_budget(read)?;
+        // still advance the stream on malformed utf8
+        self.consume(read, ReadKind::String);
+        match bytes {
+            Cow::Borrowed(bytes) => std::str::from_utf8(&bytes[0..len])
+                .map(Cow::Borrowed)
+                .map_err(|err| BitError::Utf8Error(err, len)),
+            Cow::Owned(mut bytes) => {
+                bytes.truncate(len);
+                Ok(Cow::Owned(String::from_utf8(bytes)?))
+            }
         }
     }
 
-    /// Align the stream on the next byte and returns the amount of bits read
+    /// Read a series of bytes from the stream as utf8 string into an existing `String`
+    ///
+    /// This behaves like [`read_string`](BitReadStream::read_string) but reuses the allocation of `out`
+    /// for unaligned reads, the previous contents of `out` are replaced.
     ///
     /// # Errors
     ///
-    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream to skip
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::Utf8Error`]: the read bytes are not valid utf8
+    ///
+    /// `out` is left empty when an error is returned.
     ///
     /// # Examples
     ///
@@ -435,38 +924,277 @@ where
     /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// stream.align()?;
-    /// assert_eq!(stream.pos(), 0);
-    ///
-    /// stream.skip_bits(3)?;
-    /// assert_eq!(stream.pos(), 3);
-    /// stream.align();
-    /// assert_eq!(stream.pos(), 8);
-    /// assert_eq!(stream.read_int::<u8>(4)?, 0b1010);
+    /// let bytes = b"foo\0bar\0\0";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// let mut string = String::with_capacity(8);
+    /// stream.read_string_into(&mut string, None)?;
+    /// assert_eq!(string, "foo");
+    /// stream.read_string_into(&mut string, Some(5))?;
+    /// assert_eq!(string, "bar");
+    /// assert_eq!(stream.pos(), 9 * 8);
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
     /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
-    pub fn align(&mut self) -> Result<usize> {
-        match self.pos % 8 {
-            0 => Ok(0),
-            n => self.skip_bits(8 - n).map(|_| 8 - n),
+    /// [`ReadError::Utf8Error`]: enum.ReadError.html#variant.Utf8Error
+    pub fn read_string_into(&mut self, out: &mut String, byte_len: Option<usize>) -> Result<()> {
+        let max_length = self.bits_left() / 8;
+        let mut bytes = std::mem::take(out).into_bytes();
+        bytes.clear();
+        let read_result = match byte_len {
+            Some(byte_len) => self.buffer.extend_bytes(self.pos, byte_len, &mut bytes),
+            None => self.buffer.extend_string_bytes(self.pos, &mut bytes),
+        }
+        .and_then(|_| {
+            self.check_budget(match byte_len {
+                Some(byte_len) => byte_len * 8,
+                None => min(bytes.len() + 1, max_length) * 8,
+            })
+        });
+        if let Err(err) = read_result {
+            bytes.clear();
+            *out = String::from_utf8(bytes).unwrap_or_default();
+            return Err(err);
         }
+
+        match String::from_utf8(bytes) {
+            Ok(string) => *out = string,
+            Err(err) => {
+                let utf8_error = err.utf8_error();
+                let mut bytes = err.into_bytes();
+                let len = bytes.len();
+                bytes.clear();
+                *out = String::from_utf8(bytes).unwrap_or_default();
+                // still advance the stream on malformed utf8
+                self.consume(
+                    match byte_len {
+                        Some(len) => len * 8,
+                        None => min((len + 1) * 8, max_length * 8),
+                    },
+                    ReadKind::String,
+                );
+                return Err(BitError::Utf8Error(utf8_error, len.min(max_length)));
+            }
+        }
+
+        match byte_len {
+            Some(byte_len) => {
+                out.truncate(out.trim_end_matches(char::from(0)).len());
+                self.consume(byte_len * 8, ReadKind::String);
+            }
+            None if (out.len() + 1) * 8 > self.bits_left() => {
+                // the string can be longer than the current stream, see `read_string`
+                let mut len = max_length;
+                while !out.is_char_boundary(len) {
+                    len -= 1;
+                }
+                out.truncate(len);
+                self.consume(len * 8, ReadKind::String);
+            }
+            None => self.consume((out.len() + 1) * 8, ReadKind::String),
+        }
+        Ok(())
     }
 
-    /// Set the position of the stream
+    /// Read a fixed length utf8 string into a caller provided buffer
+    ///
+    /// Reads exactly `buf.len()` bytes and returns them as string with any trailing null bytes removed.
     ///
     /// # Errors
     ///
-    /// - [`ReadError::IndexOutOfBounds`]: new position is outside the bounds of the stream
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::Utf8Error`]: the read bytes are not valid utf8
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = b"foo\0bar";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// let mut buf = [0; 4];
+    /// assert_eq!(stream.read_str_into(&mut buf)?, "foo");
+    /// assert_eq!(stream.read_str_into(&mut buf[0..3])?, "bar");
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::Utf8Error`]: enum.ReadError.html#variant.Utf8Error
+    pub fn read_str_into<'b>(&mut self, buf: &'b mut [u8]) -> Result<&'b str> {
+        self.check_budget(buf.len() * 8)?;
+        self.buffer.read_bytes_into(self.pos, buf)?;
+        // still advance the stream on malformed utf8
+        self.consume(buf.len() * 8, ReadKind::String);
+        let string = std::str::from_utf8(buf).map_err(|err| BitError::Utf8Error(err, buf.len()))?;
+        Ok(string.trim_end_matches(char::from(0)))
+    }
+
+    /// Read a fixed length utf8 string, with configurable handling of null bytes
+    ///
+    /// [`read_string`](BitReadStream::read_string) only removes trailing null bytes from fixed length strings,
+    /// this allows ending the string at the first null byte, as is common for C strings stored in a fixed size
+    /// field, or keeping all null bytes. The stream is always advanced by `byte_len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::Utf8Error`]: the read bytes are not valid utf8
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, NullPadding, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = b"foo\0\xffbar\0\0";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// // anything after the first null byte is ignored, even if it's not valid utf8
+    /// assert_eq!(stream.read_fixed_string(5, NullPadding::FirstNull)?, "foo");
+    /// assert_eq!(stream.read_fixed_string(5, NullPadding::Keep)?, "bar\0\0");
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::Utf8Error`]: enum.ReadError.html#variant.Utf8Error
+    #[inline]
+    pub fn read_fixed_string(
+        &mut self,
+        byte_len: usize,
+        padding: NullPadding,
+    ) -> Result<Cow<'a, str>> {
+        let read = byte_len.saturating_mul(8);
+        self.check_budget(read)?;
+        let result = self.buffer.read_fixed_string(self.pos, byte_len, padding);
+        // still advance the stream on malformed utf8
+        if matches!(result, Ok(_) | Err(BitError::Utf8Error(..))) {
+            self.consume(read, ReadKind::String);
+        }
+        result
+    }
+
+    /// Read a string that is absent when it's empty
+    ///
+    /// Many formats encode a missing string as an empty string, either a single null byte, a fixed length
+    /// string of only null bytes or a string with a length prefix of 0.
+    /// This reads the string like [`read_string`](BitReadStream::read_string) and returns `None` if the string is empty.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::Utf8Error`]: the read bytes are not valid utf8
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = b"\0foo\0\0\0";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// assert_eq!(stream.read_nullable_string(None)?, None);
+    /// assert_eq!(stream.read_nullable_string(None)?.as_deref(), Some("foo"));
+    /// assert_eq!(stream.read_nullable_string(Some(2))?, None);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::Utf8Error`]: enum.ReadError.html#variant.Utf8Error
+    #[inline]
+    pub fn read_nullable_string(
+        &mut self,
+        byte_len: Option<usize>,
+    ) -> Result<Option<Cow<'a, str>>> {
+        let string = self.read_string(byte_len)?;
+        Ok(if string.is_empty() {
+            None
+        } else {
+            Some(string)
+        })
+    }
+
+    /// Read a series of bytes from the stream as utf8 string, replacing invalid utf8 sequences
+    ///
+    /// This works the same as [`read_string`](BitReadStream::read_string), except that invalid
+    /// utf8 sequences are replaced with `U+FFFD REPLACEMENT CHARACTER` instead of returning an error.
+    /// The stream is advanced by the same number of bits as it would be for valid utf8.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0x48, 0x69, 0xff, 0x21, 0, 0x48, 0x69, 0xff, 0x21, 0, 0, 0];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// // null terminated
+    /// assert_eq!(stream.read_string_lossy(None)?, "Hi\u{FFFD}!");
+    /// assert_eq!(5 * 8, stream.pos());
+    /// // fixed length with null padding
+    /// assert_eq!(stream.read_string_lossy(Some(7))?, "Hi\u{FFFD}!");
+    /// assert_eq!(12 * 8, stream.pos());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    #[inline]
+    pub fn read_string_lossy(&mut self, byte_len: Option<usize>) -> Result<Cow<'a, str>> {
+        let (bytes, read) = match byte_len {
+            Some(len) => (self.buffer.read_bytes(self.pos, len)?, len * 8),
+            None => {
+                let max_length = self.bits_left() / 8;
+                let mut bytes = self.buffer.read_string_bytes(self.pos)?;
+                // the terminating null byte can be past the end of a sub stream
+                if bytes.len() > max_length {
+                    bytes = match bytes {
+                        Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[0..max_length]),
+                        Cow::Owned(mut bytes) => {
+                            bytes.truncate(max_length);
+                            Cow::Owned(bytes)
+                        }
+                    };
+                }
+                let read = min(bytes.len() + 1, max_length) * 8;
+                (bytes, read)
+            }
+        };
+        self.check_budget(read)?;
+        self.consume(read, ReadKind::String);
+
+        let string = match bytes {
+            Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+            Cow::Owned(bytes) => Cow::Owned(
+                String::from_utf8(bytes)
+                    .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()),
+            ),
+        };
+        Ok(match (byte_len, string) {
+            (None, string) => string,
+            (Some(_), Cow::Borrowed(string)) => Cow::Borrowed(string.trim_end_matches('\0')),
+            (Some(_), Cow::Owned(string)) => Cow::Owned(string.trim_end_matches('\0').to_string()),
+        })
+    }
+
+    /// Read a sequence of bits from the stream as a BitStream
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
     ///
     /// # Examples
     ///
@@ -480,27 +1208,1211 @@ where
     /// # ];
     /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
     /// # let mut stream = BitReadStream::new(buffer);
-    /// stream.set_pos(3)?;
+    /// let mut bits = stream.read_bits(3)?;
     /// assert_eq!(stream.pos(), 3);
+    /// assert_eq!(bits.pos(), 0);
+    /// assert_eq!(bits.bit_len(), 3);
     /// assert_eq!(stream.read_int::<u8>(3)?, 0b110);
+    /// assert_eq!(bits.read_int::<u8>(3)?, 0b101);
+    /// assert_eq!(true, bits.read_int::<u8>(1).is_err());
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
-    /// [`ReadError::IndexOutOfBounds`]: enum.ReadError.html#variant.IndexOutOfBounds
-    pub fn set_pos(&mut self, pos: usize) -> Result<()> {
-        if pos > self.bit_len() {
-            return Err(BitError::IndexOutOfBounds {
-                pos,
-                size: self.bit_len(),
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read_bits(&mut self, count: usize) -> Result<Self> {
+        if count > self.bits_left() {
+            return Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bits_left(),
+            });
+        }
+        let result = self.derive_substream(
+            self.buffer.get_sub_buffer(self.pos + count)?,
+            self.pos,
+            self.pos,
+        );
+        self.pos += count;
+        self.report_progress();
+        Ok(result)
+    }
+
+    /// Read a sequence of bits from the stream as a BitStream, guaranteeing that the data is borrowed
+    ///
+    /// Like [`read_bits`](BitReadStream::read_bits), but the read fails when the stream isn't at a byte
+    /// boundary of the underlying buffer. Because the returned stream then starts at a byte boundary,
+    /// reading bytes or strings from its start borrows the data from the buffer instead of copying it,
+    /// so code that relies on zero-copy reads fails loudly instead of silently becoming slower.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::NotAligned`]: the stream is not at a byte boundary, reading would require copying the data
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use std::borrow::Cow;
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = b"foobar";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// let mut bits = stream.read_bits_borrow(24)?;
+    /// assert!(matches!(bits.read_bytes(3)?, Cow::Borrowed(b"foo")));
+    ///
+    /// stream.skip_bits(1)?;
+    /// assert!(stream.read_bits_borrow(8).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::NotAligned`]: enum.ReadError.html#variant.NotAligned
+    pub fn read_bits_borrow(&mut self, count: usize) -> Result<Self> {
+        if self.pos % 8 != 0 {
+            return Err(BitError::NotAligned {
+                bit_offset: self.pos % 8,
+            });
+        }
+        self.read_bits(count)
+    }
+
+    /// Get a stream over the last `count` bits read from the stream
+    ///
+    /// The returned stream starts at the bit that was read `count` bits ago and ends at the current position,
+    /// which makes it easy to calculate a checksum over the data read so far or to log the raw bits of
+    /// a read that failed. The position of this stream is not changed.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: less than `count` bits have been read from the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let header: u16 = stream.read()?;
+    /// let mut raw = stream.last_bits(16)?;
+    /// assert_eq!(stream.pos(), 16);
+    /// assert_eq!(raw.bit_len(), 16);
+    /// assert_eq!(raw.read_bytes(2)?.as_ref(), &[0b1011_0101, 0b0110_1010]);
+    /// assert_eq!(true, stream.last_bits(17).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn last_bits(&self, count: usize) -> Result<Self> {
+        if count > self.pos() {
+            return Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.pos(),
+            });
+        }
+        Ok(self.derive_substream(
+            self.buffer.get_sub_buffer(self.pos)?,
+            self.pos - count,
+            self.pos - count,
+        ))
+    }
+
+    /// Read `count` bits as a list of booleans
+    ///
+    /// The bits are read a word at a time, which is faster than reading them one by one.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0b1010_0000];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    /// assert_eq!(stream.read_bitmap(4)?, [true, false, true, false]);
+    /// assert_eq!(stream.pos(), 4);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read_bitmap(&mut self, count: usize) -> Result<Vec<bool>> {
+        if count > self.bits_left() {
+            return Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bits_left(),
             });
         }
-        self.pos = pos + self.start_pos;
-        Ok(())
+        let mut bitmap = Vec::with_capacity(count);
+        let mut remaining = count;
+        while remaining > 0 {
+            let chunk = min(u64::BITS as usize, remaining);
+            let bits: u64 = self.read_int(chunk)?;
+            if E::is_le() {
+                // the first bit read is the least significant bit
+                bitmap.extend((0..chunk).map(|index| bits & (1 << index) != 0));
+            } else {
+                bitmap.extend((0..chunk).rev().map(|index| bits & (1 << index) != 0));
+            }
+            remaining -= chunk;
+        }
+        Ok(bitmap)
+    }
+
+    /// Count the number of consecutive bits equal to `bit` starting at the current position, without advancing the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0b0000_0111, 0b1111_1100];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    /// assert_eq!(stream.count_consecutive(false), 5);
+    /// assert_eq!(stream.count_consecutive(true), 0);
+    /// stream.skip_bits(5)?;
+    /// assert_eq!(stream.count_consecutive(true), 9);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn count_consecutive(&self, bit: bool) -> usize {
+        self.count_run(bit, self.bits_left())
+    }
+
+    /// Read a run of equal bits of at most `max_bits` bits, returning the value and length of the run
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: no bits left in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0b0000_0111, 0b1111_1100];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    /// assert_eq!(stream.read_run_length(16)?, (false, 5));
+    /// assert_eq!(stream.read_run_length(4)?, (true, 4));
+    /// assert_eq!(stream.read_run_length(16)?, (true, 5));
+    /// assert_eq!(stream.read_run_length(16)?, (false, 2));
+    /// assert!(stream.read_run_length(16).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read_run_length(&mut self, max_bits: usize) -> Result<(bool, usize)> {
+        let bit = self.buffer.read_bool(self.pos)?;
+        let length = self.count_run(bit, min(max_bits, self.bits_left()));
+        self.check_budget(length)?;
+        self.consume(length, ReadKind::Other);
+        Ok((bit, length))
+    }
+
+    /// Read an unsigned integer encoded as varint
+    ///
+    /// See [`BitWriteStream::write_varint`](crate::BitWriteStream::write_varint) for the encoding.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::ValueOutOfRange`]: the encoded value doesn't fit in a `u64`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [5, 0b1010_1100, 0b0000_0010];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// assert_eq!(stream.read_varint()?, 5);
+    /// assert_eq!(stream.read_varint()?, 300);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::ValueOutOfRange`]: enum.ReadError.html#variant.ValueOutOfRange
+    pub fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0;
+        for shift in (0..u64::BITS).step_by(7) {
+            let byte: u8 = self.read_int(8)?;
+            let group = (byte & 0x7f) as u64;
+            if group << shift >> shift != group {
+                return Err(BitError::ValueOutOfRange { type_name: "u64" });
+            }
+            value |= group << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BitError::ValueOutOfRange { type_name: "u64" })
+    }
+
+    /// Read `count` integers written with [`BitWriteStream::write_delta_seq`](crate::BitWriteStream::write_delta_seq)
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`ReadError::ValueOutOfRange`]: one of the encoded differences doesn't fit in a `u64`
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    /// [`ReadError::ValueOutOfRange`]: enum.ReadError.html#variant.ValueOutOfRange
+    pub fn read_delta_seq(&mut self, count: usize) -> Result<Vec<u64>> {
+        // every value takes at least 8 bits
+        let mut values = Vec::with_capacity(self.capacity_hint(count, Some(8)));
+        let mut previous = 0u64;
+        for _ in 0..count {
+            let delta = zigzag_decode(self.read_varint()?);
+            previous = previous.wrapping_add(delta as u64);
+            values.push(previous);
+        }
+        Ok(values)
+    }
+
+    /// Count the number of consecutive bits equal to `bit`, up to `max` bits
+    fn count_run(&self, bit: bool, max: usize) -> usize {
+        let mut count = 0;
+        while count < max {
+            let chunk = min(u64::BITS as usize, max - count);
+            // `max` is never larger than the number of bits left in the stream
+            let bits: u64 = self.buffer.read_int(self.pos + count, chunk).unwrap();
+            let run = if E::is_le() {
+                // the first bit read is the least significant bit
+                let bits = if bit { bits } else { !bits };
+                bits.trailing_ones() as usize
+            } else {
+                // the first bit read is the most significant of the `chunk` bits
+                let bits = bits << (u64::BITS as usize - chunk);
+                let bits = if bit { bits } else { !bits };
+                bits.leading_ones() as usize
+            };
+            if run < chunk {
+                return count + run;
+            }
+            count += chunk;
+        }
+        max
+    }
+
+    /// Skip a number of bits in the stream
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream to skip
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// stream.skip_bits(3)?;
+    /// assert_eq!(stream.pos(), 3);
+    /// assert_eq!(stream.read_int::<u8>(3)?, 0b110);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn skip_bits(&mut self, count: usize) -> Result<()> {
+        if count <= self.bits_left() {
+            self.pos += count;
+            self.report_progress();
+            Ok(())
+        } else {
+            Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bits_left(),
+            })
+        }
+    }
+
+    /// Skip all remaining bits in the stream and returns the amount of bits skipped
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// stream.skip_bits(3)?;
+    /// assert_eq!(stream.skip_to_end(), 61);
+    /// assert_eq!(stream.bits_left(), 0);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn skip_to_end(&mut self) -> usize {
+        let skipped = self.bits_left();
+        self.pos += skipped;
+        self.report_progress();
+        skipped
+    }
+
+    /// Read a section of `count` bits as a separate stream
+    ///
+    /// The stream passed to `body` only covers the next `count` bits. If `body` returns `Ok`,
+    /// this stream is advanced past the entire section, regardless of how many bits `body` read,
+    /// if `body` returns an error this stream is not advanced.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - any error returned by `body`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// // only read the first 3 bits of a 16 bit message, ignoring the rest
+    /// let header = stream.read_scoped(16, |message| message.read_int::<u8>(3))?;
+    /// assert_eq!(header, 0b101);
+    /// assert_eq!(stream.pos(), 16);
+    ///
+    /// // errors leave the stream untouched
+    /// assert!(stream.read_scoped(16, |message| message.read_int::<u32>(17)).is_err());
+    /// assert_eq!(stream.pos(), 16);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read_scoped<T, Err, F>(&mut self, count: usize, body: F) -> Result<T, Err>
+    where
+        Err: From<BitError>,
+        F: FnOnce(&mut BitReadStream<'a, E>) -> Result<T, Err>,
+    {
+        if count > self.bits_left() {
+            return Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bits_left(),
+            }
+            .into());
+        }
+        let mut scope = self.clone().read_bits(count)?;
+        let result = body(&mut scope)?;
+        self.pos += count;
+        self.report_progress();
+        Ok(result)
+    }
+
+    /// Read from the stream with a different endianness for the duration of `body`
+    ///
+    /// The stream passed to `body` reads the same data from the current position. If `body` returns `Ok`,
+    /// this stream is advanced by the number of bits read by `body`, if `body` returns an error this stream is not advanced.
+    ///
+    /// Since the bits in a byte are read in a different order for little and big endian, switching between them
+    /// requires the stream to be at a byte boundary both when switching and when `body` returns.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotAligned`]: switching between little and big endian at a position that isn't a byte boundary
+    /// - any error returned by `body`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BigEndian, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0x01, 0x02, 0x01, 0x02];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// assert_eq!(stream.read_int::<u16>(16)?, 0x0201);
+    /// let value = stream.with_endianness::<BigEndian, _, _, _>(|stream| stream.read_int::<u16>(16))?;
+    /// assert_eq!(value, 0x0102);
+    /// assert_eq!(stream.pos(), 32);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotAligned`]: enum.ReadError.html#variant.NotAligned
+    pub fn with_endianness<E2, T, Err, F>(&mut self, body: F) -> Result<T, Err>
+    where
+        E2: Endianness,
+        Err: From<BitError>,
+        F: FnOnce(&mut BitReadStream<'a, E2>) -> Result<T, Err>,
+    {
+        let switches_bit_order = E::is_le() != E2::is_le();
+        let require_alignment = |pos: usize| match pos % 8 {
+            bit_offset if bit_offset > 0 && switches_bit_order => {
+                Err(BitError::NotAligned { bit_offset })
+            }
+            _ => Ok(()),
+        };
+        require_alignment(self.pos)?;
+        let mut stream =
+            self.derive_substream(self.buffer.cast_endianness(), self.start_pos, self.pos);
+        let result = body(&mut stream)?;
+        require_alignment(stream.pos)?;
+        self.pos = stream.pos;
+        Ok(result)
+    }
+
+    /// Align the stream on the next byte and returns the amount of bits read
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream to skip
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// stream.align()?;
+    /// assert_eq!(stream.pos(), 0);
+    ///
+    /// stream.skip_bits(3)?;
+    /// assert_eq!(stream.pos(), 3);
+    /// stream.align();
+    /// assert_eq!(stream.pos(), 8);
+    /// assert_eq!(stream.read_int::<u8>(4)?, 0b1010);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn align(&mut self) -> Result<usize> {
+        match self.pos % 8 {
+            0 => Ok(0),
+            n => self.skip_bits(8 - n).map(|_| 8 - n),
+        }
+    }
+
+    /// Set the position of the stream
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::IndexOutOfBounds`]: new position is outside the bounds of the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// stream.set_pos(3)?;
+    /// assert_eq!(stream.pos(), 3);
+    /// assert_eq!(stream.read_int::<u8>(3)?, 0b110);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::IndexOutOfBounds`]: enum.ReadError.html#variant.IndexOutOfBounds
+    pub fn set_pos(&mut self, pos: usize) -> Result<()> {
+        if pos > self.bit_len() {
+            return Err(BitError::IndexOutOfBounds {
+                pos,
+                size: self.bit_len(),
+            });
+        }
+        self.pos = pos + self.start_pos;
+        Ok(())
+    }
+
+    /// Get the length of the stream in bits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// assert_eq!(stream.bit_len(), 64);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn bit_len(&self) -> usize {
+        self.buffer.bit_len() - self.start_pos
+    }
+
+    /// Get the current position in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// assert_eq!(stream.pos(), 0);
+    /// stream.skip_bits(5)?;
+    /// assert_eq!(stream.pos(), 5);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn pos(&self) -> usize {
+        self.pos - self.start_pos
+    }
+
+    /// Get the number of bits left in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// assert_eq!(stream.bits_left(), 64);
+    /// stream.skip_bits(5)?;
+    /// assert_eq!(stream.bits_left(), 59);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn bits_left(&self) -> usize {
+        self.bit_len() - self.pos()
+    }
+
+    /// The number of buffers sharing the owned data this stream reads from
+    ///
+    /// See [`BitReadBuffer::strong_count`] for details.
+    pub fn strong_count(&self) -> Option<usize> {
+        self.buffer.strong_count()
+    }
+
+    /// The number of bytes of owned data kept alive by this stream
+    ///
+    /// See [`BitReadBuffer::memory_usage`] for details.
+    pub fn memory_usage(&self) -> usize {
+        self.buffer.memory_usage()
+    }
+
+    /// The number of elements to pre-allocate when reading a collection of `count` elements
+    ///
+    /// When the elements have a fixed size, the capacity is limited to the number of elements that fit in the remaining
+    /// bits of the stream, otherwise it's limited to 128 elements. This allows pre-allocating the full collection for valid
+    /// data while preventing a malicious length from causing a large allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0; 1024];
+    /// let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// assert_eq!(stream.capacity_hint(1000, <u8 as BitRead<LittleEndian>>::bit_size()), 1000);
+    /// assert_eq!(stream.capacity_hint(usize::MAX, <u32 as BitRead<LittleEndian>>::bit_size()), 256);
+    /// assert_eq!(stream.capacity_hint(usize::MAX, <String as BitRead<LittleEndian>>::bit_size()), 128);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn capacity_hint(&self, count: usize, element_bit_size: Option<usize>) -> usize {
+        match element_bit_size {
+            Some(bit_size) if bit_size > 0 => min(count, self.bits_left() / bit_size),
+            _ => min(count, 128),
+        }
+    }
+
+    /// Get the bit at `position`, relative to the start of the stream, without changing the position of the stream
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::IndexOutOfBounds`]: the position is outside the bounds of the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0b1011_0000, 0b0100_0000];
+    /// let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    /// let mut stream = BitReadStream::new(buffer);
+    /// stream.skip_bits(4)?;
+    /// let sub_stream = stream.read_bits(8)?;
+    /// assert_eq!(sub_stream.get_bit(5)?, true);
+    /// assert_eq!(stream.get_bit(0)?, true);
+    /// assert!(sub_stream.get_bit(8).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::IndexOutOfBounds`]: enum.ReadError.html#variant.IndexOutOfBounds
+    pub fn get_bit(&self, position: usize) -> Result<bool> {
+        if position >= self.bit_len() {
+            return Err(BitError::IndexOutOfBounds {
+                pos: position,
+                size: self.bit_len(),
+            });
+        }
+        self.buffer.read_bool(self.start_pos + position)
+    }
+
+    /// Iterate over the remaining bits of the stream, without changing the position of the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0b1011_0000];
+    /// let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    /// let mut stream = BitReadStream::new(buffer);
+    /// stream.skip_bits(2)?;
+    /// let bits: Vec<bool> = stream.iter_bits().collect();
+    /// assert_eq!(bits, [true, true, false, false, false, false]);
+    /// assert_eq!(stream.pos(), 2);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn iter_bits(&self) -> impl DoubleEndedIterator<Item = bool> + ExactSizeIterator + '_ {
+        (self.pos..self.buffer.bit_len()).map(move |position| self.buffer[position])
+    }
+
+    /// Iterate over the remaining bytes of the stream, advancing the stream as the bytes are read
+    ///
+    /// The bytes don't have to be byte aligned, which allows byte oriented code to consume the stream one byte at a time
+    /// without reading the remainder of the stream into a buffer first.
+    ///
+    /// The iterator ends when less than 8 bits are left in the stream or if a read fails,
+    /// any remaining bits can still be read from the stream afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0b1000_0000, 0b1000_0001, 0b1111_1111];
+    /// let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    /// let mut stream = BitReadStream::new(buffer);
+    /// stream.skip_bits(1)?;
+    ///
+    /// let mut bytes = stream.bytes_iter();
+    /// assert_eq!(bytes.next(), Some(1));
+    /// assert_eq!(bytes.next(), Some(3));
+    /// assert_eq!(bytes.next(), None);
+    /// assert_eq!(stream.bits_left(), 7);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn bytes_iter(&mut self) -> BytesIter<'_, 'a, E> {
+        BytesIter { stream: self }
+    }
+
+    /// Read a value based on the provided type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let int: u8 = stream.read()?;
+    /// assert_eq!(int, 0b1011_0101);
+    /// let boolean: bool = stream.read()?;
+    /// assert_eq!(false, boolean);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// use bitbuffer::BitRead;
+    /// #
+    /// #[derive(BitRead, Debug, PartialEq)]
+    /// struct ComplexType {
+    ///     first: u8,
+    ///     #[size = 15]
+    ///     second: u16,
+    ///     third: bool,
+    /// }
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let data: ComplexType = stream.read()?;
+    /// assert_eq!(data, ComplexType {
+    ///     first: 0b1011_0101,
+    ///     second: 0b010_1100_0110_1010,
+    ///     third: true,
+    /// });
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn read<T: BitRead<'a, E>>(&mut self) -> Result<T> {
+        T::read(self)
+    }
+
+    /// Read a value, returning errors as a user defined error type
+    ///
+    /// Errors from derived implementations with a `#[bit_error = "MyError"]` attribute are returned as the
+    /// original `MyError`, other errors are converted using `From<BitError>`, see [`BitError::into_custom`].
+    ///
+    /// # Errors
+    ///
+    /// - any error returned while reading the value, converted into `Err`
+    #[inline]
+    pub fn read_with_error<T, Err>(&mut self) -> std::result::Result<T, Err>
+    where
+        T: BitRead<'a, E>,
+        Err: From<BitError> + std::error::Error + 'static,
+    {
+        T::read(self).map_err(BitError::into_custom)
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn read_unchecked<T: BitRead<'a, E>>(&mut self, checked: CheckedRead) -> Result<T> {
+        T::read_unchecked(self, checked)
+    }
+
+    /// Read a value based on the provided type and size
+    ///
+    /// The meaning of the size parameter differs depending on the type that is being read
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let int: u8 = stream.read_sized(7)?;
+    /// assert_eq!(int, 0b011_0101);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let data: Vec<u16> = stream.read_sized(3)?;
+    /// assert_eq!(data, vec![0b0110_1010_1011_0101, 0b1001_1001_1010_1100, 0b1001_1001_1001_1001]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn read_sized<T: BitReadSized<'a, E>>(&mut self, size: usize) -> Result<T> {
+        T::read(self, size)
+    }
+
+    /// Read an array or tuple where the size is applied to every element
+    ///
+    /// This is the same as [`read_sized`](BitReadStream::read_sized) for arrays and tuples
+    /// but makes it explicit that the size is per element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let data: [u8; 2] = stream.read_sized_each(4)?;
+    /// assert_eq!(data, [0b0101, 0b1011]);
+    /// assert_eq!(stream.pos(), 8);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn read_sized_each<T: BitReadSized<'a, E> + HasElementCount>(
+        &mut self,
+        size: usize,
+    ) -> Result<T> {
+        T::read(self, size)
+    }
+
+    /// Read an array or tuple where the size is divided evenly over all elements
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::UnevenSize`]: the size can't be divided evenly over the elements
+    /// - any error returned by reading the elements
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// # let bytes = vec![
+    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
+    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
+    /// # ];
+    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// # let mut stream = BitReadStream::new(buffer);
+    /// let data: (u8, u8) = stream.read_sized_total(8)?;
+    /// assert_eq!(data, (0b0101, 0b1011));
+    /// assert_eq!(stream.pos(), 8);
+    /// assert!(stream.read_sized_total::<[u8; 3]>(8).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::UnevenSize`]: enum.ReadError.html#variant.UnevenSize
+    #[inline]
+    pub fn read_sized_total<T: BitReadSized<'a, E> + HasElementCount>(
+        &mut self,
+        size: usize,
+    ) -> Result<T> {
+        T::read(self, element_size::<T>(size)?)
+    }
+
+    /// Read `count` map entries, each a key read with `key_size` followed by a value read with `value_size`
+    ///
+    /// Reading a `HashMap` sized only sets the number of entries, this allows reading keys and values
+    /// that require a size themselves. The meaning of the sizes depends on the key and value types,
+    /// the same as for [`read_sized`](BitReadStream::read_sized).
+    ///
+    /// When a key occurs multiple times, the last value is kept.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - any error returned by reading the keys or values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// # use std::collections::HashMap;
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = b"ab\x12\x36\x36\x44\x56";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, BigEndian));
+    /// // 2 entries of a 2 byte string key and a 12 bit integer value
+    /// let map: HashMap<String, u16> = stream.read_map_sized(2, 2, 12)?;
+    /// assert_eq!(map["ab"], 0x123);
+    /// assert_eq!(map["cd"], 0x456);
+    /// assert_eq!(stream.pos(), 56);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    pub fn read_map_sized<K, V>(
+        &mut self,
+        count: usize,
+        key_size: usize,
+        value_size: usize,
+    ) -> Result<HashMap<K, V>>
+    where
+        K: BitReadSized<'a, E> + Eq + Hash,
+        V: BitReadSized<'a, E>,
+    {
+        let entry_size =
+            K::bit_size_sized(key_size).and_then(|key| Some(key + V::bit_size_sized(value_size)?));
+        let mut map = HashMap::with_capacity(self.capacity_hint(count, entry_size));
+        for index in 0..count {
+            self.check_cancelled_periodic(index)?;
+            let key = self.read_sized(key_size)?;
+            let value = self.read_sized(value_size)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+
+    /// Read a value based on the provided type without advancing the stream
+    #[inline]
+    pub fn peek<T: BitRead<'a, E>>(&mut self) -> Result<T> {
+        let pos = self.pos;
+        let result = T::read(self);
+        self.pos = pos;
+        result
+    }
+
+    /// Read a value based on the provided type and size without advancing the stream
+    #[inline]
+    pub fn peek_sized<T: BitReadSized<'a, E>>(&mut self, size: usize) -> Result<T> {
+        let pos = self.pos;
+        let result = T::read(self, size);
+        self.pos = pos;
+        result
+    }
+
+    /// Read `count` records into a struct-of-arrays layout
+    ///
+    /// Every field of a record is appended to its own column, see [`BitReadColumns`] for the
+    /// supported column types.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    /// - Any error produced while reading a field
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![1, 10, 0, 2, 20, 0, 3, 30, 0];
+    /// let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// let mut stream = BitReadStream::new(buffer);
+    /// let (ids, values): (Vec<u8>, Vec<u16>) = stream.read_struct_of_arrays(3)?;
+    /// assert_eq!(vec![1, 2, 3], ids);
+    /// assert_eq!(vec![10, 20, 30], values);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    #[inline]
+    pub fn read_struct_of_arrays<T: BitReadColumns<'a, E> + Default>(
+        &mut self,
+        count: usize,
+    ) -> Result<T> {
+        let mut columns = T::default();
+        self.read_struct_of_arrays_into(count, &mut columns)?;
+        Ok(columns)
+    }
+
+    /// Read `count` records and append them to existing columns
+    ///
+    /// This allows re-using the allocations of the columns when decoding multiple tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![1, 10, 0, 2, 20, 0, 3, 30, 0];
+    /// let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    /// let mut stream = BitReadStream::new(buffer);
+    /// let mut columns: (Vec<u8>, Vec<u16>) = (Vec::new(), Vec::new());
+    /// stream.read_struct_of_arrays_into(1, &mut columns)?;
+    /// stream.read_struct_of_arrays_into(2, &mut columns)?;
+    /// assert_eq!(vec![1, 2, 3], columns.0);
+    /// assert_eq!(vec![10, 20, 30], columns.1);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn read_struct_of_arrays_into<T: BitReadColumns<'a, E>>(
+        &mut self,
+        count: usize,
+        columns: &mut T,
+    ) -> Result<()> {
+        match T::record_bit_size() {
+            Some(bit_size) => {
+                let checked = self.check_read(bit_size.saturating_mul(count))?;
+                columns.reserve(count);
+                for index in 0..count {
+                    self.check_cancelled_periodic(index)?;
+                    unsafe { columns.read_record_unchecked(self, checked) }?;
+                }
+            }
+            None => {
+                columns.reserve(self.capacity_hint(count, None));
+                for _ in 0..count {
+                    columns.read_record(self)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn read_sized_unchecked<T: BitReadSized<'a, E>>(
+        &mut self,
+        size: usize,
+        checked: CheckedRead,
+    ) -> Result<T> {
+        T::read_unchecked(self, size, checked)
+    }
+
+    /// Check if we can read a number of bits from the stream
+    ///
+    /// The returned [`CheckedRead`] allows reading up to `count` bits without further bounds checks.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`BitError::ReadBudgetExceeded`]: reading `count` bits would exceed the budget of a `LimitedReadStream`
+    pub fn check_read(&self, count: usize) -> Result<CheckedRead> {
+        self.check_budget(count)?;
+        if self.bits_left() < count {
+            Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: self.bits_left(),
+            })
+        } else {
+            Ok(CheckedRead {
+                limit: self.pos + count,
+            })
+        }
+    }
+
+    /// Ensure that an unchecked read of `count` bits is covered by `checked`
+    ///
+    /// Unlike most bounds assertions this is also done in release builds, since a proof created by another
+    /// stream could otherwise be used to read past the end of the buffer of this stream.
+    #[inline]
+    #[track_caller]
+    fn assert_checked(&self, checked: CheckedRead, count: usize) {
+        assert!(
+            checked.limit <= self.buffer.bit_len(),
+            "unchecked read of {} bits at {} past the end of the buffer of {} bits",
+            count,
+            self.pos,
+            self.buffer.bit_len()
+        );
+        assert!(
+            count <= checked.limit.saturating_sub(self.pos),
+            "unchecked read of {} bits at {} past the checked limit of {}",
+            count,
+            self.pos,
+            checked.limit
+        );
+    }
+
+    /// Check that a read of `count` bits stays within a region that has been checked before
+    #[inline]
+    pub(crate) fn check_within(&self, checked: CheckedRead, count: usize) -> Result<()> {
+        let bits_left = checked.limit.saturating_sub(self.pos);
+        if count > bits_left {
+            Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left,
+            })
+        } else {
+            Ok(())
+        }
     }
 
-    /// Get the length of the stream in bits
+    /// Proof for reading the rest of the stream, without checking the read budget
+    pub(crate) fn check_remaining(&self) -> CheckedRead {
+        CheckedRead {
+            limit: self.pos + self.bits_left(),
+        }
+    }
+
+    /// Start recording which bits are read from this stream
+    ///
+    /// Streams created from this stream using [`read_bits`](BitReadStream::read_bits) or by cloning
+    /// it share the record, reading the bits of the stream returned by `read_bits` marks them as read,
+    /// skipped bits are not marked as read.
+    ///
+    /// All positions reported by [`unread_ranges`](BitReadStream::unread_ranges) and
+    /// [`high_water_mark`](BitReadStream::high_water_mark) are relative to the position of this
+    /// stream when the tracking was started.
     ///
     /// # Examples
     ///
@@ -508,231 +2420,373 @@ where
     /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// assert_eq!(stream.bit_len(), 64);
+    /// let bytes = vec![1, 2, 3, 4];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// stream.track_coverage();
+    ///
+    /// stream.read_int::<u8>(8)?;
+    /// stream.skip_bits(4)?;
+    /// stream.read_int::<u8>(4)?;
+    /// assert_eq!(stream.high_water_mark(), Some(16));
+    /// assert_eq!(stream.unread_ranges(), Some(vec![8..12, 16..32]));
     /// #
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn bit_len(&self) -> usize {
-        self.buffer.bit_len() - self.start_pos
+    #[cfg(feature = "coverage")]
+    pub fn track_coverage(&mut self) {
+        self.coverage = Some(Rc::new(RefCell::new(ReadCoverage::new(
+            self.pos,
+            self.buffer.bit_len() - self.pos,
+        ))));
     }
 
-    /// Get the current position in the stream
+    /// The end of the furthest read from the stream, if coverage tracking is enabled
+    ///
+    /// See [`track_coverage`](BitReadStream::track_coverage)
+    #[cfg(feature = "coverage")]
+    pub fn high_water_mark(&self) -> Option<usize> {
+        self.coverage
+            .as_ref()
+            .map(|coverage| coverage.borrow().high_water_mark())
+    }
+
+    /// The ranges of bits that haven't been read yet, if coverage tracking is enabled
+    ///
+    /// See [`track_coverage`](BitReadStream::track_coverage)
+    #[cfg(feature = "coverage")]
+    pub fn unread_ranges(&self) -> Option<Vec<Range<usize>>> {
+        self.coverage
+            .as_ref()
+            .map(|coverage| coverage.borrow().unread_ranges())
+    }
+
+    /// Invoke `callback` every `interval` bits while reading the stream
+    ///
+    /// The callback receives the position in bits from the start of the underlying buffer and is invoked
+    /// once the stream is advanced past the next multiple of `interval` bits by a read or skip. A single
+    /// read or skip covering multiple intervals only invokes the callback once.
+    /// Streams created from this stream using [`read_bits`](BitReadStream::read_bits) or by cloning
+    /// it share the callback.
+    ///
+    /// Returning [`ControlFlow::Break`] from the callback cancels the parse, all further reads from
+    /// the stream and the streams sharing the callback fail with [`BitError::ReadCancelled`].
+    ///
+    /// Only available with the `progress` feature, so streams without a callback don't pay for tracking the progress.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use std::cell::Cell;
+    /// # use std::ops::ControlFlow;
+    /// # use std::rc::Rc;
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// assert_eq!(stream.pos(), 0);
-    /// stream.skip_bits(5)?;
-    /// assert_eq!(stream.pos(), 5);
+    /// let bytes = vec![0; 64];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    ///
+    /// let progress = Rc::new(Cell::new(0));
+    /// let reported = progress.clone();
+    /// stream.on_progress(128, move |pos| {
+    ///     reported.set(pos);
+    ///     if pos >= 256 {
+    ///         ControlFlow::Break(())
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// });
+    ///
+    /// stream.read_int::<u64>(64)?;
+    /// assert_eq!(0, progress.get());
+    /// stream.read_int::<u64>(64)?;
+    /// assert_eq!(128, progress.get());
+    /// stream.skip_bits(200)?;
+    /// assert_eq!(328, progress.get());
+    /// assert!(matches!(stream.read_bool(), Err(BitError::ReadCancelled { pos: 328 })));
     /// #
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn pos(&self) -> usize {
-        self.pos - self.start_pos
+    #[cfg(feature = "progress")]
+    pub fn on_progress<F>(&mut self, interval: usize, callback: F)
+    where
+        F: FnMut(usize) -> ControlFlow<()> + 'static,
+    {
+        let interval = interval.max(1);
+        self.progress = Some(Rc::new(ReadProgress {
+            interval,
+            next: Cell::new((self.pos / interval + 1).saturating_mul(interval)),
+            cancelled: Cell::new(false),
+            callback: RefCell::new(Box::new(callback)),
+        }));
     }
 
-    /// Get the number of bits left in the stream
+    /// Cancel reading from this stream once `flag` is set
+    ///
+    /// The flag can be set from another thread to abort a long-running parse, once set all further reads from
+    /// the stream and the streams created from it fail with [`BitError::ReadCancelled`].
+    /// Bulk reads of many elements, such as reading a `Vec`, check the flag periodically while reading.
+    ///
+    /// Only available with the `cancel` feature, so streams that can't be cancelled don't pay for checking the flag.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use std::sync::Arc;
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// assert_eq!(stream.bits_left(), 64);
-    /// stream.skip_bits(5)?;
-    /// assert_eq!(stream.bits_left(), 59);
+    /// let bytes = vec![0; 64];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// stream.set_cancel_flag(cancel.clone());
+    ///
+    /// stream.read_int::<u8>(8)?;
+    /// cancel.store(true, Ordering::Relaxed);
+    /// assert!(matches!(stream.read_sized::<Vec<u8>>(16), Err(BitError::ReadCancelled { pos: 8 })));
     /// #
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn bits_left(&self) -> usize {
-        self.bit_len() - self.pos()
+    #[cfg(feature = "cancel")]
+    pub fn set_cancel_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.cancel = Some(flag);
     }
 
-    /// Read a value based on the provided type
+    /// Check whether reading from the stream has been cancelled
     ///
-    /// # Examples
+    /// All checked reads do this check, implementations of [`BitRead`] that do a large number of unchecked reads
+    /// can use this to respond to cancellation while reading.
     ///
-    /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
-    /// #
-    /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// let int: u8 = stream.read()?;
-    /// assert_eq!(int, 0b1011_0101);
-    /// let boolean: bool = stream.read()?;
-    /// assert_eq!(false, boolean);
-    /// #
-    /// #     Ok(())
-    /// # }
-    /// ```
+    /// Without the `cancel` and `progress` features nothing can cancel reading, and this never fails.
     ///
-    /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
-    /// use bitbuffer::BitRead;
-    /// #
-    /// #[derive(BitRead, Debug, PartialEq)]
-    /// struct ComplexType {
-    ///     first: u8,
-    ///     #[size = 15]
-    ///     second: u16,
-    ///     third: bool,
-    /// }
-    /// #
-    /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// let data: ComplexType = stream.read()?;
-    /// assert_eq!(data, ComplexType {
-    ///     first: 0b1011_0101,
-    ///     second: 0b010_1100_0110_1010,
-    ///     third: true,
-    /// });
-    /// #
-    /// #     Ok(())
-    /// # }
-    /// ```
-    #[inline]
-    pub fn read<T: BitRead<'a, E>>(&mut self) -> Result<T> {
-        T::read(self)
+    /// # Errors
+    ///
+    /// - [`BitError::ReadCancelled`]: the cancellation flag was set or the progress callback requested cancellation
+    #[inline(always)]
+    pub fn check_cancelled(&self) -> Result<()> {
+        #[cfg(feature = "cancel")]
+        if let Some(flag) = &self.cancel {
+            if flag.load(Ordering::Relaxed) {
+                return Err(BitError::ReadCancelled { pos: self.pos() });
+            }
+        }
+        #[cfg(feature = "progress")]
+        if let Some(progress) = &self.progress {
+            if progress.cancelled.get() {
+                return Err(BitError::ReadCancelled { pos: self.pos() });
+            }
+        }
+        Ok(())
     }
 
-    #[doc(hidden)]
-    #[inline]
-    pub unsafe fn read_unchecked<T: BitRead<'a, E>>(&mut self, end: bool) -> Result<T> {
-        T::read_unchecked(self, end)
+    /// Check for cancellation once every [`CANCEL_CHECK_INTERVAL`] elements of a bulk read
+    #[inline(always)]
+    pub(crate) fn check_cancelled_periodic(&self, index: usize) -> Result<()> {
+        if index % CANCEL_CHECK_INTERVAL == 0 {
+            self.check_cancelled()
+        } else {
+            Ok(())
+        }
     }
 
-    /// Read a value based on the provided type and size
+    /// Start counting the reads done on this stream
     ///
-    /// The meaning of the size parameter differs depending on the type that is being read
+    /// Streams created from this stream using [`read_bits`](BitReadStream::read_bits) or by cloning
+    /// it share the statistics, the counts can be retrieved with [`read_stats`](BitReadStream::read_stats).
+    /// Skipped bits are not counted.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, ReadCount, Result};
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// let int: u8 = stream.read_sized(7)?;
-    /// assert_eq!(int, 0b011_0101);
+    /// let bytes = b"\x01\x02foo\0";
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    /// stream.track_stats();
+    ///
+    /// stream.read_int::<u8>(4)?;
+    /// stream.read_int::<u16>(12)?;
+    /// stream.read_string(None)?;
+    /// let stats = stream.read_stats().unwrap();
+    /// assert_eq!(stats.ints, ReadCount { calls: 2, bits: 16 });
+    /// assert_eq!(stats.strings, ReadCount { calls: 1, bits: 32 });
+    /// assert_eq!(stats.total().bits, 48);
     /// #
     /// #     Ok(())
     /// # }
     /// ```
+    #[cfg(feature = "stats")]
+    pub fn track_stats(&mut self) {
+        self.stats = Some(Rc::default());
+    }
+
+    /// The statistics of the reads done on this stream, if read statistics are enabled
+    ///
+    /// See [`track_stats`](BitReadStream::track_stats)
+    #[cfg(feature = "stats")]
+    pub fn read_stats(&self) -> Option<ReadStats> {
+        self.stats.as_ref().map(|stats| *stats.borrow())
+    }
+
+    /// Tag a position in the stream
+    ///
+    /// The markers crossed by a read can be retrieved with [`crossed_markers`](BitReadStream::crossed_markers),
+    /// which can be used to verify that reading a message didn't go past its declared end.
+    ///
+    /// Markers are copied into clones and sub-streams of the stream.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::IndexOutOfBounds`]: the position is outside the bounds of the stream
+    ///
+    /// # Examples
     ///
     /// ```
-    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use bitbuffer::{BitError, BitReadBuffer, BitReadStream, LittleEndian, Result};
     /// #
     /// # fn main() -> Result<()> {
-    /// # let bytes = vec![
-    /// #     0b1011_0101, 0b0110_1010, 0b1010_1100, 0b1001_1001,
-    /// #     0b1001_1001, 0b1001_1001, 0b1001_1001, 0b1110_0111
-    /// # ];
-    /// # let buffer = BitReadBuffer::new(&bytes, LittleEndian);
-    /// # let mut stream = BitReadStream::new(buffer);
-    /// let data: Vec<u16> = stream.read_sized(3)?;
-    /// assert_eq!(data, vec![0b0110_1010_1011_0101, 0b1001_1001_1010_1100, 0b1001_1001_1001_1001]);
+    /// let bytes = vec![1, 2, 3, 4];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// stream.mark(16, "message end")?;
+    ///
+    /// let start = stream.pos();
+    /// stream.read_int::<u8>(8)?;
+    /// stream.check_markers(start)?;
+    ///
+    /// stream.read_int::<u16>(16)?;
+    /// assert!(matches!(
+    ///     stream.check_markers(start),
+    ///     Err(BitError::MarkerCrossed { tag: "message end", pos: 16, .. })
+    /// ));
     /// #
     /// #     Ok(())
     /// # }
     /// ```
-    #[inline]
-    pub fn read_sized<T: BitReadSized<'a, E>>(&mut self, size: usize) -> Result<T> {
-        T::read(self, size)
+    ///
+    /// [`ReadError::IndexOutOfBounds`]: enum.ReadError.html#variant.IndexOutOfBounds
+    pub fn mark(&mut self, pos: usize, tag: &'static str) -> Result<()> {
+        if pos > self.bit_len() {
+            return Err(BitError::IndexOutOfBounds {
+                pos,
+                size: self.bit_len(),
+            });
+        }
+        let pos = pos + self.start_pos;
+        let markers = Rc::make_mut(self.markers.get_or_insert_with(Default::default));
+        let index = markers.partition_point(|marker| marker.pos <= pos);
+        markers.insert(index, Marker { pos, tag });
+        Ok(())
     }
 
-    /// Read a value based on the provided type without advancing the stream
-    #[inline]
-    pub fn peek<T: BitRead<'a, E>>(&mut self) -> Result<T> {
-        let pos = self.pos;
-        let result = T::read(self);
-        self.pos = pos;
-        result
+    /// All markers within the stream, ordered by position
+    ///
+    /// See [`mark`](BitReadStream::mark)
+    pub fn markers(&self) -> impl Iterator<Item = Marker> + '_ {
+        self.markers_between(0, self.bit_len())
     }
 
-    /// Read a value based on the provided type and size without advancing the stream
-    #[inline]
-    pub fn peek_sized<T: BitReadSized<'a, E>>(&mut self, size: usize) -> Result<T> {
-        let pos = self.pos;
-        let result = T::read(self, size);
-        self.pos = pos;
-        result
+    /// The markers that lie strictly between `start` and the current position, ordered by position
+    ///
+    /// A read that starts or ends exactly at a marker doesn't cross it.
+    ///
+    /// See [`mark`](BitReadStream::mark)
+    pub fn crossed_markers(&self, start: usize) -> impl Iterator<Item = Marker> + '_ {
+        let start = start + 1;
+        self.markers_between(start, self.pos().max(start) - 1)
     }
 
-    #[doc(hidden)]
-    #[inline]
-    pub unsafe fn read_sized_unchecked<T: BitReadSized<'a, E>>(
-        &mut self,
-        size: usize,
-        end: bool,
-    ) -> Result<T> {
-        T::read_unchecked(self, size, end)
+    /// Check that no markers lie strictly between `start` and the current position
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::MarkerCrossed`]: a marker lies between `start` and the current position
+    ///
+    /// See [`mark`](BitReadStream::mark)
+    ///
+    /// [`ReadError::MarkerCrossed`]: enum.ReadError.html#variant.MarkerCrossed
+    pub fn check_markers(&self, start: usize) -> Result<()> {
+        match self.crossed_markers(start).next() {
+            Some(marker) => Err(BitError::MarkerCrossed {
+                tag: marker.tag,
+                pos: marker.pos,
+                read_start: start,
+                read_end: self.pos(),
+            }),
+            None => Ok(()),
+        }
     }
 
-    /// Check if we can read a number of bits from the stream
-    pub fn check_read(&self, count: usize) -> Result<bool> {
-        if self.bits_left() < count + 64 {
-            if self.bits_left() < count {
-                Err(BitError::NotEnoughData {
-                    requested: count,
-                    bits_left: self.bits_left(),
-                })
-            } else {
-                Ok(true)
-            }
-        } else {
-            Ok(false)
+    /// The markers with a relative position within `start..=end`
+    fn markers_between(&self, start: usize, end: usize) -> impl Iterator<Item = Marker> + '_ {
+        let start_pos = self.start_pos;
+        self.markers
+            .iter()
+            .flat_map(|markers| markers.iter())
+            .filter(move |marker| marker.pos >= start_pos + start && marker.pos <= start_pos + end)
+            .map(move |marker| Marker {
+                pos: marker.pos - start_pos,
+                tag: marker.tag,
+            })
+    }
+
+    /// Stream over `buffer` that shares the coverage, statistics, markers, budget, progress and cancellation of this stream
+    ///
+    /// All streams derived from a stream have to be created trough this, so they don't lose any of the shared state.
+    fn derive_substream<'b, E2: Endianness>(
+        &self,
+        buffer: BitReadBuffer<'b, E2>,
+        start_pos: usize,
+        pos: usize,
+    ) -> BitReadStream<'b, E2> {
+        BitReadStream {
+            buffer,
+            start_pos,
+            pos,
+            #[cfg(feature = "coverage")]
+            coverage: self.coverage.clone(),
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+            markers: self.markers.clone(),
+            #[cfg(feature = "budget")]
+            budget: self.budget.clone(),
+            #[cfg(feature = "progress")]
+            progress: self.progress.clone(),
+            #[cfg(feature = "cancel")]
+            cancel: self.cancel.clone(),
         }
     }
 
+    /// Stream over `buffer` that only keeps the markers of this stream, without recording coverage or using the budget
+    fn detached_substream<'b>(
+        &self,
+        buffer: BitReadBuffer<'b, E>,
+        start_pos: usize,
+        pos: usize,
+    ) -> BitReadStream<'b, E> {
+        let mut stream = BitReadStream::new(buffer);
+        stream.start_pos = start_pos;
+        stream.pos = pos;
+        stream.markers = self.markers.clone();
+        stream
+    }
+
+    /// Clone of the stream that doesn't record coverage
+    fn untracked_clone(&self) -> Self {
+        self.detached_substream(self.buffer.share(), self.pos, self.pos)
+    }
+
     /// Create an owned copy of this stream
+    ///
+    /// Reads from the copy use the same read budget as this stream.
     pub fn to_owned(&self) -> BitReadStream<'static, E> {
-        match self.buffer.bytes {
-            Data::Owned(_) => BitReadStream {
-                // already owned, so buffer.to_owned is a cheap rc clone
-                buffer: self.buffer.to_owned(),
-                start_pos: self.pos,
-                pos: self.pos,
-            },
+        #[cfg_attr(not(feature = "budget"), allow(unused_mut))]
+        let mut stream = match self.buffer.bytes {
+            // already owned, so buffer.to_owned is a cheap rc clone
+            Data::Owned(_) => self.detached_substream(self.buffer.to_owned(), self.pos, self.pos),
             Data::Borrowed(bytes) => {
                 // instead of calling buffer.to_owned blindly, we only copy the bytes that this stream covers
                 let byte_pos = self.start_pos / 8;
@@ -745,33 +2799,109 @@ where
                 let buffer = BitReadBuffer::from(sub_bytes)
                     .get_sub_buffer(self.buffer.bit_len() - self.start_pos + bit_offset)
                     .unwrap();
-
-                BitReadStream {
+                let mut stream = self.detached_substream(
                     buffer,
-                    start_pos: bit_offset,
-                    pos: bit_offset + (self.pos - self.start_pos),
-                }
+                    bit_offset,
+                    bit_offset + (self.pos - self.start_pos),
+                );
+                // markers are stored by their position in the buffer, which starts at `byte_pos` in the copy
+                stream.markers = self.markers.as_ref().map(|markers| {
+                    Rc::new(
+                        markers
+                            .iter()
+                            .filter(|marker| marker.pos >= byte_pos * 8)
+                            .map(|marker| Marker {
+                                pos: marker.pos - byte_pos * 8,
+                                tag: marker.tag,
+                            })
+                            .collect(),
+                    )
+                });
+                stream
             }
+        };
+        #[cfg(feature = "budget")]
+        {
+            stream.budget = self.budget.clone();
         }
+        stream
+    }
+}
+
+impl<'a> BitReadStream<'a, LittleEndian> {
+    /// Create a new little endian stream reading from `bytes`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadStream, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0b0000_0101];
+    /// let mut stream = BitReadStream::le(&bytes);
+    /// assert_eq!(0b101, stream.read_int::<u8>(3)?);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn le(bytes: &'a [u8]) -> Self {
+        BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian))
+    }
+}
+
+impl<'a> BitReadStream<'a, BigEndian> {
+    /// Create a new big endian stream reading from `bytes`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadStream, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0b1010_0000];
+    /// let mut stream = BitReadStream::be(&bytes);
+    /// assert_eq!(0b101, stream.read_int::<u8>(3)?);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn be(bytes: &'a [u8]) -> Self {
+        BitReadStream::new(BitReadBuffer::new(bytes, BigEndian))
     }
 }
 
 impl<'a, E: Endianness> Clone for BitReadStream<'a, E> {
     fn clone(&self) -> Self {
-        BitReadStream {
-            buffer: self.buffer.clone(),
-            start_pos: self.pos,
-            pos: self.pos,
-        }
+        self.derive_substream(self.buffer.share(), self.pos, self.pos)
+    }
+}
+
+/// Shows the position, the number of bits left and the next 32 bits of the stream,
+/// the alternate format (`{:#?}`) shows all remaining bits
+impl<E: Endianness> Debug for BitReadStream<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitReadStream")
+            .field("pos", &self.pos())
+            .field("bits_left", &self.bits_left())
+            .field("endianness", &format_args!("{}", E::as_string()))
+            .field(
+                "next_bits",
+                &DebugBits {
+                    buffer: &self.buffer,
+                    start: self.pos,
+                    len: self.bits_left(),
+                },
+            )
+            .finish()
     }
 }
 
 impl<'a, E: Endianness> PartialEq for BitReadStream<'a, E> {
     fn eq(&self, other: &Self) -> bool {
         // clones so we can mut
-        let mut self_clone = self.clone();
+        let mut self_clone = self.untracked_clone();
         self_clone.set_pos(0).ok();
-        let mut other_clone = other.clone();
+        let mut other_clone = other.untracked_clone();
         other_clone.set_pos(0).ok();
 
         if self_clone.bits_left() != other_clone.bits_left() {
@@ -800,12 +2930,25 @@ impl<'a, E: Endianness> From<BitReadBuffer<'a, E>> for BitReadStream<'a, E> {
     }
 }
 
+/// Create a stream over an owned buffer containing the bits of the iterator
+impl<E: Endianness> FromIterator<bool> for BitReadStream<'static, E> {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        BitReadStream::new(iter.into_iter().collect())
+    }
+}
+
 impl<'a, E: Endianness> From<&'a [u8]> for BitReadStream<'a, E> {
     fn from(bytes: &'a [u8]) -> Self {
         BitReadStream::new(BitReadBuffer::from(bytes))
     }
 }
 
+impl<'a, E: Endianness> From<Vec<u8>> for BitReadStream<'a, E> {
+    fn from(bytes: Vec<u8>) -> Self {
+        BitReadStream::new(BitReadBuffer::from(bytes))
+    }
+}
+
 #[cfg(feature = "serde")]
 use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -815,7 +2958,7 @@ impl<'a, E: Endianness> Serialize for BitReadStream<'a, E> {
     where
         S: Serializer,
     {
-        let mut stream = self.clone();
+        let mut stream = self.untracked_clone();
         let mut data = stream.read_bytes(self.bits_left() / 8).unwrap().to_vec();
         if stream.bits_left() > 0 {
             data.push(stream.read_sized(stream.bits_left()).unwrap());
@@ -0,0 +1,97 @@
+use std::fmt;
+
+/// Number of bits written in a labeled section of a [`BitWriteStream`]
+///
+/// [`BitWriteStream`]: crate::BitWriteStream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionSize {
+    /// The labels of the section and all sections it is nested in, separated by `/`
+    pub path: String,
+    /// The total number of bits written in the section
+    pub bits: usize,
+    /// The number of times the section has been written
+    pub count: usize,
+}
+
+/// Breakdown of the number of bits written per labeled section of a [`BitWriteStream`]
+///
+/// Sections are listed in the order they are first written, with nested sections following their parent.
+/// The [`Display`](fmt::Display) implementation formats the breakdown as an indented table.
+///
+/// [`BitWriteStream`]: crate::BitWriteStream
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionStats {
+    sections: Vec<SectionSize>,
+    /// Path of the sections that are currently being written
+    current: Vec<String>,
+}
+
+impl SectionStats {
+    /// All sections that have been written
+    pub fn sections(&self) -> &[SectionSize] {
+        &self.sections
+    }
+
+    /// Get the size of the section for a `/` separated path of labels
+    pub fn get(&self, path: &str) -> Option<&SectionSize> {
+        self.sections.iter().find(|section| section.path == path)
+    }
+
+    pub(crate) fn enter(&mut self, label: &str) {
+        let parent = self.current.last();
+        let path = match parent {
+            Some(parent) => format!("{}/{}", parent, label),
+            None => label.to_string(),
+        };
+        if self.get(&path).is_none() {
+            // keep nested sections grouped after their parent
+            let index = match parent {
+                Some(parent) => {
+                    let prefix = format!("{}/", parent);
+                    self.sections
+                        .iter()
+                        .rposition(|s| &s.path == parent || s.path.starts_with(&prefix))
+                        .map_or(self.sections.len(), |index| index + 1)
+                }
+                None => self.sections.len(),
+            };
+            self.sections.insert(
+                index,
+                SectionSize {
+                    path: path.clone(),
+                    bits: 0,
+                    count: 0,
+                },
+            );
+        }
+        self.current.push(path);
+    }
+
+    pub(crate) fn exit(&mut self, bits: usize) {
+        if let Some(path) = self.current.pop() {
+            if let Some(section) = self.sections.iter_mut().find(|s| s.path == path) {
+                section.bits += bits;
+                section.count += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Display for SectionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for section in &self.sections {
+            let depth = section.path.matches('/').count();
+            let label = section.path.rsplit('/').next().unwrap_or_default();
+            writeln!(
+                f,
+                "{:indent$}{}: {} bits ({} times)",
+                "",
+                label,
+                section.bits,
+                section.count,
+                indent = depth * 2
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{BitError, BitReadSized, BitReadStream, Endianness, LazyBitReadSized, RawStr, Result};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The unit that the size for a sized read or write is expressed in
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SizeUnit {
+    /// The size is a number of bits (e.g. integers)
+    Bits,
+    /// The size is a number of bytes (e.g. strings)
+    Bytes,
+    /// The size is a number of items (e.g. `Vec`s)
+    Count,
+}
+
+impl SizeUnit {
+    /// Convert a size expressed in this unit into the `target` unit
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::SizeUnitMismatch`]: the size can't be expressed in the target unit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::SizeUnit;
+    /// assert_eq!(SizeUnit::Bytes.convert(4, SizeUnit::Bits).unwrap(), 32);
+    /// assert_eq!(SizeUnit::Bits.convert(16, SizeUnit::Bytes).unwrap(), 2);
+    /// assert!(SizeUnit::Bits.convert(12, SizeUnit::Bytes).is_err());
+    /// assert!(SizeUnit::Count.convert(12, SizeUnit::Bits).is_err());
+    /// ```
+    pub fn convert(self, size: usize, target: SizeUnit) -> Result<usize> {
+        match (self, target) {
+            (from, to) if from == to => Ok(size),
+            (SizeUnit::Bytes, SizeUnit::Bits) => Ok(size * 8),
+            (SizeUnit::Bits, SizeUnit::Bytes) if size % 8 == 0 => Ok(size / 8),
+            _ => Err(BitError::SizeUnitMismatch {
+                size,
+                unit: self,
+                target,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for SizeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeUnit::Bits => write!(f, "bits"),
+            SizeUnit::Bytes => write!(f, "bytes"),
+            SizeUnit::Count => write!(f, "items"),
+        }
+    }
+}
+
+/// A length in bytes
+///
+/// Used where a length could easily be confused with a number of characters, such as for
+/// [`write_string_truncated`](crate::BitWriteStream::write_string_truncated).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ByteLen(pub usize);
+
+/// Types that have a known unit for the size used by [`BitReadSized`] and [`BitWriteSized`]
+///
+/// This allows the derive macros to accept sizes in an explicit unit using `#[size(bits = 12)]`,
+/// `#[size(bytes = 4)]` or `#[size(count = 3)]`, the size is converted into the unit used by
+/// the field type before reading or writing it.
+///
+/// [`BitWriteSized`]: crate::BitWriteSized
+pub trait HasSizeUnit {
+    /// The unit that the size is expressed in
+    const SIZE_UNIT: SizeUnit;
+}
+
+macro_rules! impl_size_unit {
+    ($unit:ident, $($type:ty),*) => {
+        $(
+            impl HasSizeUnit for $type {
+                const SIZE_UNIT: SizeUnit = SizeUnit::$unit;
+            }
+        )*
+    };
+}
+
+impl_size_unit!(Bits, u8, u16, u32, u64, u128, usize);
+impl_size_unit!(Bits, i8, i16, i32, i64, i128, isize);
+impl_size_unit!(Bytes, str, String, Cow<'_, str>, Cow<'_, [u8]>, RawStr<'_>);
+
+#[cfg(feature = "std-types")]
+impl_size_unit!(Bits, std::time::Duration, std::time::SystemTime);
+
+impl<E: Endianness> HasSizeUnit for BitReadStream<'_, E> {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Bits;
+}
+
+impl<T> HasSizeUnit for Vec<T> {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Count;
+}
+
+impl<K, V, S> HasSizeUnit for HashMap<K, V, S> {
+    const SIZE_UNIT: SizeUnit = SizeUnit::Count;
+}
+
+/// The size of arrays is applied to every element
+impl<T: HasSizeUnit, const N: usize> HasSizeUnit for [T; N] {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<T: HasSizeUnit> HasSizeUnit for Option<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<T: HasSizeUnit + ?Sized> HasSizeUnit for Box<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<T: HasSizeUnit + ?Sized> HasSizeUnit for Rc<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<T: HasSizeUnit + ?Sized> HasSizeUnit for Arc<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<T: HasSizeUnit> HasSizeUnit for Cell<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<T: HasSizeUnit + ?Sized> HasSizeUnit for RefCell<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+#[cfg(feature = "std-types")]
+impl<T: HasSizeUnit + ?Sized> HasSizeUnit for std::sync::Mutex<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+#[cfg(feature = "std-types")]
+impl<T: HasSizeUnit + ?Sized> HasSizeUnit for std::sync::RwLock<T> {
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+impl<'a, T: BitReadSized<'a, E> + HasSizeUnit, E: Endianness> HasSizeUnit
+    for LazyBitReadSized<'a, T, E>
+{
+    const SIZE_UNIT: SizeUnit = T::SIZE_UNIT;
+}
+
+/// Types made up of a fixed number of elements that each get the full size of a sized read or write
+///
+/// This allows choosing between applying the size to every element using
+/// [`read_sized_each`](BitReadStream::read_sized_each) or dividing it over all elements using
+/// [`read_sized_total`](BitReadStream::read_sized_total).
+pub trait HasElementCount {
+    /// The number of elements the size is applied to
+    const ELEMENT_COUNT: usize;
+}
+
+/// Divide a total size evenly over the elements of `T`
+pub(crate) fn element_size<T: HasElementCount>(size: usize) -> Result<usize> {
+    if T::ELEMENT_COUNT == 0 || size % T::ELEMENT_COUNT != 0 {
+        return Err(BitError::UnevenSize {
+            size,
+            element_count: T::ELEMENT_COUNT,
+        });
+    }
+    Ok(size / T::ELEMENT_COUNT)
+}
+
+impl<T, const N: usize> HasElementCount for [T; N] {
+    const ELEMENT_COUNT: usize = N;
+}
+
+macro_rules! impl_element_count_tuple {
+    ($count:literal, $($type:ident),*) => {
+        impl<$($type),*> HasElementCount for ($($type),*) {
+            const ELEMENT_COUNT: usize = $count;
+        }
+    };
+}
+
+impl_element_count_tuple!(2, T1, T2);
+impl_element_count_tuple!(3, T1, T2, T3);
+impl_element_count_tuple!(4, T1, T2, T3, T4);
+
+#[test]
+fn test_size_units() {
+    use crate::LittleEndian;
+
+    assert_eq!(SizeUnit::Bits, <u8 as HasSizeUnit>::SIZE_UNIT);
+    assert_eq!(SizeUnit::Bytes, <String as HasSizeUnit>::SIZE_UNIT);
+    assert_eq!(SizeUnit::Count, <Vec<String> as HasSizeUnit>::SIZE_UNIT);
+    assert_eq!(SizeUnit::Bytes, <[String; 2] as HasSizeUnit>::SIZE_UNIT);
+    assert_eq!(SizeUnit::Bits, <Option<Box<u16>> as HasSizeUnit>::SIZE_UNIT);
+    assert_eq!(
+        SizeUnit::Bits,
+        <BitReadStream<LittleEndian> as HasSizeUnit>::SIZE_UNIT
+    );
+}
@@ -0,0 +1,150 @@
+use crate::{
+    BitError, BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized, BitWriteStream,
+    Endianness, Result,
+};
+use std::any::type_name;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Marker for reserved or unused bits with the size of `T`
+///
+/// Reading a `Skip<T>` skips over `T` without storing it and writing it emits zeroes with the size of `T`,
+/// which makes it possible to document reserved fields in derived structs while keeping the size of the struct predictable.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitRead, BitReadStream, BitWrite, BitWriteStream, LittleEndian, Result, Skip};
+/// #
+/// #[derive(BitRead, BitWrite)]
+/// struct Header {
+///     version: u8,
+///     reserved: Skip<u16>,
+///     #[size = 4]
+///     padding: Skip<u8>,
+///     flags: u8,
+/// }
+///
+/// # fn main() -> Result<()> {
+/// assert_eq!(Some(36), bitbuffer::bit_size_of::<Header>());
+///
+/// let mut data = Vec::new();
+/// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+/// stream.write(&Header {
+///     version: 1,
+///     reserved: Skip::new(),
+///     padding: Skip::new(),
+///     flags: 0xff,
+/// })?;
+/// # drop(stream);
+/// assert_eq!(data, [1, 0, 0, 0xf0, 0x0f]);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub struct Skip<T>(PhantomData<T>);
+
+impl<T> Skip<T> {
+    /// Create a new marker
+    pub fn new() -> Self {
+        Skip(PhantomData)
+    }
+}
+
+impl<T> Default for Skip<T> {
+    fn default() -> Self {
+        Skip::new()
+    }
+}
+
+impl<T> Clone for Skip<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Skip<T> {}
+
+impl<T> PartialEq for Skip<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for Skip<T> {}
+
+impl<T> fmt::Debug for Skip<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Skip<{}>", type_name::<T>())
+    }
+}
+
+impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Skip<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        T::skip(stream)?;
+        Ok(Skip::new())
+    }
+
+    #[inline]
+    fn skip(stream: &mut BitReadStream<'a, E>) -> Result<()> {
+        T::skip(stream)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        T::bit_size()
+    }
+}
+
+impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for Skip<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        T::skip(stream, size)?;
+        Ok(Skip::new())
+    }
+
+    #[inline]
+    fn skip(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<()> {
+        T::skip(stream, size)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        T::bit_size_sized(size)
+    }
+}
+
+impl<E: Endianness, T: for<'a> BitRead<'a, E>> BitWrite<E> for Skip<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        match T::bit_size() {
+            Some(size) => stream.write_run(false, size),
+            None => Err(BitError::UnknownSkipSize {
+                type_name: type_name::<T>(),
+            }),
+        }
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        T::bit_size()
+    }
+}
+
+impl<E: Endianness, T: for<'a> BitReadSized<'a, E>> BitWriteSized<E> for Skip<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        match T::bit_size_sized(len) {
+            Some(size) => stream.write_run(false, size),
+            None => Err(BitError::UnknownSkipSize {
+                type_name: type_name::<T>(),
+            }),
+        }
+    }
+
+    #[inline]
+    fn write_bit_size_sized(len: usize) -> Option<usize> {
+        T::bit_size_sized(len)
+    }
+}
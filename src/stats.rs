@@ -0,0 +1,66 @@
+use crate::readstream::ReadKind;
+use std::ops::Add;
+
+/// The number of reads of one kind and the number of bits they consumed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadCount {
+    /// The number of reads
+    pub calls: usize,
+    /// The total number of bits read
+    pub bits: usize,
+}
+
+impl Add for ReadCount {
+    type Output = ReadCount;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        ReadCount {
+            calls: self.calls + rhs.calls,
+            bits: self.bits + rhs.bits,
+        }
+    }
+}
+
+/// Statistics about the reads done on a stream, grouped by the kind of data read
+///
+/// Reads that are build on top of other reads, such as reading a varint or a struct, are counted as
+/// the primitive reads they consist of.
+///
+/// See [`BitReadStream::track_stats`](crate::BitReadStream::track_stats)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadStats {
+    /// Reads of single booleans
+    pub bools: ReadCount,
+    /// Reads of integers
+    pub ints: ReadCount,
+    /// Reads of floats
+    pub floats: ReadCount,
+    /// Reads of byte slices
+    ///
+    /// Since 0.12 this includes reading `[u8; N]` arrays, which were counted as [`ints`](ReadStats::ints) before.
+    pub bytes: ReadCount,
+    /// Reads of strings
+    pub strings: ReadCount,
+    /// Other reads, such as bit runs
+    pub other: ReadCount,
+}
+
+impl ReadStats {
+    /// The combined counts of all kinds of reads
+    pub fn total(&self) -> ReadCount {
+        self.bools + self.ints + self.floats + self.bytes + self.strings + self.other
+    }
+
+    pub(crate) fn record(&mut self, kind: ReadKind, bits: usize) {
+        let count = match kind {
+            ReadKind::Bool => &mut self.bools,
+            ReadKind::Int => &mut self.ints,
+            ReadKind::Float => &mut self.floats,
+            ReadKind::Bytes => &mut self.bytes,
+            ReadKind::String => &mut self.strings,
+            ReadKind::Other => &mut self.other,
+        };
+        count.calls += 1;
+        count.bits += bits;
+    }
+}
@@ -0,0 +1,622 @@
+//! [`BitRead`] and [`BitWrite`] implementations for commonly used std types
+//!
+//! - [`Duration`] is stored as the number of whole seconds followed by the 32 bit sub-second nanoseconds,
+//!   when read or written sized, the size sets the number of bits used for the seconds (64 by default).
+//!   [`BitReadStream::read_duration`] and [`BitWriteStream::write_duration`] also allow setting the number of bits for the nanoseconds
+//! - [`SystemTime`] is stored as the [`Duration`] since the [`UNIX_EPOCH`], using the same layout
+//! - [`Ipv4Addr`] and [`Ipv6Addr`] are stored as their 4 or 16 octets in network order, regardless of the endianness of the stream
+//! - [`IpAddr`] is stored as a boolean that is `true` for ipv6 addresses, followed by the address
+//! - [`SocketAddrV4`] and [`SocketAddrV6`] are stored as the address followed by a 16 bit port,
+//!   the flow info and scope id of ipv6 socket addresses are not stored
+//! - [`SocketAddr`] is stored as a boolean that is `true` for ipv6 addresses, followed by the socket address
+//! - `Uuid` (with the `uuid` feature) is stored as its 16 bytes
+//! - [`Mutex`] and [`RwLock`] are stored as their inner value, a poisoned lock is still written
+//! - [`Range`] and [`RangeInclusive`] are stored as the start followed by the end, when read or written sized,
+//!   the size is used for both, the exhausted state of a [`RangeInclusive`] is not stored
+//! - [`Result`](std::result::Result) is stored as a boolean that is `true` for `Err`, followed by the value
+//! - [`OsString`] and [`OsStr`] are stored like `String`, null terminated or, when read or written sized, as a fixed number
+//!   of bytes with the trailing null bytes removed. On unix the platform bytes are stored, on other platforms the string
+//!   has to be valid unicode and is stored as utf8. Writing a string with a null byte null terminated is an error.
+use crate::read::impl_read_wrapper;
+use crate::{BitError, BitRead, BitReadSized, BitReadStream, BitWrite, BitWriteSized};
+use crate::{BitWriteStream, CheckedRead, Endianness, Result};
+use std::ffi::{OsStr, OsString};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::ops::{Range, RangeInclusive};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+impl<E: Endianness> BitReadStream<'_, E> {
+    /// Read a [`Duration`] stored as `secs_bits` bits of whole seconds followed by `nanos_bits` bits of sub-second nanoseconds
+    ///
+    /// The [`BitRead`] implementation of [`Duration`] uses 64 bits for the seconds and 32 bits for the nanoseconds.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available in the stream
+    /// - [`BitError::TooManyBits`]: more than 64 bits requested for the seconds or more than 32 bits for the nanoseconds
+    /// - [`BitError::ValueOutOfRange`]: the nanoseconds are not below one second
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian, Result};
+    /// # use std::time::Duration;
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0x05, 0x0a, 0x00, 0x00, 0x00];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    /// assert_eq!(Duration::new(5, 10), stream.read_duration(8, 30)?);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn read_duration(&mut self, secs_bits: usize, nanos_bits: usize) -> Result<Duration> {
+        let secs = self.read_int::<u64>(secs_bits)?;
+        let nanos = self.read_int::<u32>(nanos_bits)?;
+        if nanos >= NANOS_PER_SEC {
+            return Err(BitError::ValueOutOfRange {
+                type_name: "Duration",
+            });
+        }
+        Ok(Duration::new(secs, nanos))
+    }
+}
+
+impl<E: Endianness> BitWriteStream<'_, E> {
+    /// Write a [`Duration`] as `secs_bits` bits of whole seconds followed by `nanos_bits` bits of sub-second nanoseconds
+    ///
+    /// The [`BitWrite`] implementation of [`Duration`] uses 64 bits for the seconds and 32 bits for the nanoseconds.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough space in the buffer
+    /// - [`BitError::TooManyBitsForValue`]: more than 64 bits requested for the seconds or more than 32 bits for the nanoseconds
+    /// - [`BitError::ValueOutOfRange`]: the seconds or nanoseconds don't fit in the requested number of bits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// # use std::time::Duration;
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_duration(&Duration::new(5, 10), 8, 30)?;
+    /// assert_eq!(38, stream.bit_len());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_duration(
+        &mut self,
+        duration: &Duration,
+        secs_bits: usize,
+        nanos_bits: usize,
+    ) -> Result<()> {
+        let secs = duration.as_secs();
+        let nanos = duration.subsec_nanos();
+        if !fits(secs, secs_bits) || !fits(u64::from(nanos), nanos_bits) {
+            return Err(BitError::ValueOutOfRange {
+                type_name: "Duration",
+            });
+        }
+        self.write_int(secs, secs_bits)?;
+        self.write_int(nanos, nanos_bits)
+    }
+}
+
+fn fits(value: u64, bits: usize) -> bool {
+    bits >= 64 || value >> bits == 0
+}
+
+fn since_epoch(time: &SystemTime) -> Result<Duration> {
+    time.duration_since(UNIX_EPOCH)
+        .map_err(|_| BitError::ValueOutOfRange {
+            type_name: "SystemTime",
+        })
+}
+
+fn from_epoch(duration: Duration) -> Result<SystemTime> {
+    UNIX_EPOCH
+        .checked_add(duration)
+        .ok_or(BitError::ValueOutOfRange {
+            type_name: "SystemTime",
+        })
+}
+
+impl<E: Endianness> BitRead<'_, E> for Duration {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        stream.read_duration(64, 32)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(64 + 32)
+    }
+}
+
+impl<E: Endianness> BitReadSized<'_, E> for Duration {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        stream.read_duration(size, 32)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(size + 32)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for Duration {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_duration(self, 64, 32)
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for Duration {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_duration(self, len, 32)
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for SystemTime {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        from_epoch(stream.read_duration(64, 32)?)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(64 + 32)
+    }
+}
+
+impl<E: Endianness> BitReadSized<'_, E> for SystemTime {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        from_epoch(stream.read_duration(size, 32)?)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(size + 32)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for SystemTime {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_duration(&since_epoch(self)?, 64, 32)
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for SystemTime {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_duration(&since_epoch(self)?, len, 32)
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for Ipv4Addr {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        let octets: [u8; 4] = stream.read()?;
+        Ok(Ipv4Addr::from(octets))
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(32)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for Ipv4Addr {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_bytes(&self.octets())
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for Ipv6Addr {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        let octets: [u8; 16] = stream.read()?;
+        Ok(Ipv6Addr::from(octets))
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(128)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for Ipv6Addr {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_bytes(&self.octets())
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for IpAddr {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        if stream.read()? {
+            Ok(IpAddr::V6(stream.read()?))
+        } else {
+            Ok(IpAddr::V4(stream.read()?))
+        }
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for IpAddr {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        match self {
+            IpAddr::V4(addr) => {
+                stream.write_bool(false)?;
+                stream.write(addr)
+            }
+            IpAddr::V6(addr) => {
+                stream.write_bool(true)?;
+                stream.write(addr)
+            }
+        }
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for SocketAddrV4 {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        Ok(SocketAddrV4::new(stream.read()?, stream.read()?))
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(32 + 16)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for SocketAddrV4 {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write(self.ip())?;
+        stream.write(&self.port())
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for SocketAddrV6 {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        Ok(SocketAddrV6::new(stream.read()?, stream.read()?, 0, 0))
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(128 + 16)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for SocketAddrV6 {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write(self.ip())?;
+        stream.write(&self.port())
+    }
+}
+
+impl<E: Endianness> BitRead<'_, E> for SocketAddr {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        if stream.read()? {
+            Ok(SocketAddr::V6(stream.read()?))
+        } else {
+            Ok(SocketAddr::V4(stream.read()?))
+        }
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for SocketAddr {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        match self {
+            SocketAddr::V4(addr) => {
+                stream.write_bool(false)?;
+                stream.write(addr)
+            }
+            SocketAddr::V6(addr) => {
+                stream.write_bool(true)?;
+                stream.write(addr)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<E: Endianness> BitRead<'_, E> for uuid::Uuid {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        let bytes: [u8; 16] = stream.read()?;
+        Ok(uuid::Uuid::from_bytes(bytes))
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        Some(128)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<E: Endianness> BitWrite<E> for uuid::Uuid {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_bytes(self.as_bytes())
+    }
+}
+
+impl_read_wrapper!(Mutex);
+impl_read_wrapper!(RwLock);
+
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Mutex<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        let guard = self.lock().unwrap_or_else(|err| err.into_inner());
+        stream.write(&*guard)
+    }
+}
+
+impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for Mutex<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        let guard = self.lock().unwrap_or_else(|err| err.into_inner());
+        stream.write_sized(&*guard, len)
+    }
+}
+
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for RwLock<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        let guard = self.read().unwrap_or_else(|err| err.into_inner());
+        stream.write(&*guard)
+    }
+}
+
+impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for RwLock<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        let guard = self.read().unwrap_or_else(|err| err.into_inner());
+        stream.write_sized(&*guard, len)
+    }
+}
+
+fn double_size(size: Option<usize>) -> Option<usize> {
+    size?.checked_mul(2)
+}
+
+impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for Range<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        Ok(stream.read()?..stream.read()?)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        double_size(T::bit_size())
+    }
+}
+
+impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for Range<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        Ok(stream.read_sized(size)?..stream.read_sized(size)?)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        double_size(T::bit_size_sized(size))
+    }
+}
+
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Range<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write(&self.start)?;
+        stream.write(&self.end)
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        double_size(T::write_bit_size())
+    }
+}
+
+impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for Range<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_sized(&self.start, len)?;
+        stream.write_sized(&self.end, len)
+    }
+
+    #[inline]
+    fn write_bit_size_sized(len: usize) -> Option<usize> {
+        double_size(T::write_bit_size_sized(len))
+    }
+}
+
+impl<'a, E: Endianness, T: BitRead<'a, E>> BitRead<'a, E> for RangeInclusive<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        Ok(stream.read()?..=stream.read()?)
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        double_size(T::bit_size())
+    }
+}
+
+impl<'a, E: Endianness, T: BitReadSized<'a, E>> BitReadSized<'a, E> for RangeInclusive<T> {
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>, size: usize) -> Result<Self> {
+        Ok(stream.read_sized(size)?..=stream.read_sized(size)?)
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        double_size(T::bit_size_sized(size))
+    }
+}
+
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for RangeInclusive<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write(self.start())?;
+        stream.write(self.end())
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        double_size(T::write_bit_size())
+    }
+}
+
+impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for RangeInclusive<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_sized(self.start(), len)?;
+        stream.write_sized(self.end(), len)
+    }
+
+    #[inline]
+    fn write_bit_size_sized(len: usize) -> Option<usize> {
+        double_size(T::write_bit_size_sized(len))
+    }
+}
+
+/// The size of a `Result` is only known if both variants have the same size
+fn result_size(ok: Option<usize>, err: Option<usize>) -> Option<usize> {
+    match (ok, err) {
+        (Some(ok), Some(err)) if ok == err => ok.checked_add(1),
+        _ => None,
+    }
+}
+
+impl<'a, E: Endianness, T: BitRead<'a, E>, U: BitRead<'a, E>> BitRead<'a, E>
+    for std::result::Result<T, U>
+{
+    #[inline]
+    fn read(stream: &mut BitReadStream<'a, E>) -> Result<Self> {
+        if stream.read()? {
+            Ok(Err(stream.read()?))
+        } else {
+            Ok(Ok(stream.read()?))
+        }
+    }
+
+    #[inline]
+    fn bit_size() -> Option<usize> {
+        result_size(T::bit_size(), U::bit_size())
+    }
+}
+
+impl<T: BitWrite<E>, U: BitWrite<E>, E: Endianness> BitWrite<E> for std::result::Result<T, U> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_bool(self.is_err())?;
+        match self {
+            Ok(value) => stream.write(value),
+            Err(err) => stream.write(err),
+        }
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        result_size(T::write_bit_size(), U::write_bit_size())
+    }
+}
+
+/// The bytes of an os string as stored in the stream
+#[cfg(unix)]
+fn os_str_bytes(string: &OsStr) -> Result<&[u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(string.as_bytes())
+}
+
+/// The bytes of an os string as stored in the stream
+#[cfg(not(unix))]
+fn os_str_bytes(string: &OsStr) -> Result<&[u8]> {
+    string
+        .to_str()
+        .map(str::as_bytes)
+        .ok_or(BitError::ValueOutOfRange {
+            type_name: "OsString",
+        })
+}
+
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: Vec<u8>) -> Result<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn os_string_from_bytes(bytes: Vec<u8>) -> Result<OsString> {
+    Ok(String::from_utf8(bytes)?.into())
+}
+
+impl<E: Endianness> BitRead<'_, E> for OsString {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>) -> Result<Self> {
+        os_string_from_bytes(stream.read_raw_string(None)?.into_owned())
+    }
+}
+
+impl<E: Endianness> BitReadSized<'_, E> for OsString {
+    #[inline]
+    fn read(stream: &mut BitReadStream<E>, size: usize) -> Result<Self> {
+        os_string_from_bytes(stream.read_raw_string(Some(size))?.into_owned())
+    }
+
+    #[inline]
+    fn bit_size_sized(size: usize) -> Option<usize> {
+        Some(8 * size)
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for OsStr {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        let bytes = os_str_bytes(self)?;
+        if let Some(position) = bytes.iter().position(|byte| *byte == 0) {
+            return Err(BitError::InteriorNul { position });
+        }
+        stream.write_string_bytes(bytes, None)
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for OsStr {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_string_bytes(os_str_bytes(self)?, Some(len))
+    }
+}
+
+impl<E: Endianness> BitWrite<E> for OsString {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        self.as_os_str().write(stream)
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for OsString {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        self.as_os_str().write_sized(stream, len)
+    }
+}
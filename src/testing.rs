@@ -0,0 +1,139 @@
+//! Property based round-trip tests for types implementing both [`BitRead`] and [`BitWrite`]
+//!
+//! Available with the `proptest` feature, the checks write randomly generated values in both endianness and assert
+//! that reading them back results in the same value and consumes exactly the written bits.
+//!
+//! # Examples
+//!
+//! ```
+//! # use bitbuffer::{BitRead, BitWrite, roundtrip_tests};
+//! # use proptest::prelude::*;
+//! #
+//! #[derive(BitRead, BitWrite, Debug, PartialEq)]
+//! struct Point {
+//!     x: i16,
+//!     #[size = 7]
+//!     y: u8,
+//! }
+//!
+//! roundtrip_tests! {
+//!     roundtrip_u16: u16,
+//!     roundtrip_pair: (bool, i32),
+//! }
+//!
+//! // in a test function, only generate `y` values that fit in 7 bits
+//! bitbuffer::testing::check_roundtrip_with(
+//!     (any::<i16>(), 0..128u8).prop_map(|(x, y)| Point { x, y }),
+//! );
+//! ```
+use crate::{
+    BigEndian, BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, Endianness,
+    LittleEndian,
+};
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::strategy::Strategy;
+use proptest::test_runner::{TestCaseError, TestRunner};
+use std::fmt::Debug;
+
+/// Generate `#[test]` functions asserting that values of each type round-trip in both endianness
+///
+/// Every type needs to implement [`proptest::arbitrary::Arbitrary`], see [`check_roundtrip`].
+///
+/// ```
+/// # use bitbuffer::roundtrip_tests;
+/// roundtrip_tests! {
+///     roundtrip_u16: u16,
+///     roundtrip_options: [Option<u8>; 3],
+/// }
+/// ```
+#[macro_export]
+macro_rules! roundtrip_tests {
+    ($($name:ident: $type:ty),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                $crate::testing::check_roundtrip::<$type>();
+            }
+        )*
+    };
+}
+
+/// Assert that random values of `T` round-trip in both endianness
+///
+/// # Panics
+///
+/// Panics with the minimal failing value if any of the generated values doesn't round-trip.
+pub fn check_roundtrip<T>()
+where
+    T: Arbitrary
+        + Debug
+        + PartialEq
+        + BitWrite<LittleEndian>
+        + BitWrite<BigEndian>
+        + for<'a> BitRead<'a, LittleEndian>
+        + for<'a> BitRead<'a, BigEndian>,
+{
+    check_roundtrip_with(any::<T>())
+}
+
+/// Assert that the values generated by `strategy` round-trip in both endianness
+///
+/// This can be used for types that don't implement [`Arbitrary`] or when only part of the possible values
+/// of a type can be written, such as integers with a `size` attribute.
+///
+/// # Panics
+///
+/// Panics with the minimal failing value if any of the generated values doesn't round-trip.
+pub fn check_roundtrip_with<T, S>(strategy: S)
+where
+    S: Strategy<Value = T>,
+    T: Debug
+        + PartialEq
+        + BitWrite<LittleEndian>
+        + BitWrite<BigEndian>
+        + for<'a> BitRead<'a, LittleEndian>
+        + for<'a> BitRead<'a, BigEndian>,
+{
+    let result = TestRunner::default().run(&strategy, |value| {
+        roundtrip(&value, LittleEndian)?;
+        roundtrip(&value, BigEndian)
+    });
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
+
+fn roundtrip<T, E>(value: &T, endianness: E) -> Result<(), TestCaseError>
+where
+    E: Endianness,
+    T: Debug + PartialEq + BitWrite<E> + for<'a> BitRead<'a, E>,
+{
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, endianness);
+    stream
+        .write(value)
+        .map_err(|err| TestCaseError::fail(format!("{} write failed: {}", E::as_string(), err)))?;
+    let bit_len = stream.bit_len();
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+    let result: T = read
+        .read()
+        .map_err(|err| TestCaseError::fail(format!("{} read failed: {}", E::as_string(), err)))?;
+    if &result != value {
+        return Err(TestCaseError::fail(format!(
+            "{} read {:?} after writing {:?}",
+            E::as_string(),
+            result,
+            value
+        )));
+    }
+    if read.pos() != bit_len {
+        return Err(TestCaseError::fail(format!(
+            "{} read {} bits after writing {} bits",
+            E::as_string(),
+            read.pos(),
+            bit_len
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Encoding the exact number of written bits in byte padded data
+use crate::{BitError, BitReadBuffer, BitWriteStream, Endianness, Result};
+
+/// How the exact number of written bits is encoded at the end of byte padded data
+///
+/// Byte oriented outputs like files and sockets can only store whole bytes, a trailer allows
+/// the reader to recover the exact bit length of the data using [`BitReadBuffer::strip_trailer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitTrailer {
+    /// A single `1` bit after the data, followed by zero padding up to the byte boundary
+    ///
+    /// This adds between 1 and 8 bits to the data.
+    StopBit,
+    /// A byte after the padded data containing the number of used bits in the last byte of the data, from 1 to 8
+    ///
+    /// Empty data is encoded as a single `0` byte.
+    LengthByte,
+}
+
+impl<E: Endianness> BitWriteStream<'_, E> {
+    /// The number of bits of the last written byte that contain data, from 1 to 8, or 0 if nothing has been written
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// assert_eq!(stream.last_byte_bits(), 0);
+    /// stream.write_int(3u16, 10)?;
+    /// assert_eq!(stream.last_byte_bits(), 2);
+    /// stream.write_int(3u16, 6)?;
+    /// assert_eq!(stream.last_byte_bits(), 8);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn last_byte_bits(&self) -> usize {
+        match self.bit_len() {
+            0 => 0,
+            bit_len => (bit_len - 1) % 8 + 1,
+        }
+    }
+
+    /// Finish writing by adding a trailer that encodes the exact number of written bits and return the number of bytes written
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: not enough capacity left in the slice backing the stream for the trailer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitTrailer, BitWriteStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    /// stream.write_int(0b101u8, 3)?;
+    /// assert_eq!(stream.finish_with_trailer(BitTrailer::LengthByte)?, 2);
+    /// assert_eq!(data, [0b1010_0000, 3]);
+    ///
+    /// let mut buffer = BitReadBuffer::new(&data, BigEndian);
+    /// buffer.strip_trailer(BitTrailer::LengthByte)?;
+    /// assert_eq!(buffer.bit_len(), 3);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn finish_with_trailer(mut self, trailer: BitTrailer) -> Result<usize> {
+        match trailer {
+            BitTrailer::StopBit => {
+                self.write_bool(true)?;
+                self.align();
+            }
+            BitTrailer::LengthByte => {
+                let last_byte_bits = self.last_byte_bits();
+                self.align();
+                self.write_int(last_byte_bits as u8, 8)?;
+            }
+        }
+        Ok(self.byte_len())
+    }
+}
+
+impl<E: Endianness> BitReadBuffer<'_, E> {
+    /// Remove the trailer written by [`BitWriteStream::finish_with_trailer`], truncating the buffer to the exact number of written bits
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::InvalidTrailer`]: the buffer doesn't end with a valid trailer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BitTrailer, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let data = [0b0001_0101];
+    /// let mut buffer = BitReadBuffer::new(&data, LittleEndian);
+    /// buffer.strip_trailer(BitTrailer::StopBit)?;
+    /// assert_eq!(buffer.bit_len(), 4);
+    /// assert_eq!(BitReadStream::new(buffer).read_int::<u8>(4)?, 0b0101);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn strip_trailer(&mut self, trailer: BitTrailer) -> Result<()> {
+        let bit_len = self.bit_len();
+        let invalid = BitError::InvalidTrailer { trailer };
+        let data_len = match trailer {
+            BitTrailer::StopBit => {
+                let last_byte = bit_len.saturating_sub(8)..bit_len;
+                let stop_bit = last_byte
+                    .rev()
+                    .find(|&pos| self.read_bool(pos).unwrap_or(false));
+                stop_bit.ok_or(invalid)?
+            }
+            BitTrailer::LengthByte => {
+                if bit_len < 8 {
+                    return Err(invalid);
+                }
+                match self.read_int::<u8>(bit_len - 8, 8)? as usize {
+                    0 if bit_len == 8 => 0,
+                    last_byte_bits @ 1..=8 if bit_len >= 16 => bit_len - 16 + last_byte_bits,
+                    _ => return Err(invalid),
+                }
+            }
+        };
+        self.truncate(data_len)
+    }
+}
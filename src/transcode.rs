@@ -0,0 +1,72 @@
+//! Re-encoding of data between byte orders
+use crate::{BitRead, BitReadStream, BitWrite, BitWriteStream, Endianness, Result};
+
+/// Read a value from a stream and write it in another endianness
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{transcode, BigEndian, BitReadBuffer, BitReadStream, LittleEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let bytes = vec![0x34, 0x12, 0x78, 0x56];
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+/// let converted = transcode::<(u16, u16), _, _>(&mut stream, BigEndian)?;
+/// assert_eq!(converted, [0x12, 0x34, 0x56, 0x78]);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub fn transcode<'a, T, From, To>(
+    stream: &mut BitReadStream<'a, From>,
+    endianness: To,
+) -> Result<Vec<u8>>
+where
+    T: BitRead<'a, From> + BitWrite<To>,
+    From: Endianness,
+    To: Endianness,
+{
+    let mut data = Vec::new();
+    let mut output = BitWriteStream::new(&mut data, endianness);
+    output.write(&stream.read::<T>()?)?;
+    Ok(data)
+}
+
+/// Read values from a stream until it's empty and write them in another endianness
+///
+/// Stops early when reading a value doesn't consume any bits, leaving the remaining bits in the stream.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{transcode_all, BigEndian, BitReadBuffer, BitReadStream, LittleEndian, Result};
+/// #
+/// # fn main() -> Result<()> {
+/// let bytes = vec![0x34, 0x12, 0x78, 0x56];
+/// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+/// let converted = transcode_all::<u16, _, _>(&mut stream, BigEndian)?;
+/// assert_eq!(converted, [0x12, 0x34, 0x56, 0x78]);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+pub fn transcode_all<'a, T, From, To>(
+    stream: &mut BitReadStream<'a, From>,
+    endianness: To,
+) -> Result<Vec<u8>>
+where
+    T: BitRead<'a, From> + BitWrite<To>,
+    From: Endianness,
+    To: Endianness,
+{
+    let mut data = Vec::new();
+    let mut output = BitWriteStream::new(&mut data, endianness);
+    while stream.bits_left() > 0 {
+        let start = stream.pos();
+        output.write(&stream.read::<T>()?)?;
+        if stream.pos() == start {
+            break;
+        }
+    }
+    Ok(data)
+}
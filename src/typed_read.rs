@@ -0,0 +1,93 @@
+//! Non-generic shorthands for reading integers and floats, avoiding the turbofish in large decoders
+use crate::{BitReadStream, Endianness, Result};
+use std::mem::size_of;
+
+macro_rules! impl_typed_read_int {
+    ($($type:ty => $read:ident, $read_bits:ident;)*) => {
+        impl<'a, E: Endianness> BitReadStream<'a, E> {
+            $(
+                #[doc = concat!("Read a full width `", stringify!($type), "`, the same as `read_int::<", stringify!($type), ">(", stringify!($type), "::BITS)`")]
+                ///
+                /// # Errors
+                ///
+                /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+                ///
+                /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+                #[inline]
+                pub fn $read(&mut self) -> Result<$type> {
+                    self.read_int::<$type>(size_of::<$type>() * 8)
+                }
+
+                #[doc = concat!("Read a `", stringify!($type), "` from `count` bits, the same as `read_int::<", stringify!($type), ">(count)`")]
+                ///
+                /// # Errors
+                ///
+                /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+                #[doc = concat!("- [`ReadError::TooManyBits`]: `count` is larger than `", stringify!($type), "::BITS`")]
+                ///
+                /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+                /// [`ReadError::TooManyBits`]: enum.ReadError.html#variant.TooManyBits
+                #[inline]
+                pub fn $read_bits(&mut self, count: usize) -> Result<$type> {
+                    self.read_int::<$type>(count)
+                }
+            )*
+        }
+    };
+}
+
+impl_typed_read_int! {
+    u8 => read_u8, read_u8_bits;
+    u16 => read_u16, read_u16_bits;
+    u32 => read_u32, read_u32_bits;
+    u64 => read_u64, read_u64_bits;
+    u128 => read_u128, read_u128_bits;
+    i8 => read_i8, read_i8_bits;
+    i16 => read_i16, read_i16_bits;
+    i32 => read_i32, read_i32_bits;
+    i64 => read_i64, read_i64_bits;
+    i128 => read_i128, read_i128_bits;
+}
+
+impl<'a, E: Endianness> BitReadStream<'a, E> {
+    /// Read an `f32`, the same as `read_float::<f32>()`
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = [0x12, 0x34, 0b1010_0000, 0x3f, 0xc0, 0x00, 0x00];
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    /// assert_eq!(stream.read_u16()?, 0x1234);
+    /// assert_eq!(stream.read_u8_bits(3)?, 0b101);
+    /// assert_eq!(stream.read_i8_bits(5)?, 0);
+    /// assert_eq!(stream.read_f32()?, 1.5);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    #[inline]
+    pub fn read_f32(&mut self) -> Result<f32> {
+        self.read_float::<f32>()
+    }
+
+    /// Read an `f64`, the same as `read_float::<f64>()`
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::NotEnoughData`]: not enough bits available in the stream
+    ///
+    /// [`ReadError::NotEnoughData`]: enum.ReadError.html#variant.NotEnoughData
+    #[inline]
+    pub fn read_f64(&mut self) -> Result<f64> {
+        self.read_float::<f64>()
+    }
+}
@@ -0,0 +1,28 @@
+//! Zig-zag and variable length integer encoding helpers
+
+/// Map signed integers to unsigned integers so that values close to zero get small encodings
+#[inline]
+pub(crate) fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[inline]
+pub(crate) fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// The number of bits used to encode `value` as varint, 7 bits of the value for every 8 bits written
+#[inline]
+pub(crate) fn varint_bit_size(value: u64) -> usize {
+    let value_bits = (u64::BITS - value.leading_zeros()).max(1) as usize;
+    (value_bits + 6) / 7 * 8
+}
+
+/// The zig-zag encoded differences between consecutive values, starting from 0
+pub(crate) fn zigzag_deltas(values: &[u64]) -> impl Iterator<Item = u64> + '_ {
+    let previous = std::iter::once(0).chain(values.iter().copied());
+    values
+        .iter()
+        .zip(previous)
+        .map(|(value, previous)| zigzag_encode(value.wrapping_sub(previous) as i64))
+}
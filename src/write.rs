@@ -1,5 +1,9 @@
-use crate::{BitReadStream, BitWriteStream, Endianness, Result};
+use crate::{BitError, BitReadStream, BitWriteStream, CheckedWrite, Endianness, Result};
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::mem::size_of;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -82,20 +86,89 @@ use std::sync::Arc;
 pub trait BitWrite<E: Endianness> {
     /// Write the type to stream
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()>;
+
+    /// Write the type to a stream that has been checked to have room for it, see [`BitWriteStream::check_write`]
+    ///
+    /// Implementations can use the unchecked write methods of the stream for the checked bits,
+    /// the default implementation uses [`write`](BitWrite::write).
+    #[inline]
+    fn write_unchecked(
+        &self,
+        stream: &mut BitWriteStream<E>,
+        _checked: CheckedWrite,
+    ) -> Result<()> {
+        self.write(stream)
+    }
+
+    /// The number of bits that will be written or None if the number of bits depends on the value
+    #[inline]
+    fn write_bit_size() -> Option<usize>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Write a slice of the type to a stream that has been checked to have room for all elements
+    ///
+    /// Types can override this to write the slice at once instead of element by element.
+    #[doc(hidden)]
+    #[inline]
+    fn write_slice_unchecked(
+        items: &[Self],
+        stream: &mut BitWriteStream<E>,
+        checked: CheckedWrite,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        for item in items {
+            item.write_unchecked(stream, checked)?;
+        }
+        Ok(())
+    }
 }
 
 macro_rules! impl_write_int {
-    ($type:ty) => {
+    ($type:ty $(, { $($extra:tt)* })?) => {
         impl<E: Endianness> BitWrite<E> for $type {
             #[inline]
             fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
                 stream.write_int::<$type>(*self, <$type>::BITS as usize)
             }
+
+            #[inline]
+            fn write_unchecked(
+                &self,
+                stream: &mut BitWriteStream<E>,
+                checked: CheckedWrite,
+            ) -> Result<()> {
+                stream.write_int_unchecked::<$type>(*self, <$type>::BITS as usize, checked);
+                Ok(())
+            }
+
+            #[inline]
+            fn write_bit_size() -> Option<usize> {
+                Some(<$type>::BITS as usize)
+            }
+
+            $($($extra)*)?
         }
     };
 }
 
-impl_write_int!(u8);
+impl_write_int!(u8, {
+    /// Copy the bytes at once instead of writing them one by one
+    #[inline]
+    fn write_slice_unchecked(
+        items: &[u8],
+        stream: &mut BitWriteStream<E>,
+        checked: CheckedWrite,
+    ) -> Result<()> {
+        stream.write_bytes_unchecked(items, checked);
+        Ok(())
+    }
+});
 impl_write_int!(u16);
 impl_write_int!(u32);
 impl_write_int!(u64);
@@ -111,6 +184,17 @@ impl<E: Endianness> BitWrite<E> for f32 {
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
         stream.write_float::<f32>(*self)
     }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        stream.write_float_unchecked::<f32>(*self, checked);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        Some(size_of::<f32>() * 8)
+    }
 }
 
 impl<E: Endianness> BitWrite<E> for f64 {
@@ -118,6 +202,17 @@ impl<E: Endianness> BitWrite<E> for f64 {
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
         stream.write_float::<f64>(*self)
     }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        stream.write_float_unchecked::<f64>(*self, checked);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        Some(size_of::<f64>() * 8)
+    }
 }
 
 impl<E: Endianness> BitWrite<E> for bool {
@@ -125,6 +220,17 @@ impl<E: Endianness> BitWrite<E> for bool {
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
         stream.write_bool(*self)
     }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        stream.write_bool_unchecked(*self, checked);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        Some(1)
+    }
 }
 
 impl<E: Endianness> BitWrite<E> for str {
@@ -141,6 +247,22 @@ impl<E: Endianness> BitWrite<E> for String {
     }
 }
 
+/// Write the bytes of the C string followed by the null terminator
+impl<E: Endianness> BitWrite<E> for CStr {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write_string_bytes(self.to_bytes(), None)
+    }
+}
+
+/// Write the bytes of the C string followed by the null terminator
+impl<E: Endianness> BitWrite<E> for CString {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        self.as_c_str().write(stream)
+    }
+}
+
 impl<E: Endianness> BitWrite<E> for BitReadStream<'_, E> {
     #[inline]
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
@@ -148,13 +270,42 @@ impl<E: Endianness> BitWrite<E> for BitReadStream<'_, E> {
     }
 }
 
+/// Write the elements in order
+///
+/// `[u8; N]` is written as raw bytes, with the same result as writing the `u8`s one by one,
+/// the endianness never changes the order of the bytes in the array.
 impl<E: Endianness, T: BitWrite<E>, const N: usize> BitWrite<E> for [T; N] {
     #[inline]
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
-        for element in self.iter() {
-            stream.write(element)?;
+        match Self::write_bit_size() {
+            Some(size) => {
+                let checked = stream.check_write(size)?;
+                self.write_unchecked(stream, checked)
+            }
+            None => {
+                for element in self.iter() {
+                    stream.write(element)?;
+                }
+                Ok(())
+            }
         }
-        Ok(())
+    }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        T::write_slice_unchecked(self, stream, checked)
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        T::write_bit_size().map(|size| size * N)
+    }
+}
+
+impl<T: BitWrite<E> + ?Sized, E: Endianness> BitWrite<E> for &T {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        (**self).write(stream)
     }
 }
 
@@ -163,6 +314,16 @@ impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Box<T> {
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
         stream.write(self.as_ref())
     }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        stream.write_unchecked(self.as_ref(), checked)
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        T::write_bit_size()
+    }
 }
 
 impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Rc<T> {
@@ -170,6 +331,16 @@ impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Rc<T> {
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
         stream.write(self.as_ref())
     }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        stream.write_unchecked(self.as_ref(), checked)
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        T::write_bit_size()
+    }
 }
 
 impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Arc<T> {
@@ -177,18 +348,123 @@ impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Arc<T> {
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
         stream.write(self.as_ref())
     }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        stream.write_unchecked(self.as_ref(), checked)
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        T::write_bit_size()
+    }
 }
 
-impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Vec<T> {
+impl<T: BitWrite<E> + Copy, E: Endianness> BitWrite<E> for Cell<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write(&self.get())
+    }
+}
+
+/// Writing panics if the value is currently mutably borrowed, reading a `RefCell` never panics
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for RefCell<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        stream.write(&*self.borrow())
+    }
+}
+
+/// Write all elements back-to-back, without a length prefix
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for [T] {
     #[inline]
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        match T::write_bit_size().and_then(|size| size.checked_mul(self.len())) {
+            Some(size) => {
+                let checked = stream.check_write(size)?;
+                self.write_unchecked(stream, checked)
+            }
+            None => {
+                for item in self {
+                    stream.write(item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
         for item in self {
-            stream.write(item)?;
+            stream.write_unchecked(item, checked)?;
         }
         Ok(())
     }
 }
 
+/// Write all elements back-to-back, without a length prefix
+impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Vec<T> {
+    #[inline]
+    fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+        self.as_slice().write(stream)
+    }
+
+    #[inline]
+    fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+        self.as_slice().write_unchecked(stream, checked)
+    }
+}
+
+macro_rules! impl_write_slice_pointer {
+    ($pointer:ident) => {
+        /// Write all elements back-to-back, without a length prefix
+        impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for $pointer<[T]> {
+            #[inline]
+            fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+                (**self).write(stream)
+            }
+
+            #[inline]
+            fn write_unchecked(
+                &self,
+                stream: &mut BitWriteStream<E>,
+                checked: CheckedWrite,
+            ) -> Result<()> {
+                (**self).write_unchecked(stream, checked)
+            }
+        }
+
+        /// Write all elements back-to-back, the size is the number of elements
+        ///
+        /// Fails with [`BitError::ElementCountMismatch`] if the number of elements doesn't match the size.
+        impl<T: BitWrite<E>, E: Endianness> BitWriteSized<E> for $pointer<[T]> {
+            #[inline]
+            fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+                (**self).write_sized(stream, len)
+            }
+
+            #[inline]
+            fn write_sized_unchecked(
+                &self,
+                stream: &mut BitWriteStream<E>,
+                len: usize,
+                checked: CheckedWrite,
+            ) -> Result<()> {
+                (**self).write_sized_unchecked(stream, len, checked)
+            }
+
+            #[inline]
+            fn write_bit_size_sized(len: usize) -> Option<usize> {
+                T::write_bit_size()?.checked_mul(len)
+            }
+        }
+    };
+}
+
+impl_write_slice_pointer!(Box);
+impl_write_slice_pointer!(Rc);
+impl_write_slice_pointer!(Arc);
+
 impl<T: BitWrite<E>, E: Endianness> BitWrite<E> for Option<T> {
     #[inline]
     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
@@ -215,6 +491,17 @@ macro_rules! impl_write_tuple {
                 $(self.$i.write(stream)?;)*
                 Ok(())
             }
+
+            #[inline]
+            fn write_unchecked(&self, stream: &mut BitWriteStream<E>, checked: CheckedWrite) -> Result<()> {
+                $(self.$i.write_unchecked(stream, checked)?;)*
+                Ok(())
+            }
+
+            #[inline]
+            fn write_bit_size() -> Option<usize> {
+                Some(0 $(+ <$type as BitWrite<E>>::write_bit_size()?)*)
+            }
         }
     };
 }
@@ -223,6 +510,19 @@ impl_write_tuple!(0: T1, 1: T2);
 impl_write_tuple!(0: T1, 1: T2, 2: T3);
 impl_write_tuple!(0: T1, 1: T2, 2: T3, 3: T4);
 
+/// `PhantomData` doesn't contain any data, nothing is written
+impl<E: Endianness, T: ?Sized> BitWrite<E> for PhantomData<T> {
+    #[inline]
+    fn write(&self, _stream: &mut BitWriteStream<E>) -> Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bit_size() -> Option<usize> {
+        Some(0)
+    }
+}
+
 /// Trait for types that can be written to a stream, requiring the size to be configured
 ///
 /// The meaning of the set sized depends on the type being written (e.g, number of bits for integers,
@@ -288,6 +588,29 @@ impl_write_tuple!(0: T1, 1: T2, 2: T3, 3: T4);
 pub trait BitWriteSized<E: Endianness> {
     /// Write the type to stream
     fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()>;
+
+    /// Write the type to a stream that has been checked to have room for it, see [`BitWriteStream::check_write`]
+    ///
+    /// Implementations can use the unchecked write methods of the stream for the checked bits,
+    /// the default implementation uses [`write_sized`](BitWriteSized::write_sized).
+    #[inline]
+    fn write_sized_unchecked(
+        &self,
+        stream: &mut BitWriteStream<E>,
+        len: usize,
+        _checked: CheckedWrite,
+    ) -> Result<()> {
+        self.write_sized(stream, len)
+    }
+
+    /// The number of bits that will be written for a size or None if the number of bits depends on the value
+    #[inline]
+    fn write_bit_size_sized(_len: usize) -> Option<usize>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 impl<E: Endianness> BitWriteSized<E> for str {
@@ -304,6 +627,29 @@ impl<E: Endianness> BitWriteSized<E> for String {
     }
 }
 
+/// Write the C string padded with null bytes to `len` bytes, there has to be room for at least one null byte
+impl<E: Endianness> BitWriteSized<E> for CStr {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        let bytes = self.to_bytes_with_nul();
+        if bytes.len() > len {
+            return Err(BitError::StringToLong {
+                string_length: bytes.len(),
+                requested_length: len,
+            });
+        }
+        stream.write_string_bytes(self.to_bytes(), Some(len))
+    }
+}
+
+/// Write the C string padded with null bytes to `len` bytes, there has to be room for at least one null byte
+impl<E: Endianness> BitWriteSized<E> for CString {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        self.as_c_str().write_sized(stream, len)
+    }
+}
+
 macro_rules! impl_write_sized_int {
     ($type:ty) => {
         impl<E: Endianness> BitWriteSized<E> for $type {
@@ -311,6 +657,23 @@ macro_rules! impl_write_sized_int {
             fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
                 stream.write_int::<$type>(*self, len)
             }
+
+            #[inline]
+            fn write_sized_unchecked(
+                &self,
+                stream: &mut BitWriteStream<E>,
+                len: usize,
+                checked: CheckedWrite,
+            ) -> Result<()> {
+                stream.write_int_unchecked::<$type>(*self, len, checked);
+                Ok(())
+            }
+
+            #[inline]
+            fn write_bit_size_sized(len: usize) -> Option<usize> {
+                // too large sizes are left to `write_sized` to report
+                (len <= size_of::<$type>() * 8).then_some(len)
+            }
         }
     };
 }
@@ -336,6 +699,7 @@ impl<E: Endianness> BitWriteSized<E> for BitReadStream<'_, E> {
     }
 }
 
+/// The size is applied to every element of the array
 impl<E: Endianness, T: BitWriteSized<E>, const N: usize> BitWriteSized<E> for [T; N] {
     #[inline]
     fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
@@ -346,6 +710,85 @@ impl<E: Endianness, T: BitWriteSized<E>, const N: usize> BitWriteSized<E> for [T
     }
 }
 
+macro_rules! impl_write_sized_tuple {
+    ($($i:tt: $type:ident),*) => {
+        /// The size is applied to every element of the tuple
+        impl<E: Endianness, $($type: BitWriteSized<E>),*> BitWriteSized<E> for ($($type),*) {
+            #[inline]
+            fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+                $(self.$i.write_sized(stream, len)?;)*
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_write_sized_tuple!(0: T1, 1: T2);
+impl_write_sized_tuple!(0: T1, 1: T2, 2: T3);
+impl_write_sized_tuple!(0: T1, 1: T2, 2: T3, 3: T4);
+
+/// Write all elements back-to-back, the size is the number of elements
+///
+/// Fails with [`BitError::ElementCountMismatch`] if the number of elements doesn't match the size.
+impl<T: BitWrite<E>, E: Endianness> BitWriteSized<E> for [T] {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        check_element_count(self.len(), len)?;
+        self.write(stream)
+    }
+
+    #[inline]
+    fn write_sized_unchecked(
+        &self,
+        stream: &mut BitWriteStream<E>,
+        len: usize,
+        checked: CheckedWrite,
+    ) -> Result<()> {
+        check_element_count(self.len(), len)?;
+        self.write_unchecked(stream, checked)
+    }
+}
+
+/// Write all elements back-to-back, the size is the number of elements
+///
+/// Fails with [`BitError::ElementCountMismatch`] if the number of elements doesn't match the size.
+impl<T: BitWrite<E>, E: Endianness> BitWriteSized<E> for Vec<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        self.as_slice().write_sized(stream, len)
+    }
+
+    #[inline]
+    fn write_sized_unchecked(
+        &self,
+        stream: &mut BitWriteStream<E>,
+        len: usize,
+        checked: CheckedWrite,
+    ) -> Result<()> {
+        self.as_slice().write_sized_unchecked(stream, len, checked)
+    }
+
+    #[inline]
+    fn write_bit_size_sized(len: usize) -> Option<usize> {
+        T::write_bit_size()?.checked_mul(len)
+    }
+}
+
+fn check_element_count(actual: usize, expected: usize) -> Result<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(BitError::ElementCountMismatch { expected, actual })
+    }
+}
+
+impl<T: BitWriteSized<E> + ?Sized, E: Endianness> BitWriteSized<E> for &T {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        (**self).write_sized(stream, len)
+    }
+}
+
 impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for Box<T> {
     #[inline]
     fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
@@ -367,6 +810,21 @@ impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for Arc<T> {
     }
 }
 
+impl<T: BitWriteSized<E> + Copy, E: Endianness> BitWriteSized<E> for Cell<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_sized(&self.get(), len)
+    }
+}
+
+/// Writing panics if the value is currently mutably borrowed, reading a `RefCell` never panics
+impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for RefCell<T> {
+    #[inline]
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
+        stream.write_sized(&*self.borrow(), len)
+    }
+}
+
 impl<T: BitWriteSized<E>, E: Endianness> BitWriteSized<E> for Option<T> {
     #[inline]
     fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> Result<()> {
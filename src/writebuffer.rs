@@ -1,14 +1,43 @@
-use crate::Endianness;
-use std::cmp::min;
+use crate::{BitError, Endianness, Result};
+use std::cmp::{max, min};
+use std::collections::TryReserveError;
 use std::marker::PhantomData;
 use std::ops::{Index, IndexMut, Range};
 
+/// How a [`BitWriteStream`](crate::BitWriteStream) writing into a `Vec` grows the `Vec` when it runs out of capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrowthStrategy {
+    /// Let the `Vec` grow itself, doubling its capacity
+    #[default]
+    Double,
+    /// Grow by at least the given number of bytes at a time
+    Chunk(usize),
+    /// Only grow by the bytes that are being written
+    Exact,
+}
+
 enum WriteData<'a> {
     Vec(&'a mut Vec<u8>),
     Slice { data: &'a mut [u8], length: usize },
 }
 
 impl<'a> WriteData<'a> {
+    /// Make room for `additional` more bytes, growing a `Vec` using `growth`
+    fn reserve(
+        &mut self,
+        additional: usize,
+        growth: GrowthStrategy,
+    ) -> std::result::Result<(), TryReserveError> {
+        match self {
+            WriteData::Vec(vec) if vec.capacity() - vec.len() < additional => match growth {
+                GrowthStrategy::Double => vec.try_reserve(additional),
+                GrowthStrategy::Chunk(chunk) => vec.try_reserve_exact(max(additional, chunk)),
+                GrowthStrategy::Exact => vec.try_reserve_exact(additional),
+            },
+            _ => Ok(()),
+        }
+    }
+
     fn pop(&mut self) -> Option<u8> {
         match self {
             WriteData::Vec(vec) => vec.pop(),
@@ -42,6 +71,20 @@ impl<'a> WriteData<'a> {
         }
     }
 
+    fn truncate(&mut self, len: usize) {
+        match self {
+            WriteData::Vec(vec) => vec.truncate(len),
+            WriteData::Slice { length, .. } => *length = min(*length, len),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            WriteData::Vec(vec) => vec.len(),
+            WriteData::Slice { length, .. } => *length,
+        }
+    }
+
     fn last_mut(&mut self) -> Option<&mut u8> {
         match self {
             WriteData::Vec(vec) => vec.last_mut(),
@@ -94,6 +137,7 @@ impl<'a> IndexMut<Range<usize>> for WriteData<'a> {
 pub struct WriteBuffer<'a, E: Endianness> {
     bit_len: usize,
     bytes: WriteData<'a>,
+    growth: GrowthStrategy,
     endianness: PhantomData<E>,
 }
 
@@ -102,9 +146,36 @@ impl<'a, E: Endianness> WriteBuffer<'a, E> {
         WriteBuffer {
             bit_len: 0,
             bytes: WriteData::Vec(bytes),
+            growth: GrowthStrategy::default(),
             endianness: PhantomData,
         }
     }
+    /// Resume writing at `bit_offset` in `bytes`, dropping any data after it
+    pub fn new_at(bytes: &'a mut Vec<u8>, bit_offset: usize, _endianness: E) -> Result<Self> {
+        if bit_offset > bytes.len() * 8 {
+            return Err(BitError::IndexOutOfBounds {
+                pos: bit_offset,
+                size: bytes.len() * 8,
+            });
+        }
+        bytes.truncate((bit_offset + 7) / 8);
+        let partial_bits = bit_offset & 7;
+        if let (Some(last), true) = (bytes.last_mut(), partial_bits > 0) {
+            // clear any stray bits after the offset in the partially written byte
+            if E::is_le() {
+                *last &= (1 << partial_bits) - 1;
+            } else {
+                *last &= !(0xff >> partial_bits);
+            }
+        }
+        Ok(WriteBuffer {
+            bit_len: bit_offset,
+            bytes: WriteData::Vec(bytes),
+            growth: GrowthStrategy::default(),
+            endianness: PhantomData,
+        })
+    }
+
     pub fn for_slice(bytes: &'a mut [u8], _endianness: E) -> Self {
         WriteBuffer {
             bit_len: 0,
@@ -112,15 +183,123 @@ impl<'a, E: Endianness> WriteBuffer<'a, E> {
                 data: bytes,
                 length: 0,
             },
+            growth: GrowthStrategy::default(),
+            endianness: PhantomData,
+        }
+    }
+
+    /// Borrow the data as a buffer with a different endianness
+    ///
+    /// Once the borrowed buffer is done, the written length has to be copied back using [`set_lengths`](WriteBuffer::set_lengths).
+    pub fn reborrow<E2: Endianness>(&mut self) -> WriteBuffer<'_, E2> {
+        let bytes = match &mut self.bytes {
+            WriteData::Vec(vec) => WriteData::Vec(vec),
+            WriteData::Slice { data, length } => WriteData::Slice {
+                data,
+                length: *length,
+            },
+        };
+        WriteBuffer {
+            bit_len: self.bit_len,
+            bytes,
+            growth: self.growth,
             endianness: PhantomData,
         }
     }
 
+    /// The number of written bits and the number of used bytes in a slice buffer
+    pub fn lengths(&self) -> (usize, usize) {
+        match &self.bytes {
+            WriteData::Vec(_) => (self.bit_len, 0),
+            WriteData::Slice { length, .. } => (self.bit_len, *length),
+        }
+    }
+
+    /// Set the lengths as returned by [`lengths`](WriteBuffer::lengths) of a buffer borrowed from this one
+    pub fn set_lengths(&mut self, (bit_len, byte_len): (usize, usize)) {
+        self.bit_len = bit_len;
+        if let WriteData::Slice { length, .. } = &mut self.bytes {
+            *length = byte_len;
+        }
+    }
+
     /// The number of written bits in the buffer
     pub fn bit_len(&self) -> usize {
         self.bit_len
     }
 
+    /// The bytes written into the buffer, the last byte might only be partially written
+    pub fn written_bytes(&self) -> &[u8] {
+        let byte_len = (self.bit_len + 7) / 8;
+        match &self.bytes {
+            WriteData::Vec(vec) => &vec[vec.len() - byte_len..],
+            WriteData::Slice { data, length } => &data[*length - byte_len..*length],
+        }
+    }
+
+    /// The number of bits that can still be written, or `None` if the buffer can grow
+    pub fn remaining_capacity_bits(&self) -> Option<usize> {
+        match &self.bytes {
+            WriteData::Vec(_) => None,
+            WriteData::Slice { data, .. } => Some(data.len() * 8 - self.bit_len),
+        }
+    }
+
+    /// The number of bits the buffer can hold without reallocating, including the written bits
+    pub fn capacity_bits(&self) -> usize {
+        match &self.bytes {
+            WriteData::Vec(vec) => {
+                // the buffer might be appending to existing data in the vec
+                let data_start = vec.len() - (self.bit_len + 7) / 8;
+                (vec.capacity() - data_start).saturating_mul(8)
+            }
+            WriteData::Slice { data, .. } => data.len().saturating_mul(8),
+        }
+    }
+
+    /// Set how the buffer grows when writing into a `Vec`
+    pub fn set_growth_strategy(&mut self, growth: GrowthStrategy) {
+        self.growth = growth;
+    }
+
+    /// Grow the `Vec` before writing `bytes` more bytes, unless it is left to grow itself
+    #[inline]
+    fn grow(&mut self, bytes: usize) {
+        if self.growth != GrowthStrategy::Double {
+            // on allocation failure the `Vec` tries to grow itself when writing, which aborts
+            let _ = self.bytes.reserve(bytes, self.growth);
+        }
+    }
+
+    /// Check that `count` more bits fit in the buffer
+    pub fn check_write(&self, count: usize) -> Result<()> {
+        let capacity_bits = match &self.bytes {
+            WriteData::Vec(_) => usize::MAX,
+            WriteData::Slice { data, .. } => data.len().saturating_mul(8),
+        };
+        match self.bit_len.checked_add(count) {
+            Some(attempted_bits) if attempted_bits <= capacity_bits => Ok(()),
+            attempted_bits => Err(BitError::WriteOverflow {
+                capacity_bits,
+                attempted_bits: attempted_bits.unwrap_or(usize::MAX),
+            }),
+        }
+    }
+
+    /// Make room for writing `count` more bits without reallocating
+    ///
+    /// Fails if the memory for the bits can't be allocated
+    pub fn reserve(&mut self, count: usize) -> Result<()> {
+        let bytes = (self.bit_len % 8).saturating_add(count).saturating_add(7) / 8;
+        if self.bytes.reserve(bytes, self.growth).is_err() {
+            return Err(BitError::WriteOverflow {
+                capacity_bits: self.capacity_bits(),
+                attempted_bits: self.bit_len.saturating_add(count),
+            });
+        }
+        Ok(())
+    }
+
     pub fn push_non_fit_bits<I>(&mut self, bits: I, count: usize)
     where
         I: ExactSizeIterator,
@@ -147,7 +326,12 @@ impl<'a, E: Endianness> WriteBuffer<'a, E> {
 
         let bit_offset = self.bit_len & 7;
 
-        debug_assert!(count <= usize::BITS as usize - bit_offset);
+        bounds_assert!(
+            count <= usize::BITS as usize - bit_offset,
+            "pushing {} bits at bit offset {} doesn't fit in a usize",
+            count,
+            bit_offset
+        );
 
         let last_written_byte = if bit_offset > 0 {
             self.bytes.pop().unwrap_or(0)
@@ -155,6 +339,7 @@ impl<'a, E: Endianness> WriteBuffer<'a, E> {
             0
         };
         let merged_byte_count = (count + bit_offset + 7) / 8;
+        self.grow(merged_byte_count);
 
         if E::is_le() {
             let merged = last_written_byte as usize | bits << bit_offset;
@@ -169,28 +354,91 @@ impl<'a, E: Endianness> WriteBuffer<'a, E> {
         self.bit_len += count;
     }
 
+    /// Overwrite `count` previously written zero bits starting at `pos` with `bits`, in the bit order of the buffer
     pub fn set_at(&mut self, pos: usize, bits: u64, count: usize) {
-        debug_assert!(count < 64 - 8);
+        bounds_assert!(
+            count <= 64 && pos + count <= self.bit_len,
+            "setting {} bits at {} outside of the {} written bits",
+            count,
+            pos,
+            self.bit_len
+        );
+        if count == 0 {
+            return;
+        }
 
         let bit_offset = pos & 7;
-        let byte_pos = pos / 8;
+        if count + bit_offset > 64 {
+            // split into two halves that both fit into a u64 with the bit offset
+            let half = count / 2;
+            let (first, second) = if E::is_le() {
+                (bits & (u64::MAX >> (64 - half)), bits >> half)
+            } else {
+                (bits >> (count - half), bits)
+            };
+            self.set_at(pos, first, half);
+            self.set_at(pos + half, second, count - half);
+            return;
+        }
+
+        // ensure there are no stray bits
+        let bits = bits & (u64::MAX >> (64 - count));
+
+        // the buffer might be appending to existing data in the vec
+        let data_start = self.bytes.len() - (self.bit_len + 7) / 8;
+        let byte_pos = data_start + pos / 8;
         let byte_count = (count + bit_offset + 7) / 8;
 
         let mut old = [0; 8];
         old[0..byte_count].copy_from_slice(&self.bytes[byte_pos..byte_pos + byte_count]);
 
-        let old = u64::from_le_bytes(old);
-        let merged = old | (bits << bit_offset);
-        let merged = merged.to_le_bytes();
+        let merged = if E::is_le() {
+            let old = u64::from_le_bytes(old);
+            (old | (bits << bit_offset)).to_le_bytes()
+        } else {
+            let old = u64::from_be_bytes(old);
+            (old | (bits << (64 - bit_offset - count))).to_be_bytes()
+        };
         self.bytes[byte_pos..byte_pos + byte_count].copy_from_slice(&merged[0..byte_count]);
     }
 
     pub fn extends_from_slice(&mut self, slice: &[u8]) {
-        debug_assert_eq!(0, self.bit_len & 7);
+        bounds_assert!(
+            self.bit_len & 7 == 0,
+            "unaligned write of {} bytes at {}",
+            slice.len(),
+            self.bit_len
+        );
+        self.grow(slice.len());
         self.bytes.extend_from_slice(slice);
         self.bit_len += slice.len() * 8
     }
 
+    /// Discard all bits written after `bit_len`
+    pub fn truncate(&mut self, bit_len: usize) {
+        if bit_len >= self.bit_len {
+            return;
+        }
+
+        // the buffer might be appending to existing data in the vec
+        let data_start = self.bytes.len() - (self.bit_len + 7) / 8;
+        self.bytes.truncate(data_start + (bit_len + 7) / 8);
+        self.bit_len = bit_len;
+
+        // clear the discarded bits in the last partial byte
+        let bit_offset = bit_len & 7;
+        if bit_offset > 0 {
+            let mask = if E::is_le() {
+                (1u8 << bit_offset) - 1
+            } else {
+                !(0xFFu8 >> bit_offset)
+            };
+            if let Some(last) = self.bytes.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+
     pub fn push_bool(&mut self, val: bool) {
         let val = val as u8;
         let bit_offset = self.bit_len() % 8;
@@ -200,6 +448,7 @@ impl<'a, E: Endianness> WriteBuffer<'a, E> {
             7 - bit_offset
         };
         if bit_offset == 0 {
+            self.grow(1);
             self.bytes.push(val << shift);
         } else {
             *self.bytes.last_mut().unwrap() |= val << shift;
@@ -1,17 +1,50 @@
-use num_traits::{Float, PrimInt};
+use crate::num_traits::{Float, PrimInt};
 use std::cmp::min;
+use std::collections::HashMap;
 use std::mem::size_of;
 use std::ops::{BitOrAssign, BitXor};
 
-use crate::endianness::Endianness;
-use crate::num_traits::{IsSigned, SplitFitUsize, UncheckedPrimitiveFloat, UncheckedPrimitiveInt};
-use crate::writebuffer::WriteBuffer;
-use crate::{BitError, BitReadStream, BitWrite, BitWriteSized, Result};
+use crate::endianness::{swap_byte_groups, swaps_bytes, BigEndian, Endianness, LittleEndian};
+use crate::num_traits::{
+    canonicalize_float, IsSigned, SplitFitUsize, UncheckedPrimitiveFloat, UncheckedPrimitiveInt,
+};
+use crate::section_stats::SectionStats;
+use crate::size_unit::element_size;
+use crate::varint::{varint_bit_size, zigzag_deltas};
+use crate::writebuffer::{GrowthStrategy, WriteBuffer};
+use crate::{
+    BitError, BitReadBuffer, BitReadStream, BitWrite, BitWriteSized, ByteLen, HasElementCount,
+    Result,
+};
 use std::fmt::Debug;
 
 const USIZE_SIZE: usize = size_of::<usize>();
 const USIZE_BITS: usize = USIZE_SIZE * 8;
 
+/// Pack up to 64 bits into an integer, such that writing the integer writes the bits in order
+pub(crate) fn pack_bits<E: Endianness, I: DoubleEndedIterator<Item = bool>>(bits: I) -> u64 {
+    if E::is_le() {
+        // the first bit written is the least significant bit
+        bits.rev()
+            .fold(0u64, |packed, bit| packed << 1 | bit as u64)
+    } else {
+        bits.fold(0u64, |packed, bit| packed << 1 | bit as u64)
+    }
+}
+
+/// Proof that a stream has been checked to have room for a write
+///
+/// This is returned by [`BitWriteStream::check_write`] and required by the unchecked write methods,
+/// which skip the capacity checks for the bits covered by the check.
+///
+/// The unchecked writes are only valid for the number of bits that were checked, in debug builds
+/// or with the `bounds-debug` feature enabled, writing past that will panic.
+#[derive(Debug, Clone, Copy)]
+pub struct CheckedWrite {
+    /// Position in the buffer up to which writes have been checked
+    limit: usize,
+}
+
 /// Stream that provides an a way to write non bit aligned adata
 ///
 /// # Examples
@@ -36,6 +69,8 @@ where
     E: Endianness,
 {
     buffer: WriteBuffer<'a, E>,
+    sections: Option<SectionStats>,
+    canonical_floats: bool,
 }
 
 impl<'a, E> BitWriteStream<'a, E>
@@ -55,17 +90,191 @@ where
     pub fn new(data: &'a mut Vec<u8>, endianness: E) -> Self {
         BitWriteStream {
             buffer: WriteBuffer::new(data, endianness),
+            sections: None,
+            canonical_floats: false,
         }
     }
 
+    /// Create a new write stream, reserving room for writing `capacity_bits` bits without reallocating
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitbuffer::{BitWriteStream, LittleEndian};
+    ///
+    /// let mut data = Vec::new();
+    /// let stream = BitWriteStream::with_capacity_bits(&mut data, 100, LittleEndian);
+    /// assert!(stream.capacity_bits() >= 100);
+    /// ```
+    pub fn with_capacity_bits(data: &'a mut Vec<u8>, capacity_bits: usize, endianness: E) -> Self {
+        data.reserve_exact((capacity_bits + 7) / 8);
+        BitWriteStream::new(data, endianness)
+    }
+
+    /// Create a write stream that resumes writing at `bit_offset` in previously written data
+    ///
+    /// Any data in `data` after `bit_offset` is discarded, including the remaining bits of a partially
+    /// written byte. The offset is included in the [`bit_len`](BitWriteStream::bit_len) of the stream.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::IndexOutOfBounds`]: `bit_offset` is past the end of `data`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_int(0b101u8, 3)?;
+    /// let bit_len = stream.bit_len();
+    ///
+    /// let mut stream = BitWriteStream::new_at(&mut data, bit_len, LittleEndian)?;
+    /// stream.write_int(0b11111u8, 5)?;
+    /// assert_eq!(8, stream.bit_len());
+    /// assert_eq!(vec![0b11111_101], data);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn new_at(data: &'a mut Vec<u8>, bit_offset: usize, endianness: E) -> Result<Self> {
+        Ok(BitWriteStream {
+            buffer: WriteBuffer::new_at(data, bit_offset, endianness)?,
+            sections: None,
+            canonical_floats: false,
+        })
+    }
+
     /// Create a new write stream
     ///
-    /// Note that the resulting stream will panic when trying to write more data then fits
-    /// in the provided slice.
+    /// Note that the resulting stream will return a [`BitError::WriteOverflow`] error when trying
+    /// to write more data then fits in the provided slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitbuffer::{BitError, BitWriteStream, LittleEndian};
+    ///
+    /// let mut data = [0; 2];
+    /// let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    /// stream.write_int(12u8, 8).unwrap();
+    /// assert_eq!(stream.remaining_capacity_bits(), Some(8));
+    /// assert!(matches!(
+    ///     stream.write_int(12u16, 16),
+    ///     Err(BitError::WriteOverflow { capacity_bits: 16, attempted_bits: 24 })
+    /// ));
+    /// ```
     pub fn from_slice(data: &'a mut [u8], endianness: E) -> Self {
         BitWriteStream {
             buffer: WriteBuffer::for_slice(data, endianness),
+            sections: None,
+            canonical_floats: false,
+        }
+    }
+
+    /// Write to the stream with a different endianness for the duration of `body`
+    ///
+    /// The stream passed to `body` writes to the same data after the bits already written to this stream,
+    /// afterwards this stream continues after the bits written by `body`, even if `body` returns an error.
+    ///
+    /// Since the bits in a byte are written in a different order for little and big endian, switching between them
+    /// requires the stream to be at a byte boundary both when switching and when `body` returns.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotAligned`]: switching between little and big endian at a position that isn't a byte boundary
+    /// - any error returned by `body`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BigEndian, BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_int(0x0201u16, 16)?;
+    /// stream.with_endianness::<BigEndian, _, _, _>(|stream| stream.write_int(0x0102u16, 16))?;
+    /// stream.write_int(0x03u8, 8)?;
+    /// assert_eq!(stream.bit_len(), 40);
+    /// # drop(stream);
+    /// assert_eq!(data, [0x01, 0x02, 0x01, 0x02, 0x03]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn with_endianness<E2, T, Err, F>(&mut self, body: F) -> Result<T, Err>
+    where
+        E2: Endianness,
+        Err: From<BitError>,
+        F: FnOnce(&mut BitWriteStream<'_, E2>) -> Result<T, Err>,
+    {
+        let switches_bit_order = E::is_le() != E2::is_le();
+        if switches_bit_order {
+            self.require_alignment()?;
         }
+        let mut stream = BitWriteStream {
+            buffer: self.buffer.reborrow(),
+            sections: self.sections.take(),
+            canonical_floats: self.canonical_floats,
+        };
+        let result = body(&mut stream);
+        self.sections = stream.sections;
+        let lengths = stream.buffer.lengths();
+        self.buffer.set_lengths(lengths);
+        let result = result?;
+        if switches_bit_order {
+            self.require_alignment()?;
+        }
+        Ok(result)
+    }
+}
+
+impl<'a> BitWriteStream<'a, LittleEndian> {
+    /// Create a new little endian write stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::le(&mut data);
+    /// stream.write_int(0b101u8, 3)?;
+    /// # drop(stream);
+    /// assert_eq!(data, [0b0000_0101]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn le(data: &'a mut Vec<u8>) -> Self {
+        BitWriteStream::new(data, LittleEndian)
+    }
+}
+
+impl<'a> BitWriteStream<'a, BigEndian> {
+    /// Create a new big endian write stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::be(&mut data);
+    /// stream.write_int(0b101u8, 3)?;
+    /// # drop(stream);
+    /// assert_eq!(data, [0b1010_0000]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn be(data: &'a mut Vec<u8>) -> Self {
+        BitWriteStream::new(data, BigEndian)
     }
 }
 
@@ -83,6 +292,190 @@ where
         (self.buffer.bit_len() + 7) / 8
     }
 
+    /// Finish writing and return the number of bytes written and the number of padding bits
+    ///
+    /// When the number of written bits is not a multiple of 8, the last byte is padded with zero bits,
+    /// the number of padding bits can be used to recover the exact number of bits written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_int(3u16, 10)?;
+    /// let (byte_len, pad_bits) = stream.finish();
+    /// assert_eq!(byte_len, 2);
+    /// assert_eq!(pad_bits, 6);
+    /// assert_eq!(data.len(), 2);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn finish(self) -> (usize, usize) {
+        let bit_len = self.bit_len();
+        (self.byte_len(), (8 - bit_len % 8) % 8)
+    }
+
+    /// The number of bits that can still be written to the stream
+    ///
+    /// Returns `None` if the stream writes into a `Vec` that can grow as needed
+    pub fn remaining_capacity_bits(&self) -> Option<usize> {
+        self.buffer.remaining_capacity_bits()
+    }
+
+    /// The number of bits the stream can hold without reallocating, including the bits already written
+    ///
+    /// For a stream writing into a slice, this is the size of the slice.
+    pub fn capacity_bits(&self) -> usize {
+        self.buffer.capacity_bits()
+    }
+
+    /// Make room for writing `count` more bits without reallocating
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: the stream writes into a slice that doesn't have room for `count` more bits,
+    ///   or the memory for the bits can't be allocated
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_int(1u8, 4)?;
+    /// stream.reserve_bits(100)?;
+    /// assert!(stream.capacity_bits() >= 104);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn reserve_bits(&mut self, count: usize) -> Result<()> {
+        self.buffer.check_write(count)?;
+        self.buffer.reserve(count)
+    }
+
+    /// Set how the stream grows the `Vec` it writes into when it runs out of capacity
+    ///
+    /// This has no effect for streams writing into a slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, GrowthStrategy, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.set_growth_strategy(GrowthStrategy::Chunk(64));
+    /// stream.write_int(1u8, 8)?;
+    /// assert!(stream.capacity_bits() >= 64 * 8);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_growth_strategy(&mut self, growth: GrowthStrategy) {
+        self.buffer.set_growth_strategy(growth);
+    }
+
+    /// Write floats in their canonical encoding
+    ///
+    /// When enabled, all NaN values are written as the same quiet NaN and `-0.0` is written as `0.0`,
+    /// making the output reproducible for formats that require canonical encodings.
+    ///
+    /// See [`canonicalize_float`](crate::num_traits::canonicalize_float)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.set_canonical_floats(true);
+    /// stream.write_float(-0.0f32)?;
+    /// assert_eq!(data, [0, 0, 0, 0]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_canonical_floats(&mut self, canonical: bool) {
+        self.canonical_floats = canonical;
+    }
+
+    /// Start recording the number of bits written in every [`section`](BitWriteStream::section)
+    ///
+    /// Sections written before the tracking is started are not recorded.
+    pub fn track_sections(&mut self) {
+        if self.sections.is_none() {
+            self.sections = Some(SectionStats::default());
+        }
+    }
+
+    /// The number of bits written per section, if [`track_sections`](BitWriteStream::track_sections) has been called
+    pub fn section_stats(&self) -> Option<&SectionStats> {
+        self.sections.as_ref()
+    }
+
+    /// Write a labeled section of data
+    ///
+    /// When section tracking is enabled using [`track_sections`](BitWriteStream::track_sections),
+    /// the number of bits written by `write` is added to the statistics of the section.
+    /// Sections can be nested and repeated, the bits are recorded for every repetition of the same
+    /// label under the same parent section. Without tracking enabled this just calls `write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.track_sections();
+    ///
+    /// stream.section("header", |stream| stream.write_int(1u8, 4))?;
+    /// for id in 0..3u16 {
+    ///     stream.section("entities", |stream| {
+    ///         stream.section("id", |stream| stream.write_int(id, 12))?;
+    ///         stream.write_bool(true)
+    ///     })?;
+    /// }
+    ///
+    /// let stats = stream.section_stats().unwrap();
+    /// assert_eq!(stats.get("header").unwrap().bits, 4);
+    /// assert_eq!(stats.get("entities").unwrap().bits, 39);
+    /// assert_eq!(stats.get("entities/id").unwrap().count, 3);
+    /// assert_eq!(
+    ///     stats.to_string(),
+    ///     "header: 4 bits (1 times)\nentities: 39 bits (3 times)\n  id: 36 bits (3 times)\n"
+    /// );
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn section<T, F>(&mut self, label: &str, write: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let start = self.bit_len();
+        if let Some(sections) = self.sections.as_mut() {
+            sections.enter(label);
+        }
+        let result = write(self);
+        let bits = self.bit_len() - start;
+        if let Some(sections) = self.sections.as_mut() {
+            sections.exit(bits);
+        }
+        result
+    }
+
     fn push_non_fit_bits<I>(&mut self, bits: I, count: usize)
     where
         I: ExactSizeIterator,
@@ -130,6 +523,121 @@ where
         }
     }
 
+    /// Check that the stream is at a byte boundary
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotAligned`]: the number of bits written is not a multiple of 8
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitError, BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_int(1u8, 3)?;
+    /// assert!(matches!(
+    ///     stream.require_alignment(),
+    ///     Err(BitError::NotAligned { bit_offset: 3 })
+    /// ));
+    /// stream.align();
+    /// stream.require_alignment()?;
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn require_alignment(&self) -> Result<()> {
+        match self.bit_len() % 8 {
+            0 => Ok(()),
+            bit_offset => Err(BitError::NotAligned { bit_offset }),
+        }
+    }
+
+    /// Assert that the stream is at a byte boundary in debug builds
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the number of bits written is not a multiple of 8
+    #[inline]
+    #[track_caller]
+    pub fn debug_assert_aligned(&self) {
+        debug_assert!(
+            self.bit_len() % 8 == 0,
+            "stream is not byte aligned, {} bits past a byte boundary",
+            self.bit_len() % 8
+        );
+    }
+
+    /// Check that `count` bits can be written to the stream, reserving room for them if the stream writes into a `Vec`
+    ///
+    /// The returned [`CheckedWrite`] allows writing up to `count` bits with the unchecked write methods
+    /// without further capacity checks, which is useful when implementing [`BitWrite`] for types with a fixed size.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadError::WriteOverflow`]: the stream writes into a slice that doesn't have room for `count` more bits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWrite, BitWriteStream, Endianness, LittleEndian, Result};
+    /// #
+    /// struct Rgb {
+    ///     r: u8,
+    ///     g: u8,
+    ///     b: u8,
+    /// }
+    ///
+    /// impl<E: Endianness> BitWrite<E> for Rgb {
+    ///     fn write(&self, stream: &mut BitWriteStream<E>) -> Result<()> {
+    ///         let checked = stream.check_write(24)?;
+    ///         stream.write_int_unchecked(self.r, 8, checked);
+    ///         stream.write_int_unchecked(self.g, 8, checked);
+    ///         stream.write_int_unchecked(self.b, 8, checked);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write(&Rgb { r: 1, g: 2, b: 3 })?;
+    /// # drop(stream);
+    /// assert_eq!(data, [1, 2, 3]);
+    ///
+    /// let mut data = [0; 2];
+    /// let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    /// assert!(stream.write(&Rgb { r: 1, g: 2, b: 3 }).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ReadError::WriteOverflow`]: enum.ReadError.html#variant.WriteOverflow
+    #[inline]
+    pub fn check_write(&mut self, count: usize) -> Result<CheckedWrite> {
+        self.buffer.check_write(count)?;
+        self.buffer.reserve(count)?;
+        Ok(CheckedWrite {
+            limit: self.bit_len() + count,
+        })
+    }
+
+    #[inline]
+    #[track_caller]
+    fn debug_assert_checked(&self, checked: CheckedWrite, count: usize) {
+        bounds_assert!(
+            self.bit_len() + count <= checked.limit,
+            "unchecked write of {} bits at {} past the checked limit of {}",
+            count,
+            self.bit_len(),
+            checked.limit
+        );
+    }
+
     /// Write a boolean into the buffer
     ///
     /// # Examples
@@ -149,16 +657,31 @@ where
     /// ```
     #[inline]
     pub fn write_bool(&mut self, value: bool) -> Result<()> {
+        self.buffer.check_write(1)?;
         self.buffer.push_bool(value);
         Ok(())
     }
 
+    /// Write a boolean into the buffer without checking the capacity of the buffer
+    ///
+    /// See [`check_write`](BitWriteStream::check_write) for details.
+    #[inline]
+    #[track_caller]
+    pub fn write_bool_unchecked(&mut self, value: bool, checked: CheckedWrite) {
+        self.debug_assert_checked(checked, 1);
+        self.buffer.push_bool(value);
+    }
+
     /// Write an integer into the buffer
     ///
+    /// # Errors
+    ///
+    /// - [`BitError::TooManyBitsForValue`]: `count` is larger than the number of bits in `T`
+    ///
     /// # Examples
     ///
     /// ```
-    /// # use bitbuffer::{BitReadBuffer, LittleEndian, Result};
+    /// # use bitbuffer::{BitError, BitReadBuffer, LittleEndian, Result};
     /// #
     /// # fn main() -> Result<()> {
     /// # use bitbuffer::{BitWriteStream, LittleEndian};
@@ -166,6 +689,10 @@ where
     /// let mut data = Vec::new();
     /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
     /// stream.write_int(123u16, 15)?;
+    /// assert!(matches!(
+    ///     stream.write_int(123u16, 17),
+    ///     Err(BitError::TooManyBitsForValue { requested: 17, max: 16, .. })
+    /// ));
     /// #
     /// #     Ok(())
     /// # }
@@ -184,22 +711,204 @@ where
         let type_bit_size = size_of::<T>() * 8;
 
         if type_bit_size < count {
-            return Err(BitError::TooManyBits {
+            return Err(BitError::TooManyBitsForValue {
+                value: format!("{:?}", value),
                 requested: count,
                 max: type_bit_size,
+                field: None,
             });
         }
-
-        if type_bit_size < USIZE_BITS || count <= (USIZE_BITS - (self.bit_len() % 8)) {
-            self.push_bits(value.into_usize_unchecked(), count);
-        } else {
-            self.push_non_fit_bits(value.split_fit_usize::<E>(count as u8), count)
-        }
-
+        self.buffer.check_write(count)?;
+        self.push_int(value, count);
         Ok(())
     }
 
-    /// Write a float into the buffer
+    /// Write an integer into the buffer without checking the capacity of the buffer
+    ///
+    /// See [`check_write`](BitWriteStream::check_write) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if `count` is larger than the number of bits in `T`
+    #[inline]
+    #[track_caller]
+    pub fn write_int_unchecked<T>(&mut self, value: T, count: usize, checked: CheckedWrite)
+    where
+        T: PrimInt
+            + BitOrAssign
+            + IsSigned
+            + UncheckedPrimitiveInt
+            + BitXor
+            + Debug
+            + SplitFitUsize,
+    {
+        self.debug_assert_checked(checked, count);
+        bounds_assert!(
+            count <= size_of::<T>() * 8,
+            "unchecked write of {} bits for an integer of {} bits",
+            count,
+            size_of::<T>() * 8
+        );
+        self.push_int(value, count);
+    }
+
+    #[inline]
+    fn push_int<T>(&mut self, value: T, count: usize)
+    where
+        T: PrimInt
+            + BitOrAssign
+            + IsSigned
+            + UncheckedPrimitiveInt
+            + BitXor
+            + Debug
+            + SplitFitUsize,
+    {
+        let value = if swaps_bytes::<E>() {
+            T::from_unchecked(swap_byte_groups(
+                value.into_u128_unchecked(),
+                count,
+                E::is_be(),
+            ))
+        } else {
+            value
+        };
+
+        if size_of::<T>() * 8 < USIZE_BITS || count <= (USIZE_BITS - (self.bit_len() % 8)) {
+            self.push_bits(value.into_usize_unchecked(), count);
+        } else {
+            self.push_non_fit_bits(value.split_fit_usize::<E>(count as u8), count)
+        }
+    }
+
+    /// Write a list of booleans as one bit each
+    ///
+    /// The bits are written a word at a time, which is faster than writing them one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    /// stream.write_bitmap(&[true, false, true, false])?;
+    /// assert_eq!(stream.bit_len(), 4);
+    /// assert_eq!(data, [0b1010_0000]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_bitmap(&mut self, bitmap: &[bool]) -> Result<()> {
+        self.buffer.check_write(bitmap.len())?;
+        for chunk in bitmap.chunks(u64::BITS as usize) {
+            self.write_int(pack_bits::<E, _>(chunk.iter().copied()), chunk.len())?;
+        }
+        Ok(())
+    }
+
+    /// Write a run of `length` bits that are all equal to `bit`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    /// stream.write_run(false, 5)?;
+    /// stream.write_run(true, 9)?;
+    /// stream.write_run(false, 2)?;
+    /// assert_eq!(data, [0b0000_0111, 0b1111_1100]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_run(&mut self, bit: bool, length: usize) -> Result<()> {
+        self.check_write(length)?;
+        let bits = if bit { usize::MAX } else { 0 };
+        // leave room for the bits already written in the last byte
+        let max_chunk = USIZE_BITS - 8;
+        let mut remaining = length;
+        while remaining > 0 {
+            let chunk = min(remaining, max_chunk);
+            self.push_bits(bits, chunk);
+            remaining -= chunk;
+        }
+        Ok(())
+    }
+
+    /// Write an unsigned integer as varint
+    ///
+    /// The value is written 7 bits at a time, least significant group first, with each group prefixed by a continuation
+    /// bit that is set when more groups follow, taking 8 to 80 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_varint(5)?;
+    /// stream.write_varint(300)?;
+    /// assert_eq!(data, [5, 0b1010_1100, 0b0000_0010]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_varint(&mut self, value: u64) -> Result<()> {
+        let checked = self.check_write(varint_bit_size(value))?;
+        self.write_varint_unchecked(value, checked);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_varint_unchecked(&mut self, mut value: u64, checked: CheckedWrite) {
+        while value >= 0x80 {
+            self.write_int_unchecked(value as u8 | 0x80, 8, checked);
+            value >>= 7;
+        }
+        self.write_int_unchecked(value as u8, 8, checked);
+    }
+
+    /// Write a sequence of integers as the zig-zag varint encoded differences between consecutive values
+    ///
+    /// The first value is encoded as the difference from `0`. Slowly changing sequences like indices or timestamps
+    /// are typically encoded in 8 or 16 bits per value. The values can be read back with
+    /// [`BitReadStream::read_delta_seq`], the number of values isn't written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let timestamps = [1_700_000_000, 1_700_000_010, 1_700_000_005];
+    ///
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_delta_seq(&timestamps)?;
+    /// assert_eq!(stream.bit_len(), 40 + 8 + 8);
+    ///
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    /// assert_eq!(stream.read_delta_seq(3)?, timestamps);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_delta_seq(&mut self, values: &[u64]) -> Result<()> {
+        let size = zigzag_deltas(values).map(varint_bit_size).sum();
+        let checked = self.check_write(size)?;
+        for delta in zigzag_deltas(values) {
+            self.write_varint_unchecked(delta, checked);
+        }
+        Ok(())
+    }
+
+    /// Write a float into the buffer
     ///
     /// # Examples
     ///
@@ -221,14 +930,93 @@ where
     where
         T: Float + UncheckedPrimitiveFloat,
     {
+        self.buffer.check_write(size_of::<T>() * 8)?;
+        self.push_float(value);
+        Ok(())
+    }
+
+    /// Write a float truncated to the first `count` bits of its IEEE 754 representation
+    ///
+    /// The sign, exponent and the most significant mantissa bits are kept while the least significant mantissa bits
+    /// are dropped, as used by formats storing floats as 24 bits. The float can be read back with
+    /// [`BitReadStream::read_float_sized`], which restores the dropped bits as zeros.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::TooManyBitsForValue`]: `count` is larger than the number of bits in the float
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BitWriteStream, BigEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    /// stream.write_float_sized(1.5f32, 24)?;
+    /// stream.write_float_sized(0.1f32, 24)?;
+    /// assert_eq!(stream.bit_len(), 48);
+    ///
+    /// let mut stream = BitReadStream::new(BitReadBuffer::new(&data, BigEndian));
+    /// assert_eq!(stream.read_float_sized::<f32>(24)?, 1.5);
+    /// assert_eq!(stream.read_float_sized::<f32>(24)?, 0.099998474);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_float_sized<T>(&mut self, value: T, count: usize) -> Result<()>
+    where
+        T: Float + UncheckedPrimitiveFloat + Debug,
+    {
+        let type_bit_size = size_of::<T>() * 8;
+        if count > type_bit_size {
+            return Err(BitError::TooManyBitsForValue {
+                value: format!("{:?}", value),
+                requested: count,
+                max: type_bit_size,
+                field: None,
+            });
+        }
+        if count == 0 {
+            return Ok(());
+        }
+        let value = if self.canonical_floats {
+            canonicalize_float(value)
+        } else {
+            value
+        };
+        self.write_int(value.to_int() >> (type_bit_size - count), count)
+    }
+
+    /// Write a float into the buffer without checking the capacity of the buffer
+    ///
+    /// See [`check_write`](BitWriteStream::check_write) for details.
+    #[inline]
+    #[track_caller]
+    pub fn write_float_unchecked<T>(&mut self, value: T, checked: CheckedWrite)
+    where
+        T: Float + UncheckedPrimitiveFloat,
+    {
+        self.debug_assert_checked(checked, size_of::<T>() * 8);
+        self.push_float(value);
+    }
+
+    #[inline]
+    fn push_float<T>(&mut self, value: T)
+    where
+        T: Float + UncheckedPrimitiveFloat,
+    {
+        let value = if self.canonical_floats {
+            canonicalize_float(value)
+        } else {
+            value
+        };
         if self.buffer.bit_len() & 7 == 0 {
             let bytes = value.to_bytes::<E>();
             self.buffer.extends_from_slice(bytes.as_ref());
         } else {
-            self.write_int(value.to_int(), size_of::<T>() * 8)?;
+            self.push_int(value.to_int(), size_of::<T>() * 8);
         }
-
-        Ok(())
     }
 
     /// Write a number of bytes into the buffer
@@ -250,6 +1038,23 @@ where
     /// ```
     #[inline]
     pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.buffer.check_write(bytes.len() * 8)?;
+        self.push_bytes(bytes);
+        Ok(())
+    }
+
+    /// Write a number of bytes into the buffer without checking the capacity of the buffer
+    ///
+    /// See [`check_write`](BitWriteStream::check_write) for details.
+    #[inline]
+    #[track_caller]
+    pub fn write_bytes_unchecked(&mut self, bytes: &[u8], checked: CheckedWrite) {
+        self.debug_assert_checked(checked, bytes.len() * 8);
+        self.push_bytes(bytes);
+    }
+
+    #[inline]
+    fn push_bytes(&mut self, bytes: &[u8]) {
         if self.buffer.bit_len() & 7 == 0 {
             self.buffer.extends_from_slice(bytes);
         } else {
@@ -258,35 +1063,186 @@ where
                 .copied()
                 .for_each(|chunk| self.push_bits(chunk as usize, 8));
         }
+    }
+
+    /// Write a number of bytes into the buffer, requiring the stream to be at a byte boundary
+    ///
+    /// Unlike [`write_bytes`](Self::write_bytes) this never falls back to writing the bytes bit by bit,
+    /// which makes it suited for blobs that have to start at a byte boundary in the output.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotAligned`]: the stream is not at a byte boundary
+    /// - [`BitError::WriteOverflow`]: not enough capacity left in the write stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_bool(true)?;
+    /// assert!(stream.write_bytes_aligned_hint(&[1, 2]).is_err());
+    /// stream.align();
+    /// stream.write_bytes_aligned_hint(&[1, 2])?;
+    /// assert_eq!(data, [1, 1, 2]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn write_bytes_aligned_hint(&mut self, bytes: &[u8]) -> Result<()> {
+        self.require_alignment()?;
+        self.buffer.check_write(bytes.len() * 8)?;
+        self.buffer.extends_from_slice(bytes);
         Ok(())
     }
 
     /// Write bits from a read stream into the buffer
     #[inline]
     pub fn write_bits(&mut self, bits: &BitReadStream<E>) -> Result<()> {
-        let mut bits = bits.clone();
+        self.buffer.check_write(bits.bits_left())?;
+        self.copy_bits(&mut bits.clone(), bits.bits_left())
+    }
+
+    /// Copy `count` bits from a read stream into the buffer, advancing both streams
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::NotEnoughData`]: not enough bits available in the read stream
+    /// - [`BitError::WriteOverflow`]: not enough capacity left in the write stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitReadBuffer, BitReadStream, BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let bytes = vec![0x12, 0x34, 0x56];
+    /// let mut read = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    ///
+    /// let mut data = Vec::new();
+    /// let mut write = BitWriteStream::new(&mut data, LittleEndian);
+    /// write.copy_from(&mut read, 8)?;
+    /// read.skip_bits(8)?;
+    /// write.write_int(0xffu8, 8)?;
+    /// write.copy_from(&mut read, 8)?;
+    /// assert_eq!(vec![0x12, 0xff, 0x56], data);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn copy_from(&mut self, source: &mut BitReadStream<E>, count: usize) -> Result<()> {
+        if source.bits_left() < count {
+            return Err(BitError::NotEnoughData {
+                requested: count,
+                bits_left: source.bits_left(),
+            });
+        }
+        self.buffer.check_write(count)?;
+        self.copy_bits(source, count)
+    }
+
+    /// Append all bits written to another stream
+    ///
+    /// This allows multiple streams to be written independently, for example by different threads,
+    /// and merging them in order afterwards, regardless of whether the streams end at a byte boundary.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::WriteOverflow`]: not enough capacity left in the write stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut chunks = vec![Vec::new(), Vec::new()];
+    /// let streams = std::thread::scope(|scope| {
+    ///     let workers: Vec<_> = chunks
+    ///         .iter_mut()
+    ///         .enumerate()
+    ///         .map(|(index, chunk)| {
+    ///             scope.spawn(move || {
+    ///                 let mut stream = BitWriteStream::new(chunk, LittleEndian);
+    ///                 stream.write_int(index as u8, 3)?;
+    ///                 Ok(stream)
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     workers
+    ///         .into_iter()
+    ///         .map(|worker| worker.join().unwrap())
+    ///         .collect::<Result<Vec<_>>>()
+    /// })?;
+    ///
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_bool(true)?;
+    /// for chunk in &streams {
+    ///     stream.append(chunk)?;
+    /// }
+    /// assert_eq!(7, stream.bit_len());
+    /// # drop(stream);
+    /// assert_eq!(vec![0b001_000_1], data);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn append(&mut self, other: &BitWriteStream<E>) -> Result<()> {
+        let bit_len = other.bit_len();
+        self.buffer.check_write(bit_len)?;
+
+        let bytes = other.buffer.written_bytes();
+        let mut buffer = BitReadBuffer::new(bytes, E::endianness());
+        buffer.truncate(bit_len)?;
+        let mut source = BitReadStream::new(buffer);
+
+        if self.bit_len() % 8 == 0 {
+            let whole_bytes = bit_len / 8;
+            self.buffer.extends_from_slice(&bytes[..whole_bytes]);
+            source.skip_bits(whole_bytes * 8)?;
+        }
+        let remaining = source.bits_left();
+        self.copy_bits(&mut source, remaining)
+    }
+
+    fn copy_bits(&mut self, source: &mut BitReadStream<E>, count: usize) -> Result<()> {
+        let mut remaining = count;
         let bit_offset = self.bit_len() % 8;
-        if bit_offset > 0 {
-            let bit_count = min(8 - bit_offset, bits.bits_left());
-            let start = bits.read_int::<u8>(bit_count)?;
+        if bit_offset > 0 && remaining > 0 {
+            let bit_count = min(8 - bit_offset, remaining);
+            let start = source.read_int::<u8>(bit_count)?;
             self.push_bits(start as usize, bit_count);
+            remaining -= bit_count;
         }
 
-        while bits.bits_left() > 32 {
-            let chunk = bits.read::<u32>()?;
+        while remaining > 32 {
+            let chunk = source.read::<u32>()?;
             self.push_bits(chunk as usize, 32);
+            remaining -= 32;
         }
 
-        if bits.bits_left() > 0 {
-            let end_bits = bits.bits_left();
-            let end = bits.read_int::<u32>(end_bits)?;
-            self.push_bits(end as usize, end_bits);
+        if remaining > 0 {
+            let end = source.read_int::<u32>(remaining)?;
+            self.push_bits(end as usize, remaining);
         }
         Ok(())
     }
 
     /// Write a string into the buffer
     ///
+    /// The string is either written null terminated, or padded with null bytes to a fixed `length`
+    /// in bytes. Note that the length is the number of utf8 encoded bytes, not the number of characters.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::StringToLong`]: the string doesn't fit in the fixed length,
+    ///   use [`write_string_truncated`](BitWriteStream::write_string_truncated) to truncate the string instead
+    ///
     /// # Examples
     ///
     /// ```
@@ -304,41 +1260,341 @@ where
     /// # }
     /// ```
     pub fn write_string(&mut self, string: &str, length: Option<usize>) -> Result<()> {
+        self.write_string_bytes(string.as_bytes(), length)
+    }
+
+    /// Write the bytes of a string, null terminated or padded to a fixed `length`, see [`write_string`](BitWriteStream::write_string)
+    pub(crate) fn write_string_bytes(&mut self, bytes: &[u8], length: Option<usize>) -> Result<()> {
         match length {
             Some(length) => {
-                if length < string.len() {
+                if length < bytes.len() {
                     return Err(BitError::StringToLong {
-                        string_length: string.len(),
+                        string_length: bytes.len(),
                         requested_length: length,
                     });
                 }
-                self.write_bytes(string.as_bytes())?;
-                for _ in 0..(length - string.len()) {
+                // reserve the padding up front, so a length that can't be allocated returns an error
+                self.check_write(length.saturating_mul(8))?;
+                self.write_bytes(bytes)?;
+                for _ in 0..(length - bytes.len()) {
                     self.push_bits(0, 8)
                 }
             }
             None => {
-                self.write_bytes(string.as_bytes())?;
+                self.check_write((bytes.len() + 1) * 8)?;
+                self.write_bytes(bytes)?;
                 self.push_bits(0, 8)
             }
         }
         Ok(())
     }
 
+    /// Write a string that might be absent, writing `None` as an empty string
+    ///
+    /// This is the counterpart of [`BitReadStream::read_nullable_string`]. Since an empty string is how `None`
+    /// is written, writing `Some` with an empty string is an error instead of being read back as `None`.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::StringToLong`]: the string doesn't fit in the fixed length
+    /// - [`BitError::ValueOutOfRange`]: the string is `Some` but empty, or only contains null bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_nullable_string(None, None)?;
+    /// stream.write_nullable_string(Some("foo"), None)?;
+    /// stream.write_nullable_string(None, Some(2))?;
+    /// assert_eq!(data, b"\0foo\0\0\0");
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_nullable_string(
+        &mut self,
+        string: Option<&str>,
+        length: Option<usize>,
+    ) -> Result<()> {
+        match string {
+            Some(string) if string.bytes().all(|byte| byte == 0) => {
+                Err(BitError::ValueOutOfRange {
+                    type_name: "Option<String>",
+                })
+            }
+            string => self.write_string(string.unwrap_or_default(), length),
+        }
+    }
+
+    /// Write a string into a fixed number of bytes, truncating it if it doesn't fit
+    ///
+    /// The string is truncated at a character boundary so no partial utf8 sequences are written,
+    /// any remaining bytes are filled with null bytes. Returns the number of bytes of the string
+    /// that have been written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, ByteLen, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// // 'é' takes 2 bytes and doesn't fit in the remaining byte
+    /// assert_eq!(stream.write_string_truncated("café", ByteLen(4))?, 3);
+    /// assert_eq!(stream.write_string_truncated("tea", ByteLen(4))?, 3);
+    /// assert_eq!(data, b"caf\0tea\0");
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_string_truncated(&mut self, string: &str, length: ByteLen) -> Result<usize> {
+        let ByteLen(length) = length;
+        let mut end = min(string.len(), length);
+        while !string.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.write_string(&string[0..end], Some(length))?;
+        Ok(end)
+    }
+
     /// Write the type to stream
     #[inline]
-    pub fn write<T: BitWrite<E>>(&mut self, value: &T) -> Result<()> {
+    pub fn write<T: BitWrite<E> + ?Sized>(&mut self, value: &T) -> Result<()> {
         value.write(self)
     }
 
+    /// Write a value, returning errors as a user defined error type
+    ///
+    /// Errors from derived implementations with a `#[bit_error = "MyError"]` attribute are returned as the
+    /// original `MyError`, other errors are converted using `From<BitError>`, see [`BitError::into_custom`].
+    #[inline]
+    pub fn write_with_error<T, Err>(&mut self, value: &T) -> std::result::Result<(), Err>
+    where
+        T: BitWrite<E> + ?Sized,
+        Err: From<BitError> + std::error::Error + 'static,
+    {
+        value.write(self).map_err(BitError::into_custom)
+    }
+
     /// Write the type to stream
     #[inline]
-    pub fn write_sized<T: BitWriteSized<E>>(&mut self, value: &T, length: usize) -> Result<()> {
+    pub fn write_sized<T: BitWriteSized<E> + ?Sized>(
+        &mut self,
+        value: &T,
+        length: usize,
+    ) -> Result<()> {
+        value.write_sized(self, length)
+    }
+
+    /// Write the type to stream without checking the capacity of the buffer
+    ///
+    /// See [`check_write`](BitWriteStream::check_write) for details.
+    #[inline]
+    pub fn write_unchecked<T: BitWrite<E> + ?Sized>(
+        &mut self,
+        value: &T,
+        checked: CheckedWrite,
+    ) -> Result<()> {
+        value.write_unchecked(self, checked)
+    }
+
+    /// Write the type to stream without checking the capacity of the buffer
+    ///
+    /// See [`check_write`](BitWriteStream::check_write) for details.
+    #[inline]
+    pub fn write_sized_unchecked<T: BitWriteSized<E> + ?Sized>(
+        &mut self,
+        value: &T,
+        length: usize,
+        checked: CheckedWrite,
+    ) -> Result<()> {
+        value.write_sized_unchecked(self, length, checked)
+    }
+
+    /// Write an array or tuple where the size is applied to every element
+    ///
+    /// This is the same as [`write_sized`](BitWriteStream::write_sized) for arrays and tuples
+    /// but makes it explicit that the size is per element.
+    #[inline]
+    pub fn write_sized_each<T: BitWriteSized<E> + HasElementCount>(
+        &mut self,
+        value: &T,
+        length: usize,
+    ) -> Result<()> {
         value.write_sized(self, length)
     }
 
+    /// Write an array or tuple where the size is divided evenly over all elements
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::UnevenSize`]: the size can't be divided evenly over the elements
+    /// - any error returned by writing the elements
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_sized_total(&[1u8, 2, 3], 12)?;
+    /// assert_eq!(stream.bit_len(), 12);
+    /// assert!(stream.write_sized_total(&[1u8, 2, 3], 8).is_err());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn write_sized_total<T: BitWriteSized<E> + HasElementCount>(
+        &mut self,
+        value: &T,
+        length: usize,
+    ) -> Result<()> {
+        value.write_sized(self, element_size::<T>(length)?)
+    }
+
+    /// Write the entries of a map, each a key written with `key_size` followed by a value written with `value_size`
+    ///
+    /// The entries are written in the iteration order of the map, the number of entries isn't written.
+    /// This is the counterpart of [`BitReadStream::read_map_sized`](crate::BitReadStream::read_map_sized).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, BigEndian, Result};
+    /// # use std::collections::HashMap;
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut map = HashMap::new();
+    /// map.insert(String::from("ab"), 0x123u16);
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    /// stream.write_map_sized(&map, 2, 12)?;
+    /// assert_eq!(stream.bit_len(), 28);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_map_sized<K, V, S>(
+        &mut self,
+        map: &HashMap<K, V, S>,
+        key_size: usize,
+        value_size: usize,
+    ) -> Result<()>
+    where
+        K: BitWriteSized<E>,
+        V: BitWriteSized<E>,
+    {
+        for (key, value) in map {
+            key.write_sized(self, key_size)?;
+            value.write_sized(self, value_size)?;
+        }
+        Ok(())
+    }
+
+    /// Write every item from an iterator to the stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_iter((1u8..4).map(|i| i * 2))?;
+    /// assert_eq!(vec![2, 4, 6], data);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn write_iter<T: BitWrite<E>, I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<()> {
+        for item in iter {
+            item.write(self)?;
+        }
+        Ok(())
+    }
+
+    /// Write every item from an iterator to the stream, using the same length for every item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// stream.write_iter_sized([1u8, 2, 3, 0], 2)?;
+    /// assert_eq!(vec![0b00_11_10_01], data);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn write_iter_sized<T: BitWriteSized<E>, I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+        length: usize,
+    ) -> Result<()> {
+        for item in iter {
+            item.write_sized(self, length)?;
+        }
+        Ok(())
+    }
+
     /// Write the length of a section before the section
-    pub fn reserve_length<Err: From<BitError>, F: Fn(&mut BitWriteStream<E>) -> Result<(), Err>>(
+    ///
+    /// Reserves `length_bit_size` bits for the length, writes the section using `body_fn` and then fills in
+    /// the number of bits written by `body_fn`. Reservations can be nested.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::TooManyBits`]: the length of the section doesn't fit in `length_bit_size` bits,
+    ///   the reserved length and the section are discarded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitbuffer::{BitError, BitWriteStream, LittleEndian, Result};
+    /// #
+    /// # fn main() -> Result<()> {
+    /// let mut data = Vec::new();
+    /// let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    /// let mut written = 0;
+    /// stream.reserve_length(8, |stream| {
+    ///     stream.reserve_length(8, |stream| {
+    ///         written += 1;
+    ///         stream.write_int(0xffu8, 4)
+    ///     })?;
+    ///     written += 1;
+    ///     stream.write_int(0u8, 4)
+    /// })?;
+    /// assert_eq!(2, written);
+    /// assert!(matches!(
+    ///     stream.reserve_length(2, |stream| stream.write_int(0u8, 4)),
+    ///     Err(BitError::TooManyBits { requested: 3, max: 2 })
+    /// ));
+    /// # drop(stream);
+    /// assert_eq!(vec![16, 4, 0x0f], data[0..3]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`BitError::TooManyBits`]: enum.BitError.html#variant.TooManyBits
+    pub fn reserve_length<
+        Err: From<BitError>,
+        F: FnOnce(&mut BitWriteStream<E>) -> Result<(), Err>,
+    >(
         &mut self,
         length_bit_size: usize,
         body_fn: F,
@@ -352,9 +1608,18 @@ where
     }
 
     /// Write the length in bytes of a section before the section, the section will be 0 padded to an even byte length
+    ///
+    /// Reservations can be nested.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::TooManyBits`]: the length of the section doesn't fit in `length_bit_size` bits,
+    ///   the reserved length and the section are discarded
+    ///
+    /// [`BitError::TooManyBits`]: enum.BitError.html#variant.TooManyBits
     pub fn reserve_byte_length<
         Err: From<BitError>,
-        F: Fn(&mut BitWriteStream<E>) -> Result<(), Err>,
+        F: FnOnce(&mut BitWriteStream<E>) -> Result<(), Err>,
     >(
         &mut self,
         length_bit_size: usize,
@@ -375,7 +1640,20 @@ where
     }
 
     /// Reserve the length to write an integer
-    pub fn reserve_int<Err: From<BitError>, F: Fn(&mut BitWriteStream<E>) -> Result<u64, Err>>(
+    ///
+    /// Reserves `count` bits, calls `body_fn` and then fills in the integer returned by `body_fn`.
+    /// Reservations can be nested.
+    ///
+    /// # Errors
+    ///
+    /// - [`BitError::TooManyBits`]: the integer returned by `body_fn` doesn't fit in `count` bits,
+    ///   the reserved bits and everything written by `body_fn` are discarded
+    ///
+    /// [`BitError::TooManyBits`]: enum.BitError.html#variant.TooManyBits
+    pub fn reserve_int<
+        Err: From<BitError>,
+        F: FnOnce(&mut BitWriteStream<E>) -> Result<u64, Err>,
+    >(
         &mut self,
         count: usize,
         body_fn: F,
@@ -384,8 +1662,65 @@ where
         self.write_int(0u64, count)?;
 
         let head_int = body_fn(self)?;
+        let required = (u64::BITS - head_int.leading_zeros()) as usize;
+        if required > count {
+            self.buffer.truncate(start);
+            return Err(BitError::TooManyBits {
+                requested: required,
+                max: count,
+            }
+            .into());
+        }
+        let head_int = if swaps_bytes::<E>() {
+            swap_byte_groups(head_int as u128, count, E::is_be()) as u64
+        } else {
+            head_int
+        };
         self.buffer.set_at(start, head_int, count);
 
         Ok(())
     }
 }
+
+/// Write each bit to the stream
+///
+/// # Panics
+///
+/// Panics if the stream is backed by a slice without enough capacity left for the bits,
+/// use [`BitWriteStream::write_iter`] for a fallible alternative.
+///
+/// # Examples
+///
+/// ```
+/// # use bitbuffer::{BitWriteStream, BigEndian};
+/// #
+/// let mut data = Vec::new();
+/// let mut stream = BitWriteStream::new(&mut data, BigEndian);
+/// stream.extend([true, false, true]);
+/// stream.extend("010".chars().map(|char| char == '1'));
+/// # drop(stream);
+/// assert_eq!(vec![0b1010_1000], data);
+/// ```
+impl<E: Endianness> Extend<bool> for BitWriteStream<'_, E> {
+    fn extend<T: IntoIterator<Item = bool>>(&mut self, iter: T) {
+        for bit in iter {
+            self.write_bool(bit)
+                .unwrap_or_else(|err| panic!("failed to extend bit stream: {}", err));
+        }
+    }
+}
+
+/// Write each byte to the stream, the bytes don't need to be aligned to a byte boundary
+///
+/// # Panics
+///
+/// Panics if the stream is backed by a slice without enough capacity left for the bytes,
+/// use [`BitWriteStream::write_bytes`] for a fallible alternative.
+impl<E: Endianness> Extend<u8> for BitWriteStream<'_, E> {
+    fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
+        for byte in iter {
+            self.write_int(byte, 8)
+                .unwrap_or_else(|err| panic!("failed to extend bit stream: {}", err));
+        }
+    }
+}
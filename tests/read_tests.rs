@@ -1,9 +1,16 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::num::NonZeroU16;
 
 use maplit::hashmap;
 
-use bitbuffer::{BigEndian, BitError, BitRead, BitReadBuffer, BitReadStream, LittleEndian};
+use bitbuffer::{
+    BigEndian, BitError, BitRead, BitReadBuffer, BitReadStream, BitWriteStream, Endianness,
+    LazyBitRead, LittleEndian, Marker,
+};
+
+#[cfg(feature = "budget")]
+use bitbuffer::LimitedReadStream;
 
 const BYTES: &[u8] = &[
     0b1011_0101,
@@ -314,18 +321,19 @@ fn read_trait_unchecked() {
     unsafe {
         let buffer = BitReadBuffer::new(BYTES, BigEndian);
         let mut stream = BitReadStream::new(buffer);
-        let a: u8 = stream.read_unchecked(true).unwrap();
+        let checked = stream.check_read(stream.bits_left()).unwrap();
+        let a: u8 = stream.read_unchecked(checked).unwrap();
         assert_eq!(0b1011_0101, a);
-        let b: i8 = stream.read_unchecked(true).unwrap();
+        let b: i8 = stream.read_unchecked(checked).unwrap();
         assert_eq!(0b110_1010, b);
-        let c: i16 = stream.read_unchecked(true).unwrap();
+        let c: i16 = stream.read_unchecked(checked).unwrap();
         assert_eq!(-0b101_0011_0110_0111, c);
-        let d: bool = stream.read_unchecked(true).unwrap();
+        let d: bool = stream.read_unchecked(checked).unwrap();
         assert!(d);
-        let e: Option<u8> = stream.read_unchecked(true).unwrap();
+        let e: Option<u8> = stream.read_unchecked(checked).unwrap();
         assert_eq!(None, e);
         stream.set_pos(0).unwrap();
-        let f: Option<u8> = stream.read_unchecked(true).unwrap();
+        let f: Option<u8> = stream.read_unchecked(checked).unwrap();
         assert_eq!(Some(0b0110_1010), f);
     }
 }
@@ -356,6 +364,13 @@ fn read_sized_trait() {
         result
     );
     stream.set_pos(0).unwrap();
+    let result: HashMap<u8, u16> = stream.read_map_sized(2, 4, 12).unwrap();
+    assert_eq!(
+        hashmap!(0b1011 => 0b0101_0110_1010, 0b1010 => 0b1100_1001_1001),
+        result
+    );
+    assert_eq!(32, stream.pos());
+    stream.set_pos(0).unwrap();
     let mut result: BitReadStream<BigEndian> = stream.read_sized(4).unwrap();
     assert_eq!(0b10u8, result.read_int::<u8>(2).unwrap());
 }
@@ -374,10 +389,11 @@ fn read_sized_trait_unchecked() {
     unsafe {
         let buffer = BitReadBuffer::new(BYTES, BigEndian);
         let mut stream = BitReadStream::new(buffer);
-        let a: u8 = stream.read_sized_unchecked(4, true).unwrap();
+        let checked = stream.check_read(stream.bits_left()).unwrap();
+        let a: u8 = stream.read_sized_unchecked(4, checked).unwrap();
         assert_eq!(0b1011, a);
         stream.set_pos(0).unwrap();
-        let vec: Vec<u16> = stream.read_sized_unchecked(3, true).unwrap();
+        let vec: Vec<u16> = stream.read_sized_unchecked(3, checked).unwrap();
         assert_eq!(
             vec![
                 0b1011_0101_0110_1010,
@@ -387,16 +403,16 @@ fn read_sized_trait_unchecked() {
             vec
         );
         stream.set_pos(0).unwrap();
-        let vec: Vec<u8> = stream.read_sized_unchecked(3, true).unwrap();
+        let vec: Vec<u8> = stream.read_sized_unchecked(3, checked).unwrap();
         assert_eq!(vec![0b1011_0101, 0b0110_1010, 0b1010_1100], vec);
         stream.set_pos(0).unwrap();
-        let result: HashMap<u8, u8> = stream.read_sized_unchecked(2, true).unwrap();
+        let result: HashMap<u8, u8> = stream.read_sized_unchecked(2, checked).unwrap();
         assert_eq!(
             hashmap!(0b1011_0101 => 0b0110_1010, 0b1010_1100 => 0b1001_1001),
             result
         );
         stream.set_pos(0).unwrap();
-        let mut result: BitReadStream<BigEndian> = stream.read_sized_unchecked(4, true).unwrap();
+        let mut result: BitReadStream<BigEndian> = stream.read_sized_unchecked(4, checked).unwrap();
         assert_eq!(0b10u8, result.read_int::<u8>(2).unwrap());
     }
 }
@@ -519,3 +535,1077 @@ fn test_invalid_utf8() {
 
     assert_eq!(stream.pos(), 6 * 8);
 }
+
+#[test]
+fn test_read_struct_of_arrays() {
+    let bytes = vec![1, 10, 0, 2, 20, 0, 3, 30, 0, 0xff];
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    let mut stream = BitReadStream::new(buffer.clone());
+
+    let (ids, values): (Vec<u8>, Vec<u16>) = stream.read_struct_of_arrays(3).unwrap();
+    assert_eq!(vec![1, 2, 3], ids);
+    assert_eq!(vec![2560, 5120, 7680], values);
+    assert_eq!(stream.pos(), 9 * 8);
+
+    let mut stream = BitReadStream::new(buffer.clone());
+    assert!(matches!(
+        stream.read_struct_of_arrays::<(Vec<u8>, Vec<u16>)>(4),
+        Err(BitError::NotEnoughData {
+            requested: 96,
+            bits_left: 80
+        })
+    ));
+
+    // variable size records
+    let bytes = vec![1, b'a', 0, 2, b'b', b'c', 0];
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let mut stream = BitReadStream::new(buffer);
+    let mut columns: (Vec<u8>, Vec<String>) = Default::default();
+    stream.read_struct_of_arrays_into(1, &mut columns).unwrap();
+    stream.read_struct_of_arrays_into(1, &mut columns).unwrap();
+    assert_eq!(vec![1, 2], columns.0);
+    assert_eq!(vec!["a".to_string(), "bc".to_string()], columns.1);
+}
+
+#[cfg(feature = "coverage")]
+#[test]
+fn test_read_coverage() {
+    let bytes = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let mut stream = BitReadStream::new(buffer);
+    stream.skip_bits(8).unwrap();
+    assert_eq!(None, stream.unread_ranges());
+
+    stream.track_coverage();
+    assert_eq!(
+        stream.unread_ranges(),
+        Some(vec![std::ops::Range { start: 0, end: 56 }])
+    );
+
+    stream.read_bool().unwrap();
+    let mut bits = stream.read_bits(16).unwrap();
+    bits.read_int::<u8>(4).unwrap();
+    stream.peek::<u8>().unwrap();
+    stream.skip_bits(16).unwrap();
+    stream.read_float::<f32>().ok();
+    stream.read_bytes(1).unwrap();
+
+    assert_eq!(Some(41), stream.high_water_mark());
+    assert_eq!(Some(vec![5..17, 25..33, 41..56]), stream.unread_ranges());
+    // sub streams share the coverage
+    assert_eq!(stream.unread_ranges(), bits.unread_ranges());
+}
+
+#[test]
+fn test_read_unchecked_exact_slice_end() {
+    // heap allocate the exact size so reading past the end of the slice would be caught by miri or asan
+    let bytes: Vec<u8> = (1..=11).map(|i| i * 0x17).collect();
+    let buffer = BitReadBuffer::new(&bytes, LittleEndian);
+    let bit_len = bytes.len() * 8;
+    for count in 1..=64 {
+        for position in bit_len.saturating_sub(count + 16)..=(bit_len - count) {
+            let expected = (0..count).fold(0u64, |value, i| {
+                value | (buffer.read_bool(position + i).unwrap() as u64) << i
+            });
+            let mut stream = BitReadStream::new(buffer.clone());
+            stream.set_pos(position).unwrap();
+            let checked = stream.check_read(count).unwrap();
+            let unchecked: u64 = unsafe { stream.read_int_unchecked(count, checked) };
+            assert_eq!(expected, unchecked, "position {} count {}", position, count);
+        }
+    }
+
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    for position in bit_len - 64..=bit_len - 32 {
+        let checked: f32 = buffer.read_float(position).unwrap();
+        let mut stream = BitReadStream::new(buffer.clone());
+        stream.set_pos(position).unwrap();
+        let check = stream.check_read(32).unwrap();
+        let unchecked: f32 = unsafe { stream.read_float_unchecked(check) };
+        assert_eq!(checked.to_bits(), unchecked.to_bits());
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "past the checked limit")]
+fn test_read_unchecked_past_checked_limit() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(BYTES, LittleEndian));
+    let checked = stream.check_read(8).unwrap();
+    let _: u16 = unsafe { stream.read_int_unchecked(16, checked) };
+}
+
+#[test]
+fn test_markers() {
+    let bytes = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    stream.mark(32, "body").unwrap();
+    stream.mark(8, "header").unwrap();
+    stream.mark(64, "end").unwrap();
+    assert!(matches!(
+        stream.mark(65, "past end"),
+        Err(BitError::IndexOutOfBounds { pos: 65, size: 64 })
+    ));
+    let tags: Vec<_> = stream.markers().map(|marker| marker.tag).collect();
+    assert_eq!(vec!["header", "body", "end"], tags);
+
+    // ending a read exactly on a marker doesn't cross it
+    stream.read_int::<u8>(8).unwrap();
+    assert_eq!(0, stream.crossed_markers(0).count());
+    stream.check_markers(0).unwrap();
+
+    stream.read_int::<u32>(32).unwrap();
+    let crossed: Vec<_> = stream.crossed_markers(8).collect();
+    assert_eq!(
+        vec![Marker {
+            pos: 32,
+            tag: "body"
+        }],
+        crossed
+    );
+    assert!(matches!(
+        stream.check_markers(8),
+        Err(BitError::MarkerCrossed {
+            tag: "body",
+            pos: 32,
+            read_start: 8,
+            read_end: 40
+        })
+    ));
+
+    // markers are relative to the start of sub streams
+    let mut sub = stream.read_bits(24).unwrap();
+    assert_eq!(
+        vec![Marker {
+            pos: 24,
+            tag: "end"
+        }],
+        sub.markers().collect::<Vec<_>>()
+    );
+    sub.mark(4, "sub").unwrap();
+    assert_eq!(2, sub.markers().count());
+    assert_eq!(3, stream.markers().count());
+
+    let owned = stream.clone().to_owned();
+    assert_eq!(
+        vec![Marker { pos: 0, tag: "end" }],
+        owned.markers().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_read_string_into() {
+    fn compare<E: Endianness>() {
+        let mut string = String::new();
+        let mut buf = [0; 8];
+        for offset in 0..8 {
+            let mut data = Vec::new();
+            let mut write = BitWriteStream::new(&mut data, E::endianness());
+            write.write_int(0u8, offset).unwrap();
+            write
+                .write_bytes("Hello wörld\0fixed\0\0\0".as_bytes())
+                .unwrap();
+            let buffer = BitReadBuffer::new_owned(data, E::endianness());
+            let mut expected = BitReadStream::new(buffer);
+            expected.skip_bits(offset).unwrap();
+            let mut stream = expected.clone();
+            let mut slice_stream = expected.clone();
+
+            for byte_len in [None, Some(8)] {
+                let expected_string = expected.read_string(byte_len).unwrap();
+                stream.read_string_into(&mut string, byte_len).unwrap();
+                assert_eq!(expected_string, string);
+                assert_eq!(expected.bits_left(), stream.bits_left());
+            }
+
+            slice_stream.skip_bits(13 * 8).unwrap();
+            assert_eq!("fixed", slice_stream.read_str_into(&mut buf).unwrap());
+            assert_eq!(expected.bits_left(), slice_stream.bits_left());
+            assert!(matches!(
+                slice_stream.read_str_into(&mut buf),
+                Err(BitError::NotEnoughData { .. })
+            ));
+        }
+    }
+    compare::<LittleEndian>();
+    compare::<BigEndian>();
+
+    let bytes = [b'a', 0xff, 0xfe, 0];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let mut string = String::from("previous");
+    assert!(matches!(
+        stream.read_string_into(&mut string, None),
+        Err(BitError::Utf8Error(_, 3))
+    ));
+    assert_eq!("", string);
+    assert_eq!(32, stream.pos());
+}
+
+#[test]
+#[should_panic(expected = "unchecked read of 16 bits at 0 past the end of the buffer of 8 bits")]
+fn test_unchecked_read_other_stream() {
+    let long = BitReadStream::new(BitReadBuffer::new(&[0; 4], LittleEndian));
+    let mut short = BitReadStream::new(BitReadBuffer::new(&[0; 1], LittleEndian));
+    let checked = long.check_read(16).unwrap();
+    let _ = unsafe { short.read_int_unchecked::<u16>(16, checked) };
+}
+
+#[test]
+fn test_read_string_capped() {
+    let bytes = b"\xffhello\0world";
+    let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, BigEndian));
+    stream.skip_bits(4).unwrap();
+    // unaligned, the string is shifted by 4 bits
+    assert!(matches!(
+        stream.read_string_capped(3),
+        Err(BitError::UnterminatedString { max_bytes: 3 })
+    ));
+    assert_eq!(4, stream.pos());
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, BigEndian));
+    stream.skip_bits(8).unwrap();
+    assert_eq!("hello", stream.read_string_capped(6).unwrap());
+    assert_eq!(5 * 8, stream.bits_left());
+    // the end of the stream ends the string before the cap is reached
+    assert_eq!("world", stream.read_string_capped(100).unwrap());
+    assert_eq!(0, stream.bits_left());
+    assert_eq!("", stream.read_string_capped(100).unwrap());
+    assert!(matches!(
+        stream.read_string_capped(0),
+        Err(BitError::UnterminatedString { max_bytes: 0 })
+    ));
+}
+
+fn test_read_until_byte_endian<E: Endianness>() {
+    // longer than a single chunk of the unaligned search
+    let packet = vec![b'a'; 100];
+    let mut data = Vec::new();
+    let mut write = BitWriteStream::new(&mut data, E::endianness());
+    write.write_int(0b101u8, 3).unwrap();
+    write.write_bytes(&packet).unwrap();
+    write.write_bytes(b";bb").unwrap();
+    drop(write);
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+    stream.skip_bits(3).unwrap();
+    assert_eq!(packet, stream.read_until_byte(b';').unwrap().as_ref());
+    assert_eq!(3 + 100 * 8, stream.pos());
+    assert_eq!(b';', stream.read::<u8>().unwrap());
+    assert!(matches!(
+        stream.read_until_byte(b';'),
+        Err(BitError::SentinelNotFound { sentinel: b';' })
+    ));
+    assert_eq!(3 + 101 * 8, stream.pos());
+    assert_eq!(b"", stream.read_until_byte(b'b').unwrap().as_ref());
+}
+
+#[test]
+fn test_read_until_byte() {
+    test_read_until_byte_endian::<LittleEndian>();
+    test_read_until_byte_endian::<BigEndian>();
+}
+
+#[test]
+fn test_take_exact() {
+    let bytes = [0x12, 0x34, 0x56, 0x78, 0, 0, 0x80, 0x3f];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert!(matches!(
+        stream.take_exact(65),
+        Err(BitError::NotEnoughData {
+            requested: 65,
+            bits_left: 64
+        })
+    ));
+    assert_eq!(0, stream.pos());
+
+    let mut reader = stream.take_exact(32).unwrap();
+    assert_eq!(0x1, reader.read_int::<u8>(4));
+    reader.skip_bits(4);
+    assert_eq!(vec![0x34, 0x56], reader.read_bytes(2).to_vec());
+    assert!(!reader.read_bool());
+    assert_eq!(-8, reader.read_int::<i8>(7));
+    assert_eq!(0, reader.remaining());
+    assert_eq!(32, stream.pos());
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    stream.skip_bits(32).unwrap();
+    let mut reader = stream.take_exact(32).unwrap();
+    assert_eq!(1.0, reader.read_float::<f32>());
+}
+
+#[test]
+#[should_panic(expected = "read of 9 bits with only 8 bits remaining in the exact reader")]
+fn test_take_exact_overread() {
+    let bytes = [0; 4];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    let mut reader = stream.take_exact(8).unwrap();
+    reader.read_int::<u16>(9);
+}
+
+#[test]
+#[should_panic(expected = "bytes with only 8 bits remaining in the exact reader")]
+fn test_take_exact_overread_bytes_overflow() {
+    let bytes = [0; 4];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    let mut reader = stream.take_exact(8).unwrap();
+    reader.read_bytes(usize::MAX / 4);
+}
+
+#[test]
+fn test_iter_bits() {
+    let buffer = BitReadBuffer::new(BYTES, LittleEndian);
+    let bits: Vec<bool> = buffer.iter_bits().collect();
+    assert_eq!(buffer.bit_len(), bits.len());
+    for (position, bit) in bits.iter().enumerate() {
+        assert_eq!(buffer.read_bool(position).unwrap(), *bit);
+        assert_eq!(buffer[position], *bit);
+    }
+
+    let mut stream = BitReadStream::new(buffer);
+    stream.skip_bits(3).unwrap();
+    let mut sub_stream = stream.read_bits(20).unwrap();
+    sub_stream.skip_bits(5).unwrap();
+    assert_eq!(15, sub_stream.iter_bits().len());
+    assert!(sub_stream.iter_bits().eq(bits[8..23].iter().copied()));
+    assert_eq!(bits[3], sub_stream.get_bit(0).unwrap());
+    assert_eq!(bits[22], sub_stream.get_bit(19).unwrap());
+    assert!(matches!(
+        sub_stream.get_bit(20),
+        Err(BitError::IndexOutOfBounds { pos: 20, size: 20 })
+    ));
+    assert_eq!(5, sub_stream.pos());
+}
+
+#[test]
+#[should_panic]
+fn test_index_out_of_bounds() {
+    let buffer = BitReadBuffer::new(&[0; 2], LittleEndian);
+    let _ = buffer[16];
+}
+
+#[test]
+#[cfg(feature = "budget")]
+fn test_limited_read_stream() {
+    #[derive(BitRead, Debug)]
+    struct Item {
+        _length: u8,
+        #[size = "_length"]
+        data: Vec<u8>,
+    }
+
+    let bytes = [3, 1, 2, 3, 2, 4, 5, 0, 0, 0];
+    let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let mut stream = LimitedReadStream::new(stream, 7 * 8);
+
+    // reads from peeks and sub streams share the budget
+    let item: Item = stream.peek().unwrap();
+    assert_eq!(vec![1, 2, 3], item.data);
+    assert_eq!(3 * 8, stream.remaining_budget());
+    let mut sub_stream = stream.read_bits(32).unwrap();
+    assert!(matches!(
+        sub_stream.read::<Item>(),
+        Err(BitError::ReadBudgetExceeded {
+            requested: 24,
+            remaining: 16
+        })
+    ));
+    assert_eq!(2 * 8, stream.remaining_budget());
+
+    // owned copies share the budget as well
+    let mut owned = stream.to_owned();
+    assert_eq!(2, owned.read::<u8>().unwrap());
+    assert_eq!(8, stream.remaining_budget());
+    assert!(matches!(
+        owned.to_owned().read::<u16>(),
+        Err(BitError::ReadBudgetExceeded {
+            requested: 16,
+            remaining: 8
+        })
+    ));
+
+    // fixed size reads check the budget upfront
+    let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let mut stream = LimitedReadStream::new(stream, 40);
+    assert!(matches!(
+        stream.read::<[u8; 6]>(),
+        Err(BitError::ReadBudgetExceeded {
+            requested: 48,
+            remaining: 40
+        })
+    ));
+    assert_eq!(0, stream.pos());
+    assert_eq!([3, 1, 2, 3, 2], stream.read::<[u8; 5]>().unwrap());
+    assert_eq!(0, stream.remaining_budget());
+    assert!(stream.read_string(None).is_err());
+
+    // skipping doesn't use the budget
+    let mut stream = stream.into_inner();
+    stream.skip_bits(16).unwrap();
+    assert_eq!(24, stream.bits_left());
+}
+
+#[test]
+fn test_capacity_hint() {
+    let bytes = vec![0; 16];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    assert_eq!(stream.capacity_hint(usize::MAX, Some(16)), 8);
+    assert_eq!(stream.capacity_hint(4, Some(16)), 4);
+    assert_eq!(stream.capacity_hint(usize::MAX, None), 128);
+    assert_eq!(stream.capacity_hint(usize::MAX, Some(0)), 128);
+
+    let vec: Vec<u16> = stream.read_sized(8).unwrap();
+    assert_eq!(vec.capacity(), 8);
+    assert!(matches!(
+        stream.read_sized::<Vec<u16>>(usize::MAX / 64),
+        Err(BitError::NotEnoughData { .. })
+    ));
+}
+
+#[test]
+fn test_buffer_memory_usage() {
+    let stream = BitReadStream::new(BitReadBuffer::new(BYTES, LittleEndian));
+    assert_eq!(stream.strong_count(), None);
+    assert_eq!(stream.memory_usage(), 0);
+
+    let mut owned = stream.to_owned();
+    assert_eq!(owned.strong_count(), Some(1));
+    assert_eq!(owned.memory_usage(), BYTES.len());
+
+    let sub_stream = owned.read_bits(8).unwrap();
+    let clone = owned.clone();
+    assert_eq!(owned.strong_count(), Some(3));
+    assert_eq!(sub_stream.memory_usage(), BYTES.len());
+
+    drop(clone);
+    drop(owned);
+    assert_eq!(sub_stream.strong_count(), Some(1));
+}
+
+#[test]
+fn test_read_varint_overflow() {
+    let bytes = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    assert!(matches!(
+        stream.read_varint(),
+        Err(BitError::ValueOutOfRange { type_name: "u64" })
+    ));
+
+    let bytes = [0x80; 11];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    assert!(matches!(
+        stream.read_varint(),
+        Err(BitError::ValueOutOfRange { type_name: "u64" })
+    ));
+}
+
+#[test]
+fn test_no_panic_on_large_sizes() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(BYTES, LittleEndian));
+    stream.skip_bits(3).unwrap();
+    assert!(matches!(
+        stream.read_bits(usize::MAX),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        stream.read_bytes(usize::MAX / 4),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        stream.read_sized::<Vec<u16>>(usize::MAX / 8),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        stream.read_sized::<String>(usize::MAX),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert_eq!(3, stream.pos());
+
+    let buffer = BitReadBuffer::new(BYTES, LittleEndian);
+    assert!(matches!(
+        buffer.read_int::<u8>(usize::MAX, 8),
+        Err(BitError::IndexOutOfBounds { .. })
+    ));
+    assert!(matches!(
+        buffer.read_float::<f32>(usize::MAX),
+        Err(BitError::IndexOutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn test_lazy_read_unknown_size() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(BYTES, LittleEndian));
+    assert!(matches!(
+        stream.read::<LazyBitRead<String, LittleEndian>>(),
+        Err(BitError::UnknownLazySize { .. })
+    ));
+}
+
+#[test]
+fn test_bits_literal() {
+    let buffer: BitReadBuffer<LittleEndian> = bitbuffer::bits!("1010_1101 0110_1010 101");
+    assert_eq!(19, buffer.bit_len());
+    let expected: Vec<bool> = "1010110101101010101".chars().map(|c| c == '1').collect();
+    assert_eq!(expected, buffer.iter_bits().collect::<Vec<_>>());
+    assert_eq!(0b1011_0101, buffer.read_int::<u8>(0, 8).unwrap());
+
+    let buffer: BitReadBuffer<BigEndian> = bitbuffer::bits!("1010_1101 0110_1010 101");
+    assert_eq!(expected, buffer.iter_bits().collect::<Vec<_>>());
+    assert_eq!(0b1010_1101, buffer.read_int::<u8>(0, 8).unwrap());
+
+    let empty: BitReadBuffer<BigEndian> = bitbuffer::bits!("");
+    assert_eq!(0, empty.bit_len());
+}
+
+#[test]
+fn test_last_bits() {
+    let mut stream = BitReadStream::new(BitReadBuffer::new(BYTES, BigEndian));
+    stream.skip_bits(4).unwrap();
+    let mut sub = stream.read_bits(20).unwrap();
+    assert_eq!(0b0101, sub.read_int::<u8>(4).unwrap());
+    assert_eq!(0b0110, sub.read_int::<u8>(4).unwrap());
+
+    let mut last = sub.last_bits(6).unwrap();
+    assert_eq!(6, last.bit_len());
+    assert_eq!(0b01_0110, last.read_int::<u8>(6).unwrap());
+    assert_eq!(8, sub.pos());
+    assert!(matches!(
+        sub.last_bits(9),
+        Err(BitError::NotEnoughData {
+            requested: 9,
+            bits_left: 8
+        })
+    ));
+
+    let mut all = stream.last_bits(24).unwrap();
+    assert_eq!(0b1011_0101, all.read_int::<u8>(8).unwrap());
+    assert_eq!(0, stream.last_bits(0).unwrap().bit_len());
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_read_stats() {
+    use bitbuffer::ReadCount;
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(BYTES, LittleEndian));
+    assert_eq!(None, stream.read_stats());
+    stream.track_stats();
+
+    stream.read_bool().unwrap();
+    stream.skip_bits(7).unwrap();
+    let mut sub = stream.read_bits(32).unwrap();
+    sub.read_float::<f32>().unwrap();
+    stream.read_bytes(2).unwrap();
+    stream.read::<[u8; 2]>().unwrap();
+
+    let stats = stream.read_stats().unwrap();
+    assert_eq!(ReadCount { calls: 1, bits: 1 }, stats.bools);
+    assert_eq!(ReadCount { calls: 1, bits: 32 }, stats.floats);
+    // byte arrays are read as bytes
+    assert_eq!(ReadCount { calls: 2, bits: 32 }, stats.bytes);
+    assert_eq!(ReadCount::default(), stats.ints);
+    assert_eq!(ReadCount { calls: 4, bits: 65 }, stats.total());
+    assert_eq!(Some(stats), sub.read_stats());
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn test_read_pod() {
+    use bitbuffer::BigEndian;
+    use bytemuck::{Pod, Zeroable};
+
+    #[derive(BitRead, Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Sample {
+        a: u16,
+        b: u8,
+        c: u8,
+        d: i32,
+    }
+
+    unsafe impl Zeroable for Sample {}
+    unsafe impl Pod for Sample {}
+
+    let bytes = vec![
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        0x18, 0xff,
+    ];
+
+    // byte aligned, unaligned and in both byte orders, the result matches the field-wise read
+    for offset in [0, 3] {
+        let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+        stream.skip_bits(offset).unwrap();
+        let mut expected_stream = stream.clone();
+        let pods: Vec<Sample> = stream.read_pod_vec(2).unwrap();
+        let expected: Vec<Sample> = expected_stream.read_sized(2).unwrap();
+        assert_eq!(expected, pods);
+        assert_eq!(expected_stream.bits_left(), stream.bits_left());
+
+        let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+        stream.skip_bits(offset).unwrap();
+        let mut expected_stream = stream.clone();
+        let pod: Sample = stream.read_pod().unwrap();
+        let expected: Sample = expected_stream.read().unwrap();
+        assert_eq!(expected, pod);
+        assert_eq!(expected_stream.bits_left(), stream.bits_left());
+    }
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    assert_eq!(
+        Sample {
+            a: 0x0201,
+            b: 0x03,
+            c: 0x04,
+            d: 0x08070605,
+        },
+        stream.read_pod().unwrap()
+    );
+    assert!(stream.read_pod_vec::<Sample>(2).is_err());
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn test_read_pod_byte_swap() {
+    use bytemuck::{Pod, Zeroable};
+
+    #[derive(BitRead, Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Swapped {
+        #[byte_swap]
+        a: u16,
+        b: u16,
+    }
+
+    #[derive(BitRead, Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Nested {
+        inner: Swapped,
+        rest: [u8; 4],
+    }
+
+    unsafe impl Zeroable for Swapped {}
+    unsafe impl Pod for Swapped {}
+    unsafe impl Zeroable for Nested {}
+    unsafe impl Pod for Nested {}
+
+    let bytes = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+    let stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    assert_eq!(
+        Swapped {
+            a: 0x0102,
+            b: 0x0403
+        },
+        stream.clone().read_pod().unwrap()
+    );
+    assert_eq!(
+        stream.clone().read::<Nested>().unwrap(),
+        stream.clone().read_pod().unwrap()
+    );
+}
+
+#[test]
+fn test_debug_bit_window() {
+    let bytes = vec![
+        0b1011_0101,
+        0b0110_1010,
+        0b1010_1100,
+        0b1001_1001,
+        0b1110_0111,
+    ];
+    let buffer = BitReadBuffer::new(&bytes, BigEndian);
+    assert_eq!(
+        "BitReadBuffer { bit_len: 40, endianness: BigEndian, bits: 10110101 01101010 10101100 10011001 ... }",
+        format!("{:?}", buffer)
+    );
+
+    let mut stream = BitReadStream::new(buffer);
+    stream.skip_bits(4).unwrap();
+    assert_eq!(
+        "BitReadStream { pos: 4, bits_left: 36, endianness: BigEndian, next_bits: 01010110 10101010 11001001 10011110 ... }",
+        format!("{:?}", stream)
+    );
+    assert!(
+        format!("{:#?}", stream).contains("next_bits: 01010110 10101010 11001001 10011110 0111,\n")
+    );
+
+    let end = stream.read_bits(8).unwrap();
+    assert_eq!(
+        "BitReadStream { pos: 0, bits_left: 8, endianness: BigEndian, next_bits: 01010110 }",
+        format!("{:?}", end)
+    );
+}
+
+#[test]
+fn test_typed_reads() {
+    let bytes = vec![
+        0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0,
+    ];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, BigEndian));
+    assert_eq!(0x12, stream.read_u8().unwrap());
+    assert_eq!(0x34, stream.read_i8().unwrap());
+    assert_eq!(0x5678, stream.read_u16().unwrap());
+    assert_eq!(0b1001, stream.read_u8_bits(4).unwrap());
+    assert_eq!(-6, stream.read_i32_bits(4).unwrap());
+    assert!(matches!(
+        stream.read_u16_bits(17),
+        Err(BitError::TooManyBits {
+            requested: 17,
+            max: 16
+        })
+    ));
+    assert_eq!(0x00bc_def0, stream.read_u32_bits(24).unwrap());
+    assert_eq!(1.5, stream.read_f64().unwrap());
+    assert!(stream.read_f32().is_err());
+}
+
+#[test]
+#[cfg(feature = "progress")]
+fn test_progress_callback() {
+    use std::cell::RefCell;
+    use std::ops::ControlFlow;
+    use std::rc::Rc;
+
+    let bytes = vec![0x55; 32];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let callback_reports = reports.clone();
+    stream.on_progress(32, move |pos| {
+        callback_reports.borrow_mut().push(pos);
+        if pos >= 192 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+
+    for _ in 0..5 {
+        stream.read_int::<u8>(7).unwrap();
+    }
+    assert_eq!(vec![35], *reports.borrow());
+
+    // sub streams share the callback, which is only invoked again once the next interval is passed
+    let mut sub = stream.read_bits(64).unwrap();
+    assert_eq!(vec![35, 99], *reports.borrow());
+    sub.read_int::<u32>(32).unwrap();
+    assert_eq!(vec![35, 99], *reports.borrow());
+
+    stream.skip_bits(100).unwrap();
+    assert_eq!(vec![35, 99, 199], *reports.borrow());
+    assert!(matches!(
+        stream.read_bool(),
+        Err(BitError::ReadCancelled { pos: 199 })
+    ));
+    assert!(matches!(
+        sub.read_int::<u8>(8),
+        Err(BitError::ReadCancelled { .. })
+    ));
+}
+
+#[test]
+#[cfg(feature = "cancel")]
+fn test_cancel_flag() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Cancelling;
+
+    impl<'a, E: Endianness> BitRead<'a, E> for Cancelling {
+        fn read(stream: &mut BitReadStream<'a, E>) -> bitbuffer::Result<Self> {
+            stream.read::<u8>()?;
+            Ok(Cancelling)
+        }
+
+        unsafe fn read_unchecked(
+            stream: &mut BitReadStream<'a, E>,
+            checked: bitbuffer::CheckedRead,
+        ) -> bitbuffer::Result<Self> {
+            // cancel the read from inside the bulk read, while reading the 11th element
+            if stream.read_unchecked::<u8>(checked)? == 0xff {
+                CANCEL.with(|flag| flag.store(true, Ordering::Relaxed));
+            }
+            Ok(Cancelling)
+        }
+
+        fn bit_size() -> Option<usize> {
+            Some(8)
+        }
+    }
+
+    thread_local! {
+        static CANCEL: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+    }
+
+    let mut bytes = vec![0; 10_000];
+    bytes[10] = 0xff;
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    stream.set_cancel_flag(CANCEL.with(|flag| flag.clone()));
+
+    // the flag is checked periodically while reading the elements
+    let mut sub = stream.clone();
+    assert!(matches!(
+        sub.read_sized::<Vec<Cancelling>>(10_000),
+        Err(BitError::ReadCancelled { pos }) if pos == 4096 * 8
+    ));
+    assert!(matches!(
+        sub.read_sized::<Vec<u8>>(1),
+        Err(BitError::ReadCancelled { .. })
+    ));
+
+    CANCEL.with(|flag| flag.store(false, Ordering::Relaxed));
+    assert_eq!(0, stream.read::<u8>().unwrap());
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "bounds-debug"))]
+fn test_read_after_parent_truncated() {
+    let bytes = vec![0x12, 0x34, 0x56, 0x78];
+    let mut buffer = BitReadBuffer::new(&bytes, LittleEndian);
+
+    let mut stream = BitReadStream::new(buffer.clone());
+    let mut owned = stream.to_owned();
+    let mut sub = stream.read_bits(16).unwrap();
+    assert_eq!(0x56, stream.read::<u8>().unwrap());
+
+    // truncating a clone doesn't affect the original
+    let mut clone = buffer.clone();
+    clone.truncate(8).unwrap();
+    assert_eq!(0x12, sub.read::<u8>().unwrap());
+
+    let mut before_truncate = BitReadStream::new(buffer.clone());
+    before_truncate.skip_bits(8).unwrap();
+    buffer.truncate(16).unwrap();
+
+    // reads inside the remaining bits and reads from owned copies are fine
+    assert_eq!(0x34, sub.read::<u8>().unwrap());
+    assert_eq!(0x12, owned.read::<u8>().unwrap());
+    assert_eq!(0x34, before_truncate.read::<u8>().unwrap());
+
+    // reads past the new end panic
+    let streams = [&mut stream, &mut before_truncate];
+    for stream in streams {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = stream.read::<u8>();
+        }));
+        assert!(result.is_err());
+    }
+
+    // streams created after the truncation are fine
+    let mut stream = BitReadStream::new(buffer.clone());
+    assert_eq!(0x3412, stream.read::<u16>().unwrap());
+}
+
+#[test]
+fn test_read_bits_borrow() {
+    let bytes = b"foobar";
+    let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    let mut bits = stream.read_bits_borrow(16).unwrap();
+    assert!(matches!(bits.read_bytes(2).unwrap(), Cow::Borrowed(b"fo")));
+    assert_eq!(32, stream.bits_left());
+
+    stream.skip_bits(4).unwrap();
+    assert!(matches!(
+        stream.read_bits_borrow(8),
+        Err(BitError::NotAligned { bit_offset: 4 })
+    ));
+    assert_eq!(28, stream.bits_left());
+
+    // alignment is checked against the underlying buffer, not the start of the sub stream
+    let mut sub = stream.read_bits(12).unwrap();
+    assert!(matches!(
+        sub.read_bits_borrow(8),
+        Err(BitError::NotAligned { bit_offset: 4 })
+    ));
+
+    assert!(matches!(
+        stream.read_bits_borrow(24),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert_eq!(
+        "ar",
+        stream
+            .read_bits_borrow(16)
+            .unwrap()
+            .read_string(Some(2))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_read_multi() {
+    let buffer = BitReadBuffer::new(BYTES, BigEndian);
+    let mut stream = BitReadStream::new(buffer.clone());
+    let mut expected = BitReadStream::new(buffer);
+    let (a, b, c, d) = stream
+        .read_multi::<(u8, u16, i8, u64)>(&[3, 11, 2, 40])
+        .unwrap();
+    assert_eq!(expected.read_int::<u8>(3).unwrap(), a);
+    assert_eq!(expected.read_int::<u16>(11).unwrap(), b);
+    assert_eq!(expected.read_int::<i8>(2).unwrap(), c);
+    assert_eq!(expected.read_int::<u64>(40).unwrap(), d);
+    assert_eq!(expected.pos(), stream.pos());
+
+    // nothing is read when any of the integers can't be read
+    let bits_left = stream.bits_left();
+    assert!(matches!(
+        stream.read_multi::<(u8, u8)>(&[9, 1]),
+        Err(BitError::TooManyBits {
+            requested: 9,
+            max: 8
+        })
+    ));
+    assert!(matches!(
+        stream.read_multi::<(u64, u64)>(&[64, 64]),
+        Err(BitError::NotEnoughData {
+            requested: 128,
+            bits_left: left
+        }) if left == bits_left
+    ));
+    assert_eq!(bits_left, stream.bits_left());
+}
+
+#[test]
+fn test_bytes_iter() {
+    let buffer = BitReadBuffer::new(BYTES, LittleEndian);
+    let mut stream = BitReadStream::new(buffer.clone());
+    let mut expected = BitReadStream::new(buffer);
+    stream.skip_bits(3).unwrap();
+    expected.skip_bits(3).unwrap();
+
+    let bytes: Vec<u8> = stream.bytes_iter().take(4).collect();
+    assert_eq!(expected.read_bytes(4).unwrap().as_ref(), bytes.as_slice());
+    assert_eq!(expected.bits_left(), stream.bits_left());
+
+    let bytes: Vec<u8> = stream.bytes_iter().collect();
+    assert_eq!(expected.bits_left() / 8, bytes.len());
+    assert_eq!(
+        expected.read_bytes(bytes.len()).unwrap().as_ref(),
+        bytes.as_slice()
+    );
+    assert_eq!(5, stream.bits_left());
+    assert_eq!(None, stream.bytes_iter().next());
+    assert_eq!(
+        expected.read_int::<u8>(5).unwrap(),
+        stream.read_int::<u8>(5).unwrap()
+    );
+}
+
+#[cfg(feature = "compat")]
+#[test]
+fn test_compat_names() {
+    use bitbuffer::compat::{BitBuffer, BitStream, ReadError};
+
+    fn read_legacy(stream: &mut BitStream<LittleEndian>) -> Result<u8, ReadError> {
+        stream.read_int(8)
+    }
+
+    let buffer: BitBuffer<LittleEndian> = BitBuffer::from(BYTES.to_vec());
+    let mut stream = BitStream::new(buffer);
+    assert_eq!(BYTES[0], read_legacy(&mut stream).unwrap());
+
+    // the legacy names are the same types, so they can be mixed with the new names
+    let mut new_stream: BitReadStream<LittleEndian> = stream.clone();
+    assert_eq!(BYTES[1], new_stream.read_int::<u8>(8).unwrap());
+
+    // the owned bytes and endianness that `BitBuffer::new` took
+    let buffer = BitBuffer::from((BYTES.to_vec(), BigEndian));
+    assert_eq!(BYTES.len() * 8, buffer.bit_len());
+    let mut stream = BitStream::from((BYTES.to_vec(), BigEndian));
+    assert_eq!(
+        buffer.read_int::<u16>(0, 16).unwrap(),
+        stream.read_int::<u16>(16).unwrap()
+    );
+
+    let mut stream = BitStream::<LittleEndian>::from(vec![0xff]);
+    stream.skip_bits(4).unwrap();
+    let error: BitError = read_legacy(&mut stream).unwrap_err();
+    assert!(matches!(
+        error,
+        ReadError::NotEnoughData {
+            requested: 8,
+            bits_left: 4
+        }
+    ));
+}
+
+#[test]
+fn test_bounded_reader() {
+    use bitbuffer::{BoundedReader, BoundsCheck};
+
+    fn parse<M: BoundsCheck>(reader: &mut BoundedReader<BigEndian, M>) -> bitbuffer::Result<u64> {
+        let flag = reader.read_bool()?;
+        let small: u8 = reader.read_int(5)?;
+        let float: f32 = reader.read_float()?;
+        let bytes = reader.read_bytes(2)?;
+        reader.skip_bits(3)?;
+        let pair: [u8; 2] = reader.read()?;
+        Ok(flag as u64
+            + small as u64
+            + float.to_bits() as u64
+            + bytes
+                .iter()
+                .chain(&pair)
+                .map(|&byte| byte as u64)
+                .sum::<u64>())
+    }
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(BYTES, BigEndian));
+    let expected = parse(&mut stream.checked_reader()).unwrap();
+    assert_eq!(73, stream.pos());
+
+    stream.set_pos(0).unwrap();
+    assert_eq!(
+        expected,
+        parse(&mut stream.check_once(73).unwrap()).unwrap()
+    );
+    assert_eq!(73, stream.pos());
+
+    stream.set_pos(0).unwrap();
+    assert_eq!(
+        expected,
+        parse(unsafe { &mut stream.trusted_reader() }).unwrap()
+    );
+    assert_eq!(73, stream.pos());
+
+    // checked once only allows reads within the checked region
+    stream.set_pos(0).unwrap();
+    let mut reader = stream.check_once(10).unwrap();
+    reader.read_int::<u8>(8).unwrap();
+    assert!(matches!(
+        reader.read_int::<u8>(3),
+        Err(BitError::NotEnoughData {
+            requested: 3,
+            bits_left: 2
+        })
+    ));
+    assert!(matches!(
+        reader.read_int::<u8>(9),
+        Err(BitError::TooManyBits {
+            requested: 9,
+            max: 8
+        })
+    ));
+    assert_eq!(8, reader.stream().pos());
+    assert!(stream.check_once(BYTES.len() * 8).is_err());
+
+    stream.set_pos(BYTES.len() * 8 - 2).unwrap();
+    let mut reader = stream.checked_reader();
+    assert!(matches!(
+        reader.read_int::<u8>(3),
+        Err(BitError::NotEnoughData {
+            requested: 3,
+            bits_left: 2
+        })
+    ));
+
+    // byte counts that overflow when converted to bits
+    stream.set_pos(0).unwrap();
+    assert!(matches!(
+        stream.checked_reader().read_bytes(usize::MAX / 8 + 1),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        stream.check_once(8).unwrap().read_bytes(usize::MAX / 8 + 1),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert_eq!(0, stream.pos());
+}
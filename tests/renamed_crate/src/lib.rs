@@ -0,0 +1,84 @@
+//! The derives with the `bitbuffer` crate only available under a different name
+#![allow(dead_code)]
+#![allow(unreachable_patterns)]
+
+use bits::{BitRead, BitReadSized, BitWrite, BitWriteSized, Endianness, FlagEnum};
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+#[bitbuffer(crate = "bits")]
+pub struct Header {
+    #[size = 4]
+    pub size: u8,
+    #[size = "size"]
+    pub value: u16,
+    pub name: String,
+    pub flags: bits::FlagSet<Flag>,
+}
+
+#[derive(BitRead, BitWrite, PartialEq, Debug)]
+#[bitbuffer(crate = "bits")]
+#[discriminant_bits = 2]
+#[discriminant_type = "u8"]
+pub enum Message {
+    Ping,
+    #[discriminant = 2]
+    Data(u8),
+    #[unknown]
+    Other(u8),
+}
+
+#[derive(BitReadSized, BitWriteSized, PartialEq, Debug)]
+#[bitbuffer(crate = "::bits")]
+#[endianness = "E"]
+pub struct Sized<E: Endianness> {
+    #[size = "input_size"]
+    pub value: u32,
+    pub endianness: std::marker::PhantomData<E>,
+}
+
+#[derive(FlagEnum, PartialEq, Debug, Clone, Copy)]
+#[bitbuffer(crate = "bits")]
+#[flag_bits = 2]
+pub enum Flag {
+    A = 1,
+    B = 2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bits::{BigEndian, BitReadStream, BitWriteStream, FlagSet};
+    use std::marker::PhantomData;
+
+    #[test]
+    fn test_renamed_roundtrip() {
+        let header = Header {
+            size: 10,
+            value: 0b11_0000_1111,
+            name: "foo".into(),
+            flags: FlagSet::from(Flag::B),
+        };
+        let messages = [Message::Ping, Message::Data(12), Message::Other(3)];
+        let sized = Sized {
+            value: 0b101,
+            endianness: PhantomData,
+        };
+
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, BigEndian);
+        stream.write(&header).unwrap();
+        for message in &messages {
+            stream.write(message).unwrap();
+        }
+        stream.write_sized(&sized, 3).unwrap();
+        drop(stream);
+
+        let mut stream = BitReadStream::<BigEndian>::from(data.as_slice());
+        assert_eq!(header, stream.read().unwrap());
+        for message in &messages {
+            assert_eq!(*message, stream.read().unwrap());
+        }
+        assert_eq!(sized, stream.read_sized(3).unwrap());
+        assert_eq!(3, Message::Other(3).discriminant());
+    }
+}
@@ -1,7 +1,10 @@
 use bitbuffer::{
-    BigEndian, BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, LittleEndian,
+    BigEndian, BitError, BitRead, BitReadBuffer, BitReadStream, BitTrailer, BitWrite,
+    BitWriteStream, Endianness, LittleEndian, LsbFirst, MsbFirst, Presence, RawStr,
 };
 use std::fmt::Debug;
+use std::rc::Rc;
+use std::sync::Arc;
 
 #[track_caller]
 fn roundtrip<
@@ -112,3 +115,1124 @@ fn test_tuple() {
     roundtrip((1, false));
     roundtrip((1, 10.12, String::from("asd")));
 }
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_std_types() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4};
+    use std::time::{Duration, SystemTime};
+
+    roundtrip(Duration::new(1234, 5678));
+    roundtrip(SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123));
+    roundtrip(Ipv4Addr::new(127, 0, 0, 1));
+    roundtrip(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)));
+    roundtrip(IpAddr::V6(Ipv6Addr::LOCALHOST));
+    roundtrip(SocketAddr::V4(SocketAddrV4::new(
+        Ipv4Addr::new(10, 0, 0, 1),
+        27015,
+    )));
+    roundtrip((true, SocketAddr::from((Ipv6Addr::LOCALHOST, 8080))));
+}
+
+#[test]
+fn test_c_string() {
+    use std::ffi::{CStr, CString};
+
+    roundtrip(CString::new("foo").unwrap());
+    roundtrip((true, CString::default()));
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_bool(true).unwrap();
+    stream
+        .write(CStr::from_bytes_with_nul(b"ab\0").unwrap())
+        .unwrap();
+    stream.write_sized(&CString::new("cd").unwrap(), 4).unwrap();
+    assert!(matches!(
+        stream.write_sized(&CString::new("cd").unwrap(), 2),
+        Err(BitError::StringToLong {
+            string_length: 3,
+            requested_length: 2
+        })
+    ));
+    assert_eq!(1 + 3 * 8 + 4 * 8, stream.bit_len());
+    drop(stream);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert!(read.read_bool().unwrap());
+    assert_eq!(b"ab", read.read::<CString>().unwrap().as_bytes());
+    assert_eq!(b"cd", read.read_sized::<CString>(4).unwrap().as_bytes());
+    assert_eq!(1 + 7 * 8, read.pos());
+    read.set_pos(1 + 3 * 8).unwrap();
+    assert!(matches!(
+        read.read_sized::<CString>(2),
+        Err(BitError::UnterminatedString { max_bytes: 2 })
+    ));
+
+    // without a terminator before the end of the stream
+    let mut read = BitReadStream::new(BitReadBuffer::new(b"abc", LittleEndian));
+    assert!(matches!(
+        read.read::<CString>(),
+        Err(BitError::UnterminatedString { max_bytes: 3 })
+    ));
+    assert_eq!(0, read.pos());
+
+    // aligned reads borrow the string
+    let bytes = b"foo\0";
+    let mut read = BitReadStream::new(BitReadBuffer::new(bytes, LittleEndian));
+    assert!(matches!(
+        read.read::<std::borrow::Cow<CStr>>().unwrap(),
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_os_string() {
+    use std::ffi::OsString;
+
+    roundtrip(OsString::from("foo"));
+    roundtrip((true, OsString::new()));
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_sized(&OsString::from("ab"), 4).unwrap();
+    assert!(matches!(
+        stream.write(&OsString::from("a\0b")),
+        Err(BitError::InteriorNul { position: 1 })
+    ));
+    drop(stream);
+    assert_eq!(data, b"ab\0\0");
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(
+        OsString::from("ab"),
+        read.read_sized::<OsString>(4).unwrap()
+    );
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_std_types_layout() {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&Ipv4Addr::new(1, 2, 3, 4)).unwrap();
+    stream.write_sized(&Duration::new(5, 6), 8).unwrap();
+    assert_eq!(data, [1, 2, 3, 4, 5, 6, 0, 0, 0]);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(Ipv4Addr::new(1, 2, 3, 4), read.read::<Ipv4Addr>().unwrap());
+    assert_eq!(Duration::new(5, 6), read.read_sized::<Duration>(8).unwrap());
+
+    let invalid = [0, 0xff, 0xff, 0xff, 0xff];
+    let mut read = BitReadStream::new(BitReadBuffer::new(&invalid, LittleEndian));
+    assert!(read.read_sized::<Duration>(8).is_err());
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_duration_nanos_width() {
+    use std::time::Duration;
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream
+        .write_duration(&Duration::new(5, 999_999_999), 8, 30)
+        .unwrap();
+    assert_eq!(38, stream.bit_len());
+    assert!(matches!(
+        stream.write_duration(&Duration::new(256, 0), 8, 30),
+        Err(BitError::ValueOutOfRange { .. })
+    ));
+    assert!(matches!(
+        stream.write_duration(&Duration::new(0, 1_000), 8, 8),
+        Err(BitError::ValueOutOfRange { .. })
+    ));
+    assert_eq!(38, stream.bit_len());
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(
+        Duration::new(5, 999_999_999),
+        read.read_duration(8, 30).unwrap()
+    );
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_ranges_and_results() {
+    roundtrip(3u8..12);
+    roundtrip(-5i32..=5);
+    roundtrip(Ok::<u16, String>(12));
+    roundtrip(Err::<u16, String>("failed".into()));
+
+    assert_eq!(Some(32), bitbuffer::bit_size_of::<std::ops::Range<u16>>());
+    assert_eq!(Some(9), bitbuffer::bit_size_of::<Result<u8, i8>>());
+    assert_eq!(None, bitbuffer::bit_size_of::<Result<u8, u16>>());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_sized(&(1u8..=2), 4).unwrap();
+    stream.write(&Err::<bool, u8>(0b101)).unwrap();
+    stream.write(&Ok::<bool, u8>(true)).unwrap();
+    assert_eq!(data, [0b0010_0001, 0b0000_1011, 0b0000_0100]);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(
+        1..=2,
+        read.read_sized::<std::ops::RangeInclusive<u8>>(4).unwrap()
+    );
+    assert_eq!(Err(0b101), read.read::<Result<bool, u8>>().unwrap());
+    assert_eq!(Ok(true), read.read::<Result<bool, u8>>().unwrap());
+}
+
+#[cfg(all(feature = "smallvec", feature = "arrayvec", feature = "heapless"))]
+#[test]
+fn test_fixed_capacity_vecs() {
+    use bitbuffer::BitError;
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    let small: smallvec::SmallVec<[u8; 2]> = smallvec::SmallVec::from_slice(&[1, 2, 3]);
+    stream.write(&small).unwrap();
+    let array: arrayvec::ArrayVec<u8, 4> = [4, 5].into_iter().collect();
+    stream.write_sized(&array, 2).unwrap();
+    let heapless: heapless::Vec<u8, 2> = heapless::Vec::from_slice(&[6, 7]).unwrap();
+    stream.write(&heapless).unwrap();
+    assert!(matches!(
+        stream.write_sized(&heapless, 3),
+        Err(BitError::ElementCountMismatch {
+            expected: 3,
+            actual: 2
+        })
+    ));
+    assert_eq!(data, [1, 2, 3, 4, 5, 6, 7]);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(
+        small,
+        read.read_sized::<smallvec::SmallVec<[u8; 2]>>(3).unwrap()
+    );
+    assert_eq!(
+        array,
+        read.read_sized::<arrayvec::ArrayVec<u8, 4>>(2).unwrap()
+    );
+    let pos = read.pos();
+    assert!(matches!(
+        read.read_sized::<heapless::Vec<u8, 2>>(3),
+        Err(BitError::CapacityExceeded {
+            requested: 3,
+            capacity: 2
+        })
+    ));
+    assert_eq!(pos, read.pos());
+    assert_eq!(
+        heapless,
+        read.read_sized::<heapless::Vec<u8, 2>>(2).unwrap()
+    );
+}
+
+#[test]
+fn test_size_units() {
+    #[derive(Debug, PartialEq, BitRead, BitWrite)]
+    struct Units {
+        #[size(bytes = 1)]
+        int: u16,
+        #[size(bits = 24)]
+        string: String,
+        count: u8,
+        #[size(bytes = "count")]
+        pair: [u16; 2],
+        #[size(bits = 12)]
+        small: u16,
+    }
+    roundtrip(Units {
+        int: 12,
+        string: "foo".into(),
+        count: 1,
+        pair: [12, 34],
+        small: 1234,
+    });
+    assert_eq!(None, bitbuffer::bit_size_of::<Units>());
+
+    #[derive(Debug, PartialEq, BitRead, BitWrite)]
+    struct FixedUnits {
+        #[size(bytes = 1)]
+        int: u16,
+        #[size(bits = 16)]
+        string: String,
+    }
+    assert_eq!(Some(8 + 16), bitbuffer::bit_size_of::<FixedUnits>());
+
+    #[derive(Debug, PartialEq, BitRead)]
+    struct Count {
+        #[size(count = 2)]
+        list: Vec<u8>,
+    }
+    let data = [1, 2, 3, 4];
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(vec![1, 2], read.read::<Count>().unwrap().list);
+
+    #[derive(Debug, PartialEq, BitRead)]
+    struct Mismatch {
+        #[size(bits = 12)]
+        string: String,
+    }
+    assert!(matches!(
+        read.read::<Mismatch>(),
+        Err(bitbuffer::BitError::SizeUnitMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_sized_each_and_total() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream
+        .write_sized_each(&(3u8, String::from("ab")), 2)
+        .unwrap();
+    stream.write_sized_total(&[1u16, 2, 3], 30).unwrap();
+    assert!(matches!(
+        stream.write_sized_total(&(1u8, 2u8), 5),
+        Err(bitbuffer::BitError::UnevenSize {
+            size: 5,
+            element_count: 2
+        })
+    ));
+    assert_eq!(2 + 16 + 30, stream.bit_len());
+
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, BigEndian));
+    assert_eq!(
+        (3u8, String::from("ab")),
+        read.read_sized_each::<(u8, String)>(2).unwrap()
+    );
+    assert_eq!([1u16, 2, 3], read.read_sized_total::<[u16; 3]>(30).unwrap());
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_lock_types() {
+    use std::sync::{Mutex, RwLock};
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&Mutex::new(12u8)).unwrap();
+    stream.write_sized(&RwLock::new(3u8), 2).unwrap();
+    assert_eq!(10, stream.bit_len());
+
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, LittleEndian));
+    let mutex: Mutex<u8> = read.read().unwrap();
+    assert_eq!(12, *mutex.lock().unwrap());
+    let lock: RwLock<u8> = read.read_sized(2).unwrap();
+    assert_eq!(3, *lock.read().unwrap());
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn test_bigint() {
+    use bitbuffer::{BitReadSized, BitWriteSized, Endianness};
+    use num_bigint::{BigInt, BigUint};
+
+    fn roundtrip_sized<E: Endianness, T>(val: T, size: usize)
+    where
+        T: for<'a> BitReadSized<'a, E> + BitWriteSized<E> + Debug + PartialEq,
+    {
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_bool(true).unwrap();
+        stream.write_sized(&val, size).unwrap();
+        stream.write_bool(true).unwrap();
+        assert_eq!(size + 2, stream.bit_len());
+
+        let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, E::endianness()));
+        assert!(read.read_bool().unwrap());
+        assert_eq!(val, read.read_sized::<T>(size).unwrap());
+        assert!(read.read_bool().unwrap());
+    }
+
+    let big = (BigUint::from(u128::MAX) << 70usize) + 12345u32;
+    roundtrip_sized::<LittleEndian, _>(big.clone(), 200);
+    roundtrip_sized::<BigEndian, _>(big, 200);
+    roundtrip_sized::<LittleEndian, _>(BigUint::from(5u8), 3);
+    roundtrip_sized::<BigEndian, _>(BigUint::from(5u8), 3);
+    roundtrip_sized::<LittleEndian, _>(BigInt::from(-12345678901234567890i128) << 80usize, 256);
+    roundtrip_sized::<BigEndian, _>(BigInt::from(-12345678901234567890i128) << 80usize, 256);
+    roundtrip_sized::<BigEndian, _>(BigInt::from(-4), 3);
+
+    // same bit layout as the primitive integers
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write_sized(&BigInt::from(-1234567i64), 45).unwrap();
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, BigEndian));
+    assert_eq!(-1234567i64, read.read_int::<i64>(45).unwrap());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    assert!(stream.write_sized(&BigUint::from(8u8), 3).is_err());
+    assert!(stream.write_sized(&BigInt::from(-5), 3).is_err());
+}
+
+#[cfg(feature = "enumflags2")]
+#[test]
+fn test_flags() {
+    use enumflags2::{bitflags, BitFlags};
+
+    #[bitflags]
+    #[repr(u16)]
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    enum Permission {
+        Read = 0b0001,
+        Write = 0b0010,
+        Execute = 0b0100,
+        Admin = 0b1000_0000_0000,
+    }
+
+    #[derive(BitRead, BitWrite, Debug, PartialEq)]
+    struct Entry {
+        #[flags(bits = 12)]
+        permissions: BitFlags<Permission>,
+        other: BitFlags<Permission>,
+    }
+
+    roundtrip(Permission::Read | Permission::Admin);
+    roundtrip(BitFlags::<Permission>::empty());
+    roundtrip(Entry {
+        permissions: Permission::Write | Permission::Execute | Permission::Admin,
+        other: BitFlags::all(),
+    });
+    assert_eq!(Some(28), <Entry as BitRead<LittleEndian>>::bit_size());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream
+        .write_sized(&(Permission::Read | Permission::Admin), 12)
+        .unwrap();
+    stream.write_int(0b1000u16, 12).unwrap();
+    assert!(stream
+        .write_sized(&BitFlags::from(Permission::Admin), 11)
+        .is_err());
+
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, LittleEndian));
+    assert_eq!(0b1000_0000_0001, read.read_int::<u16>(12).unwrap());
+    // bit 3 isn't a known flag
+    assert!(matches!(
+        read.read_sized::<BitFlags<Permission>>(12),
+        Err(bitbuffer::BitError::ValueOutOfRange { .. })
+    ));
+}
+
+const SPECIAL_F32: &[u32] = &[
+    0x0000_0000, // 0
+    0x8000_0000, // -0
+    0x0000_0001, // smallest denormal
+    0x007f_ffff, // largest denormal
+    0x0080_0000, // smallest normal
+    0x7f7f_ffff, // max
+    0x7f80_0000, // inf
+    0xff80_0000, // -inf
+    0x7fc0_0000, // quiet nan
+    0x7f80_0001, // signaling nan
+    0xffc1_2345, // negative nan with payload
+    0x3f80_0000, // 1
+];
+
+const SPECIAL_F64: &[u64] = &[
+    0x0000_0000_0000_0000,
+    0x8000_0000_0000_0000,
+    0x0000_0000_0000_0001,
+    0x000f_ffff_ffff_ffff,
+    0x0010_0000_0000_0000,
+    0x7fef_ffff_ffff_ffff,
+    0x7ff0_0000_0000_0000,
+    0xfff0_0000_0000_0000,
+    0x7ff8_0000_0000_0000,
+    0x7ff0_0000_0000_0001,
+    0xfff8_1234_5678_9abc,
+    0x3ff0_0000_0000_0000,
+];
+
+fn float_roundtrip_at<E: Endianness>(offset: usize, f32_bits: u32, f64_bits: u64) {
+    let mut data = Vec::new();
+    {
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_int(0u8, offset % 8).unwrap();
+        stream.write_int(0u64, offset - offset % 8).unwrap();
+        stream.write_float(f32::from_bits(f32_bits)).unwrap();
+        stream.write_float(f64::from_bits(f64_bits)).unwrap();
+    }
+    // only padding to the next byte after the floats, so the last read hits the end of the buffer
+    let buffer = BitReadBuffer::new_owned(data, E::endianness());
+    assert_eq!(buffer.byte_len(), (offset + 96 + 7) / 8);
+
+    assert_eq!(buffer.read_int::<u32>(offset, 32).unwrap(), f32_bits);
+    assert_eq!(buffer.read_int::<u64>(offset + 32, 64).unwrap(), f64_bits);
+    assert_eq!(
+        buffer.read_float::<f32>(offset).unwrap().to_bits(),
+        f32_bits,
+        "f32 {:#x} at offset {}",
+        f32_bits,
+        offset
+    );
+    assert_eq!(
+        buffer.read_float::<f64>(offset + 32).unwrap().to_bits(),
+        f64_bits,
+        "f64 {:#x} at offset {}",
+        f64_bits,
+        offset
+    );
+
+    let mut stream = BitReadStream::new(buffer);
+    stream.skip_bits(offset).unwrap();
+    assert_eq!(stream.read::<f32>().unwrap().to_bits(), f32_bits);
+    assert_eq!(stream.read::<f64>().unwrap().to_bits(), f64_bits);
+    assert!(stream.bits_left() < 8);
+}
+
+#[test]
+fn test_float_special_values_all_offsets() {
+    for offset in 0..24 {
+        for (f32_bits, f64_bits) in SPECIAL_F32.iter().zip(SPECIAL_F64) {
+            float_roundtrip_at::<LittleEndian>(offset, *f32_bits, *f64_bits);
+            float_roundtrip_at::<BigEndian>(offset, *f32_bits, *f64_bits);
+        }
+    }
+}
+
+#[test]
+fn test_run_length() {
+    fn runs<E: Endianness>(endianness: E) {
+        let runs = [
+            (true, 1),
+            (false, 3),
+            (true, 70),
+            (false, 129),
+            (true, 7),
+            (false, 64),
+            (true, 1),
+        ];
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, endianness);
+        for (bit, length) in runs {
+            stream.write_run(bit, length).unwrap();
+        }
+        let bit_len = stream.bit_len();
+
+        let mut buffer = BitReadBuffer::new(&data, E::endianness());
+        buffer.truncate(bit_len).unwrap();
+        let mut read = BitReadStream::new(buffer);
+        for (bit, length) in runs {
+            assert_eq!(length, read.count_consecutive(bit));
+            assert_eq!(0, read.count_consecutive(!bit));
+            assert_eq!((bit, length), read.read_run_length(usize::MAX).unwrap());
+        }
+        assert_eq!(0, read.bits_left());
+
+        // runs are split at max_bits
+        let mut read = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+        read.skip_bits(4).unwrap();
+        assert_eq!((true, 50), read.read_run_length(50).unwrap());
+        assert_eq!((true, 20), read.read_run_length(50).unwrap());
+    }
+    runs(LittleEndian);
+    runs(BigEndian);
+}
+
+#[test]
+fn test_bit_string() {
+    use bitbuffer::{from_bit_string, to_bit_string, BitError};
+
+    let value = (true, 0x1234u16, -1i8, String::from("a"));
+    let bits = to_bit_string(&value, BigEndian).unwrap();
+    assert_eq!("10001001 00011010 01111111 10110000 10000000 0", bits);
+    assert_eq!(
+        value,
+        from_bit_string::<(bool, u16, i8, String), _>(&bits, BigEndian).unwrap()
+    );
+
+    let bits = to_bit_string(&value, LittleEndian).unwrap();
+    assert_eq!(
+        value,
+        from_bit_string::<(bool, u16, i8, String), _>(&bits, LittleEndian).unwrap()
+    );
+
+    assert_eq!("", to_bit_string(&Vec::<u8>::new(), LittleEndian).unwrap());
+    assert!(matches!(
+        from_bit_string::<u8, _>("0101 012", LittleEndian),
+        Err(BitError::InvalidBitString {
+            index: 7,
+            char: '2'
+        })
+    ));
+    assert!(matches!(
+        from_bit_string::<u8, _>("0101", LittleEndian),
+        Err(BitError::NotEnoughData { .. })
+    ));
+}
+
+#[test]
+fn test_transcode() {
+    use bitbuffer::{transcode, transcode_all};
+
+    #[derive(BitRead, BitWrite, Debug, PartialEq)]
+    struct Record {
+        #[size = 4]
+        kind: u8,
+        value: i32,
+        #[size = 12]
+        small: u16,
+    }
+
+    let records = vec![
+        Record {
+            kind: 3,
+            value: -123456,
+            small: 0xabc,
+        },
+        Record {
+            kind: 15,
+            value: 0x1234_5678,
+            small: 1,
+        },
+    ];
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    for record in &records {
+        stream.write(record).unwrap();
+    }
+
+    let mut input = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    let converted = transcode_all::<Record, _, _>(&mut input, BigEndian).unwrap();
+    assert_eq!(0, input.bits_left());
+
+    let mut output = BitReadStream::new(BitReadBuffer::new(&converted, BigEndian));
+    assert_eq!(records[0], output.read::<Record>().unwrap());
+    assert_eq!(records[1], output.read::<Record>().unwrap());
+
+    let mut input = BitReadStream::new(BitReadBuffer::new(&converted, BigEndian));
+    let back = transcode::<[Record; 2], _, _>(&mut input, LittleEndian).unwrap();
+    assert_eq!(data, back);
+
+    // types that don't read any bits stop the transcoding instead of looping forever
+    let mut input = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    let converted = transcode_all::<std::marker::PhantomData<u8>, _, _>(&mut input, BigEndian);
+    assert!(converted.unwrap().is_empty());
+    assert_eq!(data.len() * 8, input.bits_left());
+}
+
+#[test]
+fn test_bitmap() {
+    fn bitmap<E: Endianness>() {
+        let bitmap: Vec<bool> = (0..200).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_bool(true).unwrap();
+        stream.write_bitmap(&bitmap).unwrap();
+        stream.write_bitmap(&[]).unwrap();
+        assert_eq!(201, stream.bit_len());
+
+        let mut read = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+        let mut bits = read.clone();
+        assert!(read.read_bool().unwrap());
+        assert_eq!(bitmap, read.read_bitmap(200).unwrap());
+        assert!(read.read_bitmap(0).unwrap().is_empty());
+        assert!(read.read_bitmap(8).is_err());
+
+        // matches reading the bits one by one
+        bits.skip_bits(1).unwrap();
+        for bit in bitmap {
+            assert_eq!(bit, bits.read_bool().unwrap());
+        }
+    }
+    bitmap::<LittleEndian>();
+    bitmap::<BigEndian>();
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn test_bitvec() {
+    use bitvec::prelude::*;
+
+    fn convert<E: Endianness, O: BitOrder>() {
+        let bits: BitVec<u8, O> = (0..75).map(|i| i % 3 == 0 || i % 5 == 0).collect();
+
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_bool(false).unwrap();
+        stream.write_bits_from_bitslice(&bits).unwrap();
+        stream.write_bits_from_bitslice(&bits[3..10]).unwrap();
+        assert_eq!(1 + 75 + 7, stream.bit_len());
+
+        let mut read = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+        read.skip_bits(1).unwrap();
+        for bit in bits.iter().by_vals() {
+            assert_eq!(bit, read.read_bool().unwrap());
+        }
+        for bit in bits[3..10].iter().by_vals() {
+            assert_eq!(bit, read.read_bool().unwrap());
+        }
+
+        let mut stream = BitReadStream::<E>::from(&bits[1..]);
+        assert_eq!(74, stream.bit_len());
+        assert_eq!(bits[1..], BitVec::<u8, O>::from(&stream));
+        stream.skip_bits(10).unwrap();
+        assert_eq!(bits[11..], BitVec::<u8, O>::from(&stream));
+
+        let buffer = BitReadBuffer::<E>::from(bits.as_bitslice());
+        assert_eq!(bits, BitVec::<u8, O>::from(&buffer));
+    }
+
+    convert::<LittleEndian, Lsb0>();
+    convert::<LittleEndian, Msb0>();
+    convert::<BigEndian, Lsb0>();
+    convert::<BigEndian, Msb0>();
+
+    let mut data = [0u8; 1];
+    let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    assert!(stream
+        .write_bits_from_bitslice(bits![1, 0, 1, 0, 1, 0, 1, 0, 1])
+        .is_err());
+    assert_eq!(0, stream.bit_len());
+}
+
+/// The bits of the lowest `count` bits of `value`, in the order they are written
+fn reference_bits<E: Endianness>(value: u128, count: usize) -> Vec<bool> {
+    let bits = (0..count).map(|i| value >> i & 1 == 1);
+    if E::is_le() {
+        bits.collect()
+    } else {
+        bits.rev().collect()
+    }
+}
+
+/// Check reading and writing integers of every size at every bit offset against writing the
+/// bits one by one, to make sure the results don't depend on the pointer width of the target
+macro_rules! check_int_bit_by_bit {
+    ($endianness:ty, $type:ty) => {{
+        const PATTERN: u128 = 0xA5C3_96E1_7B2D_F048_1F2E_3D4C_5B6A_7988;
+        for offset in 0..8 {
+            for count in 1..=<$type>::BITS as usize {
+                let pattern = PATTERN >> offset;
+                let value = pattern as $type;
+                let unused = 128 - count as u32;
+                let expected = if <$type>::MIN == 0 {
+                    (pattern << unused >> unused) as $type
+                } else {
+                    ((pattern << unused) as i128 >> unused) as $type
+                };
+
+                let mut reference = Vec::new();
+                let mut stream = BitWriteStream::new(&mut reference, <$endianness>::endianness());
+                for bit in reference_bits::<$endianness>(u128::MAX, offset)
+                    .into_iter()
+                    .chain(reference_bits::<$endianness>(pattern, count))
+                {
+                    stream.write_bool(bit).unwrap();
+                }
+                drop(stream);
+
+                let mut data = Vec::new();
+                let mut stream = BitWriteStream::new(&mut data, <$endianness>::endianness());
+                stream.write_int(u8::MAX, offset).unwrap();
+                stream.write_int(value, count).unwrap();
+                drop(stream);
+                assert_eq!(
+                    reference,
+                    data,
+                    "writing {} bits of {} at offset {}",
+                    count,
+                    stringify!($type),
+                    offset
+                );
+
+                let mut read =
+                    BitReadStream::new(BitReadBuffer::new(&reference, <$endianness>::endianness()));
+                read.skip_bits(offset).unwrap();
+                assert_eq!(
+                    expected,
+                    read.read_int::<$type>(count).unwrap(),
+                    "reading {} bits of {} at offset {}",
+                    count,
+                    stringify!($type),
+                    offset
+                );
+            }
+        }
+    }};
+}
+
+macro_rules! check_ints_bit_by_bit {
+    ($endianness:ty) => {
+        check_int_bit_by_bit!($endianness, u8);
+        check_int_bit_by_bit!($endianness, i8);
+        check_int_bit_by_bit!($endianness, u16);
+        check_int_bit_by_bit!($endianness, i16);
+        check_int_bit_by_bit!($endianness, u32);
+        check_int_bit_by_bit!($endianness, i32);
+        check_int_bit_by_bit!($endianness, u64);
+        check_int_bit_by_bit!($endianness, i64);
+        check_int_bit_by_bit!($endianness, u128);
+        check_int_bit_by_bit!($endianness, i128);
+        check_int_bit_by_bit!($endianness, usize);
+        check_int_bit_by_bit!($endianness, isize);
+    };
+}
+
+#[test]
+fn test_int_pointer_width_independent() {
+    check_ints_bit_by_bit!(LittleEndian);
+    check_ints_bit_by_bit!(BigEndian);
+}
+
+#[test]
+fn test_mixed_bit_and_byte_order() {
+    let bytes = [0b1010_0101, 0x12, 0x34, 0x56, 0x78, 0x3f, 0x80, 0x00, 0x00];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(
+        &bytes,
+        LsbFirst::<BigEndian>::endianness(),
+    ));
+    // bits are filled the same as little endian
+    assert_eq!(0b101, stream.read_int::<u8>(3).unwrap());
+    assert_eq!(0b10100, stream.read_int::<u8>(5).unwrap());
+    // bytes are ordered big endian
+    assert_eq!(0x1234, stream.read::<u16>().unwrap());
+    // the last byte only contains the remaining bits
+    assert_eq!(0x568, stream.read_int::<u16>(12).unwrap());
+    assert_eq!(0x7, stream.read_int::<u8>(4).unwrap());
+    assert_eq!(1.0, stream.read::<f32>().unwrap());
+
+    let bytes = [0x34, 0x12, 0b1010_0101];
+    let mut stream = BitReadStream::new(BitReadBuffer::new(
+        &bytes,
+        MsbFirst::<LittleEndian>::endianness(),
+    ));
+    assert_eq!(0x1234, stream.read::<u16>().unwrap());
+    assert_eq!(0b101, stream.read_int::<u8>(3).unwrap());
+    assert_eq!("LsbFirst<BigEndian>", LsbFirst::<BigEndian>::as_string());
+    assert_eq!("LittleEndian", LsbFirst::<LittleEndian>::as_string());
+}
+
+fn roundtrip_mixed<E: Endianness>() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, E::endianness());
+    stream.write_bool(true).unwrap();
+    stream.write_int(0x1234u16, 16).unwrap();
+    stream.write_int(-5i32, 27).unwrap();
+    stream.write_float(1.5f64).unwrap();
+    stream.write_int(0x0123_4567_89ab_cdefu64, 61).unwrap();
+    stream.write_float(-2.5f32).unwrap();
+    stream
+        .write_int(0x0123_4567_89ab_cdef_0123_4567_89ab_cdefu128, 128)
+        .unwrap();
+    drop(stream);
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+    assert!(stream.read_bool().unwrap());
+    assert_eq!(0x1234, stream.read_int::<u16>(16).unwrap());
+    assert_eq!(-5, stream.read_int::<i32>(27).unwrap());
+    assert_eq!(1.5, stream.read_float::<f64>().unwrap());
+    assert_eq!(0x0123_4567_89ab_cdef, stream.read_int::<u64>(61).unwrap());
+    assert_eq!(-2.5, stream.read_float::<f32>().unwrap());
+    assert_eq!(
+        0x0123_4567_89ab_cdef_0123_4567_89ab_cdef,
+        stream.read_int::<u128>(128).unwrap()
+    );
+}
+
+#[test]
+fn test_mixed_bit_and_byte_order_roundtrip() {
+    roundtrip_mixed::<LsbFirst<BigEndian>>();
+    roundtrip_mixed::<MsbFirst<LittleEndian>>();
+    roundtrip_mixed::<LsbFirst<LittleEndian>>();
+
+    // aligned values are stored in the byte order
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LsbFirst::<BigEndian>::endianness());
+    stream.write_int(0x1234u16, 16).unwrap();
+    stream.write_float(1.0f32).unwrap();
+    drop(stream);
+    assert_eq!(vec![0x12, 0x34, 0x3f, 0x80, 0, 0], data);
+}
+
+#[test]
+fn test_presence_roundtrip() {
+    roundtrip(Presence::<u16>::new(Some(1234)));
+    roundtrip(Presence::<u16>::new(None));
+    roundtrip(Presence::<u16, 3, true>::new(Some(1234)));
+    roundtrip(Presence::<u16, 3, true>::new(None));
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new(&[0b10, 0xff], LittleEndian));
+    let presence: Presence<u8, 2> = stream.read().unwrap();
+    assert_eq!(Some(0b1100_0000), presence.into_inner());
+}
+
+#[test]
+fn test_delta_seq_roundtrip() {
+    let values = [0, 5, 3, u64::MAX, 0, 1 << 63, 12, 12];
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write_bool(true).unwrap();
+    stream.write_delta_seq(&values).unwrap();
+    stream.write_varint(u64::MAX).unwrap();
+    drop(stream);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, BigEndian));
+    assert!(read.read_bool().unwrap());
+    assert_eq!(
+        values.as_slice(),
+        read.read_delta_seq(values.len()).unwrap()
+    );
+    assert_eq!(u64::MAX, read.read_varint().unwrap());
+}
+
+#[test]
+fn test_float_sized_roundtrip() {
+    fn roundtrip_float_sized<E: Endianness>() {
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_bool(true).unwrap();
+        stream.write_float_sized(-1.25f32, 24).unwrap();
+        stream.write_float_sized(1.0f64 / 3.0, 40).unwrap();
+        stream.write_float_sized(f32::INFINITY, 16).unwrap();
+        assert_eq!(stream.bit_len(), 1 + 24 + 40 + 16);
+        drop(stream);
+
+        let mut read = BitReadStream::new(BitReadBuffer::new(&data, E::endianness()));
+        assert!(read.read_bool().unwrap());
+        assert_eq!(-1.25, read.read_float_sized::<f32>(24).unwrap());
+        let third = read.read_float_sized::<f64>(40).unwrap();
+        assert_eq!((1.0f64 / 3.0).to_bits() >> 24, third.to_bits() >> 24);
+        assert_eq!(0, third.to_bits() & 0xff_ffff);
+        assert_eq!(f32::INFINITY, read.read_float_sized::<f32>(16).unwrap());
+    }
+
+    roundtrip_float_sized::<LittleEndian>();
+    roundtrip_float_sized::<BigEndian>();
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    assert!(matches!(
+        stream.write_float_sized(1.5f32, 33),
+        Err(BitError::TooManyBitsForValue {
+            requested: 33,
+            max: 32,
+            ..
+        })
+    ));
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_roundtrip {
+    use bitbuffer::testing::check_roundtrip_with;
+    use bitbuffer::{roundtrip_tests, BitRead, BitWrite};
+    use proptest::prelude::*;
+
+    #[derive(BitRead, BitWrite, Debug, PartialEq)]
+    struct Record {
+        #[size = 5]
+        small: u8,
+        flag: bool,
+        value: i64,
+        name: String,
+    }
+
+    roundtrip_tests! {
+        roundtrip_u64: u64,
+        roundtrip_i128: i128,
+        roundtrip_tuple: (bool, u8, i16),
+        roundtrip_array: [u32; 3],
+        roundtrip_option: Option<u16>,
+    }
+
+    #[test]
+    fn roundtrip_derived() {
+        check_roundtrip_with(
+            (0..32u8, any::<bool>(), any::<i64>(), "[a-z]{0,8}").prop_map(
+                |(small, flag, value, name)| Record {
+                    small,
+                    flag,
+                    value,
+                    name,
+                },
+            ),
+        );
+    }
+}
+
+#[test]
+fn test_slice_pointer_roundtrip() {
+    let boxed: Box<[u16]> = vec![1, 2, 3].into();
+    let rc: Rc<[bool]> = vec![true, false].into();
+    let arc: Arc<[String]> = vec!["foo".to_string()].into();
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_sized(&boxed, 3).unwrap();
+    stream.write(&rc).unwrap();
+    stream.write_sized(&arc, 1).unwrap();
+    drop(stream);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(boxed, read.read_sized::<Box<[u16]>>(3).unwrap());
+    assert_eq!(rc, read.read_sized::<Rc<[bool]>>(2).unwrap());
+    assert_eq!(arc, read.read_sized::<Arc<[String]>>(1).unwrap());
+    assert_eq!(3 * 16 + 2 + 4 * 8, read.pos());
+}
+
+#[test]
+fn test_raw_str() {
+    #[derive(BitRead, BitWrite, Debug, PartialEq)]
+    struct Entry<'a> {
+        flag: bool,
+        name: RawStr<'a>,
+        #[size = 4]
+        tag: RawStr<'a>,
+    }
+
+    let entry = Entry {
+        flag: true,
+        name: RawStr::from("foo"),
+        tag: RawStr::from(&b"\xffx"[..]),
+    };
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write(&entry).unwrap();
+    drop(stream);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    let result: Entry = read.read().unwrap();
+    assert_eq!(entry, result);
+    assert_eq!("foo", result.name.as_str().unwrap());
+    assert!(matches!(
+        result.tag.as_str(),
+        Err(BitError::Utf8Error(_, 2))
+    ));
+    assert_eq!("\u{FFFD}x", result.tag.to_string_lossy());
+    assert_eq!(8 * 8 + 1, read.pos());
+
+    // the same bytes as reading a String, without the validation
+    let bytes = b"abc\0de";
+    let mut stream = BitReadStream::new(BitReadBuffer::new(bytes, BigEndian));
+    let mut raw_stream = stream.clone();
+    assert_eq!(
+        stream.read::<String>().unwrap(),
+        raw_stream.read::<RawStr>().unwrap().as_str().unwrap()
+    );
+    assert_eq!(
+        stream.read::<String>().unwrap(),
+        raw_stream.read::<RawStr>().unwrap().into_string().unwrap()
+    );
+    assert_eq!(stream.bits_left(), raw_stream.bits_left());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    assert!(matches!(
+        stream.write_sized(&RawStr::from("abc"), 2),
+        Err(BitError::StringToLong { .. })
+    ));
+}
+
+fn trailer_roundtrip<E: Endianness>(trailer: BitTrailer) {
+    for bit_len in 0..=24 {
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_int(u32::MAX, bit_len).unwrap();
+        let byte_len = stream.finish_with_trailer(trailer).unwrap();
+        assert_eq!(byte_len, data.len());
+
+        let mut buffer = BitReadBuffer::new_owned(data, E::endianness());
+        buffer.strip_trailer(trailer).unwrap();
+        assert_eq!(bit_len, buffer.bit_len(), "{:?}", trailer);
+        if bit_len > 0 {
+            let mut stream = BitReadStream::new(buffer);
+            assert_eq!(
+                ((1u64 << bit_len) - 1) as u32,
+                stream.read_int::<u32>(bit_len).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_trailer_roundtrip() {
+    trailer_roundtrip::<LittleEndian>(BitTrailer::StopBit);
+    trailer_roundtrip::<LittleEndian>(BitTrailer::LengthByte);
+    trailer_roundtrip::<BigEndian>(BitTrailer::StopBit);
+    trailer_roundtrip::<BigEndian>(BitTrailer::LengthByte);
+
+    for (data, trailer) in [
+        (vec![], BitTrailer::StopBit),
+        (vec![1, 0], BitTrailer::StopBit),
+        (vec![], BitTrailer::LengthByte),
+        (vec![1, 9], BitTrailer::LengthByte),
+        (vec![1, 0], BitTrailer::LengthByte),
+        (vec![3], BitTrailer::LengthByte),
+    ] {
+        let mut buffer = BitReadBuffer::new_owned(data, BigEndian);
+        assert!(matches!(
+            buffer.strip_trailer(trailer),
+            Err(BitError::InvalidTrailer { trailer: invalid }) if invalid == trailer
+        ));
+    }
+}
+
+#[track_caller]
+fn check_byte_array<E: Endianness>(offset: usize) {
+    let bytes: [u8; 5] = [0x12, 0x34, 0xab, 0xcd, 0xef];
+
+    let mut expected = Vec::new();
+    let mut stream = BitWriteStream::new(&mut expected, E::endianness());
+    stream.write_int(0b101u8, offset).unwrap();
+    for byte in bytes {
+        stream.write(&byte).unwrap();
+    }
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, E::endianness());
+    stream.write_int(0b101u8, offset).unwrap();
+    stream.write(&bytes).unwrap();
+    assert_eq!(expected, data);
+
+    let mut stream = BitReadStream::new(BitReadBuffer::new_owned(data, E::endianness()));
+    stream.skip_bits(offset).unwrap();
+    let mut single = stream.clone();
+    assert_eq!(bytes, stream.read::<[u8; 5]>().unwrap());
+    for byte in bytes {
+        assert_eq!(byte, single.read::<u8>().unwrap());
+    }
+    assert_eq!(single.bits_left(), stream.bits_left());
+}
+
+#[test]
+fn test_byte_array() {
+    for offset in [0, 3, 8] {
+        check_byte_array::<LittleEndian>(offset);
+        check_byte_array::<BigEndian>(offset);
+    }
+
+    let mut data = vec![0u8; 2];
+    let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    stream.write_bool(true).unwrap();
+    assert!(matches!(
+        stream.write(&[1u8, 2]),
+        Err(BitError::WriteOverflow { .. })
+    ));
+}
+
+#[test]
+fn test_endianness_constructors() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::le(&mut data);
+    stream.write_int(0x1234u16, 16).unwrap();
+    drop(stream);
+    assert_eq!(0x1234u16, BitReadStream::le(&data).read::<u16>().unwrap());
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::be(&mut data);
+    stream.write_int(0x1234u16, 16).unwrap();
+    drop(stream);
+    assert_eq!(data, [0x12, 0x34]);
+    assert_eq!(0x1234u16, BitReadStream::be(&data).read::<u16>().unwrap());
+}
@@ -1,6 +1,9 @@
 use bitbuffer::num_traits::{IsSigned, SplitFitUsize, UncheckedPrimitiveInt};
-use bitbuffer::{BigEndian, BitReadBuffer, BitReadStream, BitWriteStream, LittleEndian};
-use num_traits::{PrimInt, WrappingSub};
+use bitbuffer::num_traits::{PrimInt, WrappingSub};
+use bitbuffer::{
+    BigEndian, BitEditBuffer, BitError, BitReadBuffer, BitReadStream, BitWriteStream, ByteLen,
+    Endianness, GrowthStrategy, LittleEndian, LsbFirst, MsbFirst,
+};
 use std::any::type_name;
 use std::fmt::Debug;
 use std::mem::size_of;
@@ -211,6 +214,28 @@ fn test_write_container() {
     assert_eq!(Arc::new(true), read.read().unwrap());
 }
 
+#[test]
+fn test_write_iter() {
+    let mut data = Vec::new();
+    {
+        let mut stream = BitWriteStream::new(&mut data, BigEndian);
+
+        stream.write_iter((0..3u16).map(|i| i * 1000)).unwrap();
+        stream.write_iter_sized(["ab", "c"].iter(), 3).unwrap();
+        stream.write_iter(std::iter::empty::<u8>()).unwrap();
+    }
+
+    let mut read = BitReadStream::from(BitReadBuffer::new(&data, BigEndian));
+
+    assert_eq!(
+        vec![0u16, 1000, 2000],
+        read.read_sized::<Vec<u16>>(3).unwrap()
+    );
+    assert_eq!("ab", read.read_string(Some(3)).unwrap());
+    assert_eq!("c", read.read_string(Some(3)).unwrap());
+    assert_eq!(0, read.bits_left());
+}
+
 #[test]
 fn test_write_to_slice() {
     let mut data = [0; 32];
@@ -248,6 +273,52 @@ fn test_write_last_slice() {
     assert!(read.read_bool().unwrap());
 }
 
+#[test]
+fn test_write_slice_overflow() {
+    let mut data = [0; 2];
+    let mut stream = BitWriteStream::from_slice(&mut data[..], LittleEndian);
+    assert_eq!(Some(16), stream.remaining_capacity_bits());
+
+    stream.write_int(0b101u8, 3).unwrap();
+    assert_eq!(Some(13), stream.remaining_capacity_bits());
+
+    assert!(matches!(
+        stream.write_int(0u16, 16),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 16,
+            attempted_bits: 19
+        })
+    ));
+    assert!(matches!(
+        stream.write_bytes(&[1, 2]),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert!(matches!(
+        stream.write_string("ab", None),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert!(matches!(
+        stream.write_float(1.0f32),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    // failed writes don't write anything
+    assert_eq!(3, stream.bit_len());
+
+    stream.write_int(0x1fffu16, 13).unwrap();
+    assert_eq!(Some(0), stream.remaining_capacity_bits());
+    assert!(matches!(
+        stream.write_bool(true),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 16,
+            attempted_bits: 17
+        })
+    ));
+
+    let mut vec = Vec::new();
+    let stream = BitWriteStream::new(&mut vec, LittleEndian);
+    assert_eq!(None, stream.remaining_capacity_bits());
+}
+
 #[test]
 fn test_write_be_long() {
     let mut bytes = vec![];
@@ -304,7 +375,7 @@ fn test_write_all_lengths_ty<
     let buffer = BitReadBuffer::new(&bytes, BigEndian);
     let mut reader = BitReadStream::new(buffer);
 
-    for (bits, expected_value) in (1..max_bits).zip(expected.into_iter()) {
+    for (bits, expected_value) in (1..max_bits).zip(expected) {
         let actual = reader.read_int::<T>(bits).unwrap();
         assert_eq!(
             expected_value,
@@ -315,3 +386,821 @@ fn test_write_all_lengths_ty<
         );
     }
 }
+
+#[test]
+fn test_copy_from() {
+    let bytes: Vec<u8> = (0..32).map(|i| i * 7 + 3).collect();
+    for endianness_be in [false, true] {
+        for read_offset in 0..8 {
+            for write_offset in 0..8 {
+                for count in [0, 1, 7, 9, 33, 100] {
+                    if endianness_be {
+                        check_copy_from(BigEndian, &bytes, read_offset, write_offset, count);
+                    } else {
+                        check_copy_from(LittleEndian, &bytes, read_offset, write_offset, count);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_copy_from<E: Endianness>(
+    endianness: E,
+    bytes: &[u8],
+    read_offset: usize,
+    write_offset: usize,
+    count: usize,
+) {
+    let mut source = BitReadStream::new(BitReadBuffer::new(bytes, endianness));
+    source.skip_bits(read_offset).unwrap();
+    let expected = source.clone().read_bits(count).unwrap();
+
+    let mut data = Vec::new();
+    {
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        stream.write_int(0u8, write_offset).unwrap();
+        stream.copy_from(&mut source, count).unwrap();
+        assert_eq!(write_offset + count, stream.bit_len());
+    }
+    assert_eq!(read_offset + count, source.pos());
+
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, E::endianness()));
+    read.skip_bits(write_offset).unwrap();
+    assert!(expected == read.read_bits(count).unwrap());
+}
+
+#[test]
+fn test_copy_from_errors() {
+    let bytes = vec![1, 2];
+    let mut source = BitReadStream::new(BitReadBuffer::new(&bytes, LittleEndian));
+    let mut data = [0; 1];
+    let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+
+    assert!(matches!(
+        stream.copy_from(&mut source, 17),
+        Err(BitError::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        stream.copy_from(&mut source, 9),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert_eq!(0, source.pos());
+    assert_eq!(0, stream.bit_len());
+}
+
+#[test]
+fn test_section_stats() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.section("untracked", |s| s.write_bool(true)).unwrap();
+    assert!(stream.section_stats().is_none());
+
+    stream.track_sections();
+    stream
+        .section("a", |s| s.section("x", |s| s.write_int(1u8, 3)))
+        .unwrap();
+    stream.section("b", |s| s.write_int(1u8, 5)).unwrap();
+    stream
+        .section("a", |s| {
+            s.section("y", |s| s.write_int(1u16, 10))?;
+            s.write_bool(false)
+        })
+        .unwrap();
+
+    let paths: Vec<_> = stream
+        .section_stats()
+        .unwrap()
+        .sections()
+        .iter()
+        .map(|section| (section.path.as_str(), section.bits, section.count))
+        .collect();
+    assert_eq!(
+        vec![("a", 14, 2), ("a/x", 3, 1), ("a/y", 10, 1), ("b", 5, 1)],
+        paths
+    );
+    assert_eq!(20, stream.bit_len());
+}
+
+#[test]
+fn test_write_string_truncated() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write_bool(true).unwrap();
+    // 3 byte character on the boundary
+    assert_eq!(2, stream.write_string_truncated("ab€", ByteLen(4)).unwrap());
+    assert_eq!(0, stream.write_string_truncated("€", ByteLen(2)).unwrap());
+    assert_eq!(3, stream.write_string_truncated("€", ByteLen(3)).unwrap());
+    assert_eq!(0, stream.write_string_truncated("", ByteLen(0)).unwrap());
+    assert_eq!(1 + (4 + 2 + 3) * 8, stream.bit_len());
+
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, BigEndian));
+    read.skip_bits(1).unwrap();
+    assert_eq!("ab", read.read_string(Some(4)).unwrap());
+    assert_eq!("", read.read_string(Some(2)).unwrap());
+    assert_eq!("€", read.read_string(Some(3)).unwrap());
+}
+
+#[test]
+fn test_write_string_unallocatable_length() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write_bool(true).unwrap();
+    assert!(matches!(
+        stream.write_string("foo", Some(usize::MAX / 8)),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert!(matches!(
+        stream.write_sized(&String::from("foo"), usize::MAX),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert!(matches!(
+        stream.write_sized(&bitbuffer::RawStr::new(&b"foo"[..]), usize::MAX / 16),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert_eq!(1, stream.bit_len());
+}
+
+#[test]
+fn test_write_bytes_aligned() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.require_alignment().unwrap();
+    stream.write_int(0b101u8, 3).unwrap();
+    assert!(matches!(
+        stream.require_alignment(),
+        Err(BitError::NotAligned { bit_offset: 3 })
+    ));
+    assert!(matches!(
+        stream.write_bytes_aligned_hint(&[1, 2]),
+        Err(BitError::NotAligned { bit_offset: 3 })
+    ));
+    assert_eq!(3, stream.bit_len());
+    stream.align();
+    stream.debug_assert_aligned();
+    stream.write_bytes_aligned_hint(&[1, 2]).unwrap();
+    assert_eq!(24, stream.bit_len());
+    assert_eq!(vec![0b101, 1, 2], data);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_debug_assert_aligned() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_bool(true).unwrap();
+    stream.debug_assert_aligned();
+}
+
+#[test]
+fn test_finish() {
+    let mut data = [0xff; 4];
+    let mut stream = BitWriteStream::from_slice(&mut data, BigEndian);
+    assert_eq!(
+        (0, 0),
+        BitWriteStream::new(&mut Vec::new(), BigEndian).finish()
+    );
+    stream.write_int(0b111u8, 3).unwrap();
+    stream.write_int(0u8, 8).unwrap();
+    assert_eq!((2, 5), stream.finish());
+    assert_eq!([0b1110_0000, 0, 0xff, 0xff], data);
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_int(1u16, 16).unwrap();
+    assert_eq!((2, 0), stream.finish());
+}
+
+#[test]
+fn test_write_canonical_floats() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    let signaling_nan = f32::from_bits(0x7f80_0001);
+    stream.write_float(-0.0f32).unwrap();
+    stream.write_float(signaling_nan).unwrap();
+    stream.set_canonical_floats(true);
+    stream.write_float(-0.0f32).unwrap();
+    stream.write_float(signaling_nan).unwrap();
+    // unaligned
+    stream.write_bool(false).unwrap();
+    stream.write_float(-f64::NAN).unwrap();
+
+    let mut read = BitReadStream::new(BitReadBuffer::new_owned(data, BigEndian));
+    assert_eq!(0x8000_0000, read.read_int::<u32>(32).unwrap());
+    assert_eq!(0x7f80_0001, read.read_int::<u32>(32).unwrap());
+    assert_eq!(0, read.read_int::<u32>(32).unwrap());
+    assert_eq!(f32::NAN.to_bits(), read.read_int::<u32>(32).unwrap());
+    read.skip_bits(1).unwrap();
+    assert_eq!(f64::NAN.to_bits(), read.read_int::<u64>(64).unwrap());
+}
+
+fn write_chunk<E: Endianness>(stream: &mut BitWriteStream<E>, seed: u32, count: usize) {
+    for i in 0..count as u32 {
+        stream
+            .write_int(seed.wrapping_mul(2654435761).wrapping_add(i * 7919), 13)
+            .unwrap();
+    }
+}
+
+fn test_append_endianness<E: Endianness>(lead: usize) {
+    let counts = [0, 1, 5, 8, 3, 17];
+
+    let mut expected = Vec::new();
+    let mut sequential = BitWriteStream::new(&mut expected, E::endianness());
+    sequential.write_int(1u8, lead).unwrap();
+    for (seed, count) in counts.iter().enumerate() {
+        write_chunk(&mut sequential, seed as u32, *count);
+    }
+    let expected_len = sequential.bit_len();
+
+    let mut chunks = vec![Vec::new(); counts.len()];
+    let streams: Vec<_> = chunks
+        .iter_mut()
+        .zip(counts)
+        .enumerate()
+        .map(|(seed, (chunk, count))| {
+            let mut stream = BitWriteStream::new(chunk, E::endianness());
+            write_chunk(&mut stream, seed as u32, count);
+            stream
+        })
+        .collect();
+
+    let mut data = Vec::new();
+    let mut merged = BitWriteStream::new(&mut data, E::endianness());
+    merged.write_int(1u8, lead).unwrap();
+    for stream in &streams {
+        merged.append(stream).unwrap();
+    }
+    assert_eq!(expected_len, merged.bit_len());
+    drop(merged);
+    assert_eq!(expected, data);
+
+    let mut data = vec![0; expected.len()];
+    let mut merged = BitWriteStream::from_slice(&mut data, E::endianness());
+    merged.write_int(1u8, lead).unwrap();
+    for stream in &streams {
+        merged.append(stream).unwrap();
+    }
+    assert!(matches!(
+        merged.append(&streams[5]),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    drop(merged);
+    assert_eq!(expected, data);
+}
+
+#[test]
+fn test_append() {
+    test_append_endianness::<LittleEndian>(0);
+    test_append_endianness::<LittleEndian>(3);
+    test_append_endianness::<BigEndian>(0);
+    test_append_endianness::<BigEndian>(3);
+}
+
+fn test_new_at_endianness<E: Endianness>() {
+    let mut expected = Vec::new();
+    let mut stream = BitWriteStream::new(&mut expected, E::endianness());
+    stream.write_int(0x1234u16, 13).unwrap();
+    stream.write_int(0x5678u16, 13).unwrap();
+    drop(stream);
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, E::endianness());
+    stream.write_int(0x1234u16, 13).unwrap();
+    // garbage that is overwritten when resuming
+    stream.write_int(u32::MAX, 30).unwrap();
+    drop(stream);
+
+    let mut stream = BitWriteStream::new_at(&mut data, 13, E::endianness()).unwrap();
+    assert_eq!(13, stream.bit_len());
+    stream.write_int(0x5678u16, 13).unwrap();
+    assert_eq!(26, stream.bit_len());
+    drop(stream);
+    assert_eq!(expected, data);
+
+    assert!(matches!(
+        BitWriteStream::new_at(&mut data, 33, E::endianness()),
+        Err(BitError::IndexOutOfBounds { pos: 33, size: 32 })
+    ));
+}
+
+#[test]
+fn test_new_at() {
+    test_new_at_endianness::<LittleEndian>();
+    test_new_at_endianness::<BigEndian>();
+}
+
+#[test]
+fn test_write_unchecked() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    let checked = stream.check_write(1 + 12 + 32).unwrap();
+    stream.write_bool_unchecked(true, checked);
+    stream.write_int_unchecked(0xabcu16, 12, checked);
+    stream.write_float_unchecked(-2.5f32, checked);
+    assert_eq!(45, stream.bit_len());
+    drop(stream);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, BigEndian));
+    assert!(read.read_bool().unwrap());
+    assert_eq!(0xabc, read.read_int::<u16>(12).unwrap());
+    assert_eq!(-2.5, read.read_float::<f32>().unwrap());
+
+    let mut data = [0; 2];
+    let mut stream = BitWriteStream::from_slice(&mut data, BigEndian);
+    assert!(matches!(
+        stream.check_write(17),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 16,
+            attempted_bits: 17
+        })
+    ));
+    assert!(stream.check_write(16).is_ok());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_write_unchecked_past_checked() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    let checked = stream.check_write(8).unwrap();
+    stream.write_int_unchecked(1u16, 9, checked);
+}
+
+#[test]
+fn test_write_capacity() {
+    let mut data = vec![0xff];
+    let mut stream = BitWriteStream::with_capacity_bits(&mut data, 20, LittleEndian);
+    // the existing byte in the vec isn't part of the capacity of the stream
+    assert!(stream.capacity_bits() >= 20);
+    stream.write_int(1u8, 4).unwrap();
+    stream.reserve_bits(100).unwrap();
+    assert!(stream.capacity_bits() >= 104);
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.set_growth_strategy(GrowthStrategy::Exact);
+    stream.write_int(1u8, 4).unwrap();
+    stream.write_int(1u8, 4).unwrap();
+    stream.write_bool(true).unwrap();
+    stream.write_bytes(&[1, 2, 3]).unwrap();
+    assert_eq!(5 * 8, stream.capacity_bits());
+    stream.set_growth_strategy(GrowthStrategy::Chunk(16));
+    stream.write_int(1u16, 12).unwrap();
+    assert_eq!(20 * 8, stream.capacity_bits());
+    drop(stream);
+    assert_eq!(data, [0x11, 0b11, 2 << 1, 3 << 1, 1 << 1, 0]);
+
+    let mut data = [0; 2];
+    let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    assert_eq!(16, stream.capacity_bits());
+    assert!(matches!(
+        stream.reserve_bits(17),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 16,
+            attempted_bits: 17
+        })
+    ));
+}
+
+#[test]
+fn test_write_slices() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write(&[1u8, 2][..]).unwrap();
+    stream.write(&vec![3u16]).unwrap();
+    stream.write(&vec![4u8, 5].into_boxed_slice()).unwrap();
+    stream.write_sized(&vec![6u8, 7], 2).unwrap();
+    assert!(matches!(
+        stream.write_sized(&vec![8u8], 2),
+        Err(BitError::ElementCountMismatch {
+            expected: 2,
+            actual: 1
+        })
+    ));
+    drop(stream);
+
+    assert_eq!(data, [1, 2, 0, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_write_no_panic_on_large_sizes() {
+    let mut data = [0; 2];
+    let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    stream.write_bool(true).unwrap();
+    assert!(matches!(
+        stream.write_run(false, usize::MAX),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 16,
+            attempted_bits: usize::MAX
+        })
+    ));
+    assert!(matches!(
+        stream.check_write(usize::MAX),
+        Err(BitError::WriteOverflow { .. })
+    ));
+
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.write_bool(true).unwrap();
+    assert!(matches!(
+        stream.write_run(false, usize::MAX),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert_eq!(1, stream.bit_len());
+}
+
+#[test]
+fn test_write_with_endianness_slice() {
+    let mut data = [0; 4];
+    let mut stream = BitWriteStream::from_slice(&mut data, LittleEndian);
+    stream.write_int(0b101u8, 4).unwrap();
+    assert!(matches!(
+        stream.with_endianness::<BigEndian, _, BitError, _>(|stream| stream.write_bool(true)),
+        Err(BitError::NotAligned { bit_offset: 4 })
+    ));
+    stream.write_int(0b1010u8, 4).unwrap();
+    stream
+        .with_endianness::<BigEndian, _, _, _>(|stream| stream.write_int(0xabcu16, 16))
+        .unwrap();
+    assert_eq!(24, stream.bit_len());
+    assert_eq!(Some(8), stream.remaining_capacity_bits());
+    stream.write_int(0x12u8, 8).unwrap();
+    assert!(stream.write_bool(true).is_err());
+    drop(stream);
+    assert_eq!(data, [0b1010_0101, 0x0a, 0xbc, 0x12]);
+
+    let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+    assert_eq!(0b101, read.read_int::<u8>(4).unwrap());
+    assert!(matches!(
+        read.with_endianness::<BigEndian, _, BitError, _>(|read| read.read_bool()),
+        Err(BitError::NotAligned { bit_offset: 4 })
+    ));
+    read.skip_bits(4).unwrap();
+    assert!(matches!(
+        read.with_endianness::<BigEndian, _, BitError, _>(|read| read.read_int::<u8>(3)),
+        Err(BitError::NotAligned { bit_offset: 3 })
+    ));
+    assert_eq!(8, read.pos());
+    let value = read
+        .with_endianness::<BigEndian, _, BitError, _>(|read| read.read_int::<u16>(16))
+        .unwrap();
+    assert_eq!(0xabc, value);
+    assert_eq!(0x12, read.read_int::<u8>(8).unwrap());
+}
+
+#[test]
+fn test_extend_and_collect_bits() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    stream.extend([true, true, false]);
+    stream.extend([0x12u8, 0x34]);
+    assert_eq!(19, stream.bit_len());
+    drop(stream);
+    assert_eq!(vec![0b1001_0011, 0b1010_0000, 0b001], data);
+
+    let mut slice = [0u8; 1];
+    let mut stream = BitWriteStream::from_slice(&mut slice, BigEndian);
+    stream.extend([0xabu8]);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| stream.extend([true])));
+    assert!(result.is_err());
+    assert_eq!([0xab], slice);
+
+    let buffer: BitReadBuffer<BigEndian> = [true, false, false, true, true].into_iter().collect();
+    assert_eq!(5, buffer.bit_len());
+    assert_eq!(0b10011, buffer.read_int::<u8>(0, 5).unwrap());
+
+    let mut stream: BitReadStream<LittleEndian> = (0..12).map(|index| index % 3 == 0).collect();
+    assert_eq!(12, stream.bits_left());
+    assert_eq!(
+        0b1001_0010_0100_1001u16 & 0xfff,
+        stream.read_int::<u16>(12).unwrap()
+    );
+
+    let empty: BitReadStream<LittleEndian> = std::iter::empty().collect();
+    assert_eq!(0, empty.bits_left());
+}
+
+#[test]
+fn test_nested_reserve_length() {
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+    let mut sections = Vec::new();
+    stream
+        .reserve_byte_length(8, |stream| {
+            for value in [1u8, 2] {
+                stream.reserve_length(4, |stream| {
+                    sections.push(value);
+                    stream.write_int(value, 3)
+                })?;
+            }
+            stream.reserve_int(8, |stream| {
+                stream.write_bool(true)?;
+                Ok::<_, BitError>(sections.len() as u64)
+            })
+        })
+        .unwrap();
+    assert_eq!(vec![1, 2], sections);
+
+    // a length of 256 doesn't fit in 8 bits
+    let len = stream.bit_len();
+    let result = stream.reserve_byte_length(8, |stream| stream.write_bytes(&[0; 256]));
+    assert!(matches!(
+        result,
+        Err(BitError::TooManyBits {
+            requested: 9,
+            max: 8
+        })
+    ));
+    assert_eq!(len, stream.bit_len());
+    let result = stream.reserve_int(4, |_| Ok::<_, BitError>(16));
+    assert!(matches!(
+        result,
+        Err(BitError::TooManyBits {
+            requested: 5,
+            max: 4
+        })
+    ));
+    assert_eq!(len, stream.bit_len());
+    let result = stream.reserve_int(32, |_| Ok::<_, BitError>(u32::MAX as u64));
+    assert!(result.is_ok());
+    drop(stream);
+
+    assert_eq!(
+        vec![3, 0b1001_0011, 0b1001_0001, 0b0100_0000, 0b1111_1111],
+        data[0..5]
+    );
+
+    // a failed reservation in the middle of a byte leaves no stray bits behind
+    let mut data = Vec::new();
+    let mut stream = BitWriteStream::new(&mut data, BigEndian);
+    stream.write_int(0b101u8, 3).unwrap();
+    let result = stream.reserve_int(2, |stream| {
+        stream.write_int(0xffu8, 8)?;
+        Ok::<_, BitError>(4)
+    });
+    assert!(result.is_err());
+    assert_eq!(3, stream.bit_len());
+    stream.write_int(0u8, 5).unwrap();
+    drop(stream);
+    assert_eq!(vec![0b1010_0000], data);
+}
+
+/// Slow but obviously correct bit writer to compare the write stream against
+struct ReferenceWriter {
+    le: bool,
+    bits: Vec<bool>,
+}
+
+impl ReferenceWriter {
+    fn push_int(&mut self, value: u128, count: usize) {
+        let pos = self.bits.len();
+        self.bits.resize(pos + count, false);
+        self.set_at(pos, value, count);
+    }
+
+    fn set_at(&mut self, pos: usize, value: u128, count: usize) {
+        for i in 0..count {
+            let bit = if self.le { i } else { count - 1 - i };
+            self.bits[pos + i] = (value >> bit) & 1 == 1;
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.bits
+            .chunks(8)
+            .map(|byte| {
+                byte.iter().enumerate().fold(0, |acc, (i, bit)| {
+                    let shift = if self.le { i } else { 7 - i };
+                    acc | ((*bit as u8) << shift)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Minimal xorshift rng for reproducible random write sequences
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: u64) -> usize {
+        (self.next() % max) as usize
+    }
+}
+
+/// The lowest `count` bits of `value`
+fn mask(value: u64, count: usize) -> u64 {
+    if count == 0 {
+        0
+    } else {
+        value & (u64::MAX >> (64 - count))
+    }
+}
+
+fn write_random<E: Endianness>(
+    rng: &mut Rng,
+    stream: &mut BitWriteStream<E>,
+    reference: &mut ReferenceWriter,
+    depth: usize,
+) {
+    for _ in 0..rng.below(20) {
+        let value = ((rng.next() as u128) << 64) | rng.next() as u128;
+        match rng.below(if depth < 2 { 8 } else { 7 }) {
+            0 => {
+                let bit = value & 1 == 1;
+                stream.write_bool(bit).unwrap();
+                reference.push_int(bit as u128, 1);
+            }
+            1 => {
+                let count = rng.below(9);
+                stream.write_int(value as u8, count).unwrap();
+                reference.push_int(value, count);
+            }
+            2 => {
+                let count = rng.below(17);
+                stream.write_int(value as u16, count).unwrap();
+                reference.push_int(value, count);
+            }
+            3 => {
+                let count = rng.below(33);
+                stream.write_int(value as u32, count).unwrap();
+                reference.push_int(value, count);
+            }
+            4 => {
+                let count = rng.below(65);
+                stream.write_int(value as u64, count).unwrap();
+                reference.push_int(value, count);
+            }
+            5 => {
+                let count = rng.below(129);
+                stream.write_int(value, count).unwrap();
+                reference.push_int(value, count);
+            }
+            6 => {
+                let bytes = value.to_le_bytes();
+                let bytes = &bytes[0..rng.below(8)];
+                stream.write_bytes(bytes).unwrap();
+                for byte in bytes {
+                    reference.push_int(*byte as u128, 8);
+                }
+            }
+            _ => {
+                let count = rng.below(65);
+                let head = mask(value as u64, count);
+                let start = reference.bits.len();
+                reference.push_int(0, count);
+                stream
+                    .reserve_int(count, |stream| {
+                        write_random(rng, stream, reference, depth + 1);
+                        Ok::<_, BitError>(head)
+                    })
+                    .unwrap();
+                reference.set_at(start, head as u128, count);
+            }
+        }
+        assert_eq!(reference.bits.len(), stream.bit_len());
+    }
+}
+
+fn test_write_differential<E: Endianness>() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..500 {
+        let prefix = vec![0xa5; rng.below(3)];
+        let mut data = prefix.clone();
+        let mut stream = BitWriteStream::new(&mut data, E::endianness());
+        let mut reference = ReferenceWriter {
+            le: E::is_le(),
+            bits: Vec::new(),
+        };
+        write_random(&mut rng, &mut stream, &mut reference, 0);
+        drop(stream);
+        assert_eq!(prefix, data[0..prefix.len()]);
+        assert_eq!(reference.bytes(), data[prefix.len()..]);
+
+        // slice backed streams
+        let mut slice = vec![0; 64 * 1024];
+        let mut stream = BitWriteStream::from_slice(&mut slice, E::endianness());
+        let mut reference = ReferenceWriter {
+            le: E::is_le(),
+            bits: Vec::new(),
+        };
+        write_random(&mut rng, &mut stream, &mut reference, 0);
+        let (byte_len, _) = stream.finish();
+        assert_eq!(reference.bytes(), slice[0..byte_len]);
+    }
+}
+
+#[test]
+fn test_write_differential_le() {
+    test_write_differential::<LittleEndian>();
+}
+
+#[test]
+fn test_write_differential_be() {
+    test_write_differential::<BigEndian>();
+}
+
+fn test_reserve_matches_write<E: Endianness>() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..200 {
+        let offset = rng.below(8);
+        let count = rng.below(65);
+        let value = mask(rng.next(), count);
+
+        let mut written = Vec::new();
+        let mut stream = BitWriteStream::new(&mut written, E::endianness());
+        stream.write_int(0u8, offset).unwrap();
+        stream.write_int(value, count).unwrap();
+        drop(stream);
+
+        let mut reserved = Vec::new();
+        let mut stream = BitWriteStream::new(&mut reserved, E::endianness());
+        stream.write_int(0u8, offset).unwrap();
+        stream
+            .reserve_int(count, |_| Ok::<_, BitError>(value))
+            .unwrap();
+        drop(stream);
+
+        assert_eq!(written, reserved, "{} bits at offset {}", count, offset);
+    }
+}
+
+#[test]
+fn test_reserve_matches_write_all_endianness() {
+    test_reserve_matches_write::<LittleEndian>();
+    test_reserve_matches_write::<BigEndian>();
+    test_reserve_matches_write::<LsbFirst<BigEndian>>();
+    test_reserve_matches_write::<MsbFirst<LittleEndian>>();
+}
+
+fn test_edit_buffer_patches<E: Endianness>() {
+    let mut rng = Rng(0xdead_beef_cafe_f00d);
+    for _ in 0..200 {
+        let original: Vec<u8> = (0..6).map(|_| rng.next() as u8).collect();
+        let position = rng.below(40);
+        let count = rng.below(49 - position as u64);
+        let value = mask(rng.next(), count);
+
+        let mut data = original.clone();
+        let mut buffer = BitEditBuffer::new(&mut data, E::endianness());
+        buffer.write_int(position, value, count).unwrap();
+        assert_eq!(value, buffer.read_int::<u64>(position, count).unwrap());
+
+        let bit = rng.below(48);
+        let flipped = !buffer.read_bool(bit).unwrap();
+        buffer.write_bool(bit, flipped).unwrap();
+        assert_eq!(flipped, buffer.read_bool(bit).unwrap());
+        buffer.write_bool(bit, !flipped).unwrap();
+
+        // all bits outside of the patched range are unchanged
+        let original = BitReadBuffer::new(&original, E::endianness());
+        let patched = BitReadBuffer::new(&data, E::endianness());
+        for bit in (0..position).chain(position + count..48) {
+            assert_eq!(
+                original.read_bool(bit).unwrap(),
+                patched.read_bool(bit).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_edit_buffer() {
+    test_edit_buffer_patches::<LittleEndian>();
+    test_edit_buffer_patches::<BigEndian>();
+
+    let mut data = b"hello world!".to_vec();
+    let mut buffer = BitEditBuffer::new(&mut data, LittleEndian);
+    buffer.write_sized(48, "there", 5).unwrap();
+    assert_eq!("there!", buffer.read::<String>(48).unwrap().as_str());
+
+    // writes past the end are rejected without modifying the data
+    assert!(matches!(
+        buffer.write(90, "long"),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 96,
+            attempted_bits: 130
+        })
+    ));
+    assert!(matches!(
+        buffer.write_bool(96, true),
+        Err(BitError::WriteOverflow { .. })
+    ));
+    assert!(matches!(
+        buffer.write_int(usize::MAX - 3, 0u8, 8),
+        Err(BitError::WriteOverflow {
+            capacity_bits: 96,
+            attempted_bits: usize::MAX
+        })
+    ));
+    assert_eq!(b"hello there!", data.as_slice());
+}